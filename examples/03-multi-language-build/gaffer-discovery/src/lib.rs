@@ -0,0 +1,142 @@
+//! mDNS/DNS-SD helpers shared by the example servers and CLIs.
+//!
+//! Servers call [`announce`] to advertise themselves as `_gaffer-api._tcp`;
+//! clients call [`discover`] to find them on the local network instead of
+//! hardcoding `localhost:8080`.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use mdns_sd::{IfKind, ServiceDaemon, ServiceEvent, ServiceInfo};
+
+pub use mdns_sd::Error;
+
+/// DNS-SD service type every gaffer example API is advertised under.
+pub const SERVICE_TYPE: &str = "_gaffer-api._tcp.local.";
+
+/// A registered service. Call [`Announcement::shutdown`] to send the goodbye
+/// packets; dropping it without doing so leaves the record to expire.
+pub struct Announcement {
+    daemon: ServiceDaemon,
+    fullname: String,
+}
+
+impl Announcement {
+    /// Withdraws the record and stops the responder.
+    pub fn shutdown(self) {
+        if let Ok(done) = self.daemon.unregister(&self.fullname) {
+            let _ = done.recv_timeout(Duration::from_secs(1));
+        }
+        let _ = self.daemon.shutdown();
+    }
+}
+
+/// Advertises `instance` on `port` for every local address, loopback
+/// included so a server bound to 127.0.0.1 is still discoverable on the same
+/// machine. `properties` become the TXT record.
+pub fn announce(
+    instance: &str,
+    port: u16,
+    properties: &[(&str, &str)],
+) -> Result<Announcement, Error> {
+    let daemon = daemon()?;
+    let host = format!("{}.local.", hostname());
+    let info =
+        ServiceInfo::new(SERVICE_TYPE, instance, &host, "", port, properties)?.enable_addr_auto();
+    let fullname = info.get_fullname().to_string();
+    daemon.register(info)?;
+    Ok(Announcement { daemon, fullname })
+}
+
+/// A gaffer API found on the network.
+#[derive(Debug, Clone)]
+pub struct DiscoveredService {
+    pub instance: String,
+    pub host: String,
+    pub addresses: Vec<IpAddr>,
+    pub port: u16,
+    pub properties: HashMap<String, String>,
+}
+
+impl DiscoveredService {
+    /// Base URL for the service, preferring an IPv4 address.
+    pub fn url(&self) -> Option<String> {
+        let addr = self
+            .addresses
+            .iter()
+            .find(|a| a.is_ipv4())
+            .or_else(|| self.addresses.first())?;
+        Some(match addr {
+            IpAddr::V4(v4) => format!("http://{}:{}", v4, self.port),
+            IpAddr::V6(v6) => format!("http://[{}]:{}", v6, self.port),
+        })
+    }
+}
+
+/// Browses for `_gaffer-api._tcp` services for `timeout` and returns every
+/// instance that resolved, sorted by name.
+pub fn discover(timeout: Duration) -> Result<Vec<DiscoveredService>, Error> {
+    let daemon = daemon()?;
+    let events = daemon.browse(SERVICE_TYPE)?;
+    let deadline = Instant::now() + timeout;
+    let mut found: HashMap<String, DiscoveredService> = HashMap::new();
+
+    while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+        match events.recv_timeout(left) {
+            Ok(ServiceEvent::ServiceResolved(info)) => {
+                let service = resolved(&info);
+                found.insert(info.get_fullname().to_string(), service);
+            }
+            Ok(ServiceEvent::ServiceRemoved(_, fullname)) => {
+                found.remove(&fullname);
+            }
+            Ok(_) => {}
+            Err(_) => break,
+        }
+    }
+
+    let _ = daemon.stop_browse(SERVICE_TYPE);
+    let _ = daemon.shutdown();
+
+    let mut services: Vec<_> = found.into_values().collect();
+    services.sort_by(|a, b| a.instance.cmp(&b.instance));
+    Ok(services)
+}
+
+fn daemon() -> Result<ServiceDaemon, Error> {
+    let daemon = ServiceDaemon::new()?;
+    daemon.enable_interface(vec![IfKind::LoopbackV4, IfKind::LoopbackV6])?;
+    Ok(daemon)
+}
+
+fn resolved(info: &ServiceInfo) -> DiscoveredService {
+    let instance = info
+        .get_fullname()
+        .strip_suffix(SERVICE_TYPE)
+        .map(|name| name.trim_end_matches('.'))
+        .unwrap_or(info.get_fullname())
+        .to_string();
+    let mut addresses: Vec<_> = info.get_addresses().iter().copied().collect();
+    addresses.sort();
+    DiscoveredService {
+        instance,
+        host: info.get_hostname().to_string(),
+        addresses,
+        port: info.get_port(),
+        properties: info
+            .get_properties()
+            .iter()
+            .map(|p| (p.key().to_string(), p.val_str().to_string()))
+            .collect(),
+    }
+}
+
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "gaffer".to_string())
+}
@@ -7,6 +7,7 @@ import (
 	"log"
 	"net/http"
 	"os"
+	"strings"
 	"time"
 
 	"github.com/spf13/cobra"
@@ -39,8 +40,48 @@ type MetricsData struct {
 	Components          []string `json:"components"`
 }
 
+type EventBatch struct {
+	Events     []json.RawMessage `json:"events"`
+	NextCursor uint64            `json:"next_cursor"`
+	Missed     bool              `json:"missed"`
+}
+
+type EventHeader struct {
+	Seq  uint64 `json:"seq"`
+	Type string `json:"type"`
+}
+
 const backendURL = "http://localhost:8080"
 
+// Matches the backend's default poll_timeout_secs; the client timeout leaves
+// room for the response to arrive after the server gives up waiting.
+const pollTimeoutSecs = 30
+
+// acceptLanguage turns the POSIX locale (LC_ALL, LC_MESSAGES or LANG, e.g.
+// "de_DE.UTF-8") into an Accept-Language value so the backend answers in the
+// user's language.
+func acceptLanguage() string {
+	for _, name := range []string{"LC_ALL", "LC_MESSAGES", "LANG"} {
+		locale := os.Getenv(name)
+		if locale == "" || locale == "C" || locale == "POSIX" {
+			continue
+		}
+		locale = strings.SplitN(locale, ".", 2)[0]
+		return strings.ReplaceAll(locale, "_", "-") + ", en;q=0.5"
+	}
+	return "en"
+}
+
+// get fetches a backend path, asking for responses in the user's language.
+func get(client *http.Client, url string) (*http.Response, error) {
+	req, err := http.NewRequest(http.MethodGet, url, nil)
+	if err != nil {
+		return nil, err
+	}
+	req.Header.Set("Accept-Language", acceptLanguage())
+	return client.Do(req)
+}
+
 var rootCmd = &cobra.Command{
 	Use:   "multi-cli",
 	Short: "Go CLI for multi-language application",
@@ -53,7 +94,7 @@ var healthCmd = &cobra.Command{
 	Run: func(cmd *cobra.Command, args []string) {
 		fmt.Println("🐹 Go CLI - Checking backend health...")
 		
-		resp, err := http.Get(backendURL + "/health")
+		resp, err := get(http.DefaultClient, backendURL+"/health")
 		if err != nil {
 			log.Printf("❌ Failed to connect to backend: %v", err)
 			return
@@ -92,7 +133,7 @@ var metricsCmd = &cobra.Command{
 	Run: func(cmd *cobra.Command, args []string) {
 		fmt.Println("🐹 Go CLI - Fetching metrics...")
 		
-		resp, err := http.Get(backendURL + "/metrics")
+		resp, err := get(http.DefaultClient, backendURL+"/metrics")
 		if err != nil {
 			log.Printf("❌ Failed to connect to backend: %v", err)
 			return
@@ -148,10 +189,69 @@ var statusCmd = &cobra.Command{
 	},
 }
 
+var eventsCursor uint64
+
+var eventsCmd = &cobra.Command{
+	Use:   "events",
+	Short: "Follow build events by long-polling the backend",
+	Run: func(cmd *cobra.Command, args []string) {
+		fmt.Println("🐹 Go CLI - Following backend events (Ctrl+C to stop)...")
+
+		client := &http.Client{Timeout: (pollTimeoutSecs + 10) * time.Second}
+		cursor := eventsCursor
+		for {
+			url := fmt.Sprintf("%s/events/poll?cursor=%d&timeout=%d", backendURL, cursor, pollTimeoutSecs)
+			resp, err := get(client, url)
+			if err != nil {
+				log.Printf("❌ Failed to poll backend: %v", err)
+				time.Sleep(2 * time.Second)
+				continue
+			}
+
+			body, err := io.ReadAll(resp.Body)
+			resp.Body.Close()
+			if err != nil {
+				log.Printf("❌ Failed to read response: %v", err)
+				continue
+			}
+
+			var apiResp ApiResponse
+			if err := json.Unmarshal(body, &apiResp); err != nil {
+				log.Printf("❌ Failed to parse JSON: %v", err)
+				time.Sleep(2 * time.Second)
+				continue
+			}
+
+			var batch EventBatch
+			if err := json.Unmarshal(apiResp.Data, &batch); err != nil {
+				log.Printf("❌ Failed to parse events: %v", err)
+				time.Sleep(2 * time.Second)
+				continue
+			}
+
+			if batch.Missed {
+				fmt.Println("⚠️  Some events were missed (backend restarted or client fell behind)")
+			}
+			for _, raw := range batch.Events {
+				var header EventHeader
+				if err := json.Unmarshal(raw, &header); err != nil {
+					log.Printf("❌ Failed to parse event: %v", err)
+					continue
+				}
+				fmt.Printf("📬 #%d %s: %s\n", header.Seq, header.Type, raw)
+			}
+			cursor = batch.NextCursor
+		}
+	},
+}
+
 func main() {
+	eventsCmd.Flags().Uint64Var(&eventsCursor, "cursor", 0, "resume after this event sequence number")
+
 	rootCmd.AddCommand(healthCmd)
 	rootCmd.AddCommand(metricsCmd)
 	rootCmd.AddCommand(statusCmd)
+	rootCmd.AddCommand(eventsCmd)
 
 	if err := rootCmd.Execute(); err != nil {
 		fmt.Println(err)
@@ -0,0 +1,47 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rust_backend::models::MetricsData;
+use rust_backend::response::ApiResponse;
+
+fn sample_metrics() -> MetricsData {
+    MetricsData {
+        requests_served: 1247,
+        languages_integrated: 4,
+        build_time_seconds: 12.34,
+        components: vec![
+            "rust-backend".to_string(),
+            "go-cli".to_string(),
+            "node-frontend".to_string(),
+            "python-ml".to_string(),
+        ],
+        limits: Default::default(),
+        timeouts: Default::default(),
+        handler_panics: 0,
+        cache: Default::default(),
+    }
+}
+
+fn bench_response_serialization(c: &mut Criterion) {
+    let metrics = sample_metrics();
+    let mut group = c.benchmark_group("metrics_response");
+
+    // The previous handler shape: payload -> serde_json::Value -> bytes.
+    group.bench_function("via_value", |b| {
+        b.iter(|| {
+            let response = ApiResponse::ok(serde_json::to_value(black_box(&metrics)).unwrap(), 0);
+            serde_json::to_vec(&response).unwrap()
+        })
+    });
+
+    // The generic envelope: payload -> bytes.
+    group.bench_function("generic", |b| {
+        b.iter(|| {
+            let response = ApiResponse::ok(black_box(&metrics), 0);
+            serde_json::to_vec(&response).unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_response_serialization);
+criterion_main!(benches);
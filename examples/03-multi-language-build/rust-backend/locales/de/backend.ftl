@@ -0,0 +1,21 @@
+# Vom Rust-Backend ausgelieferte Texte, auf Deutsch.
+
+## /health
+
+health-uptime = { $minutes } Min. { $seconds } Sek.
+health-built-with = gaffer-exec Mehrsprachen-Build
+
+## Errors
+
+error-at-capacity = Server ist ausgelastet, bitte später erneut versuchen
+error-idempotency-key-reused = Idempotency-Key wurde bereits für eine andere Anfrage verwendet
+error-idempotency-in-progress = eine Anfrage mit diesem Idempotency-Key wird noch bearbeitet
+error-buffer-response = Antwort konnte nicht gepuffert werden: { $error }
+error-admin-credentials = Admin-Zugangsdaten erforderlich
+error-start-build = Build konnte nicht gestartet werden: { $error }
+error-cancel-not-found = Build { $id } kann nicht abgebrochen werden: Build nicht gefunden
+error-cancel-not-running = Build { $id } kann nicht abgebrochen werden: Build läuft nicht
+error-cancel-signal = Build { $id } kann nicht abgebrochen werden: Signal fehlgeschlagen: { $error }
+error-build-history = Build-Verlauf konnte nicht abgefragt werden: { $error }
+error-build-not-found = Build { $id } nicht gefunden
+error-handler-panicked = Der Request-Handler ist abgestürzt; Details stehen im Server-Log unter dieser Request-ID.
@@ -0,0 +1,21 @@
+# Human-readable strings served by the Rust backend, in English (the default).
+
+## /health
+
+health-uptime = { $minutes }m { $seconds }s
+health-built-with = gaffer-exec multi-language build
+
+## Errors
+
+error-at-capacity = server is at capacity, retry later
+error-idempotency-key-reused = Idempotency-Key was already used for a different request
+error-idempotency-in-progress = a request with this Idempotency-Key is still in progress
+error-buffer-response = failed to buffer response: { $error }
+error-admin-credentials = admin credentials required
+error-start-build = failed to start build: { $error }
+error-cancel-not-found = cannot cancel build { $id }: build not found
+error-cancel-not-running = cannot cancel build { $id }: build is not running
+error-cancel-signal = cannot cancel build { $id }: failed to signal build: { $error }
+error-build-history = failed to query build history: { $error }
+error-build-not-found = build { $id } not found
+error-handler-panicked = The request handler panicked; see the server log for this request id.
@@ -0,0 +1,300 @@
+//! Embedded admin dashboard at `/admin`, behind HTTP Basic auth.
+//!
+//! The page itself is static HTML ([`ADMIN_PAGE`]) that polls the public API
+//! (`/health`, `/metrics`, `/builds`) plus two admin-only endpoints:
+//! `/admin/requests` for the most recent requests and `/admin/features` for
+//! which optional features this instance runs with.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+
+use axum::extract::{Request, State};
+use axum::http::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+use axum::http::{HeaderValue, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::get;
+use axum::Router;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::Serialize;
+
+use crate::clock::SharedClock;
+use crate::config::Config;
+use crate::i18n;
+use crate::recovery::REQUEST_ID_HEADER;
+use crate::response::{error_reply, reply};
+use crate::timing;
+
+/// Requests kept for the dashboard; older ones are dropped.
+const REQUEST_LOG_CAPACITY: usize = 100;
+
+const ADMIN_PAGE: &str = include_str!("admin.html");
+
+#[derive(Debug, Clone)]
+pub struct AdminConfig {
+    pub username: String,
+    pub password: String,
+}
+
+/// One handled request, as listed by `/admin/requests`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestRecord {
+    pub timestamp: u64,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    pub duration_ms: u64,
+    pub request_id: Option<String>,
+}
+
+/// The most recent requests, newest last.
+#[derive(Clone)]
+pub struct RequestLog {
+    records: Arc<Mutex<VecDeque<RequestRecord>>>,
+    clock: SharedClock,
+}
+
+impl RequestLog {
+    pub fn new(clock: SharedClock) -> Self {
+        RequestLog {
+            records: Arc::new(Mutex::new(VecDeque::with_capacity(REQUEST_LOG_CAPACITY))),
+            clock,
+        }
+    }
+
+    fn push(&self, record: RequestRecord) {
+        let mut records = self.lock();
+        if records.len() == REQUEST_LOG_CAPACITY {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Newest first.
+    pub fn recent(&self) -> Vec<RequestRecord> {
+        self.lock().iter().rev().cloned().collect()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<RequestRecord>> {
+        self.records.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Optional features this instance was started with.
+#[derive(Debug, Clone, Serialize)]
+pub struct Features {
+    pub dedicated_metrics_port: Option<u16>,
+    pub admin: bool,
+    pub response_cache: bool,
+    pub registry: bool,
+    pub mdns: bool,
+    pub mqtt: bool,
+    pub idempotency_ttl_secs: u64,
+    pub poll_timeout_secs: u64,
+    pub max_connections: usize,
+    pub max_in_flight: usize,
+}
+
+impl Features {
+    pub fn from_config(config: &Config) -> Self {
+        Features {
+            dedicated_metrics_port: config.metrics_port,
+            admin: config.admin.is_some(),
+            response_cache: !config.cache_ttls.is_empty(),
+            registry: config.registry.is_some(),
+            mdns: config.mdns_instance.is_some(),
+            mqtt: config.mqtt.is_some(),
+            idempotency_ttl_secs: config.idempotency_ttl.as_secs(),
+            poll_timeout_secs: config.poll_timeout.as_secs(),
+            max_connections: config.max_connections,
+            max_in_flight: config.max_in_flight,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct AdminState {
+    auth: Arc<AdminConfig>,
+    requests: RequestLog,
+    features: Arc<Features>,
+}
+
+/// Routes to nest under `/admin`, all requiring `auth`.
+pub fn router<S>(auth: AdminConfig, requests: RequestLog, features: Features) -> Router<S> {
+    let state = AdminState {
+        auth: Arc::new(auth),
+        requests,
+        features: Arc::new(features),
+    };
+    Router::new()
+        .route("/", get(page))
+        .route("/requests", get(recent_requests))
+        .route("/features", get(features_info))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_auth))
+        .with_state(state)
+}
+
+/// Middleware adding every request outside `/admin` to `log`, so the
+/// dashboard's own polling doesn't crowd out real traffic.
+pub async fn record_requests(State(log): State<RequestLog>, req: Request, next: Next) -> Response {
+    let path = req.uri().path();
+    if path == "/admin" || path.starts_with("/admin/") {
+        return next.run(req).await;
+    }
+    let method = req.method().to_string();
+    let path = req.uri().path().to_string();
+    let started = Instant::now();
+
+    let response = next.run(req).await;
+    log.push(RequestRecord {
+        timestamp: log.clock.unix_timestamp(),
+        method,
+        path,
+        status: response.status().as_u16(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        request_id: response
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+    });
+    response
+}
+
+async fn require_auth(State(state): State<AdminState>, req: Request, next: Next) -> Response {
+    let authorized = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Basic "))
+        .and_then(|encoded| STANDARD.decode(encoded).ok())
+        .is_some_and(|credentials| {
+            let expected = format!("{}:{}", state.auth.username, state.auth.password);
+            constant_time_eq(&credentials, expected.as_bytes())
+        });
+    if authorized {
+        return next.run(req).await;
+    }
+
+    let mut response = error_reply(
+        &*state.requests.clock,
+        StatusCode::UNAUTHORIZED,
+        i18n::text("error-admin-credentials", &[]),
+    );
+    response.headers_mut().insert(
+        WWW_AUTHENTICATE,
+        HeaderValue::from_static("Basic realm=\"gaffer admin\""),
+    );
+    response
+}
+
+/// Compares without returning early, so response timing doesn't reveal how
+/// much of a guessed password was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+async fn page() -> Response {
+    timing::mark("deser");
+    Html(ADMIN_PAGE).into_response()
+}
+
+async fn recent_requests(State(state): State<AdminState>) -> Response {
+    timing::mark("deser");
+    reply(&*state.requests.clock, state.requests.recent())
+}
+
+async fn features_info(State(state): State<AdminState>) -> Response {
+    timing::mark("deser");
+    reply(&*state.requests.clock, &*state.features)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::Body;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::clock::MockClock;
+
+    fn app() -> Router {
+        let auth = AdminConfig {
+            username: "admin".to_string(),
+            password: "hunter2".to_string(),
+        };
+        let log = RequestLog::new(Arc::new(MockClock::at(1_700_000_000)));
+        let features = Features::from_config(&Config::default());
+        Router::new()
+            .route("/health", get(|| async { "ok" }))
+            .nest("/admin", router(auth, log.clone(), features))
+            .layer(middleware::from_fn_with_state(log, record_requests))
+    }
+
+    fn get_request(path: &str, credentials: Option<&str>) -> Request {
+        let mut request = Request::get(path);
+        if let Some(credentials) = credentials {
+            request = request.header(
+                AUTHORIZATION,
+                format!("Basic {}", STANDARD.encode(credentials)),
+            );
+        }
+        request.body(Body::empty()).unwrap()
+    }
+
+    async fn body_json(response: Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn dashboard_requires_credentials() {
+        for credentials in [None, Some("admin:wrong"), Some("root:hunter2")] {
+            let response = app()
+                .oneshot(get_request("/admin", credentials))
+                .await
+                .unwrap();
+
+            assert_eq!(
+                response.status(),
+                StatusCode::UNAUTHORIZED,
+                "{:?}",
+                credentials
+            );
+            assert!(response.headers().contains_key(WWW_AUTHENTICATE));
+        }
+
+        let response = app()
+            .oneshot(get_request("/admin", Some("admin:hunter2")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn lists_recent_requests_except_its_own() {
+        let app = app();
+        app.clone()
+            .oneshot(get_request("/health", None))
+            .await
+            .unwrap();
+        app.clone()
+            .oneshot(get_request("/admin/features", Some("admin:hunter2")))
+            .await
+            .unwrap();
+
+        let response = app
+            .oneshot(get_request("/admin/requests", Some("admin:hunter2")))
+            .await
+            .unwrap();
+
+        let records = body_json(response).await["data"].clone();
+        assert_eq!(records.as_array().unwrap().len(), 1);
+        assert_eq!(records[0]["path"], "/health");
+        assert_eq!(records[0]["status"], 200);
+        assert_eq!(records[0]["timestamp"], 1_700_000_000);
+    }
+}
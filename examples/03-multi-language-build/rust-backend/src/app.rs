@@ -0,0 +1,341 @@
+use std::convert::Infallible;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderName, Method, StatusCode};
+use axum::middleware;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::Router;
+use fluent_bundle::FluentValue;
+use futures_util::StreamExt;
+use tower::ServiceBuilder;
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tower_http::trace::TraceLayer;
+
+use crate::admin::{self, Features, RequestLog};
+use crate::builds::{BuildEvent, BuildManager, CancelError, OutputStream};
+use crate::cache::{self, ResponseCache};
+use crate::clock::SharedClock;
+use crate::coalesce::{self, Coalescer};
+use crate::config::Config;
+use crate::events::PollQuery;
+use crate::history::BuildQuery;
+use crate::i18n;
+use crate::idempotency::{self, IdempotencyCache};
+use crate::limits::{self, Limits};
+use crate::metrics::ServerMetrics;
+use crate::models::{HealthStatus, MetricsData, StatusReport};
+use crate::recovery;
+use crate::response::{error_reply, reply, reply_with_status};
+use crate::timing;
+
+/// Builds listed by `/status`.
+const STATUS_RECENT_BUILDS: u32 = 5;
+
+/// Shared state handed to every handler.
+#[derive(Clone)]
+pub struct AppState {
+    pub builds: BuildManager,
+    /// Longest a long-poll request is held open.
+    pub poll_timeout: Duration,
+    pub limits: Limits,
+    pub metrics: Arc<ServerMetrics>,
+    /// Stamps response envelopes.
+    pub clock: SharedClock,
+}
+
+/// The public API with its full middleware stack. `/metrics` is left out
+/// when it is served on its own port.
+pub fn router(state: AppState, config: &Config) -> Router {
+    let mut routes = Router::new()
+        .route("/health", get(health))
+        .route("/api", get(api_info))
+        .route("/build", post(build_trigger))
+        .route("/build/{id}", delete(build_cancel))
+        .route("/build/{id}/logs", get(build_logs))
+        .route("/builds", get(build_history))
+        .route("/events/poll", get(poll_events))
+        .route("/status", get(status));
+    if config.metrics_port.is_none() {
+        routes = routes.route("/metrics", get(metrics));
+    }
+    let mut request_log = None;
+    if let Some(auth) = config.admin.clone() {
+        let log = RequestLog::new(state.clock.clone());
+        let features = Features::from_config(config);
+        routes = routes.nest("/admin", admin::router(auth, log.clone(), features));
+        request_log = Some(log);
+    }
+
+    // CORS headers
+    let cors = CorsLayer::new()
+        .allow_origin(AllowOrigin::mirror_request())
+        .allow_headers([
+            HeaderName::from_static("content-type"),
+            HeaderName::from_static(idempotency::IDEMPOTENCY_KEY_HEADER),
+            HeaderName::from_static(recovery::REQUEST_ID_HEADER),
+        ])
+        .allow_methods([Method::GET, Method::POST, Method::DELETE]);
+
+    let builds = state.builds.clone();
+    let response_cache = ResponseCache::new(
+        config.cache_ttls.clone(),
+        move || builds.events().last_seq(),
+        state.metrics.clone(),
+        state.clock.clone(),
+    );
+
+    // Outermost first. Every request is traced, and its response gzipped
+    // when the client accepts it (event streams excepted). Every request
+    // holds an in-flight slot while its handler runs; CORS answers
+    // preflights, the cache answers hits and identical concurrent misses are
+    // coalesced before that. `/admin` adds its Basic auth as a route layer.
+    let middleware = ServiceBuilder::new()
+        .layer(TraceLayer::new_for_http())
+        .layer(CompressionLayer::new())
+        .layer(middleware::from_fn(i18n::localize))
+        .layer(middleware::from_fn_with_state(
+            state.metrics.clone(),
+            recovery::catch_panic,
+        ))
+        .layer(middleware::from_fn(timing::server_timing))
+        .layer(middleware::from_fn_with_state(
+            IdempotencyCache::new(config.idempotency_ttl, state.clock.clone()),
+            idempotency::idempotency,
+        ))
+        .layer(cors)
+        .layer(middleware::from_fn_with_state(response_cache, cache::cache))
+        .layer(middleware::from_fn_with_state(
+            Coalescer::new(config.coalesce_routes.clone(), state.metrics.clone()),
+            coalesce::coalesce,
+        ))
+        .layer(middleware::from_fn_with_state(
+            state.limits.clone(),
+            limits::in_flight,
+        ));
+
+    let mut routes = routes.fallback(not_found).layer(middleware);
+    // Outside everything else, so panics and shed requests are logged too.
+    if let Some(log) = request_log {
+        routes = routes.layer(middleware::from_fn_with_state(log, admin::record_requests));
+    }
+    routes.with_state(state)
+}
+
+/// `/metrics` alone, for the dedicated metrics listener.
+pub fn metrics_router(state: AppState) -> Router {
+    Router::new()
+        .route("/metrics", get(metrics))
+        .with_state(state)
+}
+
+async fn not_found() -> StatusCode {
+    StatusCode::NOT_FOUND
+}
+
+// Health endpoint
+async fn health(State(state): State<AppState>) -> Response {
+    timing::mark("deser");
+    reply(&*state.clock, HealthStatus::current())
+}
+
+// Metrics endpoint
+async fn metrics(State(state): State<AppState>) -> Response {
+    timing::mark("deser");
+    reply(&*state.clock, metrics_snapshot(&state))
+}
+
+/// The document served by `/metrics`.
+pub fn metrics_snapshot(state: &AppState) -> MetricsData {
+    MetricsData {
+        requests_served: 1247,
+        languages_integrated: 4,
+        build_time_seconds: 12.34,
+        components: vec![
+            "rust-backend".to_string(),
+            "go-cli".to_string(),
+            "node-frontend".to_string(),
+            "python-ml".to_string(),
+        ],
+        limits: state.limits.snapshot(),
+        timeouts: state.metrics.timeouts(),
+        handler_panics: state.metrics.handler_panics.load(Ordering::Relaxed),
+        cache: state.metrics.cache(),
+    }
+}
+
+// API info endpoint
+async fn api_info(State(state): State<AppState>) -> Response {
+    timing::mark("deser");
+    let endpoints = [
+        ("health", "GET /health - Service health check"),
+        ("metrics", "GET /metrics - Application metrics"),
+        ("api", "GET /api - API information"),
+        ("build", "POST /build - Trigger a gaffer-exec build"),
+        (
+            "build_logs",
+            "GET /build/{id}/logs - Stream build output (SSE)",
+        ),
+        (
+            "build_cancel",
+            "DELETE /build/{id} - Cancel a running build",
+        ),
+        (
+            "builds",
+            "GET /builds - Build history (?status=&limit=&sort=&order=)",
+        ),
+        (
+            "events_poll",
+            "GET /events/poll - Long-poll for build events (?cursor=&timeout=&limit=)",
+        ),
+        (
+            "status",
+            "GET /status - Health, metrics and recent builds in one call",
+        ),
+        (
+            "admin",
+            "GET /admin - Admin dashboard (HTTP Basic auth, when enabled)",
+        ),
+    ];
+
+    let info = serde_json::json!({
+        "name": "Multi-Language API",
+        "description": "Rust backend for polyglot application",
+        "version": "1.0.0",
+        "endpoints": endpoints.iter().map(|(k, v)| {
+            serde_json::json!({
+                "endpoint": k,
+                "description": v
+            })
+        }).collect::<Vec<_>>()
+    });
+
+    reply(&*state.clock, info)
+}
+
+// Aggregated status: health, metrics and the latest builds
+async fn status(State(state): State<AppState>) -> Response {
+    timing::mark("deser");
+    let recent = BuildQuery {
+        limit: Some(STATUS_RECENT_BUILDS),
+        ..BuildQuery::default()
+    };
+    match state.builds.list(&recent) {
+        Ok(recent_builds) => reply(
+            &*state.clock,
+            StatusReport {
+                health: HealthStatus::current(),
+                metrics: metrics_snapshot(&state),
+                recent_builds,
+            },
+        ),
+        Err(e) => error_reply(
+            &*state.clock,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            i18n::text("error-build-history", &[("error", e.to_string().into())]),
+        ),
+    }
+}
+
+// Build trigger endpoint
+async fn build_trigger(State(state): State<AppState>) -> Response {
+    timing::mark("deser");
+    match state.builds.start() {
+        Ok(summary) => reply_with_status(&*state.clock, summary, StatusCode::ACCEPTED),
+        Err(e) => error_reply(
+            &*state.clock,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            i18n::text("error-start-build", &[("error", e.to_string().into())]),
+        ),
+    }
+}
+
+// Build cancellation
+async fn build_cancel(State(state): State<AppState>, Path(id): Path<u64>) -> Response {
+    timing::mark("deser");
+    match state.builds.cancel(id) {
+        Ok(summary) => reply_with_status(&*state.clock, summary, StatusCode::ACCEPTED),
+        Err(e) => {
+            let id_arg = ("id", FluentValue::from(id));
+            let (status, message) = match e {
+                CancelError::NotFound => (
+                    StatusCode::NOT_FOUND,
+                    i18n::text("error-cancel-not-found", &[id_arg]),
+                ),
+                CancelError::NotRunning => (
+                    StatusCode::CONFLICT,
+                    i18n::text("error-cancel-not-running", &[id_arg]),
+                ),
+                CancelError::Signal(e) => (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    i18n::text(
+                        "error-cancel-signal",
+                        &[id_arg, ("error", e.to_string().into())],
+                    ),
+                ),
+            };
+            error_reply(&*state.clock, status, message)
+        }
+    }
+}
+
+// Build history
+async fn build_history(State(state): State<AppState>, Query(query): Query<BuildQuery>) -> Response {
+    timing::mark("deser");
+    match state.builds.list(&query) {
+        Ok(records) => reply_with_status(&*state.clock, records, StatusCode::OK),
+        Err(e) => error_reply(
+            &*state.clock,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            i18n::text("error-build-history", &[("error", e.to_string().into())]),
+        ),
+    }
+}
+
+// Build log stream (server-sent events)
+async fn build_logs(State(state): State<AppState>, Path(id): Path<u64>) -> Response {
+    timing::mark("deser");
+    match state.builds.subscribe(id) {
+        Some(events) => {
+            let events = events.map(|event| Ok::<_, Infallible>(sse_event(&event)));
+            Sse::new(events)
+                .keep_alive(KeepAlive::default())
+                .into_response()
+        }
+        None => error_reply(
+            &*state.clock,
+            StatusCode::NOT_FOUND,
+            i18n::text("error-build-not-found", &[("id", id.into())]),
+        ),
+    }
+}
+
+// Event long-polling, for clients without SSE support
+async fn poll_events(State(state): State<AppState>, Query(query): Query<PollQuery>) -> Response {
+    timing::mark("deser");
+    let batch = state.builds.events().poll(&query, state.poll_timeout).await;
+    reply(&*state.clock, batch)
+}
+
+fn sse_event(event: &BuildEvent) -> Event {
+    match event {
+        BuildEvent::Output { stream, line } => Event::default()
+            .event(match stream {
+                OutputStream::Stdout => "stdout",
+                OutputStream::Stderr => "stderr",
+            })
+            .data(line),
+        BuildEvent::Cancelling => Event::default()
+            .event("cancelling")
+            .data("SIGTERM sent to build process group"),
+        BuildEvent::Finished(summary) => Event::default()
+            .event("finished")
+            .json_data(summary)
+            .unwrap_or_default(),
+    }
+}
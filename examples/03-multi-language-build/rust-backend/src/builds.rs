@@ -0,0 +1,497 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::process::{ExitStatus, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures_util::stream::{self, Stream};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+use tokio::sync::broadcast;
+
+use crate::clock::SharedClock;
+use crate::config::Config;
+use crate::events::{AppEvent, EventLog};
+use crate::history::{BuildHistory, BuildQuery};
+
+pub type BuildId = u64;
+
+/// Number of events a slow log subscriber may fall behind before it starts
+/// skipping lines.
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Finished builds kept in memory, logs included, so late log subscribers
+/// can still replay them. Older ones are only in the [`BuildHistory`].
+const RETAINED_BUILDS: usize = 32;
+
+/// Output lines kept per build for replay. Later lines still reach live
+/// subscribers but aren't stored.
+const MAX_LOG_LINES: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BuildStatus {
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl BuildStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BuildStatus::Running => "running",
+            BuildStatus::Succeeded => "succeeded",
+            BuildStatus::Failed => "failed",
+            BuildStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "running" => Some(BuildStatus::Running),
+            "succeeded" => Some(BuildStatus::Succeeded),
+            "failed" => Some(BuildStatus::Failed),
+            "cancelled" => Some(BuildStatus::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildSummary {
+    pub id: BuildId,
+    pub command: String,
+    pub status: BuildStatus,
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+    pub exit_code: Option<i32>,
+    pub duration_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// A single entry in a build's log: one line of output, a cancellation
+/// request, or the final result.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum BuildEvent {
+    Output { stream: OutputStream, line: String },
+    Cancelling,
+    Finished(BuildSummary),
+}
+
+#[derive(Debug)]
+pub enum CancelError {
+    NotFound,
+    NotRunning,
+    Signal(io::Error),
+}
+
+impl fmt::Display for CancelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CancelError::NotFound => write!(f, "build not found"),
+            CancelError::NotRunning => write!(f, "build is not running"),
+            CancelError::Signal(e) => write!(f, "failed to signal build: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CancelError {}
+
+#[derive(Debug, Clone, Copy)]
+enum Signal {
+    Terminate,
+    Kill,
+}
+
+struct Build {
+    summary: BuildSummary,
+    started: Instant,
+    pid: Option<u32>,
+    cancel_requested: bool,
+    log: Vec<BuildEvent>,
+    /// Output lines in `log`, to enforce [`MAX_LOG_LINES`].
+    log_lines: usize,
+    events: broadcast::Sender<BuildEvent>,
+}
+
+struct Inner {
+    command: String,
+    dir: PathBuf,
+    kill_grace: Duration,
+    next_id: AtomicU64,
+    builds: Mutex<HashMap<BuildId, Build>>,
+    history: BuildHistory,
+    events: EventLog,
+    clock: SharedClock,
+}
+
+/// Spawns gaffer builds as child processes and keeps their output around so
+/// it can be streamed to any number of log subscribers, late joiners included.
+/// Build records (but not their logs) are persisted to a [`BuildHistory`];
+/// only running builds and the [`RETAINED_BUILDS`] most recent finished ones
+/// stay in memory.
+#[derive(Clone)]
+pub struct BuildManager {
+    inner: Arc<Inner>,
+}
+
+impl BuildManager {
+    pub fn new(
+        config: &Config,
+        history: BuildHistory,
+        clock: SharedClock,
+    ) -> rusqlite::Result<Self> {
+        let next_id = history.last_id()? + 1;
+        Ok(BuildManager {
+            inner: Arc::new(Inner {
+                command: config.build_command.clone(),
+                dir: config.build_dir.clone(),
+                kill_grace: config.build_kill_grace,
+                next_id: AtomicU64::new(next_id),
+                builds: Mutex::new(HashMap::new()),
+                history,
+                events: EventLog::new(clock.clone()),
+                clock,
+            }),
+        })
+    }
+
+    /// Starts the configured build command and returns its initial summary.
+    pub fn start(&self) -> io::Result<BuildSummary> {
+        let mut command = shell_command(&self.inner.command);
+        command
+            .current_dir(&self.inner.dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        // Run the build in its own process group so cancellation reaches
+        // gaffer-exec and every task it spawned, not just the shell.
+        #[cfg(unix)]
+        command.process_group(0);
+        let mut child = command.spawn()?;
+
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        let summary = BuildSummary {
+            id,
+            command: self.inner.command.clone(),
+            status: BuildStatus::Running,
+            started_at: self.inner.clock.unix_timestamp(),
+            finished_at: None,
+            exit_code: None,
+            duration_ms: None,
+        };
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        self.lock().insert(
+            id,
+            Build {
+                summary: summary.clone(),
+                started: Instant::now(),
+                pid: child.id(),
+                cancel_requested: false,
+                log: Vec::new(),
+                log_lines: 0,
+                events,
+            },
+        );
+        self.persist(&summary);
+        self.inner
+            .events
+            .publish(AppEvent::BuildStarted(summary.clone()));
+
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let (_, _, status) = tokio::join!(
+                manager.pump(id, OutputStream::Stdout, stdout),
+                manager.pump(id, OutputStream::Stderr, stderr),
+                child.wait(),
+            );
+            manager.finish(id, status);
+        });
+
+        Ok(summary)
+    }
+
+    /// The build's summary, from memory or, once evicted, from the history.
+    pub fn get(&self, id: BuildId) -> rusqlite::Result<Option<BuildSummary>> {
+        if let Some(build) = self.lock().get(&id) {
+            return Ok(Some(build.summary.clone()));
+        }
+        self.inner.history.get(id)
+    }
+
+    /// Asks a running build to stop: SIGTERM to its process group now, and
+    /// SIGKILL if it is still running once the grace period has passed.
+    pub fn cancel(&self, id: BuildId) -> Result<BuildSummary, CancelError> {
+        let summary = {
+            let mut builds = self.lock();
+            let build = builds.get_mut(&id).ok_or(CancelError::NotFound)?;
+            if build.summary.status != BuildStatus::Running {
+                return Err(CancelError::NotRunning);
+            }
+            if build.cancel_requested {
+                return Ok(build.summary.clone());
+            }
+            let pid = build.pid.ok_or(CancelError::NotRunning)?;
+            signal_group(pid, Signal::Terminate).map_err(CancelError::Signal)?;
+            build.cancel_requested = true;
+            build.summary.clone()
+        };
+        self.record(id, BuildEvent::Cancelling);
+
+        let manager = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(manager.inner.kill_grace).await;
+            let builds = manager.lock();
+            if let Some(build) = builds.get(&id) {
+                if build.summary.status == BuildStatus::Running {
+                    if let Some(pid) = build.pid {
+                        let _ = signal_group(pid, Signal::Kill);
+                    }
+                }
+            }
+        });
+
+        Ok(summary)
+    }
+
+    /// Past and current builds from the persistent history.
+    pub fn list(&self, query: &BuildQuery) -> rusqlite::Result<Vec<BuildSummary>> {
+        self.inner.history.query(query)
+    }
+
+    /// Application-wide log of build activity, for long-polling clients.
+    pub fn events(&self) -> &EventLog {
+        &self.inner.events
+    }
+
+    /// Returns the build's events so far followed by live ones, ending after
+    /// the `Finished` event. `None` if no such build is in memory.
+    pub fn subscribe(&self, id: BuildId) -> Option<impl Stream<Item = BuildEvent>> {
+        let (backlog, receiver) = {
+            let builds = self.lock();
+            let build = builds.get(&id)?;
+            // Snapshot and subscribe under the same lock so no line is missed
+            // or delivered twice.
+            (
+                build.log.iter().cloned().collect::<VecDeque<_>>(),
+                build.events.subscribe(),
+            )
+        };
+
+        Some(stream::unfold(
+            (backlog, receiver, false),
+            |(mut backlog, mut receiver, done)| async move {
+                if done {
+                    return None;
+                }
+                let event = match backlog.pop_front() {
+                    Some(event) => event,
+                    None => loop {
+                        match receiver.recv().await {
+                            Ok(event) => break event,
+                            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                            Err(broadcast::error::RecvError::Closed) => return None,
+                        }
+                    },
+                };
+                let done = matches!(event, BuildEvent::Finished(_));
+                Some((event, (backlog, receiver, done)))
+            },
+        ))
+    }
+
+    async fn pump<R>(&self, id: BuildId, stream: OutputStream, reader: Option<R>)
+    where
+        R: AsyncRead + Unpin,
+    {
+        let Some(reader) = reader else { return };
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            self.record(id, BuildEvent::Output { stream, line });
+        }
+    }
+
+    fn record(&self, id: BuildId, event: BuildEvent) {
+        let app_event = match &event {
+            BuildEvent::Output { stream, line } => AppEvent::BuildOutput {
+                build_id: id,
+                stream: *stream,
+                line: line.clone(),
+            },
+            BuildEvent::Cancelling => AppEvent::BuildCancelling { build_id: id },
+            BuildEvent::Finished(summary) => AppEvent::BuildFinished(summary.clone()),
+        };
+        if let Some(build) = self.lock().get_mut(&id) {
+            let output = matches!(event, BuildEvent::Output { .. });
+            if !output || build.log_lines < MAX_LOG_LINES {
+                build.log_lines += usize::from(output);
+                build.log.push(event.clone());
+            }
+            // No receivers just means nobody is watching the logs right now.
+            let _ = build.events.send(event);
+        }
+        self.inner.events.publish(app_event);
+    }
+
+    fn finish(&self, id: BuildId, status: io::Result<ExitStatus>) {
+        let summary = {
+            let mut builds = self.lock();
+            let Some(build) = builds.get_mut(&id) else {
+                return;
+            };
+            let exit_code = status.as_ref().ok().and_then(ExitStatus::code);
+            build.summary.status = match status {
+                _ if build.cancel_requested => BuildStatus::Cancelled,
+                Ok(status) if status.success() => BuildStatus::Succeeded,
+                _ => BuildStatus::Failed,
+            };
+            build.summary.exit_code = exit_code;
+            build.summary.finished_at = Some(self.inner.clock.unix_timestamp());
+            build.summary.duration_ms = Some(build.started.elapsed().as_millis() as u64);
+            build.summary.clone()
+        };
+        self.persist(&summary);
+        self.evict(id);
+        self.record(id, BuildEvent::Finished(summary));
+    }
+
+    /// Drops the oldest finished builds beyond [`RETAINED_BUILDS`], other than
+    /// `finishing`, whose `Finished` event is still to be sent. The others'
+    /// subscribers have all received theirs.
+    fn evict(&self, finishing: BuildId) {
+        let mut builds = self.lock();
+        let mut finished: Vec<BuildId> = builds
+            .values()
+            .filter(|build| build.summary.status != BuildStatus::Running)
+            .map(|build| build.summary.id)
+            .filter(|&id| id != finishing)
+            .collect();
+        if finished.len() < RETAINED_BUILDS {
+            return;
+        }
+        finished.sort_unstable();
+        // Keep room for `finishing`.
+        for id in &finished[..=finished.len() - RETAINED_BUILDS] {
+            builds.remove(id);
+        }
+    }
+
+    fn persist(&self, summary: &BuildSummary) {
+        // History is best-effort: a failed write must not take the build down.
+        if let Err(e) = self.inner.history.save(summary) {
+            eprintln!("⚠️  Failed to record build {}: {}", summary.id, e);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<BuildId, Build>> {
+        // A poisoned map still holds consistent build records; keep serving them.
+        self.inner
+            .builds
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(unix)]
+fn signal_group(pgid: u32, signal: Signal) -> io::Result<()> {
+    let signal = match signal {
+        Signal::Terminate => libc::SIGTERM,
+        Signal::Kill => libc::SIGKILL,
+    };
+    // SAFETY: killpg has no memory-safety preconditions. The group id is the
+    // pid of a child spawned as its own process-group leader.
+    if unsafe { libc::killpg(pgid as libc::pid_t, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(unix))]
+fn signal_group(_pgid: u32, _signal: Signal) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "build cancellation requires process groups",
+    ))
+}
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use futures_util::StreamExt;
+
+    fn manager(command: &str) -> BuildManager {
+        let config = Config {
+            build_command: command.to_string(),
+            build_dir: PathBuf::from("."),
+            ..Config::default()
+        };
+        let history = BuildHistory::open(":memory:").unwrap();
+        BuildManager::new(&config, history, Arc::new(MockClock::at(0))).unwrap()
+    }
+
+    async fn wait(builds: &BuildManager, id: BuildId) -> Vec<BuildEvent> {
+        builds.subscribe(id).unwrap().collect().await
+    }
+
+    #[tokio::test]
+    async fn test_finished_builds_are_evicted() {
+        let builds = manager("echo done");
+        let mut ids = Vec::new();
+        for _ in 0..RETAINED_BUILDS + 2 {
+            let id = builds.start().unwrap().id;
+            wait(&builds, id).await;
+            ids.push(id);
+        }
+
+        assert_eq!(builds.lock().len(), RETAINED_BUILDS);
+        for &id in &ids[..2] {
+            assert!(builds.subscribe(id).is_none());
+            let summary = builds.get(id).unwrap().unwrap();
+            assert_eq!(summary.status, BuildStatus::Succeeded);
+        }
+        let last = *ids.last().unwrap();
+        assert!(builds.subscribe(last).is_some());
+        assert!(builds.get(last).unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_log_replay_is_capped() {
+        let builds = manager(&format!("seq {}", MAX_LOG_LINES + 5));
+        let id = builds.start().unwrap().id;
+        wait(&builds, id).await;
+
+        let replayed = wait(&builds, id).await;
+        assert_eq!(replayed.len(), MAX_LOG_LINES + 1);
+        assert!(matches!(replayed.last(), Some(BuildEvent::Finished(_))));
+    }
+}
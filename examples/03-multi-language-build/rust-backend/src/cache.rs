@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+use axum::body::{Body, Bytes};
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::header::{HeaderMap, HeaderValue, ACCEPT_LANGUAGE};
+use axum::http::{Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use crate::clock::SharedClock;
+use crate::metrics::ServerMetrics;
+
+pub const CACHE_HEADER: &str = "x-cache";
+
+/// Cached responses kept at most; new ones are not stored beyond this until
+/// older ones expire.
+const MAX_ENTRIES: usize = 1024;
+
+/// Built-in TTLs by route; `[cache] ttl_secs` overrides or extends them.
+pub fn default_ttls() -> HashMap<String, Duration> {
+    [
+        ("/health", 5),
+        ("/api", 60),
+        ("/metrics", 1),
+        ("/builds", 5),
+    ]
+    .into_iter()
+    .map(|(route, secs)| (route.to_string(), Duration::from_secs(secs)))
+    .collect()
+}
+
+#[derive(Clone)]
+struct Cached {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+    expires: SystemTime,
+    version: u64,
+}
+
+/// In-memory cache of `GET` responses, keyed by path, query and
+/// `Accept-Language`, for the routes given a TTL.
+///
+/// Entries are dropped when their TTL runs out, when any `POST` or `DELETE`
+/// succeeds, and when the version source moves on (the event log's sequence
+/// number, so a build starting, logging or finishing invalidates `/builds`
+/// without waiting for the TTL).
+#[derive(Clone)]
+pub struct ResponseCache {
+    ttls: Arc<HashMap<String, Duration>>,
+    entries: Arc<Mutex<HashMap<String, Cached>>>,
+    version: Arc<dyn Fn() -> u64 + Send + Sync>,
+    metrics: Arc<ServerMetrics>,
+    clock: SharedClock,
+}
+
+impl ResponseCache {
+    pub fn new(
+        ttls: HashMap<String, Duration>,
+        version: impl Fn() -> u64 + Send + Sync + 'static,
+        metrics: Arc<ServerMetrics>,
+        clock: SharedClock,
+    ) -> Self {
+        ResponseCache {
+            ttls: Arc::new(ttls),
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            version: Arc::new(version),
+            metrics,
+            clock,
+        }
+    }
+
+    /// Drops every cached response.
+    pub fn invalidate(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Cached>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn lookup(&self, key: &str, version: u64) -> Option<Cached> {
+        let mut entries = self.lock();
+        match entries.get(key) {
+            Some(cached) if cached.expires > self.clock.now() && cached.version == version => {
+                Some(cached.clone())
+            }
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn store(&self, key: String, cached: Cached) {
+        let mut entries = self.lock();
+        if entries.len() >= MAX_ENTRIES {
+            let now = self.clock.now();
+            entries.retain(|_, cached| cached.expires > now);
+        }
+        if entries.len() < MAX_ENTRIES {
+            entries.insert(key, cached);
+        }
+    }
+}
+
+/// Middleware answering `GET`s on cached routes from the [`ResponseCache`]
+/// and clearing it after successful mutations. Responses carry `x-cache:
+/// hit` or `x-cache: miss`.
+pub async fn cache(State(cache): State<ResponseCache>, req: Request, next: Next) -> Response {
+    if req.method() != Method::GET {
+        let mutation = req.method() == Method::POST || req.method() == Method::DELETE;
+        let response = next.run(req).await;
+        if mutation && response.status().is_success() {
+            cache.invalidate();
+        }
+        return response;
+    }
+
+    let ttl = req
+        .extensions()
+        .get::<MatchedPath>()
+        .and_then(|route| cache.ttls.get(route.as_str()))
+        .copied();
+    let Some(ttl) = ttl.filter(|ttl| !ttl.is_zero()) else {
+        return next.run(req).await;
+    };
+
+    let key = request_key(&req);
+    let version = (cache.version)();
+    if let Some(cached) = cache.lookup(&key, version) {
+        ServerMetrics::increment(&cache.metrics.cache_hits);
+        return cached_response(cached, "hit");
+    }
+    ServerMetrics::increment(&cache.metrics.cache_misses);
+
+    let response = next.run(req).await;
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+    let (parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    let cached = Cached {
+        status: parts.status,
+        headers: parts.headers,
+        body,
+        expires: cache.clock.now() + ttl,
+        version,
+    };
+    cache.store(key, cached.clone());
+    cached_response(cached, "miss")
+}
+
+/// Identifies a `GET` by path, query and `Accept-Language`.
+pub(crate) fn request_key(req: &Request) -> String {
+    let language = req
+        .headers()
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("");
+    format!(
+        "{}?{}|{}",
+        req.uri().path(),
+        req.uri().query().unwrap_or(""),
+        language
+    )
+}
+
+fn cached_response(cached: Cached, outcome: &'static str) -> Response {
+    let mut response = Response::new(Body::from(cached.body));
+    *response.status_mut() = cached.status;
+    *response.headers_mut() = cached.headers;
+    response
+        .headers_mut()
+        .insert(CACHE_HEADER, HeaderValue::from_static(outcome));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use axum::routing::get;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::clock::MockClock;
+
+    /// Serves `/short` (5s TTL), `/long` (60s) and `/uncached`, each
+    /// answering with how many times any of them has run.
+    fn app(clock: Arc<MockClock>, runs: Arc<AtomicUsize>) -> Router {
+        let handler = move || {
+            let runs = runs.clone();
+            async move { (runs.fetch_add(1, Ordering::SeqCst) + 1).to_string() }
+        };
+        let ttls = [("/short", 5), ("/long", 60)]
+            .into_iter()
+            .map(|(route, secs)| (route.to_string(), Duration::from_secs(secs)))
+            .collect();
+        let cache = ResponseCache::new(ttls, || 0, Arc::new(ServerMetrics::default()), clock);
+        Router::new()
+            .route("/short", get(handler.clone()))
+            .route("/long", get(handler.clone()))
+            .route("/uncached", get(handler))
+            .layer(axum::middleware::from_fn_with_state(cache, self::cache))
+    }
+
+    /// The body and `x-cache` header of `GET uri`.
+    async fn get_uri(app: &Router, uri: &str) -> (String, Option<String>) {
+        let req = Request::get(uri).body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(req).await.unwrap();
+        let outcome = response
+            .headers()
+            .get(CACHE_HEADER)
+            .map(|value| value.to_str().unwrap().to_string());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (String::from_utf8(body.to_vec()).unwrap(), outcome)
+    }
+
+    fn served(body: &str, outcome: &str) -> (String, Option<String>) {
+        (body.to_string(), Some(outcome.to_string()))
+    }
+
+    #[tokio::test]
+    async fn entries_expire_after_their_route_ttl() {
+        let clock = Arc::new(MockClock::at(1_700_000_000));
+        let app = app(clock.clone(), Arc::new(AtomicUsize::new(0)));
+
+        assert_eq!(get_uri(&app, "/short").await, served("1", "miss"));
+        assert_eq!(get_uri(&app, "/long").await, served("2", "miss"));
+
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(get_uri(&app, "/short").await, served("1", "hit"));
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(get_uri(&app, "/short").await, served("3", "miss"));
+        assert_eq!(get_uri(&app, "/long").await, served("2", "hit"));
+
+        clock.advance(Duration::from_secs(55));
+        assert_eq!(get_uri(&app, "/long").await, served("4", "miss"));
+    }
+
+    #[tokio::test]
+    async fn queries_are_cached_separately() {
+        let app = app(
+            Arc::new(MockClock::at(1_700_000_000)),
+            Arc::new(AtomicUsize::new(0)),
+        );
+
+        assert_eq!(get_uri(&app, "/long?page=1").await, served("1", "miss"));
+        assert_eq!(get_uri(&app, "/long?page=2").await, served("2", "miss"));
+        assert_eq!(get_uri(&app, "/long").await, served("3", "miss"));
+
+        assert_eq!(get_uri(&app, "/long?page=1").await, served("1", "hit"));
+        assert_eq!(get_uri(&app, "/long?page=2").await, served("2", "hit"));
+        assert_eq!(get_uri(&app, "/long").await, served("3", "hit"));
+    }
+
+    #[tokio::test]
+    async fn routes_without_a_ttl_are_not_cached() {
+        let app = app(
+            Arc::new(MockClock::at(1_700_000_000)),
+            Arc::new(AtomicUsize::new(0)),
+        );
+
+        assert_eq!(get_uri(&app, "/uncached").await, ("1".to_string(), None));
+        assert_eq!(get_uri(&app, "/uncached").await, ("2".to_string(), None));
+    }
+}
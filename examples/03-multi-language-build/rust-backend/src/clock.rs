@@ -0,0 +1,95 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Source of wall-clock time for response envelopes, build records and
+/// events, so tests can pin it with a [`MockClock`].
+pub trait Clock: Send + Sync {
+    fn now(&self) -> SystemTime;
+
+    /// Seconds since the Unix epoch. A clock set before 1970 reads as 0
+    /// instead of failing the request.
+    fn unix_timestamp(&self) -> u64 {
+        self.now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs())
+    }
+}
+
+/// A [`Clock`] shared between handlers, middleware and background tasks.
+pub type SharedClock = Arc<dyn Clock>;
+
+/// The real system clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+impl SystemClock {
+    pub fn shared() -> SharedClock {
+        Arc::new(SystemClock)
+    }
+}
+
+/// A clock that only moves when told to, with one-second resolution.
+#[derive(Debug, Default)]
+pub struct MockClock {
+    secs: AtomicU64,
+}
+
+impl MockClock {
+    /// A clock reading `secs` seconds after the Unix epoch.
+    pub fn at(secs: u64) -> Self {
+        MockClock {
+            secs: AtomicU64::new(secs),
+        }
+    }
+
+    pub fn set(&self, secs: u64) {
+        self.secs.store(secs, Ordering::Relaxed);
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.secs.fetch_add(by.as_secs(), Ordering::Relaxed);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.secs.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct BeforeEpoch;
+
+    impl Clock for BeforeEpoch {
+        fn now(&self) -> SystemTime {
+            UNIX_EPOCH - Duration::from_secs(60)
+        }
+    }
+
+    #[test]
+    fn mock_clock_moves_only_when_told() {
+        let clock = MockClock::at(1_700_000_000);
+        assert_eq!(clock.unix_timestamp(), 1_700_000_000);
+
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(clock.unix_timestamp(), 1_700_000_090);
+
+        clock.set(5);
+        assert_eq!(clock.unix_timestamp(), 5);
+    }
+
+    #[test]
+    fn time_before_the_epoch_reads_as_zero() {
+        assert_eq!(BeforeEpoch.unix_timestamp(), 0);
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use axum::body::{Body, Bytes};
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::header::{HeaderMap, HeaderValue};
+use axum::http::{Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use tokio::sync::watch;
+
+use crate::cache;
+use crate::metrics::ServerMetrics;
+
+pub const COALESCED_HEADER: &str = "x-coalesced";
+
+/// Routes coalesced by default: the non-streaming `GET`s.
+pub const DEFAULT_ROUTES: [&str; 5] = ["/health", "/api", "/metrics", "/builds", "/status"];
+
+/// A finished response, shared with every request that waited for it.
+struct Shared {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+type Outcome = watch::Receiver<Option<Arc<Shared>>>;
+
+enum Role {
+    /// Computes the response and publishes it.
+    Leader(watch::Sender<Option<Arc<Shared>>>),
+    /// Waits for the leader's response.
+    Follower(Outcome),
+}
+
+/// Identical `GET`s currently being computed, keyed like the response cache.
+#[derive(Clone)]
+pub struct Coalescer {
+    routes: Arc<HashSet<String>>,
+    pending: Arc<Mutex<HashMap<String, Outcome>>>,
+    metrics: Arc<ServerMetrics>,
+}
+
+impl Coalescer {
+    pub fn new(routes: HashSet<String>, metrics: Arc<ServerMetrics>) -> Self {
+        Coalescer {
+            routes: Arc::new(routes),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            metrics,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Outcome>> {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Middleware running identical concurrent `GET`s on coalesced routes only
+/// once: the first request computes the response and every request that
+/// arrives while it runs gets a copy, marked `x-coalesced: true`.
+///
+/// If the first request fails to finish (its client disconnects or its
+/// handler panics), the waiters run their own requests instead.
+pub async fn coalesce(State(coalescer): State<Coalescer>, req: Request, next: Next) -> Response {
+    let coalesced = req.method() == Method::GET
+        && req
+            .extensions()
+            .get::<MatchedPath>()
+            .is_some_and(|route| coalescer.routes.contains(route.as_str()));
+    if !coalesced {
+        return next.run(req).await;
+    }
+
+    let key = cache::request_key(&req);
+    let role = {
+        let mut pending = coalescer.lock();
+        match pending.get(&key) {
+            Some(outcome) => Role::Follower(outcome.clone()),
+            None => {
+                let (sender, receiver) = watch::channel(None);
+                pending.insert(key.clone(), receiver);
+                Role::Leader(sender)
+            }
+        }
+    };
+    let sender = match role {
+        Role::Leader(sender) => sender,
+        Role::Follower(mut outcome) => {
+            if let Ok(shared) = outcome.wait_for(Option::is_some).await {
+                if let Some(shared) = shared.as_ref() {
+                    ServerMetrics::increment(&coalescer.metrics.coalesced_requests);
+                    let mut response = shared_response(shared);
+                    response
+                        .headers_mut()
+                        .insert(COALESCED_HEADER, HeaderValue::from_static("true"));
+                    return response;
+                }
+            }
+            return next.run(req).await;
+        }
+    };
+
+    // Stops new requests joining once this one is done or abandoned.
+    let _leader = Leader {
+        coalescer: coalescer.clone(),
+        key,
+    };
+    let response = next.run(req).await;
+    let (parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    let shared = Arc::new(Shared {
+        status: parts.status,
+        headers: parts.headers,
+        body,
+    });
+    sender.send_replace(Some(shared.clone()));
+    shared_response(&shared)
+}
+
+struct Leader {
+    coalescer: Coalescer,
+    key: String,
+}
+
+impl Drop for Leader {
+    fn drop(&mut self) {
+        self.coalescer.lock().remove(&self.key);
+    }
+}
+
+fn shared_response(shared: &Shared) -> Response {
+    let mut response = Response::new(Body::from(shared.body.clone()));
+    *response.status_mut() = shared.status;
+    *response.headers_mut() = shared.headers.clone();
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use axum::routing::get;
+    use axum::Router;
+    use futures_util::future::join_all;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn app(runs: Arc<AtomicUsize>, metrics: Arc<ServerMetrics>) -> Router {
+        let slow = move || {
+            let runs = runs.clone();
+            async move {
+                runs.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                "expensive"
+            }
+        };
+        let routes = HashSet::from(["/slow".to_string()]);
+        Router::new()
+            .route("/slow", get(slow))
+            .layer(axum::middleware::from_fn_with_state(
+                Coalescer::new(routes, metrics),
+                coalesce,
+            ))
+    }
+
+    async fn get_all(app: &Router, paths: &[&str]) -> Vec<Response> {
+        join_all(paths.iter().map(|path| {
+            let req = Request::get(*path).body(Body::empty()).unwrap();
+            app.clone().oneshot(req)
+        }))
+        .await
+        .into_iter()
+        .map(Result::unwrap)
+        .collect()
+    }
+
+    #[tokio::test]
+    async fn identical_requests_share_one_run() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let metrics = Arc::new(ServerMetrics::default());
+        let app = app(runs.clone(), metrics.clone());
+
+        let responses = get_all(&app, &["/slow"; 5]).await;
+
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(metrics.coalesced_requests.load(Ordering::Relaxed), 4);
+        let marked = responses
+            .iter()
+            .filter(|response| response.headers().contains_key(COALESCED_HEADER))
+            .count();
+        assert_eq!(marked, 4);
+        for response in responses {
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            assert_eq!(body, "expensive");
+        }
+    }
+
+    #[tokio::test]
+    async fn different_queries_and_later_requests_run_again() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let app = app(runs.clone(), Arc::new(ServerMetrics::default()));
+
+        get_all(&app, &["/slow?a=1", "/slow?a=2"]).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+        get_all(&app, &["/slow?a=1"]).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,504 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::Parser;
+use serde::Deserialize;
+
+use crate::admin::AdminConfig;
+use crate::cache;
+use crate::coalesce;
+use crate::mqtt::MqttConfig;
+use crate::registry::RegistryConfig;
+
+const DEFAULT_BUILD_COMMAND: &str = "gaffer-exec run multi-language-build --graph graph.json";
+
+#[derive(Debug, Parser)]
+#[command(name = "rust-backend")]
+#[command(about = "Rust API server for the multi-language build example", long_about = None)]
+struct Args {
+    /// TOML config file; flags and environment variables override its values
+    #[arg(short, long, env = "GAFFER_BACKEND_CONFIG")]
+    config: Option<PathBuf>,
+
+    /// Shell command spawned by POST /build
+    #[arg(long, env = "GAFFER_BUILD_COMMAND")]
+    build_command: Option<String>,
+
+    /// Working directory for the build command
+    #[arg(long, env = "GAFFER_BUILD_DIR")]
+    build_dir: Option<PathBuf>,
+
+    /// SQLite database holding the build history
+    #[arg(long, env = "GAFFER_BUILD_DB")]
+    build_db: Option<PathBuf>,
+
+    /// Seconds between SIGTERM and SIGKILL when cancelling a build
+    #[arg(long, env = "GAFFER_BUILD_KILL_GRACE_SECS")]
+    build_kill_grace_secs: Option<u64>,
+
+    /// Serve /metrics on its own listener on this port instead of the main API port
+    #[arg(long, env = "GAFFER_METRICS_PORT")]
+    metrics_port: Option<u16>,
+
+    /// Write a JSON readiness report here once the listeners are bound
+    #[arg(long, env = "GAFFER_READY_FILE")]
+    ready_file: Option<PathBuf>,
+
+    /// Maximum number of open client connections
+    #[arg(long, env = "GAFFER_MAX_CONNECTIONS")]
+    max_connections: Option<usize>,
+
+    /// Maximum number of requests handled at once before answering 503
+    #[arg(long, env = "GAFFER_MAX_IN_FLIGHT")]
+    max_in_flight: Option<usize>,
+
+    /// Seconds a client gets to send a complete request head
+    #[arg(long, env = "GAFFER_HEADER_READ_TIMEOUT_SECS")]
+    header_read_timeout_secs: Option<u64>,
+
+    /// Seconds a connection may make no read or write progress
+    #[arg(long, env = "GAFFER_IDLE_TIMEOUT_SECS")]
+    idle_timeout_secs: Option<u64>,
+
+    /// Service registry to heartbeat to (e.g. http://localhost:9000)
+    #[arg(long, env = "GAFFER_REGISTRY_URL")]
+    registry_url: Option<String>,
+
+    /// Seconds a response is kept for replay under its Idempotency-Key
+    #[arg(long, env = "GAFFER_IDEMPOTENCY_TTL_SECS")]
+    idempotency_ttl_secs: Option<u64>,
+
+    /// Longest a GET /events/poll request is held open waiting for an event
+    #[arg(long, env = "GAFFER_POLL_TIMEOUT_SECS")]
+    poll_timeout_secs: Option<u64>,
+
+    /// MQTT broker host to publish metrics and events to
+    #[arg(long, env = "GAFFER_MQTT_HOST")]
+    mqtt_host: Option<String>,
+
+    /// Password for the /admin dashboard; the dashboard is off without one
+    #[arg(long, env = "GAFFER_ADMIN_PASSWORD", hide_env_values = true)]
+    admin_password: Option<String>,
+
+    /// Turn off the in-memory response cache
+    #[arg(long, env = "GAFFER_NO_RESPONSE_CACHE")]
+    no_response_cache: bool,
+
+    /// Advertise the API over mDNS as `_gaffer-api._tcp`
+    #[arg(long, env = "GAFFER_MDNS")]
+    mdns: bool,
+}
+
+/// Layout of the optional TOML config file. Every key is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FileConfig {
+    build: BuildSection,
+    server: ServerSection,
+    limits: LimitsSection,
+    timeouts: TimeoutsSection,
+    registry: RegistrySection,
+    mdns: MdnsSection,
+    idempotency: IdempotencySection,
+    events: EventsSection,
+    mqtt: MqttSection,
+    admin: AdminSection,
+    cache: CacheSection,
+    coalesce: CoalesceSection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct BuildSection {
+    command: Option<String>,
+    dir: Option<PathBuf>,
+    db: Option<PathBuf>,
+    kill_grace_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ServerSection {
+    metrics_port: Option<u16>,
+    ready_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct LimitsSection {
+    max_connections: Option<usize>,
+    max_in_flight: Option<usize>,
+    retry_after_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct TimeoutsSection {
+    header_read_secs: Option<u64>,
+    idle_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RegistrySection {
+    url: Option<String>,
+    advertise_address: Option<String>,
+    interval_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct MdnsSection {
+    enabled: Option<bool>,
+    instance: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct IdempotencySection {
+    ttl_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct EventsSection {
+    poll_timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct MqttSection {
+    host: Option<String>,
+    port: Option<u16>,
+    client_id: Option<String>,
+    metrics_topic: Option<String>,
+    events_topic: Option<String>,
+    interval_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct AdminSection {
+    username: Option<String>,
+    password: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct CacheSection {
+    enabled: Option<bool>,
+    ttl_secs: HashMap<String, u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct CoalesceSection {
+    routes: Option<Vec<String>>,
+}
+
+/// Runtime settings for the backend. Each value comes from the command line,
+/// then the environment, then the config file, then a built-in default.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Shell command spawned by `POST /build`.
+    pub build_command: String,
+    /// Working directory the build command runs in. Defaults to the example
+    /// root, where `graph.json` lives.
+    pub build_dir: PathBuf,
+    /// SQLite database holding the build history.
+    pub build_db: PathBuf,
+    /// How long a cancelled build gets between SIGTERM and SIGKILL.
+    pub build_kill_grace: Duration,
+    /// Port of the dedicated metrics listener, if any.
+    pub metrics_port: Option<u16>,
+    /// Where to write the readiness report, if anywhere. Removed again on
+    /// shutdown.
+    pub ready_file: Option<PathBuf>,
+    /// Connections beyond this many wait in the accept queue.
+    pub max_connections: usize,
+    /// Requests beyond this many are answered with 503.
+    pub max_in_flight: usize,
+    /// `Retry-After` sent with those 503s.
+    pub retry_after: Duration,
+    /// Time a client gets to send a complete request head.
+    pub header_read_timeout: Duration,
+    /// Time a connection may make no read or write progress.
+    pub idle_timeout: Duration,
+    /// Service registry to heartbeat to, if any.
+    pub registry: Option<RegistryConfig>,
+    /// How long a POST response is replayed for retries with the same
+    /// `Idempotency-Key`.
+    pub idempotency_ttl: Duration,
+    /// Longest a long-poll request waits for the next event.
+    pub poll_timeout: Duration,
+    /// MQTT broker to publish metrics and events to, if any.
+    pub mqtt: Option<MqttConfig>,
+    /// Credentials for the `/admin` dashboard, which is only served when set.
+    pub admin: Option<AdminConfig>,
+    /// How long `GET` responses are cached, by route. Routes not listed are
+    /// never cached.
+    pub cache_ttls: HashMap<String, Duration>,
+    /// Routes whose identical concurrent `GET`s share one response.
+    pub coalesce_routes: HashSet<String>,
+    /// mDNS instance name to advertise under, if announcing is enabled.
+    pub mdns_instance: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    /// A setting, by its config file name, and what is wrong with it.
+    Invalid(&'static str, &'static str),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "invalid config {}: {}", path.display(), e),
+            ConfigError::Invalid(setting, reason) => write!(f, "invalid {}: {}", setting, reason),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Parses the command line and environment, merging in the config file
+    /// if one was given.
+    pub fn load() -> Result<Self, ConfigError> {
+        let args = Args::parse();
+        let file = match &args.config {
+            Some(path) => read_file(path)?,
+            None => FileConfig::default(),
+        };
+
+        let defaults = Config::default();
+        Config {
+            build_command: args
+                .build_command
+                .or(file.build.command)
+                .unwrap_or(defaults.build_command),
+            build_dir: args
+                .build_dir
+                .or(file.build.dir)
+                .unwrap_or(defaults.build_dir),
+            build_db: args.build_db.or(file.build.db).unwrap_or(defaults.build_db),
+            build_kill_grace: args
+                .build_kill_grace_secs
+                .or(file.build.kill_grace_secs)
+                .map_or(defaults.build_kill_grace, Duration::from_secs),
+            metrics_port: args.metrics_port.or(file.server.metrics_port),
+            ready_file: args.ready_file.or(file.server.ready_file),
+            max_connections: args
+                .max_connections
+                .or(file.limits.max_connections)
+                .unwrap_or(defaults.max_connections),
+            max_in_flight: args
+                .max_in_flight
+                .or(file.limits.max_in_flight)
+                .unwrap_or(defaults.max_in_flight),
+            retry_after: file
+                .limits
+                .retry_after_secs
+                .map_or(defaults.retry_after, Duration::from_secs),
+            header_read_timeout: args
+                .header_read_timeout_secs
+                .or(file.timeouts.header_read_secs)
+                .map_or(defaults.header_read_timeout, Duration::from_secs),
+            idle_timeout: args
+                .idle_timeout_secs
+                .or(file.timeouts.idle_secs)
+                .map_or(defaults.idle_timeout, Duration::from_secs),
+            registry: args
+                .registry_url
+                .or(file.registry.url)
+                .map(|url| RegistryConfig {
+                    url,
+                    advertise_address: file
+                        .registry
+                        .advertise_address
+                        .unwrap_or_else(|| "http://127.0.0.1:8080".to_string()),
+                    interval: Duration::from_secs(file.registry.interval_secs.unwrap_or(15)),
+                }),
+            idempotency_ttl: args
+                .idempotency_ttl_secs
+                .or(file.idempotency.ttl_secs)
+                .map_or(defaults.idempotency_ttl, Duration::from_secs),
+            poll_timeout: args
+                .poll_timeout_secs
+                .or(file.events.poll_timeout_secs)
+                .map_or(defaults.poll_timeout, Duration::from_secs),
+            mqtt: args.mqtt_host.or(file.mqtt.host).map(|host| MqttConfig {
+                host,
+                port: file.mqtt.port.unwrap_or(1883),
+                client_id: file
+                    .mqtt
+                    .client_id
+                    .unwrap_or_else(|| "rust-backend".to_string()),
+                metrics_topic: file
+                    .mqtt
+                    .metrics_topic
+                    .unwrap_or_else(|| "gaffer/rust-backend/metrics".to_string()),
+                events_topic: file
+                    .mqtt
+                    .events_topic
+                    .unwrap_or_else(|| "gaffer/rust-backend/events".to_string()),
+                interval: Duration::from_secs(file.mqtt.interval_secs.unwrap_or(10)),
+            }),
+            admin: args
+                .admin_password
+                .or(file.admin.password)
+                .map(|password| AdminConfig {
+                    username: file.admin.username.unwrap_or_else(|| "admin".to_string()),
+                    password,
+                }),
+            cache_ttls: if args.no_response_cache || file.cache.enabled == Some(false) {
+                HashMap::new()
+            } else {
+                let mut ttls = defaults.cache_ttls;
+                for (route, secs) in file.cache.ttl_secs {
+                    ttls.insert(route, Duration::from_secs(secs));
+                }
+                ttls
+            },
+            coalesce_routes: file
+                .coalesce
+                .routes
+                .map_or(defaults.coalesce_routes, |routes| {
+                    routes.into_iter().collect()
+                }),
+            mdns_instance: (args.mdns || file.mdns.enabled.unwrap_or(false)).then(|| {
+                file.mdns
+                    .instance
+                    .unwrap_or_else(|| "rust-backend".to_string())
+            }),
+        }
+        .validate()
+    }
+
+    /// Rejects settings the server can't run with.
+    fn validate(self) -> Result<Self, ConfigError> {
+        // Zero slots would accept no connection, or shed every request.
+        if self.max_connections == 0 {
+            return Err(ConfigError::Invalid(
+                "max_connections",
+                "must be at least 1",
+            ));
+        }
+        if self.max_in_flight == 0 {
+            return Err(ConfigError::Invalid("max_in_flight", "must be at least 1"));
+        }
+        // A zero interval would heartbeat the registry in a busy loop.
+        if self
+            .registry
+            .as_ref()
+            .is_some_and(|registry| registry.interval.is_zero())
+        {
+            return Err(ConfigError::Invalid(
+                "registry.interval_secs",
+                "must be at least 1",
+            ));
+        }
+        Ok(self)
+    }
+}
+
+impl Default for Config {
+    /// The built-in defaults, with no registry, MQTT bridge, admin dashboard
+    /// or mDNS announcement.
+    fn default() -> Self {
+        Config {
+            build_command: DEFAULT_BUILD_COMMAND.to_string(),
+            build_dir: PathBuf::from(".."),
+            build_db: PathBuf::from("builds.db"),
+            build_kill_grace: Duration::from_secs(10),
+            metrics_port: None,
+            ready_file: None,
+            max_connections: 1024,
+            max_in_flight: 256,
+            retry_after: Duration::from_secs(1),
+            header_read_timeout: Duration::from_secs(10),
+            idle_timeout: Duration::from_secs(60),
+            registry: None,
+            idempotency_ttl: Duration::from_secs(86_400),
+            poll_timeout: Duration::from_secs(30),
+            mqtt: None,
+            admin: None,
+            cache_ttls: cache::default_ttls(),
+            coalesce_routes: coalesce::DEFAULT_ROUTES
+                .iter()
+                .map(|route| route.to_string())
+                .collect(),
+            mdns_instance: None,
+        }
+    }
+}
+
+fn read_file(path: &Path) -> Result<FileConfig, ConfigError> {
+    let text = fs::read_to_string(path).map_err(|e| ConfigError::Read(path.to_path_buf(), e))?;
+    toml::from_str(&text).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zero_limits_are_rejected() {
+        assert!(Config::default().validate().is_ok());
+
+        let err = Config {
+            max_connections: 0,
+            ..Config::default()
+        }
+        .validate()
+        .err()
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "invalid max_connections: must be at least 1"
+        );
+
+        let err = Config {
+            max_in_flight: 0,
+            ..Config::default()
+        }
+        .validate()
+        .err()
+        .unwrap();
+        assert_eq!(err.to_string(), "invalid max_in_flight: must be at least 1");
+    }
+
+    #[test]
+    fn test_zero_registry_interval_is_rejected() {
+        let registry = |secs| {
+            Some(RegistryConfig {
+                url: "http://registry".to_string(),
+                advertise_address: "http://127.0.0.1:8080".to_string(),
+                interval: Duration::from_secs(secs),
+            })
+        };
+        let config = Config {
+            registry: registry(1),
+            ..Config::default()
+        };
+        assert!(config.validate().is_ok());
+
+        let err = Config {
+            registry: registry(0),
+            ..Config::default()
+        }
+        .validate()
+        .err()
+        .unwrap();
+        assert_eq!(
+            err.to_string(),
+            "invalid registry.interval_secs: must be at least 1"
+        );
+    }
+}
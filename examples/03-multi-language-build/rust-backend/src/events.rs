@@ -0,0 +1,245 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+use crate::builds::{BuildId, BuildSummary, OutputStream};
+use crate::clock::SharedClock;
+
+/// Events kept for clients that fall behind; older ones are dropped.
+const EVENT_LOG_CAPACITY: usize = 1000;
+
+/// Default and maximum number of events returned by one poll.
+const DEFAULT_POLL_LIMIT: usize = 100;
+const MAX_POLL_LIMIT: usize = 500;
+
+/// Something that happened in the backend, as seen by polling clients.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AppEvent {
+    BuildStarted(BuildSummary),
+    BuildOutput {
+        build_id: BuildId,
+        stream: OutputStream,
+        line: String,
+    },
+    BuildCancelling {
+        build_id: BuildId,
+    },
+    BuildFinished(BuildSummary),
+}
+
+/// An [`AppEvent`] with its position in the log.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoggedEvent {
+    /// Strictly increasing, starting at 1. Pass the last one seen back as
+    /// the `cursor` to resume.
+    pub seq: u64,
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub event: AppEvent,
+}
+
+/// Query string of `GET /events/poll`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PollQuery {
+    /// Last `seq` the client has seen; 0 (the default) for everything kept.
+    pub cursor: u64,
+    /// Seconds to wait for a new event when none is pending.
+    pub timeout: Option<u64>,
+    pub limit: Option<usize>,
+}
+
+/// Result of one poll.
+#[derive(Debug, Serialize)]
+pub struct EventBatch {
+    pub events: Vec<LoggedEvent>,
+    /// Cursor for the next poll.
+    pub next_cursor: u64,
+    /// True when events after the given cursor were already dropped from the
+    /// log, or the cursor came from before a server restart.
+    pub missed: bool,
+}
+
+struct Log {
+    events: VecDeque<LoggedEvent>,
+    last_seq: u64,
+}
+
+/// Bounded, in-memory log of recent [`AppEvent`]s that clients read with a
+/// resume cursor, waiting for new entries when they are caught up.
+pub struct EventLog {
+    log: Mutex<Log>,
+    latest: watch::Sender<u64>,
+    clock: SharedClock,
+}
+
+impl EventLog {
+    pub fn new(clock: SharedClock) -> Self {
+        EventLog {
+            log: Mutex::new(Log {
+                events: VecDeque::with_capacity(EVENT_LOG_CAPACITY),
+                last_seq: 0,
+            }),
+            latest: watch::channel(0).0,
+            clock,
+        }
+    }
+
+    pub fn publish(&self, event: AppEvent) {
+        let seq = {
+            let mut log = self.lock();
+            log.last_seq += 1;
+            let seq = log.last_seq;
+            if log.events.len() == EVENT_LOG_CAPACITY {
+                log.events.pop_front();
+            }
+            log.events.push_back(LoggedEvent {
+                seq,
+                timestamp: self.clock.unix_timestamp(),
+                event,
+            });
+            seq
+        };
+        self.latest.send_replace(seq);
+    }
+
+    /// Sequence number of the newest event, 0 before the first.
+    pub fn last_seq(&self) -> u64 {
+        *self.latest.borrow()
+    }
+
+    /// Returns the events after `query.cursor`, waiting up to the query's
+    /// timeout (capped at `max_wait`) for one to arrive if there are none yet.
+    pub async fn poll(&self, query: &PollQuery, max_wait: Duration) -> EventBatch {
+        let wait = query
+            .timeout
+            .map_or(max_wait, Duration::from_secs)
+            .min(max_wait);
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_POLL_LIMIT)
+            .clamp(1, MAX_POLL_LIMIT);
+
+        let mut latest = self.latest.subscribe();
+        let batch = self.read(query.cursor, limit);
+        if !batch.events.is_empty() || batch.missed || wait.is_zero() {
+            return batch;
+        }
+
+        let cursor = batch.next_cursor;
+        // Timing out just means nothing happened; answer with an empty batch.
+        let _ = tokio::time::timeout(wait, latest.wait_for(|&seq| seq > cursor)).await;
+        self.read(cursor, limit)
+    }
+
+    fn read(&self, cursor: u64, limit: usize) -> EventBatch {
+        let log = self.lock();
+        // A cursor from the future means the server restarted; start over.
+        let (cursor, restarted) = if cursor > log.last_seq {
+            (0, true)
+        } else {
+            (cursor, false)
+        };
+        let oldest = log.events.front().map_or(log.last_seq + 1, |e| e.seq);
+        let missed = restarted || cursor + 1 < oldest;
+
+        let events: Vec<_> = log
+            .events
+            .iter()
+            .filter(|e| e.seq > cursor)
+            .take(limit)
+            .cloned()
+            .collect();
+        let next_cursor = events.last().map_or(cursor, |e| e.seq);
+        EventBatch {
+            events,
+            next_cursor,
+            missed,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Log> {
+        self.log.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::clock::MockClock;
+
+    fn cancelling(build_id: BuildId) -> AppEvent {
+        AppEvent::BuildCancelling { build_id }
+    }
+
+    fn query(cursor: u64) -> PollQuery {
+        PollQuery {
+            cursor,
+            ..PollQuery::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn resumes_from_the_cursor() {
+        let log = EventLog::new(Arc::new(MockClock::at(1_700_000_000)));
+        for id in 1..=3 {
+            log.publish(cancelling(id));
+        }
+
+        let batch = log.poll(&query(1), Duration::from_secs(1)).await;
+
+        assert_eq!(batch.events[0].timestamp, 1_700_000_000);
+        let seqs: Vec<_> = batch.events.iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, [2, 3]);
+        assert_eq!(batch.next_cursor, 3);
+        assert!(!batch.missed);
+    }
+
+    #[tokio::test]
+    async fn waits_for_the_next_event() {
+        let log = Arc::new(EventLog::new(Arc::new(MockClock::at(1_700_000_000))));
+        let publisher = log.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            publisher.publish(cancelling(7));
+        });
+
+        let batch = log.poll(&query(0), Duration::from_secs(5)).await;
+
+        assert_eq!(batch.events.len(), 1);
+        assert_eq!(batch.next_cursor, 1);
+    }
+
+    #[tokio::test]
+    async fn times_out_with_an_empty_batch() {
+        let log = EventLog::new(Arc::new(MockClock::at(1_700_000_000)));
+        log.publish(cancelling(1));
+
+        let batch = log.poll(&query(1), Duration::from_millis(20)).await;
+
+        assert!(batch.events.is_empty());
+        assert_eq!(batch.next_cursor, 1);
+    }
+
+    #[tokio::test]
+    async fn flags_dropped_and_restarted_cursors() {
+        let log = EventLog::new(Arc::new(MockClock::at(1_700_000_000)));
+        for id in 0..EVENT_LOG_CAPACITY as u64 + 5 {
+            log.publish(cancelling(id));
+        }
+
+        let behind = log.poll(&query(2), Duration::ZERO).await;
+        assert!(behind.missed);
+        assert_eq!(behind.events[0].seq, 6);
+
+        let restarted = log.poll(&query(5000), Duration::ZERO).await;
+        assert!(restarted.missed);
+        assert_eq!(restarted.events[0].seq, 6);
+    }
+}
@@ -0,0 +1,319 @@
+use std::path::Path;
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Deserialize;
+
+use crate::builds::{BuildId, BuildStatus, BuildSummary};
+
+const DEFAULT_LIMIT: u32 = 20;
+const MAX_LIMIT: u32 = 500;
+
+/// Query parameters accepted by `GET /builds`.
+#[derive(Debug, Default, Deserialize)]
+pub struct BuildQuery {
+    pub status: Option<BuildStatus>,
+    pub limit: Option<u32>,
+    pub sort: Option<SortField>,
+    pub order: Option<SortOrder>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    #[default]
+    StartedAt,
+    Duration,
+    Id,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Build records persisted in an embedded SQLite database, so history
+/// survives restarts of the backend.
+pub struct BuildHistory {
+    conn: Mutex<Connection>,
+}
+
+impl BuildHistory {
+    pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    fn init(conn: Connection) -> rusqlite::Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS builds (
+                id          INTEGER PRIMARY KEY,
+                command     TEXT    NOT NULL,
+                status      TEXT    NOT NULL,
+                started_at  INTEGER NOT NULL,
+                finished_at INTEGER,
+                exit_code   INTEGER,
+                duration_ms INTEGER
+            );
+            CREATE INDEX IF NOT EXISTS builds_status ON builds (status);",
+        )?;
+        // Builds still marked running belonged to a previous process that
+        // went away without recording their outcome.
+        conn.execute(
+            "UPDATE builds SET status = ?1 WHERE status = ?2",
+            params![BuildStatus::Failed.as_str(), BuildStatus::Running.as_str()],
+        )?;
+        Ok(BuildHistory {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Highest build id recorded so far, used to keep ids unique across restarts.
+    pub fn last_id(&self) -> rusqlite::Result<BuildId> {
+        let id: Option<i64> = self
+            .lock()
+            .query_row("SELECT MAX(id) FROM builds", [], |row| row.get(0))
+            .optional()?
+            .flatten();
+        Ok(id.unwrap_or(0) as BuildId)
+    }
+
+    /// Inserts or updates the record for `summary.id`.
+    pub fn save(&self, summary: &BuildSummary) -> rusqlite::Result<()> {
+        self.lock().execute(
+            "INSERT INTO builds (id, command, status, started_at, finished_at, exit_code, duration_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT (id) DO UPDATE SET
+                status = excluded.status,
+                finished_at = excluded.finished_at,
+                exit_code = excluded.exit_code,
+                duration_ms = excluded.duration_ms",
+            params![
+                summary.id as i64,
+                summary.command,
+                summary.status.as_str(),
+                summary.started_at as i64,
+                summary.finished_at.map(|t| t as i64),
+                summary.exit_code,
+                summary.duration_ms.map(|d| d as i64),
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get(&self, id: BuildId) -> rusqlite::Result<Option<BuildSummary>> {
+        self.lock()
+            .query_row(
+                "SELECT id, command, status, started_at, finished_at, exit_code, duration_ms
+                 FROM builds WHERE id = ?1",
+                params![id as i64],
+                summary_from_row,
+            )
+            .optional()
+    }
+
+    pub fn query(&self, query: &BuildQuery) -> rusqlite::Result<Vec<BuildSummary>> {
+        // Column and direction come from closed enums, so interpolating them is safe.
+        let column = match query.sort.unwrap_or_default() {
+            SortField::StartedAt => "started_at",
+            SortField::Duration => "duration_ms",
+            SortField::Id => "id",
+        };
+        let direction = match query.order.unwrap_or_default() {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        };
+        let sql = format!(
+            "SELECT id, command, status, started_at, finished_at, exit_code, duration_ms
+             FROM builds
+             WHERE ?1 IS NULL OR status = ?1
+             ORDER BY {column} {direction}, id {direction}
+             LIMIT ?2"
+        );
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+
+        let conn = self.lock();
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(
+            params![query.status.map(BuildStatus::as_str), limit],
+            summary_from_row,
+        )?;
+        rows.collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn summary_from_row(row: &Row<'_>) -> rusqlite::Result<BuildSummary> {
+    let status: String = row.get(2)?;
+    Ok(BuildSummary {
+        id: row.get::<_, i64>(0)? as BuildId,
+        command: row.get(1)?,
+        status: BuildStatus::parse(&status).unwrap_or(BuildStatus::Failed),
+        started_at: row.get::<_, i64>(3)? as u64,
+        finished_at: row.get::<_, Option<i64>>(4)?.map(|t| t as u64),
+        exit_code: row.get(5)?,
+        duration_ms: row.get::<_, Option<i64>>(6)?.map(|d| d as u64),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history() -> BuildHistory {
+        BuildHistory::init(Connection::open_in_memory().unwrap()).unwrap()
+    }
+
+    fn build(id: BuildId, status: BuildStatus, started_at: u64, duration_ms: u64) -> BuildSummary {
+        let running = status == BuildStatus::Running;
+        BuildSummary {
+            id,
+            command: format!("build-{}", id),
+            status,
+            started_at,
+            finished_at: (!running).then_some(started_at + duration_ms / 1000),
+            exit_code: (!running).then_some(i32::from(status != BuildStatus::Succeeded)),
+            duration_ms: (!running).then_some(duration_ms),
+        }
+    }
+
+    /// Mixed statuses, with start times and durations in different orders.
+    fn seeded() -> BuildHistory {
+        let history = history();
+        for summary in [
+            build(1, BuildStatus::Succeeded, 100, 3000),
+            build(2, BuildStatus::Failed, 300, 1000),
+            build(3, BuildStatus::Succeeded, 200, 5000),
+            build(4, BuildStatus::Failed, 400, 2000),
+            build(5, BuildStatus::Cancelled, 500, 500),
+        ] {
+            history.save(&summary).unwrap();
+        }
+        history
+    }
+
+    fn ids(history: &BuildHistory, query: BuildQuery) -> Vec<BuildId> {
+        history
+            .query(&query)
+            .unwrap()
+            .iter()
+            .map(|summary| summary.id)
+            .collect()
+    }
+
+    #[test]
+    fn test_status_filter() {
+        let history = seeded();
+        let failed = BuildQuery {
+            status: Some(BuildStatus::Failed),
+            ..BuildQuery::default()
+        };
+        assert_eq!(ids(&history, failed), [4, 2]);
+        let running = BuildQuery {
+            status: Some(BuildStatus::Running),
+            ..BuildQuery::default()
+        };
+        assert!(ids(&history, running).is_empty());
+        assert_eq!(ids(&history, BuildQuery::default()), [5, 4, 2, 3, 1]);
+    }
+
+    #[test]
+    fn test_sort_and_order() {
+        let history = seeded();
+        let sorted = |sort, order| BuildQuery {
+            sort: Some(sort),
+            order: Some(order),
+            ..BuildQuery::default()
+        };
+        assert_eq!(
+            ids(&history, sorted(SortField::StartedAt, SortOrder::Asc)),
+            [1, 3, 2, 4, 5]
+        );
+        assert_eq!(
+            ids(&history, sorted(SortField::Duration, SortOrder::Desc)),
+            [3, 1, 4, 2, 5]
+        );
+        assert_eq!(
+            ids(&history, sorted(SortField::Duration, SortOrder::Asc)),
+            [5, 2, 4, 1, 3]
+        );
+        assert_eq!(
+            ids(&history, sorted(SortField::Id, SortOrder::Asc)),
+            [1, 2, 3, 4, 5]
+        );
+    }
+
+    #[test]
+    fn test_limit_defaults_and_is_clamped() {
+        let history = history();
+        for id in 1..=MAX_LIMIT as BuildId + 10 {
+            history
+                .save(&build(id, BuildStatus::Succeeded, id, 10))
+                .unwrap();
+        }
+        let limited = |limit| BuildQuery {
+            limit,
+            ..BuildQuery::default()
+        };
+        assert_eq!(ids(&history, limited(Some(2))), [510, 509]);
+        assert_eq!(ids(&history, limited(None)).len(), DEFAULT_LIMIT as usize);
+        assert_eq!(
+            ids(&history, limited(Some(10_000))).len(),
+            MAX_LIMIT as usize
+        );
+    }
+
+    #[test]
+    fn test_save_updates_an_existing_build() {
+        let history = history();
+        history
+            .save(&build(7, BuildStatus::Running, 100, 0))
+            .unwrap();
+        let mut finished = build(7, BuildStatus::Failed, 100, 4000);
+        // Only the outcome is updated; the command and start stay as first saved.
+        finished.command = "renamed".to_string();
+        finished.started_at = 999;
+        history.save(&finished).unwrap();
+
+        let saved = history.get(7).unwrap().unwrap();
+        assert_eq!(saved.command, "build-7");
+        assert_eq!(saved.started_at, 100);
+        assert_eq!(saved.status, BuildStatus::Failed);
+        assert_eq!(saved.finished_at, Some(104));
+        assert_eq!(saved.exit_code, Some(1));
+        assert_eq!(saved.duration_ms, Some(4000));
+        assert_eq!(history.last_id().unwrap(), 7);
+        assert!(history.get(8).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_running_builds_fail_on_restart() {
+        let history = history();
+        history
+            .save(&build(1, BuildStatus::Running, 100, 0))
+            .unwrap();
+        history
+            .save(&build(2, BuildStatus::Succeeded, 200, 1000))
+            .unwrap();
+
+        let conn = history.conn.into_inner().unwrap();
+        let restarted = BuildHistory::init(conn).unwrap();
+
+        assert_eq!(
+            restarted.get(1).unwrap().unwrap().status,
+            BuildStatus::Failed
+        );
+        assert_eq!(
+            restarted.get(2).unwrap().unwrap().status,
+            BuildStatus::Succeeded
+        );
+        assert_eq!(restarted.last_id().unwrap(), 2);
+    }
+}
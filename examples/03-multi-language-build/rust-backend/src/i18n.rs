@@ -0,0 +1,187 @@
+use std::sync::LazyLock;
+
+use axum::extract::Request;
+use axum::http::header::{HeaderValue, ACCEPT_LANGUAGE, CONTENT_LANGUAGE};
+use axum::middleware::Next;
+use axum::response::Response;
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use fluent_langneg::{negotiate_languages, NegotiationStrategy};
+use unic_langid::{langid, LanguageIdentifier};
+
+/// Bundled translations, the default locale first.
+const SOURCES: [(LanguageIdentifier, &str); 2] = [
+    (
+        langid!("en-US"),
+        include_str!("../locales/en-US/backend.ftl"),
+    ),
+    (langid!("de"), include_str!("../locales/de/backend.ftl")),
+];
+
+tokio::task_local! {
+    static LOCALE: usize;
+}
+
+struct Locales {
+    available: Vec<LanguageIdentifier>,
+    bundles: Vec<FluentBundle<FluentResource>>,
+}
+
+static LOCALES: LazyLock<Locales> = LazyLock::new(|| {
+    let mut locales = Locales {
+        available: Vec::new(),
+        bundles: Vec::new(),
+    };
+    for (langid, source) in SOURCES {
+        let resource =
+            FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, errors)| {
+                eprintln!("⚠️  Errors in {} translations: {:?}", langid, errors);
+                resource
+            });
+        let mut bundle = FluentBundle::new_concurrent(vec![langid.clone()]);
+        // Plain text: no Unicode isolation marks around interpolated values.
+        bundle.set_use_isolating(false);
+        if let Err(errors) = bundle.add_resource(resource) {
+            eprintln!("⚠️  Duplicate {} translations: {:?}", langid, errors);
+        }
+        locales.available.push(langid);
+        locales.bundles.push(bundle);
+    }
+    locales
+});
+
+/// The string `id` in the locale negotiated for the request being handled,
+/// or in English outside the [`localize`] middleware. Falls back to `id`
+/// itself if no translation exists.
+pub fn text(id: &str, args: &[(&str, FluentValue)]) -> String {
+    let locale = LOCALE.try_with(|locale| *locale).unwrap_or(0);
+    let bundle = &LOCALES.bundles[locale];
+    let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) else {
+        return id.to_string();
+    };
+
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, value.clone());
+    }
+    let mut errors = Vec::new();
+    bundle
+        .format_pattern(pattern, Some(&fluent_args), &mut errors)
+        .into_owned()
+}
+
+/// Middleware choosing a bundled locale from `Accept-Language` for
+/// everything [`text`] produces while the request is handled, and naming it
+/// in `Content-Language`.
+pub async fn localize(req: Request, next: Next) -> Response {
+    let locale = req
+        .headers()
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map_or(0, negotiate);
+    let mut response = LOCALE.scope(locale, next.run(req)).await;
+    if let Ok(value) = HeaderValue::from_str(&LOCALES.available[locale].to_string()) {
+        response.headers_mut().insert(CONTENT_LANGUAGE, value);
+    }
+    response
+}
+
+/// Index of the bundled locale best matching an `Accept-Language` value.
+fn negotiate(accept_language: &str) -> usize {
+    let requested = accepted_languages(accept_language);
+    let available = &LOCALES.available;
+    negotiate_languages(
+        &requested,
+        available,
+        Some(&available[0]),
+        NegotiationStrategy::Lookup,
+    )
+    .first()
+    .and_then(|chosen| available.iter().position(|langid| langid == *chosen))
+    .unwrap_or(0)
+}
+
+/// Languages in an `Accept-Language` value, most preferred first. Entries
+/// with `q=0`, wildcards and unparsable tags are dropped.
+fn accepted_languages(accept_language: &str) -> Vec<LanguageIdentifier> {
+    let mut weighted: Vec<(f32, LanguageIdentifier)> = accept_language
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let tag = parts.next()?.trim();
+            let q = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.parse::<f32>().ok())?;
+            if q <= 0.0 || tag == "*" {
+                return None;
+            }
+            Some((q, tag.parse().ok()?))
+        })
+        .collect();
+    // Stable, so equally weighted languages keep the client's order.
+    weighted.sort_by(|a, b| b.0.total_cmp(&a.0));
+    weighted.into_iter().map(|(_, langid)| langid).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn in_locale(
+        accept_language: &str,
+        id: &str,
+        args: &[(&str, FluentValue<'_>)],
+    ) -> String {
+        LOCALE
+            .scope(negotiate(accept_language), async { text(id, args) })
+            .await
+    }
+
+    #[test]
+    fn orders_languages_by_quality() {
+        let languages = accepted_languages("en;q=0.5, de-AT, fr;q=0.8, *;q=0.1, es;q=0");
+        let tags: Vec<_> = languages.iter().map(|l| l.to_string()).collect();
+        assert_eq!(tags, ["de-AT", "fr", "en"]);
+    }
+
+    #[tokio::test]
+    async fn picks_the_closest_bundled_locale() {
+        let args = [("id", FluentValue::from(7))];
+        assert_eq!(
+            in_locale("de-AT,en;q=0.5", "error-build-not-found", &args).await,
+            "Build 7 nicht gefunden"
+        );
+        assert_eq!(
+            in_locale("fr, en-GB;q=0.9", "error-build-not-found", &args).await,
+            "build 7 not found"
+        );
+        assert_eq!(
+            in_locale("ja", "error-build-not-found", &args).await,
+            "build 7 not found"
+        );
+    }
+
+    #[test]
+    fn defaults_to_english_outside_a_request() {
+        assert_eq!(
+            text("error-at-capacity", &[]),
+            "server is at capacity, retry later"
+        );
+        assert_eq!(text("no-such-message", &[]), "no-such-message");
+    }
+
+    #[test]
+    fn every_locale_translates_every_message() {
+        let english = &LOCALES.bundles[0];
+        let source = SOURCES[0].1;
+        let ids = source
+            .lines()
+            .filter_map(|line| line.split_once(" = ").map(|(id, _)| id))
+            .filter(|id| english.has_message(id));
+        for id in ids {
+            for (bundle, langid) in LOCALES.bundles.iter().zip(&LOCALES.available) {
+                assert!(bundle.has_message(id), "{} is missing {}", langid, id);
+            }
+        }
+    }
+}
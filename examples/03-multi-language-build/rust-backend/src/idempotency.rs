@@ -0,0 +1,327 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime};
+
+use axum::body::{Body, Bytes};
+use axum::extract::{Request, State};
+use axum::http::header::{HeaderMap, HeaderValue};
+use axum::http::{Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::Response;
+
+use crate::clock::SharedClock;
+use crate::i18n;
+use crate::response::error_reply;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+pub const REPLAYED_HEADER: &str = "idempotency-replayed";
+
+/// A response recorded for replay.
+#[derive(Clone)]
+struct Recorded {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+enum Entry {
+    /// The first request with this key is still being handled.
+    InFlight { path: String },
+    Done {
+        path: String,
+        response: Recorded,
+        expires: SystemTime,
+    },
+}
+
+/// First responses to `POST` requests, keyed by their `Idempotency-Key`.
+#[derive(Clone)]
+pub struct IdempotencyCache {
+    ttl: Duration,
+    clock: SharedClock,
+    entries: Arc<Mutex<HashMap<String, Entry>>>,
+}
+
+impl IdempotencyCache {
+    pub fn new(ttl: Duration, clock: SharedClock) -> Self {
+        IdempotencyCache {
+            ttl,
+            clock,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Entry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Middleware replaying the first response to a retried `POST` carrying the
+/// same `Idempotency-Key` instead of repeating its side effects.
+///
+/// Only responses below 500 are recorded; after a server error the key is
+/// released so the retry runs for real. A retry that arrives while the first
+/// request is still running gets a 409, and reusing a key for a different
+/// path gets a 422.
+pub async fn idempotency(
+    State(cache): State<IdempotencyCache>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let key = match idempotency_key(&req) {
+        Some(key) => key,
+        None => return next.run(req).await,
+    };
+    let path = req.uri().path().to_string();
+
+    {
+        let mut entries = cache.lock();
+        let now = cache.clock.now();
+        entries.retain(|_, entry| match entry {
+            Entry::Done { expires, .. } => *expires > now,
+            Entry::InFlight { .. } => true,
+        });
+        match entries.get(&key) {
+            Some(Entry::InFlight { path: first }) | Some(Entry::Done { path: first, .. })
+                if *first != path =>
+            {
+                return error_reply(
+                    &*cache.clock,
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    i18n::text("error-idempotency-key-reused", &[]),
+                );
+            }
+            Some(Entry::InFlight { .. }) => {
+                return error_reply(
+                    &*cache.clock,
+                    StatusCode::CONFLICT,
+                    i18n::text("error-idempotency-in-progress", &[]),
+                );
+            }
+            Some(Entry::Done { response, .. }) => return replay(response),
+            None => {
+                entries.insert(key.clone(), Entry::InFlight { path: path.clone() });
+            }
+        }
+    }
+
+    // Releases the key if the handler panics or the client goes away.
+    let guard = InFlightGuard {
+        cache: cache.clone(),
+        key: Some(key),
+    };
+    let response = next.run(req).await;
+    if response.status().is_server_error() {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            return error_reply(
+                &*cache.clock,
+                StatusCode::INTERNAL_SERVER_ERROR,
+                i18n::text("error-buffer-response", &[("error", e.to_string().into())]),
+            );
+        }
+    };
+    guard.complete(
+        path,
+        Recorded {
+            status: parts.status,
+            headers: parts.headers.clone(),
+            body: body.clone(),
+        },
+    );
+    Response::from_parts(parts, Body::from(body))
+}
+
+struct InFlightGuard {
+    cache: IdempotencyCache,
+    key: Option<String>,
+}
+
+impl InFlightGuard {
+    fn complete(mut self, path: String, response: Recorded) {
+        if let Some(key) = self.key.take() {
+            let expires = self.cache.clock.now() + self.cache.ttl;
+            self.cache.lock().insert(
+                key,
+                Entry::Done {
+                    path,
+                    response,
+                    expires,
+                },
+            );
+        }
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.cache.lock().remove(&key);
+        }
+    }
+}
+
+fn idempotency_key(req: &Request) -> Option<String> {
+    if req.method() != Method::POST {
+        return None;
+    }
+    req.headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|key| !key.is_empty())
+        .map(str::to_string)
+}
+
+fn replay(recorded: &Recorded) -> Response {
+    let mut response = Response::new(Body::from(recorded.body.clone()));
+    *response.status_mut() = recorded.status;
+    *response.headers_mut() = recorded.headers.clone();
+    response
+        .headers_mut()
+        .insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use axum::routing::post;
+    use axum::Router;
+    use tokio::sync::{mpsc, Notify};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::clock::MockClock;
+
+    const TTL: Duration = Duration::from_secs(60);
+
+    /// Counts the runs of `POST /builds`, answering each with its number.
+    fn app(clock: Arc<MockClock>, runs: Arc<AtomicUsize>) -> Router {
+        let handler = move || {
+            let runs = runs.clone();
+            async move { (runs.fetch_add(1, Ordering::SeqCst) + 1).to_string() }
+        };
+        Router::new()
+            .route("/builds", post(handler))
+            .layer(axum::middleware::from_fn_with_state(
+                IdempotencyCache::new(TTL, clock),
+                idempotency,
+            ))
+    }
+
+    fn post_request(path: &str, key: &str) -> Request {
+        Request::post(path)
+            .header(IDEMPOTENCY_KEY_HEADER, key)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    async fn send(app: &Router, req: Request) -> (StatusCode, bool, String) {
+        let response = app.clone().oneshot(req).await.unwrap();
+        let status = response.status();
+        let replayed = response.headers().contains_key(REPLAYED_HEADER);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, replayed, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn retries_within_the_ttl_are_replayed() {
+        let clock = Arc::new(MockClock::at(1_700_000_000));
+        let runs = Arc::new(AtomicUsize::new(0));
+        let app = app(clock.clone(), runs.clone());
+
+        let first = send(&app, post_request("/builds", "abc")).await;
+        clock.advance(TTL - Duration::from_secs(1));
+        let retry = send(&app, post_request("/builds", "abc")).await;
+
+        assert_eq!(first, (StatusCode::OK, false, "1".to_string()));
+        assert_eq!(retry, (StatusCode::OK, true, "1".to_string()));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn expired_keys_run_again() {
+        let clock = Arc::new(MockClock::at(1_700_000_000));
+        let runs = Arc::new(AtomicUsize::new(0));
+        let app = app(clock.clone(), runs.clone());
+
+        send(&app, post_request("/builds", "abc")).await;
+        clock.advance(TTL);
+        let retry = send(&app, post_request("/builds", "abc")).await;
+
+        assert_eq!(retry, (StatusCode::OK, false, "2".to_string()));
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn requests_without_a_key_always_run() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let app = app(Arc::new(MockClock::at(1_700_000_000)), runs.clone());
+
+        for _ in 0..2 {
+            let req = Request::post("/builds").body(Body::empty()).unwrap();
+            send(&app, req).await;
+        }
+
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn a_key_reused_for_another_path_is_rejected() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let app = app(Arc::new(MockClock::at(1_700_000_000)), runs.clone());
+
+        send(&app, post_request("/builds", "abc")).await;
+        let (status, _, _) = send(&app, post_request("/other", "abc")).await;
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn a_retry_while_the_first_is_running_conflicts() {
+        let (entered, mut running) = mpsc::unbounded_channel();
+        let release = Arc::new(Notify::new());
+        let handler = {
+            let release = release.clone();
+            move || {
+                let (entered, release) = (entered.clone(), release.clone());
+                async move {
+                    let _ = entered.send(());
+                    release.notified().await;
+                    "done"
+                }
+            }
+        };
+        let app = Router::new().route("/builds", post(handler)).layer(
+            axum::middleware::from_fn_with_state(
+                IdempotencyCache::new(TTL, Arc::new(MockClock::at(1_700_000_000))),
+                idempotency,
+            ),
+        );
+
+        let first = tokio::spawn({
+            let app = app.clone();
+            async move { send(&app, post_request("/builds", "abc")).await }
+        });
+        running.recv().await.unwrap();
+        let (status, _, _) = send(&app, post_request("/builds", "abc")).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        release.notify_one();
+        assert_eq!(
+            first.await.unwrap(),
+            (StatusCode::OK, false, "done".to_string())
+        );
+        let replay = send(&app, post_request("/builds", "abc")).await;
+        assert_eq!(replay, (StatusCode::OK, true, "done".to_string()));
+    }
+}
@@ -0,0 +1,21 @@
+pub mod admin;
+pub mod app;
+pub mod builds;
+pub mod cache;
+pub mod clock;
+pub mod coalesce;
+pub mod config;
+pub mod events;
+pub mod history;
+pub mod i18n;
+pub mod idempotency;
+pub mod limits;
+pub mod metrics;
+pub mod models;
+pub mod mqtt;
+pub mod recovery;
+pub mod registry;
+pub mod response;
+pub mod server;
+pub mod startup;
+pub mod timing;
@@ -0,0 +1,181 @@
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use axum::extract::{Request, State};
+use axum::http::header::{HeaderValue, RETRY_AFTER};
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::Response;
+use futures_util::stream::{self, Stream};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, TryAcquireError};
+
+use crate::clock::SharedClock;
+use crate::config::Config;
+use crate::i18n;
+use crate::metrics::ServerMetrics;
+use crate::models::LimitsMetrics;
+use crate::response::error_reply;
+
+/// Connection and request concurrency limits.
+///
+/// Connections over the limit are not accepted until a slot frees up, so
+/// excess clients queue in the kernel's accept backlog. Requests over the
+/// in-flight limit are shed immediately with `503 Service Unavailable`.
+#[derive(Clone)]
+pub struct Limits {
+    connections: Arc<Semaphore>,
+    max_connections: usize,
+    active_connections: Arc<AtomicUsize>,
+    requests: Arc<Semaphore>,
+    max_in_flight: usize,
+    retry_after: Duration,
+    metrics: Arc<ServerMetrics>,
+    clock: SharedClock,
+}
+
+impl Limits {
+    pub fn new(config: &Config, metrics: Arc<ServerMetrics>, clock: SharedClock) -> Self {
+        Limits {
+            connections: Arc::new(Semaphore::new(config.max_connections)),
+            max_connections: config.max_connections,
+            active_connections: Arc::new(AtomicUsize::new(0)),
+            requests: Arc::new(Semaphore::new(config.max_in_flight)),
+            max_in_flight: config.max_in_flight,
+            retry_after: config.retry_after,
+            metrics,
+            clock,
+        }
+    }
+
+    pub fn snapshot(&self) -> LimitsMetrics {
+        LimitsMetrics {
+            max_connections: self.max_connections,
+            active_connections: self.active_connections.load(Ordering::Relaxed),
+            connections_saturated: self.metrics.connections_saturated.load(Ordering::Relaxed),
+            max_in_flight: self.max_in_flight,
+            in_flight_requests: self.max_in_flight - self.requests.available_permits(),
+            requests_shed: self.metrics.requests_shed.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Waits for a free connection slot, or returns `None` once the
+    /// semaphore has been closed.
+    async fn acquire_connection(&self) -> Option<OwnedSemaphorePermit> {
+        match self.connections.clone().try_acquire_owned() {
+            Ok(permit) => Some(permit),
+            Err(TryAcquireError::Closed) => None,
+            Err(TryAcquireError::NoPermits) => {
+                ServerMetrics::increment(&self.metrics.connections_saturated);
+                self.connections.clone().acquire_owned().await.ok()
+            }
+        }
+    }
+}
+
+/// Accepts connections from `listener`, never holding more than the
+/// configured number open at once. The stream ends if the connection slots
+/// are closed.
+pub fn incoming(listener: TcpListener, limits: Limits) -> impl Stream<Item = LimitedConnection> {
+    stream::unfold((listener, limits), |(listener, limits)| async move {
+        let permit = limits.acquire_connection().await?;
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    limits.active_connections.fetch_add(1, Ordering::Relaxed);
+                    let conn = LimitedConnection {
+                        stream,
+                        _permit: permit,
+                        active: limits.active_connections.clone(),
+                    };
+                    return Some((conn, (listener, limits)));
+                }
+                // Usually fd exhaustion; back off instead of ending the server.
+                Err(e) => {
+                    eprintln!("⚠️  Failed to accept connection: {}", e);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            }
+        }
+    })
+}
+
+/// A client connection holding one of the connection slots until dropped.
+pub struct LimitedConnection {
+    stream: TcpStream,
+    _permit: OwnedSemaphorePermit,
+    active: Arc<AtomicUsize>,
+}
+
+impl Drop for LimitedConnection {
+    fn drop(&mut self) {
+        self.active.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl AsyncRead for LimitedConnection {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for LimitedConnection {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stream).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.stream.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_shutdown(cx)
+    }
+}
+
+/// Middleware holding an in-flight slot while the rest of the stack produces
+/// a response, or answering `503` with `Retry-After` when none is free.
+pub async fn in_flight(State(limits): State<Limits>, req: Request, next: Next) -> Response {
+    let _permit = match limits.requests.clone().try_acquire_owned() {
+        Ok(permit) => permit,
+        Err(_) => {
+            ServerMetrics::increment(&limits.metrics.requests_shed);
+            let mut response = error_reply(
+                &*limits.clock,
+                StatusCode::SERVICE_UNAVAILABLE,
+                i18n::text("error-at-capacity", &[]),
+            );
+            let retry_after = limits.retry_after.as_secs().max(1);
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(retry_after));
+            return response;
+        }
+    };
+    next.run(req).await
+}
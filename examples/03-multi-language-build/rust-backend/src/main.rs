@@ -1,150 +1,146 @@
-use serde::{Deserialize, Serialize};
-use warp::Filter;
+use std::net::SocketAddr;
+use std::sync::Arc;
 
-#[derive(Debug, Serialize, Deserialize)]
-struct ApiResponse {
-    success: bool,
-    data: serde_json::Value,
-    timestamp: u64,
-    language: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct HealthStatus {
-    status: String,
-    version: String,
-    uptime: String,
-    build_info: BuildInfo,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct BuildInfo {
-    built_with: String,
-    orchestrator: String,
-    languages: Vec<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct MetricsData {
-    requests_served: u64,
-    languages_integrated: u32,
-    build_time_seconds: f32,
-    components: Vec<String>,
-}
+use rust_backend::app::{self, AppState};
+use rust_backend::builds::BuildManager;
+use rust_backend::clock::SystemClock;
+use rust_backend::config::Config;
+use rust_backend::history::BuildHistory;
+use rust_backend::limits::Limits;
+use rust_backend::metrics::ServerMetrics;
+use rust_backend::mqtt::MqttBridge;
+use rust_backend::registry::Registration;
+use rust_backend::server::{self, Timeouts};
+use rust_backend::startup::{self, StartupReport};
 
 #[tokio::main]
 async fn main() {
-    println!("🦀 Starting Rust Backend Server...");
-    
-    // Health endpoint
-    let health = warp::path("health")
-        .and(warp::get())
-        .map(|| {
-            let health = HealthStatus {
-                status: "healthy".to_string(),
-                version: "1.0.0".to_string(),
-                uptime: "5m 32s".to_string(),
-                build_info: BuildInfo {
-                    built_with: "gaffer-exec multi-language build".to_string(),
-                    orchestrator: "gaffer-exec".to_string(),
-                    languages: vec![
-                        "Rust".to_string(),
-                        "Go".to_string(), 
-                        "Node.js".to_string(),
-                        "Python".to_string(),
-                    ],
-                },
-            };
-            
-            warp::reply::json(&ApiResponse {
-                success: true,
-                data: serde_json::to_value(&health).unwrap(),
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs(),
-                language: "Rust".to_string(),
-            })
-        });
-
-    // Metrics endpoint
-    let metrics = warp::path("metrics")
-        .and(warp::get())
-        .map(|| {
-            let metrics = MetricsData {
-                requests_served: 1247,
-                languages_integrated: 4,
-                build_time_seconds: 12.34,
-                components: vec![
-                    "rust-backend".to_string(),
-                    "go-cli".to_string(),
-                    "node-frontend".to_string(),
-                    "python-ml".to_string(),
-                ],
-            };
-            
-            warp::reply::json(&ApiResponse {
-                success: true,
-                data: serde_json::to_value(&metrics).unwrap(),
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs(),
-                language: "Rust".to_string(),
-            })
-        });
+    // Request traces from the tower layers, e.g. RUST_LOG=tower_http=debug.
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(1);
+        }
+    };
+    let history = match BuildHistory::open(&config.build_db) {
+        Ok(history) => history,
+        Err(e) => {
+            eprintln!(
+                "❌ Failed to open build history {}: {}",
+                config.build_db.display(),
+                e
+            );
+            std::process::exit(1);
+        }
+    };
+    let clock = SystemClock::shared();
+    let builds = match BuildManager::new(&config, history, clock.clone()) {
+        Ok(builds) => builds,
+        Err(e) => {
+            eprintln!("❌ Failed to read build history: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let server_metrics = Arc::new(ServerMetrics::default());
+    let limits = Limits::new(&config, server_metrics.clone(), clock.clone());
+    let state = AppState {
+        builds,
+        poll_timeout: config.poll_timeout,
+        limits: limits.clone(),
+        metrics: server_metrics.clone(),
+        clock: clock.clone(),
+    };
+    let routes = app::router(state.clone(), &config);
 
-    // API info endpoint
-    let api_info = warp::path("api")
-        .and(warp::get())
-        .map(|| {
-            let endpoints = vec![
-                ("health", "GET /health - Service health check"),
-                ("metrics", "GET /metrics - Application metrics"),
-                ("api", "GET /api - API information"),
-            ];
-            
-            let info = serde_json::json!({
-                "name": "Multi-Language API",
-                "description": "Rust backend for polyglot application",
-                "version": "1.0.0",
-                "endpoints": endpoints.iter().map(|(k, v)| {
-                    serde_json::json!({
-                        "endpoint": k,
-                        "description": v
-                    })
-                }).collect::<Vec<_>>()
-            });
-            
-            warp::reply::json(&ApiResponse {
-                success: true,
-                data: info,
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs(),
-                language: "Rust".to_string(),
-            })
-        });
+    // Scrapers get their own listener, outside the public API's middleware.
+    let mut metrics_addr = None;
+    if let Some(port) = config.metrics_port {
+        match tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => {
+                metrics_addr = listener.local_addr().ok();
+                let metrics_routes = app::metrics_router(state.clone());
+                tokio::spawn(async move {
+                    let _ = axum::serve(listener, metrics_routes).await;
+                });
+            }
+            Err(e) => {
+                eprintln!(
+                    "❌ Failed to bind metrics listener 127.0.0.1:{}: {}",
+                    port, e
+                );
+                std::process::exit(1);
+            }
+        }
+    }
 
-    // CORS headers
-    let cors = warp::cors()
-        .allow_any_origin()
-        .allow_headers(vec!["content-type"])
-        .allow_methods(vec!["GET", "POST"]);
+    let listener = match tokio::net::TcpListener::bind(("127.0.0.1", 8080)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("❌ Failed to bind 127.0.0.1:8080: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let api_addr = listener
+        .local_addr()
+        .unwrap_or_else(|_| SocketAddr::from(([127, 0, 0, 1], 8080)));
+    let report = StartupReport::new(&config, api_addr, metrics_addr, &*clock);
+    report.print();
+    if let Some(path) = &config.ready_file {
+        if let Err(e) = report.write_ready_file(path) {
+            eprintln!("⚠️  Failed to write ready file {}: {}", path.display(), e);
+        }
+    }
+    let registration = config.registry.clone().map(Registration::start);
+    let bridge = config
+        .mqtt
+        .clone()
+        .map(|mqtt| MqttBridge::start(mqtt, state.clone()));
+    let announcement = config.mdns_instance.as_deref().and_then(|instance| {
+        let properties = [("version", "1.0.0"), ("language", "rust")];
+        match gaffer_discovery::announce(instance, 8080, &properties) {
+            Ok(announcement) => {
+                println!(
+                    "📣 Advertising {} as {}",
+                    instance,
+                    gaffer_discovery::SERVICE_TYPE
+                );
+                Some(announcement)
+            }
+            Err(e) => {
+                eprintln!("⚠️  mDNS announcement failed: {}", e);
+                None
+            }
+        }
+    });
 
-    let routes = health
-        .or(metrics)
-        .or(api_info)
-        .with(cors);
+    tokio::select! {
+        _ = server::serve(
+            listener,
+            routes,
+            limits,
+            Timeouts::from_config(&config),
+            server_metrics,
+        ) => {}
+        _ = server::shutdown_signal() => println!("🛑 Shutting down..."),
+    }
 
-    println!("🚀 Rust backend running on http://localhost:8080");
-    println!("📡 Available endpoints:");
-    println!("   - GET /health");
-    println!("   - GET /metrics");
-    println!("   - GET /api");
-
-    warp::serve(routes)
-        .run(([127, 0, 0, 1], 8080))
-        .await;
-}
\ No newline at end of file
+    if let Some(registration) = registration {
+        registration.deregister().await;
+    }
+    if let Some(bridge) = bridge {
+        bridge.shutdown().await;
+    }
+    if let Some(announcement) = announcement {
+        announcement.shutdown();
+    }
+    if let Some(path) = &config.ready_file {
+        if let Err(e) = startup::remove_ready_file(path) {
+            eprintln!("⚠️  Failed to remove ready file {}: {}", path.display(), e);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::models::{CacheMetrics, TimeoutMetrics};
+
+/// Counters for server-level events, shared by the listener, middleware and
+/// the `/metrics` handler.
+#[derive(Debug, Default)]
+pub struct ServerMetrics {
+    /// Accepts that had to wait because the connection limit was reached.
+    pub connections_saturated: AtomicU64,
+    /// Requests answered with 503 because the in-flight limit was reached.
+    pub requests_shed: AtomicU64,
+    /// Connections closed for not sending a complete request head in time.
+    pub header_read_timeouts: AtomicU64,
+    /// Connections closed for making no read or write progress in time.
+    pub idle_timeouts: AtomicU64,
+    /// Requests whose handler panicked and were answered with a 500.
+    pub handler_panics: AtomicU64,
+    /// `GET`s answered from the response cache.
+    pub cache_hits: AtomicU64,
+    /// Cacheable `GET`s that had to run their handler.
+    pub cache_misses: AtomicU64,
+    /// `GET`s answered with the response of an identical concurrent request.
+    pub coalesced_requests: AtomicU64,
+}
+
+impl ServerMetrics {
+    pub fn increment(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn timeouts(&self) -> TimeoutMetrics {
+        TimeoutMetrics {
+            header_read: self.header_read_timeouts.load(Ordering::Relaxed),
+            idle: self.idle_timeouts.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn cache(&self) -> CacheMetrics {
+        CacheMetrics {
+            hits: self.cache_hits.load(Ordering::Relaxed),
+            misses: self.cache_misses.load(Ordering::Relaxed),
+            coalesced: self.coalesced_requests.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+
+use crate::builds::BuildSummary;
+use crate::i18n;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HealthStatus {
+    pub status: String,
+    pub version: String,
+    pub uptime: String,
+    pub build_info: BuildInfo,
+}
+
+impl HealthStatus {
+    pub fn current() -> Self {
+        HealthStatus {
+            status: "healthy".to_string(),
+            version: "1.0.0".to_string(),
+            uptime: i18n::text(
+                "health-uptime",
+                &[("minutes", 5.into()), ("seconds", 32.into())],
+            ),
+            build_info: BuildInfo {
+                built_with: i18n::text("health-built-with", &[]),
+                orchestrator: "gaffer-exec".to_string(),
+                languages: vec![
+                    "Rust".to_string(),
+                    "Go".to_string(),
+                    "Node.js".to_string(),
+                    "Python".to_string(),
+                ],
+            },
+        }
+    }
+}
+
+/// Everything `/status` reports in one document.
+#[derive(Debug, Serialize)]
+pub struct StatusReport {
+    pub health: HealthStatus,
+    pub metrics: MetricsData,
+    pub recent_builds: Vec<BuildSummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BuildInfo {
+    pub built_with: String,
+    pub orchestrator: String,
+    pub languages: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetricsData {
+    pub requests_served: u64,
+    pub languages_integrated: u32,
+    pub build_time_seconds: f32,
+    pub components: Vec<String>,
+    pub limits: LimitsMetrics,
+    pub timeouts: TimeoutMetrics,
+    pub handler_panics: u64,
+    pub cache: CacheMetrics,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LimitsMetrics {
+    pub max_connections: usize,
+    pub active_connections: usize,
+    pub connections_saturated: u64,
+    pub max_in_flight: usize,
+    pub in_flight_requests: usize,
+    pub requests_shed: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TimeoutMetrics {
+    pub header_read: u64,
+    pub idle: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CacheMetrics {
+    pub hits: u64,
+    pub misses: u64,
+    pub coalesced: u64,
+}
@@ -0,0 +1,251 @@
+//! Optional bridge publishing to an MQTT broker.
+//!
+//! While running, the backend publishes a `/metrics` snapshot to
+//! `metrics_topic` every `interval` (retained, so new subscribers get the
+//! latest one straight away) and every build event from the event log to
+//! `events_topic`, in the same JSON shape `GET /events/poll` returns.
+
+use std::time::Duration;
+
+use rumqttc::{AsyncClient, Event, EventLoop, MqttOptions, Outgoing, QoS};
+use tokio::task::JoinHandle;
+
+use crate::app::{self, AppState};
+use crate::events::{EventBatch, PollQuery};
+use crate::models::MetricsData;
+
+/// Requests queued for the broker before publishing waits.
+const QUEUE_CAPACITY: usize = 64;
+
+/// How long each read of the event log waits for something new.
+const EVENT_WAIT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub struct MqttConfig {
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    pub metrics_topic: String,
+    pub events_topic: String,
+    pub interval: Duration,
+}
+
+/// A running bridge. Call [`MqttBridge::shutdown`] on shutdown.
+pub struct MqttBridge {
+    client: AsyncClient,
+    connection: JoinHandle<()>,
+    publishers: Vec<JoinHandle<()>>,
+}
+
+impl MqttBridge {
+    pub fn start(config: MqttConfig, state: AppState) -> Self {
+        let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+        options.set_keep_alive(Duration::from_secs(30));
+        let (client, event_loop) = AsyncClient::new(options, QUEUE_CAPACITY);
+
+        let connection = tokio::spawn(connection_loop(event_loop, config.clone()));
+        let publishers = vec![
+            tokio::spawn(metrics_loop(client.clone(), state.clone(), config.clone())),
+            tokio::spawn(events_loop(client.clone(), state, config)),
+        ];
+        MqttBridge {
+            client,
+            connection,
+            publishers,
+        }
+    }
+
+    /// Stops publishing and disconnects from the broker, giving queued
+    /// messages a moment to go out first.
+    pub async fn shutdown(self) {
+        for task in self.publishers {
+            task.abort();
+        }
+        let _ = self.client.disconnect().await;
+        let abort = self.connection.abort_handle();
+        if tokio::time::timeout(Duration::from_secs(2), self.connection)
+            .await
+            .is_err()
+        {
+            abort.abort();
+        }
+    }
+}
+
+/// Drives the connection until the client disconnects; rumqttc reconnects on
+/// the next poll after an error.
+async fn connection_loop(mut event_loop: EventLoop, config: MqttConfig) {
+    let mut connected: Option<bool> = None;
+    loop {
+        // Only log transitions, not every packet or retry.
+        match (event_loop.poll().await, connected) {
+            (Ok(Event::Outgoing(Outgoing::Disconnect)), _) => break,
+            (Ok(_), Some(true)) => {}
+            (Ok(_), _) => {
+                println!(
+                    "📶 Connected to MQTT broker {}:{}",
+                    config.host, config.port
+                );
+                connected = Some(true);
+            }
+            (Err(_), Some(false)) => tokio::time::sleep(Duration::from_secs(1)).await,
+            (Err(e), _) => {
+                eprintln!("⚠️  MQTT connection failing, will keep retrying: {}", e);
+                connected = Some(false);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+    }
+}
+
+/// One message for the broker.
+#[derive(Debug)]
+struct Publication<'a> {
+    topic: &'a str,
+    retain: bool,
+    payload: Vec<u8>,
+}
+
+impl Publication<'_> {
+    async fn send(self, client: &AsyncClient) {
+        let _ = client
+            .publish(self.topic, QoS::AtLeastOnce, self.retain, self.payload)
+            .await;
+    }
+}
+
+/// A `/metrics` snapshot, retained so new subscribers get the latest one.
+fn metrics_publication<'a>(config: &'a MqttConfig, metrics: &MetricsData) -> Publication<'a> {
+    Publication {
+        topic: &config.metrics_topic,
+        retain: true,
+        payload: serde_json::to_vec(metrics).unwrap_or_default(),
+    }
+}
+
+/// One message per event in `batch`, oldest first.
+fn event_publications<'a>(config: &'a MqttConfig, batch: &EventBatch) -> Vec<Publication<'a>> {
+    batch
+        .events
+        .iter()
+        .map(|event| Publication {
+            topic: &config.events_topic,
+            retain: false,
+            payload: serde_json::to_vec(event).unwrap_or_default(),
+        })
+        .collect()
+}
+
+async fn metrics_loop(client: AsyncClient, state: AppState, config: MqttConfig) {
+    let mut ticks = tokio::time::interval(config.interval);
+    loop {
+        ticks.tick().await;
+        metrics_publication(&config, &app::metrics_snapshot(&state))
+            .send(&client)
+            .await;
+    }
+}
+
+async fn events_loop(client: AsyncClient, state: AppState, config: MqttConfig) {
+    let mut query = PollQuery::default();
+    loop {
+        let batch = state.builds.events().poll(&query, EVENT_WAIT).await;
+        for publication in event_publications(&config, &batch) {
+            publication.send(&client).await;
+        }
+        query.cursor = batch.next_cursor;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::events::{AppEvent, LoggedEvent};
+
+    fn config() -> MqttConfig {
+        MqttConfig {
+            host: "localhost".to_string(),
+            port: 1883,
+            client_id: "rust-backend".to_string(),
+            metrics_topic: "gaffer/metrics".to_string(),
+            events_topic: "gaffer/events".to_string(),
+            interval: Duration::from_secs(10),
+        }
+    }
+
+    fn json(payload: &[u8]) -> Value {
+        serde_json::from_slice(payload).unwrap()
+    }
+
+    #[test]
+    fn metrics_are_retained_on_the_metrics_topic() {
+        let config = config();
+        let metrics = MetricsData {
+            requests_served: 7,
+            languages_integrated: 4,
+            build_time_seconds: 1.5,
+            components: vec!["rust-backend".to_string()],
+            limits: Default::default(),
+            timeouts: Default::default(),
+            handler_panics: 0,
+            cache: Default::default(),
+        };
+
+        let publication = metrics_publication(&config, &metrics);
+
+        assert_eq!(publication.topic, "gaffer/metrics");
+        assert!(publication.retain);
+        let payload = json(&publication.payload);
+        assert_eq!(payload["requests_served"], 7);
+        assert_eq!(payload["components"], json!(["rust-backend"]));
+    }
+
+    #[test]
+    fn events_are_published_in_the_poll_shape() {
+        let config = config();
+        let batch = EventBatch {
+            events: (1..=2)
+                .map(|seq| LoggedEvent {
+                    seq,
+                    timestamp: 1_700_000_000,
+                    event: AppEvent::BuildCancelling { build_id: seq * 10 },
+                })
+                .collect(),
+            next_cursor: 2,
+            missed: false,
+        };
+
+        let publications = event_publications(&config, &batch);
+
+        assert_eq!(publications.len(), 2);
+        for (publication, event) in publications.iter().zip(&batch.events) {
+            assert_eq!(publication.topic, "gaffer/events");
+            assert!(!publication.retain);
+            assert_eq!(
+                json(&publication.payload),
+                serde_json::to_value(event).unwrap()
+            );
+        }
+        assert_eq!(
+            json(&publications[1].payload),
+            json!({
+                "seq": 2,
+                "timestamp": 1_700_000_000,
+                "type": "build_cancelling",
+                "build_id": 20,
+            })
+        );
+    }
+
+    #[test]
+    fn an_empty_batch_publishes_nothing() {
+        let batch = EventBatch {
+            events: Vec::new(),
+            next_cursor: 0,
+            missed: false,
+        };
+        assert!(event_publications(&config(), &batch).is_empty());
+    }
+}
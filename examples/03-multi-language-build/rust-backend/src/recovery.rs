@@ -0,0 +1,187 @@
+use std::any::Any;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+
+use axum::extract::{Request, State};
+use axum::http::header::{HeaderValue, CONTENT_TYPE};
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use futures_util::FutureExt;
+use serde::Serialize;
+
+use crate::i18n;
+use crate::metrics::ServerMetrics;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// RFC 9457 problem details body sent when a handler panics.
+#[derive(Debug, Serialize)]
+struct Problem<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    title: &'static str,
+    status: u16,
+    detail: String,
+    request_id: &'a str,
+}
+
+/// Middleware turning a panic while handling a request into a
+/// `application/problem+json` 500 instead of killing the connection task.
+///
+/// Every response carries an `x-request-id` header: the client's own if it
+/// sent one, otherwise a generated id, so a 500 can be matched to the panic
+/// message in the server log.
+pub async fn catch_panic(
+    State(metrics): State<Arc<ServerMetrics>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let request_id = request_id(&req);
+    let mut response = match AssertUnwindSafe(next.run(req)).catch_unwind().await {
+        Ok(response) => response,
+        Err(payload) => {
+            ServerMetrics::increment(&metrics.handler_panics);
+            eprintln!(
+                "💥 Handler panicked (request {}): {}",
+                request_id,
+                panic_message(&*payload)
+            );
+            problem_response(&request_id)
+        }
+    };
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+fn request_id(req: &Request) -> String {
+    req.headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{:016x}", rand::random::<u64>()))
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "non-string panic payload"
+    }
+}
+
+fn problem_response(request_id: &str) -> Response {
+    let problem = Problem {
+        kind: "about:blank",
+        title: "Internal Server Error",
+        status: 500,
+        detail: i18n::text("error-handler-panicked", &[]),
+        request_id,
+    };
+    let body = serde_json::to_vec(&problem).unwrap_or_default();
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        [(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/problem+json"),
+        )],
+        body,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use axum::body::Body;
+    use axum::routing::get;
+    use axum::Router;
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn boom() -> &'static str {
+        panic!("deliberate test panic");
+    }
+
+    async fn boom_formatted() -> &'static str {
+        panic!("deliberate test panic #{}", 2);
+    }
+
+    fn app(metrics: Arc<ServerMetrics>) -> Router {
+        Router::new()
+            .route("/fine", get(|| async { "fine" }))
+            .route("/boom", get(boom))
+            .route("/boom-formatted", get(boom_formatted))
+            .layer(axum::middleware::from_fn_with_state(metrics, catch_panic))
+    }
+
+    fn get_request(path: &str) -> Request {
+        Request::get(path).body(Body::empty()).unwrap()
+    }
+
+    async fn body_json(response: Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn panicking_handler_returns_problem_json() {
+        let metrics = Arc::new(ServerMetrics::default());
+        let req = Request::get("/boom")
+            .header(REQUEST_ID_HEADER, "abc123")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app(metrics.clone()).oneshot(req).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/problem+json");
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "abc123");
+        let body = body_json(response).await;
+        assert_eq!(body["status"], 500);
+        assert_eq!(body["request_id"], "abc123");
+        assert_eq!(metrics.handler_panics.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn formatted_panic_gets_a_generated_request_id() {
+        let metrics = Arc::new(ServerMetrics::default());
+
+        let response = app(metrics.clone())
+            .oneshot(get_request("/boom-formatted"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let id = response.headers()[REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(id.len(), 16);
+        assert_eq!(body_json(response).await["request_id"], id.as_str());
+        assert_eq!(metrics.handler_panics.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn service_keeps_working_after_a_panic() {
+        let metrics = Arc::new(ServerMetrics::default());
+        let app = app(metrics.clone());
+
+        let failed = app.clone().oneshot(get_request("/boom")).await.unwrap();
+        assert_eq!(failed.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let response = app.oneshot(get_request("/fine")).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().contains_key(REQUEST_ID_HEADER));
+        assert_eq!(metrics.handler_panics.load(Ordering::Relaxed), 1);
+    }
+}
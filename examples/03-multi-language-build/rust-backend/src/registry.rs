@@ -0,0 +1,223 @@
+//! Heartbeat registration with a service registry.
+//!
+//! While running, the backend periodically announces itself with
+//! `POST {registry}/services`, sending an [`Instance`] document. Registries
+//! should expire an instance that misses heartbeats for `ttl_secs`. On
+//! shutdown the backend deregisters with `DELETE {registry}/services/{id}`.
+
+use std::time::Duration;
+
+use rand::Rng;
+use serde::Serialize;
+use tokio::task::JoinHandle;
+
+use crate::models::HealthStatus;
+
+#[derive(Debug, Clone)]
+pub struct RegistryConfig {
+    pub url: String,
+    pub advertise_address: String,
+    pub interval: Duration,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Instance {
+    id: String,
+    name: &'static str,
+    address: String,
+    version: String,
+    health: String,
+    ttl_secs: u64,
+}
+
+/// A running heartbeat task. Call [`Registration::deregister`] on shutdown.
+pub struct Registration {
+    client: reqwest::Client,
+    url: String,
+    id: String,
+    heartbeat: JoinHandle<()>,
+}
+
+impl Registration {
+    pub fn start(config: RegistryConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap_or_default();
+        let url = config.url.trim_end_matches('/').to_string();
+        let id = format!("rust-backend@{}", config.advertise_address);
+
+        let heartbeat = tokio::spawn(heartbeat_loop(
+            client.clone(),
+            format!("{}/services", url),
+            id.clone(),
+            config,
+        ));
+
+        Registration {
+            client,
+            url,
+            id,
+            heartbeat,
+        }
+    }
+
+    /// Stops heartbeating and removes this instance from the registry.
+    pub async fn deregister(self) {
+        self.heartbeat.abort();
+        let url = format!("{}/services/{}", self.url, urlencode(&self.id));
+        match self.client.delete(&url).send().await {
+            Ok(response) if response.status().is_success() => {
+                println!("📇 Deregistered from {}", self.url);
+            }
+            Ok(response) => {
+                eprintln!("⚠️  Registry refused deregistration: {}", response.status());
+            }
+            Err(e) => eprintln!("⚠️  Failed to deregister from {}: {}", self.url, e),
+        }
+    }
+}
+
+async fn heartbeat_loop(client: reqwest::Client, url: String, id: String, config: RegistryConfig) {
+    // Heartbeats every `interval` on average; missing three counts as gone.
+    let ttl_secs = (config.interval * 3).as_secs().max(1);
+    let mut registered: Option<bool> = None;
+
+    loop {
+        let health = HealthStatus::current();
+        let instance = Instance {
+            id: id.clone(),
+            name: "rust-backend",
+            address: config.advertise_address.clone(),
+            version: health.version,
+            health: health.status,
+            ttl_secs,
+        };
+
+        let result = client
+            .post(&url)
+            .json(&instance)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        // Only log transitions, not every heartbeat.
+        match (result, registered) {
+            (Ok(_), Some(true)) | (Err(_), Some(false)) => {}
+            (Ok(_), _) => {
+                println!("📇 Registered with {} as {}", config.url, id);
+                registered = Some(true);
+            }
+            (Err(e), _) => {
+                eprintln!("⚠️  Registry heartbeat failing, will keep retrying: {}", e);
+                registered = Some(false);
+            }
+        }
+
+        tokio::time::sleep(jittered(config.interval)).await;
+    }
+}
+
+/// Spreads heartbeats ±20% around `interval` so a fleet restarted together
+/// doesn't hit the registry in lockstep.
+fn jittered(interval: Duration) -> Duration {
+    interval.mul_f64(rand::thread_rng().gen_range(0.8..1.2))
+}
+
+fn urlencode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Request, State};
+    use axum::routing::{delete, post};
+    use axum::{Json, Router};
+    use serde_json::Value;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn test_jitter_stays_within_twenty_percent() {
+        let interval = Duration::from_secs(10);
+        for _ in 0..1000 {
+            let jittered = jittered(interval);
+            assert!(jittered >= Duration::from_secs(8), "{:?}", jittered);
+            assert!(jittered < Duration::from_secs(12), "{:?}", jittered);
+        }
+    }
+
+    #[test]
+    fn test_urlencode() {
+        assert_eq!(urlencode("abc-XYZ_0.9~"), "abc-XYZ_0.9~");
+        assert_eq!(
+            urlencode("rust-backend@http://10.0.0.1:8080"),
+            "rust-backend%40http%3A%2F%2F10.0.0.1%3A8080"
+        );
+        assert_eq!(urlencode("a b/é"), "a%20b%2F%C3%A9");
+    }
+
+    #[derive(Debug)]
+    enum Call {
+        Heartbeat(Value),
+        Deregister(String),
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_and_deregister() {
+        let (calls, mut received) = mpsc::unbounded_channel();
+        let registry = Router::new()
+            .route(
+                "/services",
+                post(
+                    |State(calls): State<mpsc::UnboundedSender<Call>>, Json(body): Json<Value>| async move {
+                        let _ = calls.send(Call::Heartbeat(body));
+                    },
+                ),
+            )
+            .route(
+                "/services/{id}",
+                delete(
+                    |State(calls): State<mpsc::UnboundedSender<Call>>, req: Request| async move {
+                        let _ = calls.send(Call::Deregister(req.uri().path().to_string()));
+                    },
+                ),
+            )
+            .with_state(calls);
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, registry).await });
+
+        let registration = Registration::start(RegistryConfig {
+            url: format!("http://{}/", addr),
+            advertise_address: "http://10.0.0.1:8080".to_string(),
+            interval: Duration::from_secs(2),
+        });
+        let Some(Call::Heartbeat(body)) = received.recv().await else {
+            panic!("expected a heartbeat first");
+        };
+        assert_eq!(body["id"], "rust-backend@http://10.0.0.1:8080");
+        assert_eq!(body["name"], "rust-backend");
+        assert_eq!(body["address"], "http://10.0.0.1:8080");
+        assert_eq!(body["ttl_secs"], 6);
+        assert_eq!(body["health"], "healthy");
+        assert!(body["version"].is_string());
+
+        registration.deregister().await;
+        let Some(Call::Deregister(path)) = received.recv().await else {
+            panic!("expected a deregistration");
+        };
+        assert_eq!(
+            path,
+            "/services/rust-backend%40http%3A%2F%2F10.0.0.1%3A8080"
+        );
+    }
+}
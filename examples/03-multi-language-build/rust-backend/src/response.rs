@@ -0,0 +1,72 @@
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Serialize;
+
+use crate::clock::Clock;
+use crate::timing;
+
+/// Envelope wrapped around every endpoint's payload.
+///
+/// Generic over the payload so handlers hand over their typed data and it is
+/// serialized exactly once, straight into the response body, instead of going
+/// through an intermediate `serde_json::Value`.
+#[derive(Debug, Serialize)]
+pub struct ApiResponse<T> {
+    pub success: bool,
+    pub data: T,
+    pub timestamp: u64,
+    pub language: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorBody {
+    pub error: String,
+}
+
+impl<T: Serialize> ApiResponse<T> {
+    pub fn ok(data: T, timestamp: u64) -> Self {
+        ApiResponse {
+            success: true,
+            data,
+            timestamp,
+            language: "Rust",
+        }
+    }
+}
+
+impl ApiResponse<ErrorBody> {
+    pub fn error(message: impl Into<String>, timestamp: u64) -> Self {
+        ApiResponse {
+            success: false,
+            data: ErrorBody {
+                error: message.into(),
+            },
+            timestamp,
+            language: "Rust",
+        }
+    }
+}
+
+/// Wraps `data` in a successful [`ApiResponse`] stamped by `clock` and
+/// serializes it as the JSON reply.
+pub fn reply<T: Serialize>(clock: &dyn Clock, data: T) -> Response {
+    reply_with_status(clock, data, StatusCode::OK)
+}
+
+/// Like [`reply`], but with an explicit status code.
+pub fn reply_with_status<T: Serialize>(clock: &dyn Clock, data: T, status: StatusCode) -> Response {
+    json_reply(status, ApiResponse::ok(data, clock.unix_timestamp()))
+}
+
+/// A failed [`ApiResponse`] carrying `message`, with the given status code.
+pub fn error_reply(clock: &dyn Clock, status: StatusCode, message: impl Into<String>) -> Response {
+    json_reply(status, ApiResponse::error(message, clock.unix_timestamp()))
+}
+
+fn json_reply<T: Serialize>(status: StatusCode, body: ApiResponse<T>) -> Response {
+    timing::mark("handler");
+    let response = (status, Json(body)).into_response();
+    timing::mark("ser");
+    response
+}
@@ -0,0 +1,202 @@
+use std::error::Error;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use axum::Router;
+use futures_util::StreamExt;
+use hyper_util::rt::{TokioExecutor, TokioIo, TokioTimer};
+use hyper_util::server::conn::auto;
+use hyper_util::service::TowerToHyperService;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpListener;
+use tokio::time::{Instant, Sleep};
+
+use crate::config::Config;
+use crate::limits::{self, Limits};
+use crate::metrics::ServerMetrics;
+
+/// Defences against slowloris-style clients.
+#[derive(Debug, Clone, Copy)]
+pub struct Timeouts {
+    /// Time allowed to receive a complete request head. hyper also applies
+    /// it while a keep-alive connection waits for its next request.
+    pub header_read: Duration,
+    /// Time a connection may go without any read or write progress.
+    pub idle: Duration,
+}
+
+impl Timeouts {
+    pub fn from_config(config: &Config) -> Self {
+        Timeouts {
+            header_read: config.header_read_timeout,
+            idle: config.idle_timeout,
+        }
+    }
+}
+
+/// Serves `app` on `listener` over HTTP/1 and HTTP/2, applying the
+/// connection limit and the slow-client timeouts to every connection.
+pub async fn serve(
+    listener: TcpListener,
+    app: Router,
+    limits: Limits,
+    timeouts: Timeouts,
+    metrics: Arc<ServerMetrics>,
+) {
+    let mut http = auto::Builder::new(TokioExecutor::new());
+    http.http1()
+        .timer(TokioTimer::new())
+        .header_read_timeout(timeouts.header_read);
+    let service = TowerToHyperService::new(app);
+
+    let connections = limits::incoming(listener, limits);
+    futures_util::pin_mut!(connections);
+    while let Some(conn) = connections.next().await {
+        let conn = TokioIo::new(IdleTimeout::new(conn, timeouts.idle, metrics.clone()));
+        let http = http.clone();
+        let service = service.clone();
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            if let Err(e) = http.serve_connection(conn, service).await {
+                if is_header_timeout(&*e) {
+                    ServerMetrics::increment(&metrics.header_read_timeouts);
+                }
+            }
+        });
+    }
+}
+
+/// Resolves on Ctrl-C, or SIGTERM on Unix.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(_) => std::future::pending().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+fn is_header_timeout(e: &(dyn Error + Send + Sync + 'static)) -> bool {
+    e.downcast_ref::<hyper::Error>()
+        .is_some_and(hyper::Error::is_timeout)
+}
+
+/// Fails the connection with `TimedOut` once it has made no read or write
+/// progress for the configured duration.
+pub struct IdleTimeout<T> {
+    inner: T,
+    timeout: Duration,
+    timer: Pin<Box<Sleep>>,
+    timed_out: bool,
+    metrics: Arc<ServerMetrics>,
+}
+
+impl<T> IdleTimeout<T> {
+    pub fn new(inner: T, timeout: Duration, metrics: Arc<ServerMetrics>) -> Self {
+        IdleTimeout {
+            inner,
+            timeout,
+            timer: Box::pin(tokio::time::sleep(timeout)),
+            timed_out: false,
+            metrics,
+        }
+    }
+
+    fn progress(&mut self) {
+        self.timer.as_mut().reset(Instant::now() + self.timeout);
+    }
+
+    fn poll_expired(&mut self, cx: &mut Context<'_>) -> Poll<io::Error> {
+        if self.timer.as_mut().poll(cx).is_pending() {
+            return Poll::Pending;
+        }
+        if !self.timed_out {
+            self.timed_out = true;
+            ServerMetrics::increment(&self.metrics.idle_timeouts);
+        }
+        Poll::Ready(io::Error::new(
+            io::ErrorKind::TimedOut,
+            "connection idle for too long",
+        ))
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for IdleTimeout<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        match Pin::new(&mut this.inner).poll_read(cx, buf) {
+            Poll::Ready(result) => {
+                this.progress();
+                Poll::Ready(result)
+            }
+            Poll::Pending => this.poll_expired(cx).map(Err),
+        }
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for IdleTimeout<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        match Pin::new(&mut this.inner).poll_write(cx, buf) {
+            Poll::Ready(result) => {
+                this.progress();
+                Poll::Ready(result)
+            }
+            Poll::Pending => this.poll_expired(cx).map(Err),
+        }
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        match Pin::new(&mut this.inner).poll_write_vectored(cx, bufs) {
+            Poll::Ready(result) => {
+                this.progress();
+                Poll::Ready(result)
+            }
+            Poll::Pending => this.poll_expired(cx).map(Err),
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,211 @@
+//! What the server reports once it is listening: a summary on stdout and,
+//! when `ready_file` is configured, the same report as JSON for downstream
+//! gaffer tasks to wait on before they start talking to the API.
+
+use std::fs;
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::admin::Features;
+use crate::clock::Clock;
+use crate::config::Config;
+
+/// The build settings `POST /build` runs with.
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildSettings {
+    pub command: String,
+    pub dir: PathBuf,
+    pub db: PathBuf,
+}
+
+/// Configuration, bound addresses and enabled features of a running server.
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupReport {
+    pub service: String,
+    pub version: String,
+    pub pid: u32,
+    /// Unix time the listeners were bound.
+    pub started_at: u64,
+    pub api_url: String,
+    pub metrics_url: String,
+    pub build: BuildSettings,
+    pub endpoints: Vec<String>,
+    pub features: Features,
+}
+
+impl StartupReport {
+    /// Describes a server listening on `api`, with `/metrics` on
+    /// `metrics` if it has a listener of its own.
+    pub fn new(
+        config: &Config,
+        api: SocketAddr,
+        metrics: Option<SocketAddr>,
+        clock: &dyn Clock,
+    ) -> Self {
+        let api_url = format!("http://{}", api);
+        StartupReport {
+            service: "rust-backend".to_string(),
+            version: "1.0.0".to_string(),
+            pid: std::process::id(),
+            started_at: clock.unix_timestamp(),
+            metrics_url: match metrics {
+                Some(addr) => format!("http://{}/metrics", addr),
+                None => format!("{}/metrics", api_url),
+            },
+            api_url,
+            build: BuildSettings {
+                command: config.build_command.clone(),
+                dir: config.build_dir.clone(),
+                db: config.build_db.clone(),
+            },
+            endpoints: endpoints(config),
+            features: Features::from_config(config),
+        }
+    }
+
+    /// Prints the report as aligned `key  value` lines.
+    pub fn print(&self) {
+        let features = &self.features;
+        let mut enabled = Vec::new();
+        if features.admin {
+            enabled.push("admin".to_string());
+        }
+        if features.response_cache {
+            enabled.push("response cache".to_string());
+        }
+        if features.registry {
+            enabled.push("registry".to_string());
+        }
+        if features.mqtt {
+            enabled.push("mqtt".to_string());
+        }
+        if features.mdns {
+            enabled.push("mdns".to_string());
+        }
+        if enabled.is_empty() {
+            enabled.push("none".to_string());
+        }
+
+        println!("{} {} ready (pid {})", self.service, self.version, self.pid);
+        println!("  api        {}", self.api_url);
+        println!("  metrics    {}", self.metrics_url);
+        println!("  build      {}", self.build.command);
+        println!("  build dir  {}", self.build.dir.display());
+        println!("  build db   {}", self.build.db.display());
+        println!(
+            "  limits     {} connections, {} in flight",
+            features.max_connections, features.max_in_flight
+        );
+        println!(
+            "  timeouts   idempotency {}s, long-poll {}s",
+            features.idempotency_ttl_secs, features.poll_timeout_secs
+        );
+        println!("  features   {}", enabled.join(", "));
+        for (i, endpoint) in self.endpoints.iter().enumerate() {
+            let label = if i == 0 { "endpoints" } else { "" };
+            println!("  {:<9}  {}", label, endpoint);
+        }
+    }
+
+    /// Writes the report to `path` as JSON. The file is written under a
+    /// temporary name and renamed into place, so a task polling for it
+    /// never reads half a report.
+    pub fn write_ready_file(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, path)
+    }
+}
+
+/// Removes a ready file on shutdown, so a later run's dependents don't
+/// mistake it for the new server's. A file already gone is fine.
+pub fn remove_ready_file(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Routes served on the API port, as `METHOD /path`.
+fn endpoints(config: &Config) -> Vec<String> {
+    let mut endpoints: Vec<String> = [
+        "GET /health",
+        "GET /api",
+        "POST /build",
+        "GET /build/{id}/logs",
+        "DELETE /build/{id}",
+        "GET /builds",
+        "GET /events/poll",
+        "GET /status",
+    ]
+    .iter()
+    .map(|endpoint| endpoint.to_string())
+    .collect();
+    if config.metrics_port.is_none() {
+        endpoints.insert(1, "GET /metrics".to_string());
+    }
+    if config.admin.is_some() {
+        endpoints.push("GET /admin".to_string());
+    }
+    endpoints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::admin::AdminConfig;
+    use crate::clock::MockClock;
+
+    fn addr(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn reports_bound_addresses_and_routes() {
+        let mut config = Config::default();
+        let clock = MockClock::at(1_700_000_000);
+
+        let report = StartupReport::new(&config, addr(8080), None, &clock);
+        assert_eq!(report.api_url, "http://127.0.0.1:8080");
+        assert_eq!(report.metrics_url, "http://127.0.0.1:8080/metrics");
+        assert_eq!(report.started_at, 1_700_000_000);
+        assert!(report.endpoints.contains(&"GET /metrics".to_string()));
+        assert!(!report.features.admin);
+
+        config.metrics_port = Some(9100);
+        config.admin = Some(AdminConfig {
+            username: "admin".to_string(),
+            password: "hunter2".to_string(),
+        });
+        let report = StartupReport::new(&config, addr(8080), Some(addr(9100)), &clock);
+        assert_eq!(report.metrics_url, "http://127.0.0.1:9100/metrics");
+        assert!(!report.endpoints.contains(&"GET /metrics".to_string()));
+        assert!(report.endpoints.contains(&"GET /admin".to_string()));
+        assert!(report.features.admin);
+    }
+
+    #[test]
+    fn ready_file_is_written_whole_and_removed() {
+        let dir = std::env::temp_dir().join(format!("rust-backend-ready-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ready.json");
+        let report = StartupReport::new(&Config::default(), addr(8080), None, &MockClock::at(5));
+
+        report.write_ready_file(&path).unwrap();
+        let written: serde_json::Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(written["api_url"], "http://127.0.0.1:8080");
+        assert_eq!(written["started_at"], 5);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        remove_ready_file(&path).unwrap();
+        assert!(!path.exists());
+        remove_ready_file(&path).unwrap();
+        fs::remove_dir(&dir).unwrap();
+    }
+}
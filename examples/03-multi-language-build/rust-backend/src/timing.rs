@@ -0,0 +1,76 @@
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::extract::Request;
+use axum::http::header::HeaderValue;
+use axum::middleware::Next;
+use axum::response::Response;
+
+pub const SERVER_TIMING_HEADER: &str = "server-timing";
+
+tokio::task_local! {
+    static TIMINGS: Timings;
+}
+
+/// Phase durations recorded while one request is handled.
+#[derive(Clone)]
+struct Timings(Arc<Mutex<Phases>>);
+
+struct Phases {
+    started: Instant,
+    last: Instant,
+    recorded: Vec<(&'static str, Duration)>,
+}
+
+impl Timings {
+    fn new() -> Self {
+        let now = Instant::now();
+        Timings(Arc::new(Mutex::new(Phases {
+            started: now,
+            last: now,
+            recorded: Vec::new(),
+        })))
+    }
+
+    fn header_value(&self) -> String {
+        let phases = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let mut value = String::new();
+        for (name, duration) in &phases.recorded {
+            let _ = write!(value, "{};dur={:.3}, ", name, millis(*duration));
+        }
+        let _ = write!(value, "total;dur={:.3}", millis(phases.started.elapsed()));
+        value
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Ends the current phase of the request being handled, attributing the time
+/// since the previous mark (or the request's arrival) to `phase`. Does
+/// nothing outside the [`server_timing`] middleware.
+///
+/// Handlers call `mark("deser")` once their extractors have run; the JSON
+/// reply helpers mark `handler` and `ser`.
+pub fn mark(phase: &'static str) {
+    let _ = TIMINGS.try_with(|timings| {
+        let mut phases = timings.0.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let elapsed = now - phases.last;
+        phases.last = now;
+        phases.recorded.push((phase, elapsed));
+    });
+}
+
+/// Middleware adding a `Server-Timing` header that breaks the response's
+/// latency down into the phases recorded with [`mark`] plus `total`.
+pub async fn server_timing(req: Request, next: Next) -> Response {
+    let timings = Timings::new();
+    let mut response = TIMINGS.scope(timings.clone(), next.run(req)).await;
+    if let Ok(value) = HeaderValue::from_str(&timings.header_value()) {
+        response.headers_mut().insert(SERVER_TIMING_HEADER, value);
+    }
+    response
+}
@@ -0,0 +1,368 @@
+//! Responses of the axum server compared byte for byte against fixtures
+//! recorded from the original warp implementation. Those are frozen, except
+//! `api.json` and `metrics.json`, which have since grown with the endpoints
+//! and metrics added after the port.
+//!
+//! The server runs on a [`MockClock`] pinned to the Unix epoch, so wall-clock
+//! fields (`timestamp`, `started_at`, `finished_at`) read 0 like the masked
+//! fixtures. `duration_ms` is measured with a monotonic timer and is zeroed
+//! before comparing.
+
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::{Method, StatusCode};
+use rust_backend::app::{self, AppState};
+use rust_backend::builds::BuildManager;
+use rust_backend::clock::MockClock;
+use rust_backend::config::Config;
+use rust_backend::history::BuildHistory;
+use rust_backend::limits::Limits;
+use rust_backend::metrics::ServerMetrics;
+use rust_backend::server::{self, Timeouts};
+use tokio::net::TcpListener;
+
+const MASKED_FIELDS: [&str; 1] = ["duration_ms"];
+
+fn test_config() -> Config {
+    Config {
+        build_command: "echo hello; echo world".to_string(),
+        build_dir: PathBuf::from("."),
+        build_db: PathBuf::from(":memory:"),
+        ..Config::default()
+    }
+}
+
+async fn spawn(config: Config) -> SocketAddr {
+    let history = BuildHistory::open(&config.build_db).unwrap();
+    let clock = Arc::new(MockClock::at(0));
+    let builds = BuildManager::new(&config, history, clock.clone()).unwrap();
+    let metrics = Arc::new(ServerMetrics::default());
+    let limits = Limits::new(&config, metrics.clone(), clock.clone());
+    let state = AppState {
+        builds,
+        poll_timeout: config.poll_timeout,
+        limits: limits.clone(),
+        metrics: metrics.clone(),
+        clock,
+    };
+    let routes = app::router(state, &config);
+
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let timeouts = Timeouts::from_config(&config);
+    tokio::spawn(server::serve(listener, routes, limits, timeouts, metrics));
+    addr
+}
+
+struct Client {
+    http: reqwest::Client,
+    addr: SocketAddr,
+}
+
+impl Client {
+    fn new(addr: SocketAddr) -> Self {
+        Client {
+            http: reqwest::Client::new(),
+            addr,
+        }
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+    ) -> (StatusCode, reqwest::header::HeaderMap, String) {
+        self.send_with(
+            self.http
+                .request(method, format!("http://{}{}", self.addr, path)),
+        )
+        .await
+    }
+
+    async fn send_with(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> (StatusCode, reqwest::header::HeaderMap, String) {
+        let response = request.send().await.unwrap();
+        let status = response.status();
+        let headers = response.headers().clone();
+        (status, headers, response.text().await.unwrap())
+    }
+
+    async fn wait_for_build(&self, id: u64) {
+        for _ in 0..100 {
+            let (_, _, body) = self.send(Method::GET, "/builds").await;
+            if body.contains(&format!("\"id\":{},", id)) && !body.contains("\"running\"") {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("build {} did not finish", id);
+    }
+}
+
+/// Zeroes the number after each of the `MASKED_FIELDS`: `duration_ms` is
+/// measured on the monotonic clock, which `MockClock` doesn't pin.
+fn mask(body: &str) -> String {
+    let mut masked = body.to_string();
+    for field in MASKED_FIELDS {
+        let key = format!("\"{}\":", field);
+        let mut from = 0;
+        while let Some(found) = masked[from..].find(&key) {
+            let start = from + found + key.len();
+            let digits = masked[start..]
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(masked.len() - start);
+            if digits > 0 {
+                masked.replace_range(start..start + digits, "0");
+            }
+            from = start;
+        }
+    }
+    masked
+}
+
+fn fixture(name: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/compat")
+        .join(name);
+    std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("cannot read {}: {}", path.display(), e))
+        .trim_end_matches('\n')
+        .to_string()
+}
+
+fn assert_json(headers: &reqwest::header::HeaderMap, body: &str, name: &str) {
+    assert_eq!(headers["content-type"], "application/json", "{}", name);
+    assert_eq!(mask(body), fixture(&format!("{}.json", name)), "{}", name);
+}
+
+/// `(event, data)` pairs of an event stream, so framing differences between
+/// SSE encoders (`data:x` vs `data: x`) don't matter but payloads do.
+fn sse_events(stream: &str) -> Vec<(String, String)> {
+    stream
+        .split("\n\n")
+        .filter(|block| !block.trim().is_empty())
+        .map(|block| {
+            let mut event = String::new();
+            let mut data = String::new();
+            for line in block.lines() {
+                let (field, value) = line.split_once(':').unwrap_or((line, ""));
+                let value = value.strip_prefix(' ').unwrap_or(value);
+                match field {
+                    "event" => event = value.to_string(),
+                    "data" => data = value.to_string(),
+                    _ => {}
+                }
+            }
+            (event, mask(&data))
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn static_endpoints_match_warp() {
+    let addr = spawn(test_config()).await;
+
+    // First request on a fresh connection, as when the fixture was recorded.
+    let client = Client::new(addr);
+    let (status, headers, body) = client.send(Method::GET, "/metrics").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_json(&headers, &body, "metrics");
+
+    for name in ["health", "api"] {
+        let (status, headers, body) = client.send(Method::GET, &format!("/{}", name)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_json(&headers, &body, name);
+    }
+}
+
+#[tokio::test]
+async fn responses_are_gzipped_on_request() {
+    let addr = spawn(test_config()).await;
+    let client = Client::new(addr);
+    let url = format!("http://{}/api", addr);
+
+    let request = client.http.get(&url).header("accept-encoding", "gzip");
+    let (status, headers, _) = client.send_with(request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["content-encoding"], "gzip");
+
+    let (_, headers, _) = client.send(Method::GET, "/api").await;
+    assert!(headers.get("content-encoding").is_none());
+}
+
+#[tokio::test]
+async fn build_endpoints_match_warp() {
+    let client = Client::new(spawn(test_config()).await);
+
+    let trigger = client
+        .http
+        .post(format!("http://{}/build", client.addr))
+        .header("idempotency-key", "compat");
+    let (status, headers, body) = client.send_with(trigger).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_json(&headers, &body, "build_started");
+    client.wait_for_build(1).await;
+
+    let retry = client
+        .http
+        .post(format!("http://{}/build", client.addr))
+        .header("idempotency-key", "compat");
+    let (status, headers, body) = client.send_with(retry).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(headers["idempotency-replayed"], "true");
+    assert_json(&headers, &body, "build_started");
+
+    let reused = client
+        .http
+        .post(format!("http://{}/builds", client.addr))
+        .header("idempotency-key", "compat");
+    let (status, headers, body) = client.send_with(reused).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_json(&headers, &body, "idempotency_mismatch");
+
+    let (status, headers, body) = client.send(Method::GET, "/builds").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_json(&headers, &body, "builds");
+
+    let (status, headers, body) = client
+        .send(Method::GET, "/builds?status=failed&limit=5")
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_json(&headers, &body, "builds_failed");
+
+    let (status, headers, body) = client.send(Method::DELETE, "/build/999").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_json(&headers, &body, "cancel_not_found");
+
+    let (status, headers, body) = client.send(Method::DELETE, "/build/1").await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_json(&headers, &body, "cancel_finished");
+
+    let (status, headers, body) = client.send(Method::GET, "/build/999/logs").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_json(&headers, &body, "logs_not_found");
+
+    let (status, headers, body) = client.send(Method::GET, "/build/1/logs").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["content-type"], "text/event-stream");
+    assert_eq!(sse_events(&body), sse_events(&fixture("build_logs.sse")));
+}
+
+#[tokio::test]
+async fn overload_and_unknown_routes_match_warp() {
+    let client = Client::new(
+        spawn(Config {
+            max_in_flight: 0,
+            ..test_config()
+        })
+        .await,
+    );
+
+    let (status, headers, body) = client.send(Method::GET, "/health").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(headers["retry-after"], "1");
+    assert_json(&headers, &body, "overloaded");
+
+    let client = Client::new(spawn(test_config()).await);
+    let (status, _, body) = client.send(Method::GET, "/nope").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body, "");
+}
+
+#[tokio::test]
+async fn middleware_headers_survive_the_port() {
+    let client = Client::new(spawn(test_config()).await);
+
+    let request = client
+        .http
+        .get(format!("http://{}/health", client.addr))
+        .header("origin", "http://example.test")
+        .header("x-request-id", "compat-1");
+    let (status, headers, _) = client.send_with(request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        headers["access-control-allow-origin"],
+        "http://example.test"
+    );
+    assert_eq!(headers["x-request-id"], "compat-1");
+    let timing = headers["server-timing"].to_str().unwrap();
+    for phase in ["deser;dur=", "handler;dur=", "ser;dur=", "total;dur="] {
+        assert!(timing.contains(phase), "{} missing from {}", phase, timing);
+    }
+
+    let preflight = client
+        .http
+        .request(Method::OPTIONS, format!("http://{}/build", client.addr))
+        .header("origin", "http://example.test")
+        .header("access-control-request-method", "POST");
+    let (status, headers, _) = client.send_with(preflight).await;
+    assert_eq!(status, StatusCode::OK);
+    let methods = headers["access-control-allow-methods"].to_str().unwrap();
+    for method in ["GET", "POST", "DELETE"] {
+        assert!(
+            methods.contains(method),
+            "{} missing from {}",
+            method,
+            methods
+        );
+    }
+}
+
+#[tokio::test]
+async fn accept_language_localizes_messages_only() {
+    let client = Client::new(spawn(test_config()).await);
+
+    let request = client
+        .http
+        .get(format!("http://{}/build/999/logs", client.addr))
+        .header("accept-language", "de-CH, en;q=0.5");
+    let (status, headers, body) = client.send_with(request).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(headers["content-language"], "de");
+    assert!(
+        body.contains("\"error\":\"Build 999 nicht gefunden\""),
+        "{}",
+        body
+    );
+
+    let request = client
+        .http
+        .get(format!("http://{}/health", client.addr))
+        .header("accept-language", "de");
+    let (_, _, body) = client.send_with(request).await;
+    assert!(body.contains("\"uptime\":\"5 Min. 32 Sek.\""), "{}", body);
+    assert!(body.contains("\"status\":\"healthy\""), "{}", body);
+}
+
+#[tokio::test]
+async fn cached_gets_are_invalidated_by_builds() {
+    let client = Client::new(spawn(test_config()).await);
+
+    let (_, first, _) = client.send(Method::GET, "/health").await;
+    let (_, second, _) = client.send(Method::GET, "/health").await;
+    assert_eq!(first["x-cache"], "miss");
+    assert_eq!(second["x-cache"], "hit");
+
+    let (_, _, empty) = client.send(Method::GET, "/builds").await;
+    let (_, headers, _) = client.send(Method::GET, "/builds").await;
+    assert_eq!(headers["x-cache"], "hit");
+
+    let (status, _, _) = client.send(Method::POST, "/build").await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    let (_, headers, started) = client.send(Method::GET, "/builds").await;
+    assert_eq!(headers["x-cache"], "miss");
+    assert_ne!(started, empty);
+
+    // Finishing is not an HTTP mutation; the build's events invalidate it.
+    client.wait_for_build(1).await;
+    let (_, _, finished) = client.send(Method::GET, "/builds").await;
+    assert!(finished.contains("\"succeeded\""), "{}", finished);
+
+    let (_, _, metrics) = client.send(Method::GET, "/metrics").await;
+    assert!(metrics.contains("\"cache\":{\"hits\":"), "{}", metrics);
+}
@@ -0,0 +1,214 @@
+use crate::api::{self, Api};
+use crate::health::Service;
+use serde::Serialize;
+use serde_json::Value;
+use std::time::Instant;
+use tokio::task::JoinSet;
+
+/// The figures `metrics --all` compares, whatever each service calls them.
+/// A service that doesn't report one leaves it `None`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct NormalizedMetrics {
+    pub uptime_secs: Option<f64>,
+    pub requests: Option<i64>,
+    pub memory_mb: Option<f64>,
+    /// Goroutines, threads or workers.
+    pub workers: Option<i64>,
+    pub cpu_cores: Option<i64>,
+}
+
+/// How one service answered `/metrics`.
+#[derive(Debug, Serialize)]
+pub struct ServiceMetrics {
+    pub name: String,
+    pub url: String,
+    pub metrics: Option<NormalizedMetrics>,
+    pub latency_ms: u128,
+    pub error: Option<String>,
+}
+
+/// Sums over the services that reported each figure.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Totals {
+    pub requests: i64,
+    pub memory_mb: f64,
+    pub workers: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AggregateReport {
+    pub services: Vec<ServiceMetrics>,
+    pub totals: Totals,
+}
+
+impl AggregateReport {
+    pub fn failed(&self) -> usize {
+        self.services.iter().filter(|s| s.error.is_some()).count()
+    }
+}
+
+// The names each backend in the example uses, first match wins. Dotted
+// names reach into nested objects.
+const UPTIME: [&str; 3] = ["uptime", "uptime_seconds", "uptime_secs"];
+const REQUESTS: [&str; 5] = [
+    "requests_total",
+    "requests_served",
+    "total_requests",
+    "request_count",
+    "requests",
+];
+const MEMORY_MB: [&str; 2] = ["memory_usage_mb", "memory_mb"];
+const MEMORY_BYTES: [&str; 3] = ["memory_bytes", "rss_bytes", "memory.rss"];
+const WORKERS: [&str; 4] = ["goroutines", "threads", "workers", "worker_count"];
+const CPU_CORES: [&str; 3] = ["cpu_cores", "cpus", "cpu_count"];
+
+/// Fetches every service's `/metrics` at once with `api`'s settings,
+/// returning results in manifest order.
+pub async fn fetch_all(api: &Api, services: &[Service]) -> AggregateReport {
+    let mut fetches = JoinSet::new();
+    for (i, service) in services.iter().enumerate() {
+        let api = api.with_base_url(&service.url);
+        let name = service.name.clone();
+        fetches.spawn(async move {
+            let started = Instant::now();
+            let result = api::get_metrics_document(&api).await;
+            let latency_ms = started.elapsed().as_millis();
+            let (metrics, error) = match result {
+                Ok(document) => (Some(normalize(&document)), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            let service = ServiceMetrics {
+                name,
+                url: api.base_url().to_string(),
+                metrics,
+                latency_ms,
+                error,
+            };
+            (i, service)
+        });
+    }
+    let mut results: Vec<(usize, ServiceMetrics)> = fetches.join_all().await;
+    results.sort_by_key(|(i, _)| *i);
+    let services: Vec<ServiceMetrics> = results.into_iter().map(|(_, s)| s).collect();
+    let totals = totals(&services);
+    AggregateReport { services, totals }
+}
+
+/// Maps one service's `/metrics` document onto [`NormalizedMetrics`]. The
+/// rust-backend's `{"success": ..., "data": ...}` wrapper is looked through.
+pub fn normalize(document: &Value) -> NormalizedMetrics {
+    let document = match document.get("data") {
+        Some(data) if document.get("success").is_some() => data,
+        _ => document,
+    };
+    let first = |names: &[&str]| names.iter().find_map(|name| lookup(document, name));
+    NormalizedMetrics {
+        uptime_secs: first(&UPTIME).and_then(|v| match v {
+            Value::String(text) => parse_go_duration(text),
+            v => v.as_f64(),
+        }),
+        requests: first(&REQUESTS).and_then(Value::as_i64),
+        memory_mb: first(&MEMORY_MB).and_then(Value::as_f64).or_else(|| {
+            first(&MEMORY_BYTES)
+                .and_then(Value::as_f64)
+                .map(|bytes| bytes / (1024.0 * 1024.0))
+        }),
+        workers: first(&WORKERS).and_then(Value::as_i64),
+        cpu_cores: first(&CPU_CORES).and_then(Value::as_i64),
+    }
+}
+
+fn lookup<'a>(document: &'a Value, dotted: &str) -> Option<&'a Value> {
+    dotted
+        .split('.')
+        .try_fold(document, |value, key| value.get(key))
+        .filter(|value| !value.is_null())
+}
+
+/// Seconds in a Go `time.Duration` string such as `1h2m3.5s` or `150ms`.
+pub fn parse_go_duration(text: &str) -> Option<f64> {
+    let mut rest = text.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    let mut secs = 0.0;
+    while !rest.is_empty() {
+        let split = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .filter(|&at| at > 0)?;
+        let (number, tail) = rest.split_at(split);
+        let number: f64 = number.parse().ok()?;
+        let unit_len = tail
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        secs += number
+            * match unit {
+                "h" => 3600.0,
+                "m" => 60.0,
+                "s" => 1.0,
+                "ms" => 1e-3,
+                "us" | "µs" => 1e-6,
+                "ns" => 1e-9,
+                _ => return None,
+            };
+        rest = tail;
+    }
+    Some(secs)
+}
+
+fn totals(services: &[ServiceMetrics]) -> Totals {
+    let mut totals = Totals::default();
+    for metrics in services.iter().filter_map(|s| s.metrics.as_ref()) {
+        totals.requests += metrics.requests.unwrap_or(0);
+        totals.memory_mb += metrics.memory_mb.unwrap_or(0.0);
+        totals.workers += metrics.workers.unwrap_or(0);
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_normalizes_each_backends_names() {
+        let go = normalize(&json!({
+            "uptime": "1h2m3.5s",
+            "requests_total": 42,
+            "memory_usage_mb": 12.5,
+            "goroutines": 4,
+            "cpu_cores": 8
+        }));
+        assert_eq!(
+            go,
+            NormalizedMetrics {
+                uptime_secs: Some(3723.5),
+                requests: Some(42),
+                memory_mb: Some(12.5),
+                workers: Some(4),
+                cpu_cores: Some(8),
+            }
+        );
+
+        let rust = normalize(&json!({
+            "success": true,
+            "data": { "requests_served": 1247, "languages_integrated": 4 }
+        }));
+        assert_eq!(rust.requests, Some(1247));
+        assert_eq!(rust.uptime_secs, None);
+
+        let node = normalize(&json!({ "uptime": 90, "memory": { "rss": 52428800 } }));
+        assert_eq!(node.uptime_secs, Some(90.0));
+        assert_eq!(node.memory_mb, Some(50.0));
+    }
+
+    #[test]
+    fn test_go_durations() {
+        assert_eq!(parse_go_duration("150ms"), Some(0.15));
+        assert_eq!(parse_go_duration("2m"), Some(120.0));
+        assert_eq!(parse_go_duration("5 minutes"), None);
+        assert_eq!(parse_go_duration("h"), None);
+    }
+}
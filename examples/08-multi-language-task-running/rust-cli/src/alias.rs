@@ -0,0 +1,177 @@
+use crate::config::{self, Alias};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::path::PathBuf;
+
+/// An alias as shown by `alias list`.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct AliasSummary {
+    pub name: String,
+    pub command: Vec<String>,
+    /// A built-in command has the same name, and wins.
+    pub shadowed: bool,
+}
+
+/// Every alias in name order, noting those a built-in command hides.
+pub fn list(aliases: &BTreeMap<String, Alias>, cli: &clap::Command) -> Vec<AliasSummary> {
+    aliases
+        .iter()
+        .map(|(name, alias)| AliasSummary {
+            name: name.clone(),
+            command: alias.words(),
+            shadowed: is_builtin(cli, name),
+        })
+        .collect()
+}
+
+/// The process's arguments with an alias in the command position replaced
+/// by what it stands for. Aliases come from the config file that `--config`
+/// or `PREDICTION_CLI_CONFIG` names, or the default one; a file that can't
+/// be read is left for the command itself to report.
+pub fn expand_args(args: Vec<String>, cli: &clap::Command) -> Result<Vec<String>, String> {
+    let Some(at) = command_index(&args, cli) else {
+        return Ok(args);
+    };
+    if is_builtin(cli, &args[at]) {
+        return Ok(args);
+    }
+    let explicit =
+        config_arg(&args[..at]).or_else(|| env::var_os("PREDICTION_CLI_CONFIG").map(PathBuf::from));
+    let aliases = config::resolve_path(explicit.as_deref())
+        .and_then(|path| config::load(Some(&path), false).ok())
+        .map(|file| file.aliases)
+        .unwrap_or_default();
+    expand(args, at, &aliases)
+}
+
+fn expand(
+    mut args: Vec<String>,
+    at: usize,
+    aliases: &BTreeMap<String, Alias>,
+) -> Result<Vec<String>, String> {
+    let Some(alias) = aliases.get(&args[at]) else {
+        return Ok(args);
+    };
+    let words = alias.words();
+    if words.is_empty() {
+        return Err(format!("alias {:?} is empty", args[at]));
+    }
+    args.splice(at..=at, words);
+    Ok(args)
+}
+
+fn is_builtin(cli: &clap::Command, name: &str) -> bool {
+    name == "help"
+        || cli.get_subcommands().any(|command| {
+            command.get_name() == name || command.get_all_aliases().any(|a| a == name)
+        })
+}
+
+/// Where the command word is in `args`, after the program name and any
+/// global options (and their values) before it. `cli` must be built.
+fn command_index(args: &[String], cli: &clap::Command) -> Option<usize> {
+    let takes_value = |arg: &clap::Arg| arg.get_action().takes_values();
+    let long_takes_value = |name: &str| {
+        cli.get_arguments()
+            .any(|arg| arg.get_long() == Some(name) && takes_value(arg))
+    };
+    let short_takes_value = |c: char| {
+        cli.get_arguments()
+            .any(|arg| arg.get_short() == Some(c) && takes_value(arg))
+    };
+
+    let mut i = 1;
+    while i < args.len() {
+        let arg = &args[i];
+        if arg == "--" {
+            return None;
+        }
+        if let Some(long) = arg.strip_prefix("--") {
+            if !long.contains('=') && long_takes_value(long) {
+                i += 1;
+            }
+        } else if let Some(shorts) = arg.strip_prefix('-').filter(|s| !s.is_empty()) {
+            // In a cluster such as `-vu URL` the first option that takes a
+            // value gets the rest of the word, or the next word.
+            if let Some((at, _)) = shorts.char_indices().find(|(_, c)| short_takes_value(*c)) {
+                if at + 1 == shorts.len() {
+                    i += 1;
+                }
+            }
+        } else {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// The value of a `--config`/`-c` among global options.
+fn config_arg(args: &[String]) -> Option<PathBuf> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(path.into());
+        }
+        if arg == "--config" || arg == "-c" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("-c").filter(|p| !p.is_empty()) {
+            return Some(path.into());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Cli;
+    use clap::CommandFactory;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_expands_the_command_word_only() {
+        let mut cli = Cli::command();
+        cli.build();
+        let aliases = BTreeMap::from([
+            (
+                "hm".to_string(),
+                Alias::Line("health -u http://prod:8080".to_string()),
+            ),
+            (
+                "p".to_string(),
+                Alias::Words(vec![
+                    "predict".to_string(),
+                    "-f".to_string(),
+                    "0.1, 0.2".to_string(),
+                ]),
+            ),
+        ]);
+        let expanded = |line: &str| {
+            let args = args(line);
+            let at = command_index(&args, &cli).unwrap();
+            expand(args, at, &aliases).unwrap().join(" ")
+        };
+
+        assert_eq!(
+            expanded("cli -u http://x -vv hm -o json"),
+            "cli -u http://x -vv health -u http://prod:8080 -o json"
+        );
+        assert_eq!(
+            expanded("cli --timeout=3 p"),
+            "cli --timeout=3 predict -f 0.1, 0.2"
+        );
+        assert_eq!(expanded("cli -c hm.toml list hm"), "cli -c hm.toml list hm");
+        assert_eq!(command_index(&args("cli -o json"), &cli), None);
+        assert_eq!(
+            config_arg(&args("cli -v --config a.toml")),
+            Some("a.toml".into())
+        );
+        assert!(is_builtin(&cli, "list") && !is_builtin(&cli, "hm"));
+    }
+}
@@ -1,26 +1,104 @@
+use crate::cache::{self, ResponseCache};
+use crate::cassette::{Cassette, Interaction};
+use crate::contract::Contract;
+use crate::hooks;
+use crate::logging;
+use crate::rate::{Limiter, Rate};
+use crate::schema::Schema;
+use crate::signing;
+use crate::tasks::TaskStatus;
+use crate::unix;
+use colored::*;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct HealthResponse {
     pub status: String,
     pub timestamp: String,
     pub version: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct Prediction {
     pub label: String,
     pub confidence: f64,
+    /// When it was made, from servers that record it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct PredictionsResponse {
     pub predictions: Vec<Prediction>,
     pub count: usize,
     pub timestamp: String,
+    /// Where the next page starts, from servers that paginate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+/// Which page of `/predictions` to ask for; servers that don't paginate
+/// ignore it and send everything.
+#[derive(Debug, Clone, Default)]
+pub struct Page {
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+    /// A `next_cursor` from an earlier page.
+    pub cursor: Option<String>,
+    /// Filters for servers that apply them, e.g. `("label", "cats")`.
+    pub filters: Vec<(&'static str, String)>,
+}
+
+impl Page {
+    fn path(&self) -> String {
+        let mut query = Vec::new();
+        if let Some(limit) = self.limit {
+            query.push(format!("limit={}", limit));
+        }
+        if let Some(offset) = self.offset {
+            query.push(format!("offset={}", offset));
+        }
+        if let Some(cursor) = &self.cursor {
+            query.push(format!("cursor={}", encode_query(cursor)));
+        }
+        for (name, value) in &self.filters {
+            query.push(format!("{}={}", name, encode_query(value)));
+        }
+        if query.is_empty() {
+            "/predictions".to_string()
+        } else {
+            format!("/predictions?{}", query.join("&"))
+        }
+    }
+}
+
+/// Percent-encodes everything but RFC 3986 unreserved characters.
+fn encode_query(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct MetricsResponse {
     pub uptime: String,
     pub requests_total: i64,
@@ -29,48 +107,1062 @@ pub struct MetricsResponse {
     pub cpu_cores: i32,
 }
 
+/// An entry in the rust-backend's event log. Only the envelope is fixed; the
+/// rest depends on `type`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Event {
+    pub seq: u64,
+    pub timestamp: u64,
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(flatten)]
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+/// One `GET /events/poll` answer.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EventBatch {
+    pub events: Vec<Event>,
+    pub next_cursor: u64,
+    /// Events after the cursor were dropped, or the backend restarted.
+    pub missed: bool,
+}
+
+/// A model artifact the rust-backend can serve `/predict` with. Only `id`
+/// is fixed; backends add what they know, such as accuracy or size.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Model {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    /// Whether it is the one serving `/predict`.
+    #[serde(default)]
+    pub active: bool,
+    #[serde(flatten)]
+    pub details: serde_json::Map<String, serde_json::Value>,
+}
+
+/// `POST /explain`'s answer: how much each feature pushed the prediction.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExplainResponse {
+    pub contributions: Contributions,
+}
+
+/// In the order the features were sent, or by feature name.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Contributions {
+    List(Vec<f64>),
+    Named(BTreeMap<String, f64>),
+}
+
+/// The rust-backend's `{"success": ..., "data": ...}` wrapper.
+#[derive(Deserialize)]
+struct Envelope<T> {
+    data: T,
+}
+
 #[derive(Serialize)]
-struct PredictRequest {
-    features: Vec<f64>,
+struct PredictRequest<T> {
+    features: T,
+}
+
+/// Features given by name, sent as a JSON object in the order given.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedFeatures(pub Vec<(String, f64)>);
+
+impl Serialize for NamedFeatures {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (name, value) in &self.0 {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+/// Longest wait between two attempts, however many retries came before.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Idle connections kept per host: enough for `bench`'s default concurrency
+/// to reuse one each.
+const POOL_MAX_IDLE_PER_HOST: usize = 32;
+
+/// How long an idle pooled connection is kept.
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// TCP keepalive probes on pooled connections, so ones a NAT or load
+/// balancer dropped are noticed rather than reused.
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+/// How idempotent requests are retried after connection errors and 5xx
+/// responses.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Attempts after the first.
+    pub retries: u32,
+    /// Wait before the first retry; doubled for each one after.
+    pub delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Wait before retry number `retry` (counting from 1). Up to half of it
+    /// is taken off at random so clients that failed together don't retry in
+    /// lockstep.
+    fn backoff(&self, retry: u32) -> Duration {
+        let doubled = self
+            .delay
+            .saturating_mul(1 << retry.saturating_sub(1).min(16));
+        doubled
+            .min(MAX_RETRY_DELAY)
+            .mul_f64(1.0 - fastrand::f64() / 2.0)
+    }
 }
 
-pub async fn check_health(base_url: &str) -> Result<HealthResponse, Box<dyn std::error::Error>> {
-    let url = format!("{}/health", base_url);
-    let response = reqwest::get(&url).await?;
-    let health = response.json::<HealthResponse>().await?;
-    Ok(health)
+/// A request that still failed after every attempt it was allowed.
+#[derive(Debug)]
+pub struct RequestFailed {
+    pub request: String,
+    pub attempts: u32,
+    pub last_error: String,
+    /// The last attempt never got an answer: it couldn't connect or timed
+    /// out.
+    pub unreachable: bool,
+}
+
+impl fmt::Display for RequestFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plural = if self.attempts == 1 { "" } else { "s" };
+        write!(
+            f,
+            "{} failed after {} attempt{}: {}",
+            self.request, self.attempts, plural, self.last_error
+        )
+    }
+}
+
+impl Error for RequestFailed {}
+
+/// The API answered with a status that isn't success and isn't retried.
+#[derive(Debug)]
+pub struct StatusError {
+    pub request: String,
+    pub status: StatusCode,
+    pub body: String,
+}
+
+impl fmt::Display for StatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} returned {}", self.request, self.status)?;
+        if !self.body.trim().is_empty() {
+            write!(f, ": {}", self.body.trim())?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for StatusError {}
+
+impl StatusError {
+    fn new(request: String, status: StatusCode, body: &[u8]) -> Self {
+        StatusError {
+            request,
+            status,
+            body: String::from_utf8_lossy(body).into_owned(),
+        }
+    }
+}
+
+/// Where and how to reach the API.
+#[derive(Debug, Clone)]
+pub struct ApiOptions {
+    pub base_url: String,
+    /// Base URLs tried in order when the one in use can't be connected to.
+    pub fallback_urls: Vec<String>,
+    /// Abandon a request, body included, after this long.
+    pub timeout: Duration,
+    /// Give up connecting after this long.
+    pub connect_timeout: Duration,
+    /// Sent as `Authorization: Bearer <token>`.
+    pub token: Option<String>,
+    /// Sent as `X-API-Key: <key>`.
+    pub api_key: Option<String>,
+    /// Signs every request with an HMAC under this key; see [`signing`].
+    pub signing_key: Option<String>,
+    /// Accept any server certificate, self-signed or not.
+    pub insecure: bool,
+    /// PEM bundle of extra certificate authorities to trust.
+    pub ca_cert: Option<PathBuf>,
+    /// Send every request through this proxy, hosts in `NO_PROXY` aside.
+    /// Without it the usual `HTTP_PROXY`/`HTTPS_PROXY`/`NO_PROXY` apply.
+    pub proxy: Option<String>,
+    pub retry: RetryPolicy,
+    /// Pace batch and benchmark requests to no more than this.
+    pub rate: Option<Rate>,
+    /// How much of each exchange to print to stderr: 1 for request and
+    /// status lines with timing, 2 to add headers and bodies.
+    pub verbose: u8,
+    /// Print the first request instead of sending it, and end the run.
+    pub dry_run: Option<Preview>,
+    /// Show credentials where requests are printed instead of redacting them.
+    pub show_secrets: bool,
+    /// Reuse connections between requests; off only to measure what that
+    /// saves.
+    pub keep_alive: bool,
+    /// Shell command whose `Name: value` lines are added as headers to
+    /// every request; see [`hooks::pre_request`].
+    pub pre_request: Option<String>,
+}
+
+/// How `--dry-run` prints the request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preview {
+    /// Method, URL, headers and body.
+    Request,
+    /// An equivalent `curl` command.
+    Curl,
+}
+
+/// The API, reached through one HTTP client shared by every request so
+/// connections are reused across retries.
+#[derive(Debug, Clone)]
+pub struct Api {
+    options: ApiOptions,
+    client: reqwest::Client,
+    /// Shared by every clone, so one cassette holds the whole run.
+    cassette: Option<Arc<Cassette>>,
+    /// Where successful `GET`s are kept, or with `--offline` answered from.
+    cache: Option<Arc<ResponseCache>>,
+    /// With `--validate`, the spec every response is checked against.
+    contract: Option<Arc<Contract>>,
+    /// Shared by every clone too, so `--rate` bounds the whole run however
+    /// many requests are in flight. See [`Api::throttle`].
+    limiter: Option<Arc<Limiter>>,
+    /// Index of the endpoint requests go to first, among the base URL and
+    /// then the fallbacks: the last one that answered. Shared by every clone.
+    active: Arc<AtomicUsize>,
+    /// How to reach the base URL and then each fallback.
+    targets: Vec<Target>,
+}
+
+/// One endpoint, with the client that reaches it: the shared one over TCP,
+/// or for a `unix://` URL one connecting to its socket.
+#[derive(Debug, Clone)]
+struct Target {
+    /// What request URLs start with; see [`unix::request_base`].
+    base: String,
+    client: reqwest::Client,
+}
+
+impl Api {
+    pub fn new(options: ApiOptions) -> Result<Self, Box<dyn Error>> {
+        for url in &options.fallback_urls {
+            if unix::socket_path(url).is_none() {
+                reqwest::Url::parse(url)
+                    .map_err(|e| format!("invalid fallback URL {}: {}", url, e))?;
+            }
+        }
+        let client = Api::client_builder(&options)?.build()?;
+        let targets = std::iter::once(&options.base_url)
+            .chain(&options.fallback_urls)
+            .map(|url| Api::target(&options, &client, url))
+            .collect::<Result<_, _>>()?;
+        let limiter = options.rate.map(|rate| Arc::new(Limiter::new(rate)));
+        Ok(Api {
+            options,
+            client,
+            cassette: None,
+            cache: None,
+            contract: None,
+            limiter,
+            active: Arc::new(AtomicUsize::new(0)),
+            targets,
+        })
+    }
+
+    /// The client settings `options` ask for, before any socket.
+    fn client_builder(options: &ApiOptions) -> Result<reqwest::ClientBuilder, Box<dyn Error>> {
+        // One client for the whole run, and clones share its pool, so
+        // requests after the first skip the TCP and TLS handshakes. HTTPS
+        // servers that offer HTTP/2 get it through ALPN.
+        let idle_per_host = if options.keep_alive {
+            POOL_MAX_IDLE_PER_HOST
+        } else {
+            0
+        };
+        let mut builder = reqwest::Client::builder()
+            .timeout(options.timeout)
+            .connect_timeout(options.connect_timeout)
+            .pool_max_idle_per_host(idle_per_host)
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .tcp_keepalive(TCP_KEEPALIVE)
+            .danger_accept_invalid_certs(options.insecure);
+        if let Some(path) = &options.ca_cert {
+            let pem = fs::read(path)
+                .map_err(|e| format!("cannot read CA bundle {}: {}", path.display(), e))?;
+            let certs = reqwest::Certificate::from_pem_bundle(&pem)
+                .map_err(|e| format!("invalid CA bundle {}: {}", path.display(), e))?;
+            if certs.is_empty() {
+                return Err(format!("no certificates in CA bundle {}", path.display()).into());
+            }
+            for cert in certs {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        if let Some(url) = &options.proxy {
+            let proxy = reqwest::Proxy::all(url)
+                .map_err(|e| format!("invalid proxy {}: {}", url, e))?
+                .no_proxy(reqwest::NoProxy::from_env());
+            builder = builder.proxy(proxy);
+        }
+        Ok(builder)
+    }
+
+    /// How to reach `url`: through `client`, or for a `unix://` URL a
+    /// client of its own connected to the socket.
+    fn target(
+        options: &ApiOptions,
+        client: &reqwest::Client,
+        url: &str,
+    ) -> Result<Target, Box<dyn Error>> {
+        let client = match unix::socket_path(url) {
+            Some(path) => unix::connect_to(Api::client_builder(options)?, path)?.build()?,
+            None => client.clone(),
+        };
+        Ok(Target {
+            base: unix::request_base(url).to_string(),
+            client,
+        })
+    }
+
+    /// Default options against a test server, without retries.
+    #[cfg(test)]
+    pub fn for_tests(base_url: String) -> Self {
+        Api::new(ApiOptions {
+            base_url,
+            fallback_urls: Vec::new(),
+            timeout: Duration::from_secs(5),
+            connect_timeout: Duration::from_secs(1),
+            token: None,
+            api_key: None,
+            signing_key: None,
+            insecure: false,
+            ca_cert: None,
+            proxy: None,
+            retry: RetryPolicy {
+                retries: 0,
+                delay: Duration::ZERO,
+            },
+            rate: None,
+            verbose: 0,
+            dry_run: None,
+            show_secrets: false,
+            keep_alive: true,
+            pre_request: None,
+        })
+        .unwrap()
+    }
+
+    pub fn options(&self) -> &ApiOptions {
+        &self.options
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.options.base_url
+    }
+
+    /// Records every exchange to `cassette`, or answers every request from
+    /// it, depending on how it was opened.
+    pub fn with_cassette(mut self, cassette: Cassette) -> Api {
+        self.cassette = Some(Arc::new(cassette));
+        self
+    }
+
+    /// Keeps each successful `GET` in `cache`, or with `--offline` answers
+    /// every request from it.
+    pub fn with_cache(mut self, cache: ResponseCache) -> Api {
+        self.cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Checks every response from the API against `contract`, reporting
+    /// where they differ.
+    pub fn with_contract(mut self, contract: Arc<Contract>) -> Api {
+        self.contract = Some(contract);
+        self
+    }
+
+    /// Waits until `--rate` allows another request, or returns at once
+    /// without one. Batch and benchmark loops call it before each request.
+    pub async fn throttle(&self) {
+        if let Some(limiter) = &self.limiter {
+            limiter.wait().await;
+        }
+    }
+
+    /// The same client and options aimed at another deployment, without
+    /// the fallbacks.
+    pub fn with_base_url(&self, base_url: &str) -> Api {
+        let mut api = self.clone();
+        api.options.base_url = base_url.to_string();
+        api.options.fallback_urls.clear();
+        api.active = Arc::new(AtomicUsize::new(0));
+        // Without a socket client, requests fail naming the unix:// URL.
+        let target = Api::target(&self.options, &self.client, base_url).unwrap_or_else(|e| {
+            tracing::warn!(url = base_url, error = %e, "cannot reach the socket");
+            Target {
+                base: base_url.to_string(),
+                client: self.client.clone(),
+            }
+        });
+        api.targets = vec![target];
+        api
+    }
+
+    /// The base URL for index 0, then the fallbacks in order.
+    fn endpoint(&self, index: usize) -> &str {
+        match index {
+            0 => &self.options.base_url,
+            i => &self.options.fallback_urls[i - 1],
+        }
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let active = &self.targets[self.active.load(Ordering::Relaxed)];
+        let url = format!("{}{}", active.base, path);
+        let mut request = active.client.request(method, url);
+        if let Some(token) = &self.options.token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(key) = &self.options.api_key {
+            request = request.header("X-API-Key", key);
+        }
+        request
+    }
+
+    /// Sends `request` as a single attempt, in a `request` span of its own.
+    async fn send(&self, request: RequestBuilder) -> Result<(StatusCode, Vec<u8>), Box<dyn Error>> {
+        let request = request.build()?;
+        let span = tracing::info_span!(
+            "request",
+            method = %request.method(),
+            path = request_path(request.url())
+        );
+        self.attempt(request).instrument(span).await
+    }
+
+    /// Sends `request` and reads the whole body, printing both sides to
+    /// stderr as `verbose` asks. With a cassette, the exchange is recorded
+    /// or replayed instead of sent.
+    async fn attempt(
+        &self,
+        mut request: reqwest::Request,
+    ) -> Result<(StatusCode, Vec<u8>), Box<dyn Error>> {
+        let answered_here = self.cassette.as_ref().is_some_and(|c| c.replaying())
+            || self.cache.as_ref().is_some_and(|c| c.offline());
+        if let Some(hook) = self
+            .options
+            .pre_request
+            .as_deref()
+            .filter(|_| !answered_here)
+        {
+            let method = request.method().to_string();
+            let url = request.url().to_string();
+            let headers = hooks::pre_request(hook, &method, &url, self.options.timeout).await?;
+            request.headers_mut().extend(headers);
+        }
+        if let Some(key) = &self.options.signing_key {
+            signing::sign(&mut request, key);
+        }
+        let reveal = self.options.show_secrets;
+        if let Some(preview) = self.options.dry_run {
+            match preview {
+                Preview::Request => print!("{}", describe_request(&request, reveal)),
+                Preview::Curl => println!("{}", curl_command(&request, &self.options)),
+            }
+            return Err(Box::new(DryRun));
+        }
+        logging::propagate(request.headers_mut());
+        let verbose = self.options.verbose;
+        if verbose > 0 {
+            eprintln!("> {} {}", request.method(), request.url());
+        }
+        if verbose > 1 {
+            let body = request.body().and_then(|body| body.as_bytes());
+            eprint!("{}", exchange_details(">", request.headers(), body, reveal));
+        }
+        let method = request.method().to_string();
+        let path = request_path(request.url());
+        let request_body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|body| String::from_utf8_lossy(body).into_owned());
+        if let Some(cassette) = self.cassette.as_ref().filter(|c| c.replaying()) {
+            let (status, body) = cassette.answer(&method, &path, request_body.as_deref())?;
+            tracing::info!(%method, %path, status = status.as_u16(), replayed = true, "api call");
+            if verbose > 0 {
+                eprintln!("< {} (replayed)", status);
+            }
+            return Ok((status, body));
+        }
+        let cache_key = format!("{}{}", self.options.base_url, path);
+        if let Some(cache) = self.cache.as_ref().filter(|c| c.offline()) {
+            let (body, fetched_at) = cache.answer(&cache_key)?;
+            if cache.announce() {
+                eprintln!(
+                    "{} answering from the cache; stale as of {}",
+                    "Offline:".yellow().bold(),
+                    cache::describe(fetched_at)
+                );
+            }
+            if verbose > 0 {
+                eprintln!("< 200 OK (cached)");
+            }
+            return Ok((StatusCode::OK, body));
+        }
+
+        let started = Instant::now();
+        let exchanged = self.exchange(request).await;
+        let elapsed_ms = started.elapsed().as_micros() as f64 / 1000.0;
+        let (status, headers, body) = match exchanged {
+            Ok(exchanged) => exchanged,
+            Err(e) => {
+                tracing::warn!(%method, %path, elapsed_ms, error = %e, "api call failed");
+                return Err(e.into());
+            }
+        };
+        if status.is_success() {
+            tracing::info!(%method, %path, status = status.as_u16(), elapsed_ms, "api call");
+        } else {
+            tracing::warn!(%method, %path, status = status.as_u16(), elapsed_ms, "api call failed");
+        }
+        if verbose > 0 {
+            eprintln!("< {} ({:.1?})", status, started.elapsed());
+        }
+        if verbose > 1 {
+            eprint!("{}", exchange_details("<", &headers, Some(&body), reveal));
+        }
+        if let Some(contract) = &self.contract {
+            contract.report(&method, &path, status, &body);
+        }
+        if let Some(cache) = self.cache.as_ref() {
+            if status.is_success() && method == "GET" {
+                // Best effort: a cache that can't be written only costs --offline.
+                if let Err(e) = cache.store(&cache_key, &body) {
+                    tracing::warn!(error = %e, "cannot update the response cache");
+                }
+            }
+        }
+        if let Some(cassette) = &self.cassette {
+            cassette.add(Interaction {
+                method,
+                path,
+                request_body,
+                status: status.as_u16(),
+                response_body: String::from_utf8_lossy(&body).into_owned(),
+            })?;
+        }
+        Ok((status, body))
+    }
+
+    /// Sends `request` to the endpoint in use, failing over to the next one
+    /// in turn while an endpoint can't be connected to; nothing reached the
+    /// server then, so even a `POST` is safe to resend. The endpoint that
+    /// answers is the one later requests go to first.
+    async fn exchange(
+        &self,
+        mut request: reqwest::Request,
+    ) -> Result<(StatusCode, HeaderMap, Vec<u8>), reqwest::Error> {
+        let first = self.active.load(Ordering::Relaxed);
+        let count = 1 + self.options.fallback_urls.len();
+        let path = request_path(request.url());
+        let mut tried = 0;
+        loop {
+            tried += 1;
+            let spare = if tried < count {
+                request.try_clone()
+            } else {
+                None
+            };
+            let index = (first + tried - 1) % count;
+            let result = async {
+                let response = self.targets[index].client.execute(request).await?;
+                let status = response.status();
+                let headers = response.headers().clone();
+                Ok::<_, reqwest::Error>((status, headers, response.bytes().await?.to_vec()))
+            }
+            .await;
+            let next_url = reqwest::Url::parse(&format!(
+                "{}{}",
+                self.targets[(index + 1) % count].base,
+                path
+            ));
+            match (result, spare, next_url) {
+                (Err(e), Some(mut spare), Ok(url)) if e.is_connect() => {
+                    tracing::warn!(endpoint = self.endpoint(index), error = %e, "failing over");
+                    if self.options.verbose > 0 {
+                        eprintln!("> {} {} (failover)", spare.method(), url);
+                    }
+                    *spare.url_mut() = url;
+                    request = spare;
+                }
+                (result, _, _) => {
+                    if result.is_ok() && index != first {
+                        self.active.store(index, Ordering::Relaxed);
+                        eprintln!(
+                            "{} {} could not be reached; {} answered instead",
+                            "Warning:".yellow().bold(),
+                            self.endpoint(first),
+                            self.endpoint(index)
+                        );
+                    }
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// `GET`s `path` and decodes the JSON body, retrying per [`RetryPolicy`].
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T, Box<dyn Error>> {
+        let span = tracing::info_span!("request", method = "GET", path);
+        self.get_json_attempts(path).instrument(span).await
+    }
+
+    /// [`Api::get_json`]'s attempts, each in an `attempt` span.
+    async fn get_json_attempts<T: DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> Result<T, Box<dyn Error>> {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            // The timeout covers the body too, so a stalled body is retried
+            // like a stalled connection.
+            let (last_error, unreachable) = match self
+                .attempt(self.request(Method::GET, path).build()?)
+                .instrument(tracing::info_span!("attempt", attempt = attempts))
+                .await
+            {
+                Ok((status, body)) if status.is_server_error() => {
+                    let body = String::from_utf8_lossy(&body);
+                    match body.trim() {
+                        "" => (status.to_string(), false),
+                        body => (format!("{}: {}", status, body), false),
+                    }
+                }
+                Ok((status, body)) if !status.is_success() => {
+                    return Err(Box::new(StatusError::new(
+                        format!("GET {}", path),
+                        status,
+                        &body,
+                    )))
+                }
+                Ok((_, body)) => return Ok(serde_json::from_slice(&body)?),
+                Err(e) if is_unreachable(&*e) => (e.to_string(), true),
+                Err(e) => return Err(e),
+            };
+            if attempts > self.options.retry.retries {
+                tracing::warn!(path, attempts, error = %last_error, "giving up");
+                return Err(Box::new(RequestFailed {
+                    request: format!("GET {}", path),
+                    attempts,
+                    last_error,
+                    unreachable,
+                }));
+            }
+            let delay = self.options.retry.backoff(attempts);
+            tracing::debug!(
+                path,
+                attempt = attempts,
+                delay_ms = delay.as_millis() as u64,
+                error = %last_error,
+                "retrying"
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+pub async fn check_health(api: &Api) -> Result<HealthResponse, Box<dyn Error>> {
+    api.get_json("/health").await
 }
 
 pub async fn get_predictions(
-    base_url: &str,
-) -> Result<PredictionsResponse, Box<dyn std::error::Error>> {
-    let url = format!("{}/predictions", base_url);
-    let response = reqwest::get(&url).await?;
-    let predictions = response.json::<PredictionsResponse>().await?;
-    Ok(predictions)
-}
-
-pub async fn make_prediction(
-    base_url: &str,
-    features: &[f64],
-) -> Result<Prediction, Box<dyn std::error::Error>> {
-    let url = format!("{}/predict", base_url);
-    let client = reqwest::Client::new();
-    let request_body = PredictRequest {
-        features: features.to_vec(),
+    api: &Api,
+    page: &Page,
+) -> Result<PredictionsResponse, Box<dyn Error>> {
+    api.get_json(&page.path()).await
+}
+
+/// Every page from `page` on, following `next_cursor` until the server
+/// stops sending one.
+pub async fn get_all_predictions(
+    api: &Api,
+    page: &Page,
+) -> Result<PredictionsResponse, Box<dyn Error>> {
+    let mut all = get_predictions(api, page).await?;
+    let mut page = Page {
+        offset: None,
+        ..page.clone()
     };
+    while let Some(cursor) = all.next_cursor.take() {
+        // A server that ignores the cursor would hand back the same one forever.
+        if page.cursor.as_ref() == Some(&cursor) {
+            break;
+        }
+        page.cursor = Some(cursor);
+        let next = get_predictions(api, &page).await?;
+        all.predictions.extend(next.predictions);
+        all.timestamp = next.timestamp;
+        all.next_cursor = next.next_cursor;
+    }
+    all.count = all.predictions.len();
+    Ok(all)
+}
+
+/// Not retried: the API would record a second prediction.
+pub async fn make_prediction(api: &Api, features: &[f64]) -> Result<Prediction, Box<dyn Error>> {
+    post_prediction(api, features).await
+}
+
+/// Like [`make_prediction`], with the features as a `{name: value}` object.
+pub async fn make_named_prediction(
+    api: &Api,
+    features: &NamedFeatures,
+) -> Result<Prediction, Box<dyn Error>> {
+    post_prediction(api, features).await
+}
 
-    let response = client.post(&url).json(&request_body).send().await?;
+async fn post_prediction<T: Serialize + ?Sized>(
+    api: &Api,
+    features: &T,
+) -> Result<Prediction, Box<dyn Error>> {
+    let request_body = PredictRequest { features };
 
-    let prediction = response.json::<Prediction>().await?;
+    let (status, body) = api
+        .send(api.request(Method::POST, "/predict").json(&request_body))
+        .await?;
+    if !status.is_success() {
+        return Err(Box::new(StatusError::new(
+            "POST /predict".to_string(),
+            status,
+            &body,
+        )));
+    }
+
+    let prediction = serde_json::from_slice(&body)?;
     Ok(prediction)
 }
 
-pub async fn get_metrics(base_url: &str) -> Result<MetricsResponse, Box<dyn std::error::Error>> {
-    let url = format!("{}/metrics", base_url);
-    let response = reqwest::get(&url).await?;
-    let metrics = response.json::<MetricsResponse>().await?;
-    Ok(metrics)
+#[derive(Serialize)]
+struct ImportRequest<'a> {
+    predictions: &'a [Prediction],
+    /// Replace the target's records with the same labels and timestamps.
+    replace: bool,
+}
+
+#[derive(Deserialize)]
+struct ImportResponse {
+    imported: usize,
+}
+
+/// Writes prediction records as they are, for `import`. Not retried, for
+/// the same reason as [`make_prediction`].
+pub async fn import_predictions(
+    api: &Api,
+    predictions: &[Prediction],
+    replace: bool,
+) -> Result<usize, Box<dyn Error>> {
+    let request_body = ImportRequest {
+        predictions,
+        replace,
+    };
+    let (status, body) = api
+        .send(
+            api.request(Method::POST, "/predictions")
+                .json(&request_body),
+        )
+        .await?;
+    if !status.is_success() {
+        return Err(Box::new(StatusError::new(
+            "POST /predictions".to_string(),
+            status,
+            &body,
+        )));
+    }
+    let response: ImportResponse = serde_json::from_slice(&body)?;
+    Ok(response.imported)
+}
+
+/// The path and query string of `url`, as the server sees it.
+pub fn request_path(url: &reqwest::Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    }
+}
+
+/// Couldn't connect, or timed out.
+fn is_unreachable(error: &(dyn Error + 'static)) -> bool {
+    error
+        .downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_connect() || e.is_timeout())
+}
+
+/// Ends a `--dry-run` at the first request, which has been printed instead
+/// of sent.
+#[derive(Debug)]
+pub struct DryRun;
+
+impl fmt::Display for DryRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "dry run: the request was not sent")
+    }
+}
+
+impl Error for DryRun {}
+
+/// The method and URL, the headers with credentials redacted, and the body,
+/// pretty-printed when it is JSON.
+fn describe_request(request: &reqwest::Request, reveal: bool) -> String {
+    let mut description = format!("{} {}\n", request.method(), request.url());
+    for (name, value) in request.headers() {
+        description += &format!("{}: {}\n", name, shown_value(name, value, reveal));
+    }
+    let body = request.body().and_then(|body| body.as_bytes());
+    if let Some(body) = body.filter(|body| !body.is_empty()) {
+        let pretty = serde_json::from_slice::<serde_json::Value>(body)
+            .ok()
+            .and_then(|json| serde_json::to_string_pretty(&json).ok());
+        description += "\n";
+        description += &pretty.unwrap_or_else(|| String::from_utf8_lossy(body).into_owned());
+        description += "\n";
+    }
+    description
+}
+
+/// A `curl` command that sends `request` as the CLI would, with the same
+/// TLS, proxy and timeout settings.
+fn curl_command(request: &reqwest::Request, options: &ApiOptions) -> String {
+    let mut words = vec!["curl".to_string()];
+    if request.method() != Method::GET {
+        words.push(format!("-X {}", request.method()));
+    }
+    words.push(shell_quote(request.url().as_str()));
+    for (name, value) in request.headers() {
+        let header = format!(
+            "{}: {}",
+            name,
+            shown_value(name, value, options.show_secrets)
+        );
+        words.push(format!("-H {}", shell_quote(&header)));
+    }
+    if let Some(body) = request.body().and_then(|body| body.as_bytes()) {
+        words.push(format!(
+            "--data {}",
+            shell_quote(&String::from_utf8_lossy(body))
+        ));
+    }
+    if let Some(path) = unix::socket_path(&options.base_url) {
+        words.push(format!(
+            "--unix-socket {}",
+            shell_quote(&path.display().to_string())
+        ));
+    }
+    if options.insecure {
+        words.push("--insecure".to_string());
+    }
+    if let Some(path) = &options.ca_cert {
+        words.push(format!(
+            "--cacert {}",
+            shell_quote(&path.display().to_string())
+        ));
+    }
+    if let Some(proxy) = &options.proxy {
+        words.push(format!("--proxy {}", shell_quote(proxy)));
+    }
+    words.push(format!(
+        "--connect-timeout {} --max-time {}",
+        options.connect_timeout.as_secs_f64(),
+        options.timeout.as_secs_f64()
+    ));
+    words.join(" \\\n  ")
+}
+
+/// `value` in single quotes for a POSIX shell, unless it needs none.
+fn shell_quote(value: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c);
+    if !value.is_empty() && value.chars().all(plain) {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+/// Headers that carry credentials, shown only as `[redacted]`.
+const SECRET_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "x-api-key",
+    "cookie",
+    "set-cookie",
+];
+
+/// Shown as `[redacted]` unless `reveal`, like any header marked sensitive,
+/// such as those a `pre_request` hook adds.
+fn shown_value<'a>(name: &HeaderName, value: &'a HeaderValue, reveal: bool) -> Cow<'a, str> {
+    if !reveal && (SECRET_HEADERS.contains(&name.as_str()) || value.is_sensitive()) {
+        "[redacted]".into()
+    } else {
+        String::from_utf8_lossy(value.as_bytes())
+    }
+}
+
+/// Headers, a blank line and the body (if any), each line after `marker`
+/// like `curl -v`.
+fn exchange_details(
+    marker: &str,
+    headers: &HeaderMap,
+    body: Option<&[u8]>,
+    reveal: bool,
+) -> String {
+    let mut details = String::new();
+    for (name, value) in headers {
+        details += &format!(
+            "{} {}: {}\n",
+            marker,
+            name,
+            shown_value(name, value, reveal)
+        );
+    }
+    details += &format!("{}\n", marker);
+    if let Some(body) = body.filter(|body| !body.is_empty()) {
+        details += &String::from_utf8_lossy(body);
+        details += "\n";
+    }
+    details
+}
+
+/// Waits up to `timeout_secs` for events after `cursor`. Not retried; the
+/// caller polls again from the same cursor.
+pub async fn poll_events(
+    api: &Api,
+    cursor: u64,
+    timeout_secs: u64,
+) -> Result<EventBatch, Box<dyn Error>> {
+    let path = format!("/events/poll?cursor={}&timeout={}", cursor, timeout_secs);
+    // Room for the answer to arrive after the backend stops waiting.
+    let timeout = Duration::from_secs(timeout_secs + 10);
+    let (status, body) = api
+        .send(api.request(Method::GET, &path).timeout(timeout))
+        .await?;
+    if !status.is_success() {
+        return Err(Box::new(StatusError::new(
+            format!("GET {}", path),
+            status,
+            &body,
+        )));
+    }
+    let envelope: Envelope<EventBatch> = serde_json::from_slice(&body)?;
+    Ok(envelope.data)
+}
+
+/// Sends one request to `path` and checks only its status: a `GET`, or for
+/// `/predict` a `POST` of `features`. Not retried; for load testing.
+pub async fn send_raw(api: &Api, path: &str, features: &[f64]) -> Result<(), Box<dyn Error>> {
+    let (method, request) = if path == "/predict" {
+        let body = PredictRequest { features };
+        (Method::POST, api.request(Method::POST, path).json(&body))
+    } else {
+        (Method::GET, api.request(Method::GET, path))
+    };
+    let (status, body) = api.send(request).await?;
+    if !status.is_success() {
+        return Err(Box::new(StatusError::new(
+            format!("{} {}", method, path),
+            status,
+            &body,
+        )));
+    }
+    Ok(())
+}
+
+pub async fn get_metrics(api: &Api) -> Result<MetricsResponse, Box<dyn Error>> {
+    api.get_json("/metrics").await
+}
+
+/// `/metrics` as whatever JSON the service sends, for services that don't
+/// share the Go API's fields.
+pub async fn get_metrics_document(api: &Api) -> Result<serde_json::Value, Box<dyn Error>> {
+    api.get_json("/metrics").await
+}
+
+pub async fn get_schema(api: &Api) -> Result<Schema, Box<dyn Error>> {
+    api.get_json("/schema").await
+}
+
+/// The API's OpenAPI spec, as JSON.
+pub async fn get_openapi(api: &Api) -> Result<Vec<u8>, Box<dyn Error>> {
+    let spec: serde_json::Value = api.get_json("/openapi.json").await?;
+    Ok(serde_json::to_vec(&spec)?)
+}
+
+/// Asks for the features' contributions to their prediction. Not retried,
+/// like [`make_prediction`].
+pub async fn explain<T: Serialize + ?Sized>(
+    api: &Api,
+    features: &T,
+) -> Result<ExplainResponse, Box<dyn Error>> {
+    let request_body = PredictRequest { features };
+    let (status, body) = api
+        .send(api.request(Method::POST, "/explain").json(&request_body))
+        .await?;
+    if !status.is_success() {
+        return Err(Box::new(StatusError::new(
+            "POST /explain".to_string(),
+            status,
+            &body,
+        )));
+    }
+    Ok(serde_json::from_slice(&body)?)
+}
+
+/// The gaffer build's tasks, from the backend.
+pub async fn get_tasks(api: &Api) -> Result<TaskStatus, Box<dyn Error>> {
+    let envelope: Envelope<TaskStatus> = api.get_json("/gaffer/tasks").await?;
+    Ok(envelope.data)
+}
+
+pub async fn get_models(api: &Api) -> Result<Vec<Model>, Box<dyn Error>> {
+    let envelope: Envelope<Vec<Model>> = api.get_json("/models").await?;
+    Ok(envelope.data)
+}
+
+pub async fn get_model(api: &Api, id: &str) -> Result<Model, Box<dyn Error>> {
+    let envelope: Envelope<Model> = api
+        .get_json(&format!("/models/{}", encode_query(id)))
+        .await?;
+    Ok(envelope.data)
+}
+
+/// Makes `id` the model serving `/predict`; answers with it as it now is.
+pub async fn activate_model(api: &Api, id: &str) -> Result<Model, Box<dyn Error>> {
+    let path = format!("/models/{}/activate", encode_query(id));
+    let (status, body) = api.send(api.request(Method::POST, &path)).await?;
+    if !status.is_success() {
+        return Err(Box::new(StatusError::new(
+            format!("POST {}", path),
+            status,
+            &body,
+        )));
+    }
+    let envelope: Envelope<Model> = serde_json::from_slice(&body)?;
+    Ok(envelope.data)
 }
 
 #[cfg(test)]
@@ -85,5 +1177,476 @@ mod tests {
         let json = serde_json::to_string(&request).unwrap();
         assert!(json.contains("features"));
         assert!(json.contains("0.1"));
+
+        let named = NamedFeatures(vec![
+            ("width".to_string(), 2.0),
+            ("height".to_string(), 1.5),
+        ]);
+        let json = serde_json::to_string(&PredictRequest { features: &named }).unwrap();
+        assert_eq!(json, r#"{"features":{"width":2.0,"height":1.5}}"#);
+    }
+
+    fn api(url: String, retries: u32) -> Api {
+        Api::new(ApiOptions {
+            base_url: url,
+            fallback_urls: Vec::new(),
+            timeout: Duration::from_secs(5),
+            connect_timeout: Duration::from_secs(1),
+            token: None,
+            api_key: None,
+            signing_key: None,
+            insecure: false,
+            ca_cert: None,
+            proxy: None,
+            retry: RetryPolicy {
+                retries,
+                delay: Duration::from_millis(1),
+            },
+            rate: None,
+            verbose: 0,
+            dry_run: None,
+            show_secrets: false,
+            keep_alive: true,
+            pre_request: None,
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_are_retried_then_summarized() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/health")
+            .with_status(503)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let err = check_health(&api(server.url(), 2)).await.unwrap_err();
+
+        mock.assert_async().await;
+        assert_eq!(
+            err.to_string(),
+            "GET /health failed after 3 attempts: 503 Service Unavailable"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_are_reported_with_their_status() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/metrics")
+            .with_status(401)
+            .with_body("missing token")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let err = get_metrics(&api(server.url(), 2)).await.unwrap_err();
+
+        mock.assert_async().await;
+        assert_eq!(
+            err.to_string(),
+            "GET /metrics returned 401 Unauthorized: missing token"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_predictions_are_not_retried() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/predict")
+            .with_status(503)
+            .expect(1)
+            .create_async()
+            .await;
+
+        assert!(make_prediction(&api(server.url(), 2), &[0.1])
+            .await
+            .is_err());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_slow_responses_time_out() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/health")
+            .with_chunked_body(|_| {
+                std::thread::sleep(Duration::from_millis(500));
+                Ok(())
+            })
+            .create_async()
+            .await;
+        let mut options = api(server.url(), 0).options;
+        options.timeout = Duration::from_millis(50);
+        let api = Api::new(options).unwrap();
+
+        let err = check_health(&api).await.unwrap_err();
+
+        assert!(
+            err.to_string().contains("failed after 1 attempt"),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_credentials_are_sent_as_headers() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/health")
+            .match_header("authorization", "Bearer secret")
+            .match_header("x-api-key", "key")
+            .with_body(r#"{"status":"healthy","timestamp":"now","version":"1"}"#)
+            .create_async()
+            .await;
+        let mut options = api(server.url(), 0).options;
+        options.token = Some("secret".to_string());
+        options.api_key = Some("key".to_string());
+
+        check_health(&Api::new(options).unwrap()).await.unwrap();
+
+        mock.assert_async().await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_pre_request_hook_headers_are_sent_and_redacted() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/health")
+            .match_header("x-vault-token", "GET fresh")
+            .with_body(r#"{"status":"healthy","timestamp":"now","version":"1"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let mut options = api(server.url(), 0).options;
+        options.pre_request = Some(r#"echo "X-Vault-Token: $PREDICTION_CLI_METHOD fresh""#.into());
+        let api = Api::new(options).unwrap();
+
+        check_health(&api).await.unwrap();
+        check_health(&api).await.unwrap();
+        let mut request = api.request(Method::GET, "/health").build().unwrap();
+        let mut token = HeaderValue::from_static("s3cret");
+        token.set_sensitive(true);
+        request.headers_mut().insert("x-vault-token", token);
+        assert!(describe_request(&request, false).contains("x-vault-token: [redacted]"));
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_describes_the_request_without_sending_it() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/predict")
+            .expect(0)
+            .create_async()
+            .await;
+        let mut options = api(server.url(), 0).options;
+        options.token = Some("secret".to_string());
+        options.dry_run = Some(Preview::Request);
+        let api = Api::new(options).unwrap();
+
+        let err = make_prediction(&api, &[0.1]).await.unwrap_err();
+        assert!(err.is::<DryRun>());
+        mock.assert_async().await;
+
+        let request = api
+            .request(Method::POST, "/predict")
+            .json(&PredictRequest { features: [0.1] })
+            .build()
+            .unwrap();
+        assert_eq!(
+            describe_request(&request, false),
+            format!(
+                "POST {}/predict\nauthorization: [redacted]\ncontent-type: application/json\n\n{{\n  \"features\": [\n    0.1\n  ]\n}}\n",
+                server.url()
+            )
+        );
+    }
+
+    #[test]
+    fn test_curl_command_reproduces_the_request() {
+        let mut options = api("http://localhost:8080".to_string(), 0).options;
+        options.token = Some("secret".to_string());
+        options.insecure = true;
+        let request = Api::new(options.clone())
+            .unwrap()
+            .request(Method::POST, "/predict")
+            .body(r#"{"label":"it's"}"#)
+            .build()
+            .unwrap();
+        let timeouts = format!(
+            "--connect-timeout {} --max-time {}",
+            options.connect_timeout.as_secs_f64(),
+            options.timeout.as_secs_f64()
+        );
+
+        assert_eq!(
+            curl_command(&request, &options),
+            format!(
+                "curl \\\n  -X POST \\\n  http://localhost:8080/predict \\\n  \
+                 -H 'authorization: [redacted]' \\\n  \
+                 --data '{{\"label\":\"it'\\''s\"}}' \\\n  --insecure \\\n  {}",
+                timeouts
+            )
+        );
+        options.show_secrets = true;
+        assert!(curl_command(&request, &options).contains("-H 'authorization: Bearer secret'"));
+    }
+
+    #[tokio::test]
+    async fn test_signed_requests_verify_against_the_key() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/predict")
+            .with_body_from_request(|request| {
+                let header = |name| request.header(name)[0].to_str().unwrap().to_string();
+                let timestamp = header(signing::TIMESTAMP_HEADER).parse().unwrap();
+                let expected = signing::signature(
+                    b"shared",
+                    "POST",
+                    "/predict",
+                    timestamp,
+                    request.body().unwrap(),
+                );
+                let label = if header(signing::SIGNATURE_HEADER) == expected {
+                    "verified"
+                } else {
+                    "forged"
+                };
+                format!(r#"{{"label":"{}","confidence":1.0}}"#, label).into()
+            })
+            .create_async()
+            .await;
+        let mut options = api(server.url(), 0).options;
+        options.signing_key = Some("shared".to_string());
+
+        let prediction = make_prediction(&Api::new(options).unwrap(), &[0.1, 0.2])
+            .await
+            .unwrap();
+
+        assert_eq!(prediction.label, "verified");
+    }
+
+    #[tokio::test]
+    async fn test_requests_go_through_the_proxy() {
+        let mut proxy = mockito::Server::new_async().await;
+        let mock = proxy
+            .mock("GET", "/health")
+            .match_header("host", "api.invalid")
+            .with_body(r#"{"status":"healthy","timestamp":"now","version":"1"}"#)
+            .create_async()
+            .await;
+        let mut options = api("http://api.invalid".to_string(), 0).options;
+        options.proxy = Some(proxy.url());
+
+        check_health(&Api::new(options).unwrap()).await.unwrap();
+
+        mock.assert_async().await;
+    }
+
+    /// Connections a bare HTTP/1.1 server accepted while answering `requests`
+    /// health checks.
+    async fn connections_for(requests: usize, keep_alive: bool) -> usize {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let accepted = Arc::new(AtomicUsize::new(0));
+        let counter = accepted.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let body = r#"{"status":"healthy","timestamp":"now","version":"1"}"#;
+                    let mut buf = [0; 1024];
+                    // Each read is one whole GET, which has no body.
+                    while matches!(socket.read(&mut buf).await, Ok(n) if n > 0) {
+                        let response = format!(
+                            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                            body.len(),
+                            body
+                        );
+                        if socket.write_all(response.as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        let mut options = api(url, 0).options;
+        options.keep_alive = keep_alive;
+        let api = Api::new(options).unwrap();
+        for _ in 0..requests {
+            check_health(&api).await.unwrap();
+        }
+        accepted.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn test_connections_are_reused_unless_keep_alive_is_off() {
+        assert_eq!(connections_for(3, true).await, 1);
+        assert_eq!(connections_for(3, false).await, 3);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_endpoints_fail_over_to_the_next() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/predict")
+            .with_body(r#"{"label":"cats","confidence":0.5}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let closed_url = format!("http://{}", closed.local_addr().unwrap());
+        drop(closed);
+        let mut options = api(closed_url.clone(), 0).options;
+        options.fallback_urls = vec![closed_url, server.url()];
+        let api = Api::new(options).unwrap();
+
+        make_prediction(&api, &[0.1]).await.unwrap();
+        // The endpoint that answered is tried first from then on.
+        assert_eq!(api.active.load(Ordering::Relaxed), 2);
+        make_prediction(&api, &[0.1]).await.unwrap();
+
+        mock.assert_async().await;
+        assert!(api
+            .with_base_url(&server.url())
+            .options
+            .fallback_urls
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failover_gives_up_after_every_endpoint() {
+        let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let closed_url = format!("http://{}", closed.local_addr().unwrap());
+        drop(closed);
+        let mut options = api(closed_url.clone(), 0).options;
+        options.fallback_urls = vec![closed_url];
+
+        let err = make_prediction(&Api::new(options).unwrap(), &[0.1])
+            .await
+            .unwrap_err();
+
+        assert!(is_unreachable(&*err));
+    }
+
+    #[test]
+    fn test_ca_bundle_must_hold_certificates() {
+        let path = std::env::temp_dir().join(format!("empty-ca-{}.pem", std::process::id()));
+        fs::write(&path, "not a certificate\n").unwrap();
+        let mut options = api("http://localhost".to_string(), 0).options;
+        options.ca_cert = Some(path.clone());
+
+        let err = Api::new(options.clone()).unwrap_err();
+        fs::remove_file(&path).unwrap();
+        assert!(err.to_string().starts_with("no certificates"), "{}", err);
+
+        options.ca_cert = Some(path);
+        let err = Api::new(options).unwrap_err();
+        assert!(
+            err.to_string().starts_with("cannot read CA bundle"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_exchange_details_redact_credentials() {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer secret".parse().unwrap());
+        headers.insert("content-type", "application/json".parse().unwrap());
+
+        let details = exchange_details(">", &headers, Some(br#"{"features":[1.0]}"#), false);
+
+        assert_eq!(
+            details,
+            "> authorization: [redacted]\n> content-type: application/json\n>\n{\"features\":[1.0]}\n"
+        );
+        assert!(!details.contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn test_poll_events_unwraps_the_envelope() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/events/poll")
+            .match_query("cursor=7&timeout=1")
+            .with_body(
+                r#"{"success":true,"timestamp":0,"language":"rust","data":{
+                    "events":[{"seq":8,"timestamp":5,"type":"build_cancelling","build_id":3}],
+                    "next_cursor":8,"missed":false}}"#,
+            )
+            .create_async()
+            .await;
+
+        let batch = poll_events(&api(server.url(), 0), 7, 1).await.unwrap();
+
+        assert_eq!(batch.next_cursor, 8);
+        assert_eq!(batch.events[0].kind, "build_cancelling");
+        assert_eq!(batch.events[0].fields["build_id"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_all_predictions_follow_the_cursor() {
+        let mut server = mockito::Server::new_async().await;
+        let page = |labels: &[&str], next: Option<&str>| {
+            let predictions: Vec<String> = labels
+                .iter()
+                .map(|l| format!(r#"{{"label":"{}","confidence":0.5}}"#, l))
+                .collect();
+            let next = next.map_or(String::new(), |c| format!(r#","next_cursor":"{}""#, c));
+            format!(
+                r#"{{"predictions":[{}],"count":{},"timestamp":"t"{}}}"#,
+                predictions.join(","),
+                labels.len(),
+                next
+            )
+        };
+        server
+            .mock("GET", "/predictions")
+            .match_query("limit=2&offset=1")
+            .with_body(page(&["a", "b"], Some("c/3")))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/predictions")
+            .match_query("limit=2&cursor=c%2F3")
+            .with_body(page(&["c"], None))
+            .create_async()
+            .await;
+
+        let first = Page {
+            limit: Some(2),
+            offset: Some(1),
+            ..Page::default()
+        };
+        let all = get_all_predictions(&api(server.url(), 0), &first)
+            .await
+            .unwrap();
+
+        let labels: Vec<&str> = all.predictions.iter().map(|p| p.label.as_str()).collect();
+        assert_eq!(labels, ["a", "b", "c"]);
+        assert_eq!(all.count, 3);
+        assert_eq!(all.next_cursor, None);
+    }
+
+    #[test]
+    fn test_backoff_doubles_with_jitter_and_is_capped() {
+        let policy = RetryPolicy {
+            retries: 5,
+            delay: Duration::from_millis(100),
+        };
+        for retry in 1..=3 {
+            let full = Duration::from_millis(100 << (retry - 1));
+            let backoff = policy.backoff(retry);
+            assert!(backoff <= full && backoff >= full / 2, "{:?}", backoff);
+        }
+        assert!(policy.backoff(40) <= MAX_RETRY_DELAY);
     }
 }
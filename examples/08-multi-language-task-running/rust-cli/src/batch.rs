@@ -0,0 +1,363 @@
+use crate::api::{self, Api, DryRun};
+use crate::exit;
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// One feature row and the prediction made for it.
+#[derive(Debug, Serialize)]
+pub struct RowResult {
+    /// 1-based position among the data rows, headers and blank lines aside.
+    pub row: usize,
+    pub features: Vec<f64>,
+    pub label: String,
+    pub confidence: f64,
+}
+
+/// A row whose prediction failed.
+#[derive(Debug, Serialize)]
+pub struct RowFailure {
+    pub row: usize,
+    pub features: Vec<f64>,
+    pub error: String,
+}
+
+/// Statistics over a whole batch. Confidences and labels cover the rows
+/// that succeeded.
+#[derive(Debug, Default, Serialize)]
+pub struct Summary {
+    pub rows: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub mean_confidence: f64,
+    pub min_confidence: f64,
+    pub max_confidence: f64,
+    /// How many rows got each label.
+    pub labels: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchReport {
+    pub results: Vec<RowResult>,
+    pub failures: Vec<RowFailure>,
+    pub summary: Summary,
+}
+
+/// A row in a JSON input file: a bare array, or an object with `features`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonRow {
+    Features(Vec<f64>),
+    Object { features: Vec<f64> },
+}
+
+/// Reads feature rows from `path`: a JSON array if it ends in `.json`,
+/// otherwise CSV.
+pub fn read_rows(path: &Path) -> Result<Vec<Vec<f64>>, Box<dyn Error>> {
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let rows = if is_json {
+        parse_json(&text)
+    } else {
+        parse_csv(&text)
+    };
+    rows.map_err(|e| format!("{}: {}", path.display(), e).into())
+}
+
+/// Reads feature rows from stdin, in the same line format as CSV files.
+pub fn read_stdin() -> Result<Vec<Vec<f64>>, Box<dyn Error>> {
+    let text = io::read_to_string(io::stdin()).map_err(|e| format!("cannot read stdin: {}", e))?;
+    parse_csv(&text).map_err(|e| format!("stdin: {}", e).into())
+}
+
+/// Parses one feature row per line, each comma-separated numbers or a JSON
+/// array. Blank lines are skipped, and so is the first line if it isn't
+/// numeric (a header).
+pub fn parse_csv(text: &str) -> Result<Vec<Vec<f64>>, String> {
+    let mut rows = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_line(line) {
+            Ok(row) => rows.push(row),
+            Err(_) if i == 0 => continue,
+            Err(e) => return Err(format!("line {}: {}", i + 1, e)),
+        }
+    }
+    if rows.is_empty() {
+        return Err("no feature rows".to_string());
+    }
+    Ok(rows)
+}
+
+/// Parses a JSON array of rows, each an array of numbers or an object with
+/// a `features` array.
+pub fn parse_json(text: &str) -> Result<Vec<Vec<f64>>, String> {
+    let rows: Vec<JsonRow> = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let rows: Vec<Vec<f64>> = rows
+        .into_iter()
+        .map(|row| match row {
+            JsonRow::Features(features) | JsonRow::Object { features } => features,
+        })
+        .collect();
+    if let Some(i) = rows.iter().position(Vec::is_empty) {
+        return Err(format!("row {} has no features", i + 1));
+    }
+    if rows.is_empty() {
+        return Err("no feature rows".to_string());
+    }
+    Ok(rows)
+}
+
+/// Parses one JSON line of features: an array of numbers, or an object with
+/// a `features` array.
+pub fn parse_json_line(line: &str) -> Result<Vec<f64>, String> {
+    let row: JsonRow = serde_json::from_str(line).map_err(|_| {
+        "expected a JSON array of numbers or an object with \"features\"".to_string()
+    })?;
+    let (JsonRow::Features(features) | JsonRow::Object { features }) = row;
+    if features.is_empty() {
+        return Err("no features".to_string());
+    }
+    Ok(features)
+}
+
+/// Parses comma-separated numbers, or a JSON array of them.
+fn parse_line(line: &str) -> Result<Vec<f64>, String> {
+    let line = line.trim();
+    if line.starts_with('[') {
+        return serde_json::from_str(line).map_err(|e| e.to_string());
+    }
+    line.split(',')
+        .map(|field| {
+            let field = field.trim();
+            field
+                .parse()
+                .map_err(|_| format!("invalid number {:?}", field))
+        })
+        .collect()
+}
+
+/// Predicts every row, up to `concurrency` at a time, keeping results in
+/// input order. A row that fails is reported with the others rather than
+/// ending the batch, unless every row fails: then the first row's error is
+/// returned, so the exit code says why.
+pub async fn predict_all(
+    api: &Api,
+    rows: Vec<Vec<f64>>,
+    concurrency: usize,
+) -> Result<BatchReport, Box<dyn Error>> {
+    let answers: Vec<_> = stream::iter(rows.into_iter().enumerate())
+        .map(|(i, features)| async move {
+            api.throttle().await;
+            let prediction = api::make_prediction(api, &features).await;
+            (i + 1, features, prediction)
+        })
+        .buffered(concurrency.max(1))
+        .collect()
+        .await;
+
+    let mut results = Vec::new();
+    let mut failures = Vec::new();
+    let mut first_error = None;
+    for (row, features, prediction) in answers {
+        match prediction {
+            Ok(prediction) => results.push(RowResult {
+                row,
+                features,
+                label: prediction.label,
+                confidence: prediction.confidence,
+            }),
+            Err(e) if e.is::<DryRun>() => return Err(e),
+            Err(e) => {
+                failures.push(RowFailure {
+                    row,
+                    features,
+                    error: e.to_string(),
+                });
+                first_error.get_or_insert((row, e));
+            }
+        }
+    }
+    if results.is_empty() {
+        if let Some((row, e)) = first_error {
+            return Err(exit::context(format!("row {}", row), e));
+        }
+    }
+    let summary = summarize(&results, failures.len());
+    Ok(BatchReport {
+        results,
+        failures,
+        summary,
+    })
+}
+
+/// Fails with [`exit::Code::PartialFailure`] if any row of `report` failed.
+pub fn check_failures(report: &BatchReport) -> Result<(), Box<dyn Error>> {
+    let Summary { rows, failed, .. } = report.summary;
+    if failed == 0 {
+        return Ok(());
+    }
+    let numbers: Vec<String> = report.failures.iter().map(|f| f.row.to_string()).collect();
+    Err(exit::partial_failure(format!(
+        "{} of {} rows failed (row{} {})",
+        failed,
+        rows,
+        if failed == 1 { "" } else { "s" },
+        numbers.join(", ")
+    )))
+}
+
+fn summarize(results: &[RowResult], failed: usize) -> Summary {
+    if results.is_empty() {
+        return Summary {
+            rows: failed,
+            failed,
+            ..Summary::default()
+        };
+    }
+    let mut summary = Summary {
+        rows: results.len() + failed,
+        succeeded: results.len(),
+        failed,
+        min_confidence: f64::INFINITY,
+        max_confidence: f64::NEG_INFINITY,
+        ..Summary::default()
+    };
+    let mut total = 0.0;
+    for result in results {
+        total += result.confidence;
+        summary.min_confidence = summary.min_confidence.min(result.confidence);
+        summary.max_confidence = summary.max_confidence.max(result.confidence);
+        *summary.labels.entry(result.label.clone()).or_default() += 1;
+    }
+    summary.mean_confidence = total / results.len() as f64;
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_skips_header_and_blank_lines() {
+        let rows = parse_csv("a,b,c\n0.1,0.2,0.3\n\n1, 2 ,3\n").unwrap();
+        assert_eq!(rows, vec![vec![0.1, 0.2, 0.3], vec![1.0, 2.0, 3.0]]);
+    }
+
+    #[test]
+    fn test_lines_may_be_json_arrays() {
+        let rows = parse_csv("[0.1, 0.2]\n0.3,0.4\n[5]\n").unwrap();
+        assert_eq!(rows, vec![vec![0.1, 0.2], vec![0.3, 0.4], vec![5.0]]);
+        assert!(parse_csv("1,2\n[1, \"x\"]\n").is_err());
+    }
+
+    #[test]
+    fn test_csv_reports_bad_lines() {
+        let err = parse_csv("0.1,0.2\n0.3,x\n").unwrap_err();
+        assert_eq!(err, "line 2: invalid number \"x\"");
+        assert!(parse_csv("a,b\n").is_err());
+    }
+
+    #[test]
+    fn test_json_accepts_arrays_and_objects() {
+        let rows = parse_json(r#"[[0.1, 0.2], {"features": [0.3]}]"#).unwrap();
+        assert_eq!(rows, vec![vec![0.1, 0.2], vec![0.3]]);
+        assert!(parse_json("[[]]").is_err());
+    }
+
+    #[test]
+    fn test_json_lines_are_arrays_or_objects() {
+        assert_eq!(parse_json_line("[0.1, 0.2]").unwrap(), vec![0.1, 0.2]);
+        assert_eq!(parse_json_line(r#"{"features": [3]}"#).unwrap(), vec![3.0]);
+        assert!(parse_json_line("0.1,0.2").is_err());
+        assert_eq!(parse_json_line("[]").unwrap_err(), "no features");
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_results_keep_input_order() {
+        let mut server = mockito::Server::new_async().await;
+        for (feature, label) in [(1.0, "one"), (2.0, "two"), (3.0, "three")] {
+            server
+                .mock("POST", "/predict")
+                .match_body(mockito::Matcher::Json(
+                    serde_json::json!({ "features": [feature] }),
+                ))
+                .with_body(format!(r#"{{"label":"{}","confidence":0.5}}"#, label))
+                .create_async()
+                .await;
+        }
+        let rows = vec![vec![3.0], vec![1.0], vec![2.0], vec![1.0]];
+
+        let report = predict_all(&Api::for_tests(server.url()), rows, 3)
+            .await
+            .unwrap();
+
+        let labels: Vec<&str> = report.results.iter().map(|r| r.label.as_str()).collect();
+        assert_eq!(labels, ["three", "one", "two", "one"]);
+        assert_eq!(report.results[3].row, 4);
+    }
+
+    #[tokio::test]
+    async fn test_failed_rows_are_reported_with_the_rest() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/predict")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({ "features": [0.0] }),
+            ))
+            .with_status(422)
+            .expect(2)
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/predict")
+            .with_body(r#"{"label":"ok","confidence":0.5}"#)
+            .create_async()
+            .await;
+        let api = Api::for_tests(server.url());
+
+        let report = predict_all(&api, vec![vec![1.0], vec![0.0], vec![2.0]], 2)
+            .await
+            .unwrap();
+
+        assert_eq!(report.results.len(), 2);
+        assert_eq!(report.failures[0].row, 2);
+        assert!(report.failures[0].error.contains("422"));
+        let err = check_failures(&report).unwrap_err();
+        assert_eq!(err.to_string(), "1 of 3 rows failed (row 2)");
+        assert_eq!(exit::code_for(&*err), exit::Code::PartialFailure);
+
+        let err = predict_all(&api, vec![vec![0.0]], 1).await.unwrap_err();
+        assert_eq!(exit::code_for(&*err), exit::Code::InvalidInput);
+        assert!(err.to_string().starts_with("row 1: "));
+    }
+
+    #[test]
+    fn test_summary_statistics() {
+        let result = |label: &str, confidence| RowResult {
+            row: 1,
+            features: vec![],
+            label: label.to_string(),
+            confidence,
+        };
+        let summary = summarize(
+            &[result("cat", 0.5), result("dog", 0.9), result("cat", 0.7)],
+            1,
+        );
+        assert_eq!(summary.rows, 4);
+        assert_eq!((summary.succeeded, summary.failed), (3, 1));
+        assert!((summary.mean_confidence - 0.7).abs() < 1e-9);
+        assert_eq!(summary.min_confidence, 0.5);
+        assert_eq!(summary.max_confidence, 0.9);
+        assert_eq!(summary.labels["cat"], 2);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::api::{self, Api};
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Endpoints `bench` can drive; `/predict` is sent `--features`.
+pub const ENDPOINTS: [&str; 4] = ["/health", "/predictions", "/metrics", "/predict"];
+
+/// Latencies in milliseconds.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Latency {
+    pub min: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BenchReport {
+    pub endpoint: String,
+    pub requests: usize,
+    pub concurrency: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub elapsed_secs: f64,
+    pub requests_per_sec: f64,
+    /// Whether connections were reused; see `--no-keep-alive`.
+    pub keep_alive: bool,
+    /// Over successful requests only.
+    pub latency_ms: Latency,
+    /// The first few distinct failures, to tell what went wrong.
+    pub errors: Vec<String>,
+}
+
+/// Distinct errors kept in a report.
+const MAX_ERRORS: usize = 5;
+
+/// Sends `requests` requests to `endpoint`, `concurrency` at a time, and
+/// measures each one. Nothing is retried.
+pub async fn run(
+    api: &Api,
+    endpoint: &str,
+    features: &[f64],
+    requests: usize,
+    concurrency: usize,
+) -> BenchReport {
+    measure(api, endpoint, requests, concurrency, || async {
+        api::send_raw(api, endpoint, features)
+            .await
+            .map_err(|e| e.to_string())
+    })
+    .await
+}
+
+/// Calls `send` `requests` times, `concurrency` at a time, and reports on
+/// the calls as requests to `endpoint`. Calls are paced by `api`'s
+/// `--rate`, and the wait for a turn isn't counted in their latency.
+pub async fn measure<F, Fut>(
+    api: &Api,
+    endpoint: &str,
+    requests: usize,
+    concurrency: usize,
+    send: F,
+) -> BenchReport
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<(), String>>,
+{
+    let started = Instant::now();
+    let outcomes: Vec<Result<Duration, String>> = stream::iter(0..requests)
+        .map(|_| {
+            let call = send();
+            async move {
+                api.throttle().await;
+                let sent = Instant::now();
+                call.await.map(|()| sent.elapsed())
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+    let elapsed = started.elapsed();
+
+    let mut latencies = Vec::with_capacity(outcomes.len());
+    let mut errors: Vec<String> = Vec::new();
+    for outcome in outcomes {
+        match outcome {
+            Ok(latency) => latencies.push(latency),
+            Err(e) if errors.len() < MAX_ERRORS && !errors.contains(&e) => errors.push(e),
+            Err(_) => {}
+        }
+    }
+    let secs = elapsed.as_secs_f64();
+    BenchReport {
+        endpoint: endpoint.to_string(),
+        requests,
+        concurrency,
+        succeeded: latencies.len(),
+        failed: requests - latencies.len(),
+        elapsed_secs: secs,
+        requests_per_sec: if secs > 0.0 {
+            latencies.len() as f64 / secs
+        } else {
+            0.0
+        },
+        keep_alive: api.options().keep_alive,
+        latency_ms: summarize(&mut latencies),
+        errors,
+    }
+}
+
+/// Latency statistics over `latencies`, which are sorted in place.
+pub fn summarize(latencies: &mut [Duration]) -> Latency {
+    if latencies.is_empty() {
+        return Latency::default();
+    }
+    latencies.sort();
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    let total: Duration = latencies.iter().sum();
+    Latency {
+        min: ms(latencies[0]),
+        mean: ms(total / latencies.len() as u32),
+        p50: ms(percentile(latencies, 50.0)),
+        p95: ms(percentile(latencies, 95.0)),
+        p99: ms(percentile(latencies, 99.0)),
+        max: ms(latencies[latencies.len() - 1]),
+    }
+}
+
+/// Nearest-rank percentile of sorted, non-empty `values`.
+fn percentile(values: &[Duration], p: f64) -> Duration {
+    let rank = (p / 100.0 * values.len() as f64).ceil() as usize;
+    values[rank.clamp(1, values.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nearest_rank_percentiles() {
+        let mut latencies: Vec<Duration> = (1..=100).rev().map(Duration::from_millis).collect();
+        let latency = summarize(&mut latencies);
+        assert_eq!(latency.min, 1.0);
+        assert_eq!(latency.p50, 50.0);
+        assert_eq!(latency.p95, 95.0);
+        assert_eq!(latency.p99, 99.0);
+        assert_eq!(latency.max, 100.0);
+        assert_eq!(latency.mean, 50.5);
+        assert_eq!(summarize(&mut []), Latency::default());
+    }
+
+    #[tokio::test]
+    async fn test_counts_failures() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/health")
+            .with_status(500)
+            .create_async()
+            .await;
+
+        let report = run(&Api::for_tests(server.url()), "/health", &[], 6, 3).await;
+
+        assert_eq!(report.succeeded, 0);
+        assert_eq!(report.failed, 6);
+        assert_eq!(
+            report.errors,
+            ["GET /health returned 500 Internal Server Error"]
+        );
+    }
+}
@@ -0,0 +1,165 @@
+use crate::mock_server;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Responses kept; the oldest go first.
+const MAX_ENTRIES: usize = 200;
+
+/// The last successful answer to one `GET`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct Entry {
+    /// Seconds since the Unix epoch when it was fetched.
+    fetched_at: u64,
+    body: String,
+}
+
+/// The last answers `health`, `metrics` and `list` got from each API, kept
+/// so `--offline` can show them again without the network.
+#[derive(Debug)]
+pub struct ResponseCache {
+    path: PathBuf,
+    offline: bool,
+    /// By base URL, path and query string.
+    entries: Mutex<BTreeMap<String, Entry>>,
+    /// Whether the stale banner has been shown.
+    announced: AtomicBool,
+}
+
+/// `$XDG_CACHE_HOME/prediction-cli`, or `~/.cache/prediction-cli` when that
+/// isn't set.
+pub fn dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("prediction-cli"))
+}
+
+/// `responses.json` in [`dir`].
+pub fn default_path() -> Option<PathBuf> {
+    Some(dir()?.join("responses.json"))
+}
+
+impl ResponseCache {
+    /// Opens the cache at `path` to keep answers in, or with `offline` to
+    /// answer from. A missing or unreadable cache starts out empty.
+    pub fn open(path: &Path, offline: bool) -> Self {
+        let entries = fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        ResponseCache {
+            path: path.to_path_buf(),
+            offline,
+            entries: Mutex::new(entries),
+            announced: AtomicBool::new(false),
+        }
+    }
+
+    pub fn offline(&self) -> bool {
+        self.offline
+    }
+
+    /// The cached body for `key`, and when it was fetched.
+    pub fn answer(&self, key: &str) -> Result<(Vec<u8>, SystemTime), NotCached> {
+        let entries = self.entries.lock().unwrap();
+        let entry = entries.get(key).ok_or_else(|| NotCached {
+            request: format!("GET {}", key),
+        })?;
+        let fetched_at = UNIX_EPOCH + Duration::from_secs(entry.fetched_at);
+        Ok((entry.body.clone().into_bytes(), fetched_at))
+    }
+
+    /// True the first time only, so the banner is shown once a run.
+    pub fn announce(&self) -> bool {
+        !self.announced.swap(true, Ordering::Relaxed)
+    }
+
+    /// Keeps `body` as the answer for `key` and rewrites the cache file.
+    pub fn store(&self, key: &str, body: &[u8]) -> Result<(), Box<dyn Error>> {
+        let mut entries = self.entries.lock().unwrap();
+        let fetched_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        entries.insert(
+            key.to_string(),
+            Entry {
+                fetched_at,
+                body: String::from_utf8_lossy(body).into_owned(),
+            },
+        );
+        while entries.len() > MAX_ENTRIES {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.fetched_at)
+                .map(|(key, _)| key.clone());
+            entries.remove(&oldest.unwrap_or_default());
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_string(&*entries)? + "\n")?;
+        Ok(())
+    }
+}
+
+/// `time` for the stale banner.
+pub fn describe(time: SystemTime) -> String {
+    mock_server::rfc3339(time)
+}
+
+/// An `--offline` request nothing was cached for.
+#[derive(Debug)]
+pub struct NotCached {
+    pub request: String,
+}
+
+impl fmt::Display for NotCached {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "nothing cached for {}; run the command once while the API is up",
+            self.request
+        )
+    }
+}
+
+impl Error for NotCached {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{self, Api};
+
+    #[tokio::test]
+    async fn test_offline_answers_what_was_fetched_online() {
+        let path = env::temp_dir().join(format!("responses-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/health")
+            .with_body(r#"{"status":"healthy","timestamp":"then","version":"1"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let online = Api::for_tests(server.url()).with_cache(ResponseCache::open(&path, false));
+        api::check_health(&online).await.unwrap();
+
+        let offline = Api::for_tests(server.url()).with_cache(ResponseCache::open(&path, true));
+        let health = api::check_health(&offline).await.unwrap();
+        let err = api::get_metrics(&offline).await.unwrap_err();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(health.timestamp, "then");
+        assert!(err.is::<NotCached>());
+        assert!(err.to_string().contains("/metrics; run the command once"));
+    }
+}
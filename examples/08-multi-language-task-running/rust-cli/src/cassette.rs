@@ -0,0 +1,187 @@
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// One request and the answer it got. Headers aren't kept, so credentials
+/// never end up in a cassette.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Interaction {
+    pub method: String,
+    /// Path and query string.
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<String>,
+    pub status: u16,
+    pub response_body: String,
+}
+
+impl Interaction {
+    fn answers(&self, method: &str, path: &str, body: Option<&str>) -> bool {
+        self.method == method && self.path == path && self.request_body.as_deref() == body
+    }
+}
+
+/// A cassette file's contents.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Tape {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Record,
+    Replay,
+}
+
+/// Records every exchange to a cassette file, or answers every request from
+/// one without touching the network.
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: Mode,
+    tape: Mutex<Tape>,
+    /// How many times each interaction has been replayed.
+    replayed: Mutex<Vec<usize>>,
+}
+
+impl Cassette {
+    /// Appends to the cassette at `path`, creating it if needed, so several
+    /// runs can build up one cassette.
+    pub fn record(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let tape = if path.exists() {
+            load(path)?
+        } else {
+            Tape::default()
+        };
+        Ok(Cassette {
+            path: path.to_path_buf(),
+            mode: Mode::Record,
+            tape: Mutex::new(tape),
+            replayed: Mutex::new(Vec::new()),
+        })
+    }
+
+    pub fn replay(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let tape = load(path)?;
+        Ok(Cassette {
+            path: path.to_path_buf(),
+            mode: Mode::Replay,
+            replayed: Mutex::new(vec![0; tape.interactions.len()]),
+            tape: Mutex::new(tape),
+        })
+    }
+
+    pub fn replaying(&self) -> bool {
+        self.mode == Mode::Replay
+    }
+
+    /// The recorded answer to a request. Matching interactions are used in
+    /// the order they were recorded; once all have been, the last one keeps
+    /// answering, as a polling loop would expect.
+    pub fn answer(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&str>,
+    ) -> Result<(StatusCode, Vec<u8>), Box<dyn Error>> {
+        let tape = self.tape.lock().unwrap();
+        let mut replayed = self.replayed.lock().unwrap();
+        let matching: Vec<usize> = tape
+            .interactions
+            .iter()
+            .enumerate()
+            .filter(|(_, interaction)| interaction.answers(method, path, body))
+            .map(|(i, _)| i)
+            .collect();
+        let Some(&last) = matching.last() else {
+            return Err(Box::new(Unrecorded {
+                request: format!("{} {}", method, path),
+                cassette: self.path.clone(),
+            }));
+        };
+        let i = matching
+            .into_iter()
+            .find(|&i| replayed[i] == 0)
+            .unwrap_or(last);
+        replayed[i] += 1;
+        let interaction = &tape.interactions[i];
+        let status = StatusCode::from_u16(interaction.status)
+            .map_err(|e| format!("{}: {}", self.path.display(), e))?;
+        Ok((status, interaction.response_body.clone().into_bytes()))
+    }
+
+    /// Adds an exchange and rewrites the cassette, so it is complete even if
+    /// the run fails later.
+    pub fn add(&self, interaction: Interaction) -> Result<(), Box<dyn Error>> {
+        let mut tape = self.tape.lock().unwrap();
+        tape.interactions.push(interaction);
+        fs::write(&self.path, serde_json::to_string_pretty(&*tape)? + "\n")
+            .map_err(|e| format!("cannot write cassette {}: {}", self.path.display(), e).into())
+    }
+}
+
+fn load(path: &Path) -> Result<Tape, Box<dyn Error>> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("cannot read cassette {}: {}", path.display(), e))?;
+    serde_json::from_str(&text)
+        .map_err(|e| format!("{} is not a cassette: {}", path.display(), e).into())
+}
+
+/// A replayed request the cassette has no answer for.
+#[derive(Debug)]
+pub struct Unrecorded {
+    pub request: String,
+    pub cassette: PathBuf,
+}
+
+impl fmt::Display for Unrecorded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} was never recorded in {}",
+            self.request,
+            self.cassette.display()
+        )
+    }
+}
+
+impl Error for Unrecorded {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn interaction(path: &str, response_body: &str) -> Interaction {
+        Interaction {
+            method: "GET".to_string(),
+            path: path.to_string(),
+            request_body: None,
+            status: 200,
+            response_body: response_body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_replays_in_recorded_order() {
+        let path = env::temp_dir().join(format!("cassette-{}.json", std::process::id()));
+        let recorder = Cassette::record(&path).unwrap();
+        recorder.add(interaction("/metrics", "first")).unwrap();
+        recorder.add(interaction("/metrics", "second")).unwrap();
+        let cassette = Cassette::replay(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let body = |cassette: &Cassette| cassette.answer("GET", "/metrics", None).unwrap().1;
+        assert_eq!(body(&cassette), b"first");
+        assert_eq!(body(&cassette), b"second");
+        assert_eq!(body(&cassette), b"second");
+        let err = cassette.answer("POST", "/metrics", Some("{}")).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("POST /metrics was never recorded"));
+    }
+}
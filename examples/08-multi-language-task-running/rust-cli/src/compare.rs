@@ -0,0 +1,173 @@
+use crate::api::{self, Api, HealthResponse, MetricsResponse, Prediction};
+use serde::Serialize;
+
+/// What one deployment answered; a failed request leaves its part empty and
+/// its error in `errors`.
+#[derive(Debug, Default, Serialize)]
+pub struct Side {
+    pub url: String,
+    pub health: Option<HealthResponse>,
+    pub metrics: Option<MetricsResponse>,
+    pub prediction: Option<Prediction>,
+    pub errors: Vec<String>,
+}
+
+/// One compared value, `None` where that side couldn't be fetched.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Field {
+    pub name: &'static str,
+    pub a: Option<String>,
+    pub b: Option<String>,
+    pub differs: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Comparison {
+    pub a: Side,
+    pub b: Side,
+    pub fields: Vec<Field>,
+}
+
+/// Fetches health and metrics from both deployments at once, and predicts
+/// `features` on both if given.
+pub async fn compare(a: &Api, b: &Api, features: Option<&[f64]>) -> Comparison {
+    let (a_side, b_side) = tokio::join!(fetch(a, features), fetch(b, features));
+    let fields = compare_fields(&a_side, &b_side, features.is_some());
+    Comparison {
+        a: a_side,
+        b: b_side,
+        fields,
+    }
+}
+
+async fn fetch(api: &Api, features: Option<&[f64]>) -> Side {
+    let mut side = Side {
+        url: api.base_url().to_string(),
+        ..Side::default()
+    };
+    let prediction = async {
+        match features {
+            Some(features) => Some(api::make_prediction(api, features).await),
+            None => None,
+        }
+    };
+    let (health, metrics, prediction) =
+        tokio::join!(api::check_health(api), api::get_metrics(api), prediction);
+    match health {
+        Ok(health) => side.health = Some(health),
+        Err(e) => side.errors.push(format!("health: {}", e)),
+    }
+    match metrics {
+        Ok(metrics) => side.metrics = Some(metrics),
+        Err(e) => side.errors.push(format!("metrics: {}", e)),
+    }
+    match prediction {
+        Some(Ok(prediction)) => side.prediction = Some(prediction),
+        Some(Err(e)) => side.errors.push(format!("predict: {}", e)),
+        None => {}
+    }
+    side
+}
+
+/// The values worth comparing, in display order; the prediction's only when
+/// `predicting`.
+fn compare_fields(a: &Side, b: &Side, predicting: bool) -> Vec<Field> {
+    type Getter = fn(&Side) -> Option<String>;
+    let mut getters: Vec<(&'static str, Getter)> = vec![
+        ("status", |side| {
+            side.health.as_ref().map(|h| h.status.clone())
+        }),
+        ("version", |side| {
+            side.health.as_ref().map(|h| h.version.clone())
+        }),
+        ("uptime", |side| {
+            side.metrics.as_ref().map(|m| m.uptime.clone())
+        }),
+        ("requests_total", |side| {
+            side.metrics.as_ref().map(|m| m.requests_total.to_string())
+        }),
+        ("memory_usage_mb", |side| {
+            side.metrics
+                .as_ref()
+                .map(|m| format!("{:.2}", m.memory_usage_mb))
+        }),
+        ("goroutines", |side| {
+            side.metrics.as_ref().map(|m| m.goroutines.to_string())
+        }),
+        ("cpu_cores", |side| {
+            side.metrics.as_ref().map(|m| m.cpu_cores.to_string())
+        }),
+    ];
+    if predicting {
+        getters.push(("label", |side| {
+            side.prediction.as_ref().map(|p| p.label.clone())
+        }));
+        getters.push(("confidence", |side| {
+            side.prediction
+                .as_ref()
+                .map(|p| format!("{:.4}", p.confidence))
+        }));
+    }
+    getters
+        .into_iter()
+        .map(|(name, get)| {
+            let (a, b) = (get(a), get(b));
+            Field {
+                name,
+                differs: a != b,
+                a,
+                b,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn side(version: &str, label: Option<&str>) -> Side {
+        Side {
+            url: "http://x".to_string(),
+            health: Some(HealthResponse {
+                status: "healthy".to_string(),
+                timestamp: "now".to_string(),
+                version: version.to_string(),
+            }),
+            prediction: label.map(|label| Prediction {
+                label: label.to_string(),
+                confidence: 0.5,
+                timestamp: None,
+            }),
+            ..Side::default()
+        }
+    }
+
+    fn field<'a>(fields: &'a [Field], name: &str) -> &'a Field {
+        fields.iter().find(|f| f.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_marks_differences() {
+        let fields = compare_fields(&side("1.0", Some("cat")), &side("1.1", Some("cat")), true);
+        assert!(!field(&fields, "status").differs);
+        assert!(field(&fields, "version").differs);
+        assert!(!field(&fields, "label").differs);
+        assert_eq!(field(&fields, "confidence").a.as_deref(), Some("0.5000"));
+    }
+
+    #[test]
+    fn test_prediction_fields_only_when_predicting() {
+        let fields = compare_fields(&side("1.0", None), &side("1.0", None), false);
+        assert!(fields.iter().all(|f| f.name != "label"));
+
+        let mut failed = side("1.0", None);
+        failed.health = None;
+        failed
+            .errors
+            .push("predict: connection refused".to_string());
+        let fields = compare_fields(&side("1.0", Some("cat")), &failed, true);
+        assert_eq!(field(&fields, "label").b, None);
+        assert!(field(&fields, "status").differs);
+    }
+}
@@ -0,0 +1,718 @@
+use crate::api::{ApiOptions, RetryPolicy};
+use crate::output::OutputFormat;
+use crate::rate::Rate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const DEFAULT_URL: &str = "http://localhost:8080";
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 5;
+pub const DEFAULT_RETRIES: u32 = 2;
+pub const DEFAULT_RETRY_DELAY_MS: u64 = 200;
+
+/// Layout of `config.toml`. Every key is optional. A `[profiles.<name>]`
+/// table holds the same keys, which win over the top-level ones when that
+/// profile is selected.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FileConfig {
+    /// API base URL
+    pub url: Option<String>,
+    /// Base URLs to fail over to, in order, when `url` can't be reached
+    pub fallback_urls: Option<Vec<String>>,
+    /// Output format used when `--output` isn't given
+    pub output: Option<OutputFormat>,
+    /// Seconds before a request is abandoned
+    pub timeout_secs: Option<u64>,
+    /// Seconds to wait for a connection to be established
+    pub connect_timeout_secs: Option<u64>,
+    /// Bearer token sent with every request
+    pub token: Option<String>,
+    /// Sent as `X-API-Key` with every request
+    pub api_key: Option<String>,
+    /// Key every request is signed with
+    pub signing_key: Option<String>,
+    /// Skip TLS certificate verification
+    pub insecure: Option<bool>,
+    /// PEM bundle of extra certificate authorities to trust
+    pub ca_cert: Option<PathBuf>,
+    /// Proxy for every request, instead of `HTTP_PROXY`/`HTTPS_PROXY`
+    pub proxy: Option<String>,
+    /// Times an idempotent request is retried
+    pub retries: Option<u32>,
+    /// Milliseconds before the first retry, doubled for each one after
+    pub retry_delay_ms: Option<u64>,
+    /// Most requests sent per unit of time, such as `"10/s"`
+    pub rate: Option<Rate>,
+    /// Shell command run before each request; each `Name: value` line it
+    /// prints is sent as a header, e.g. a token fresh from a secret manager
+    pub pre_request: Option<String>,
+    /// Profile used when `--profile` isn't given
+    pub default_profile: Option<String>,
+    /// Named sets of settings, such as one per environment
+    pub profiles: BTreeMap<String, FileConfig>,
+    /// Shorthands for whole command lines, such as `hm = "health -u http://prod:8080"`
+    pub aliases: BTreeMap<String, Alias>,
+}
+
+/// What an alias stands for: a command line split on whitespace, or a list
+/// of words for arguments that contain spaces themselves.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Alias {
+    Line(String),
+    Words(Vec<String>),
+}
+
+impl Alias {
+    pub fn words(&self) -> Vec<String> {
+        match self {
+            Alias::Line(line) => line.split_whitespace().map(str::to_string).collect(),
+            Alias::Words(words) => words.clone(),
+        }
+    }
+}
+
+impl FileConfig {
+    /// The settings to use: profile `name`'s, or `default_profile`'s if no
+    /// name is given, over the top-level ones.
+    pub fn select(mut self, name: Option<&str>) -> Result<FileConfig, ConfigError> {
+        let Some(name) = name.map(str::to_string).or(self.default_profile.take()) else {
+            return Ok(self);
+        };
+        let Some(profile) = self.profiles.remove(&name) else {
+            let known = self.profiles.into_keys().collect();
+            return Err(ConfigError::UnknownProfile(name, known));
+        };
+        if profile.nests() {
+            return Err(ConfigError::NestedProfile(name));
+        }
+        Ok(profile.or(self))
+    }
+
+    /// Whether this, as a profile, sets what only the top level may.
+    fn nests(&self) -> bool {
+        self.default_profile.is_some() || !self.profiles.is_empty() || !self.aliases.is_empty()
+    }
+
+    /// `self`'s values, falling back to `base`'s where unset.
+    fn or(self, base: FileConfig) -> FileConfig {
+        FileConfig {
+            url: self.url.or(base.url),
+            fallback_urls: self.fallback_urls.or(base.fallback_urls),
+            output: self.output.or(base.output),
+            timeout_secs: self.timeout_secs.or(base.timeout_secs),
+            connect_timeout_secs: self.connect_timeout_secs.or(base.connect_timeout_secs),
+            token: self.token.or(base.token),
+            api_key: self.api_key.or(base.api_key),
+            signing_key: self.signing_key.or(base.signing_key),
+            insecure: self.insecure.or(base.insecure),
+            ca_cert: self.ca_cert.or(base.ca_cert),
+            proxy: self.proxy.or(base.proxy),
+            retries: self.retries.or(base.retries),
+            retry_delay_ms: self.retry_delay_ms.or(base.retry_delay_ms),
+            rate: self.rate.or(base.rate),
+            pre_request: self.pre_request.or(base.pre_request),
+            default_profile: None,
+            profiles: BTreeMap::new(),
+            aliases: base.aliases,
+        }
+    }
+
+    /// Every profile, in name order.
+    pub fn profile_list(&self) -> Vec<ProfileSummary> {
+        self.profiles
+            .iter()
+            .map(|(name, profile)| ProfileSummary {
+                name: name.clone(),
+                url: profile.url.clone().or_else(|| self.url.clone()),
+                default: self.default_profile.as_ref() == Some(name),
+            })
+            .collect()
+    }
+}
+
+/// A profile as shown by `profile list`.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ProfileSummary {
+    pub name: String,
+    /// The profile's URL, or the top-level one it inherits.
+    pub url: Option<String>,
+    pub default: bool,
+}
+
+/// Values from the command line or the environment, which win over the
+/// config file.
+#[derive(Debug, Default)]
+pub struct Overrides {
+    pub url: Option<String>,
+    /// Replace the file's fallbacks when not empty.
+    pub fallback_urls: Vec<String>,
+    pub output: Option<OutputFormat>,
+    pub timeout_secs: Option<u64>,
+    pub connect_timeout_secs: Option<u64>,
+    pub token: Option<String>,
+    pub api_key: Option<String>,
+    pub signing_key: Option<String>,
+    /// `--insecure` was given.
+    pub insecure: bool,
+    pub ca_cert: Option<PathBuf>,
+    pub proxy: Option<String>,
+    pub retries: Option<u32>,
+    pub retry_delay_ms: Option<u64>,
+    pub rate: Option<Rate>,
+    /// Times `-v` was given.
+    pub verbose: u8,
+}
+
+/// Settings for a run, taken from the command line or environment first and
+/// the config file second, falling back to built-in defaults.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub output: OutputFormat,
+    pub api: ApiOptions,
+}
+
+impl Settings {
+    pub fn resolve(overrides: Overrides, file: FileConfig) -> Self {
+        let secs = |value: Option<u64>, fallback: Option<u64>, default: u64| {
+            Duration::from_secs(value.or(fallback).unwrap_or(default))
+        };
+        Settings {
+            output: overrides.output.or(file.output).unwrap_or_default(),
+            api: ApiOptions {
+                base_url: overrides
+                    .url
+                    .or(file.url)
+                    .unwrap_or_else(|| DEFAULT_URL.to_string()),
+                fallback_urls: if overrides.fallback_urls.is_empty() {
+                    file.fallback_urls.unwrap_or_default()
+                } else {
+                    overrides.fallback_urls
+                },
+                timeout: secs(
+                    overrides.timeout_secs,
+                    file.timeout_secs,
+                    DEFAULT_TIMEOUT_SECS,
+                ),
+                connect_timeout: secs(
+                    overrides.connect_timeout_secs,
+                    file.connect_timeout_secs,
+                    DEFAULT_CONNECT_TIMEOUT_SECS,
+                ),
+                token: overrides.token.or(file.token),
+                api_key: overrides.api_key.or(file.api_key),
+                signing_key: overrides.signing_key.or(file.signing_key),
+                insecure: overrides.insecure || file.insecure.unwrap_or(false),
+                ca_cert: overrides.ca_cert.or(file.ca_cert),
+                proxy: overrides.proxy.or(file.proxy),
+                retry: RetryPolicy {
+                    retries: overrides
+                        .retries
+                        .or(file.retries)
+                        .unwrap_or(DEFAULT_RETRIES),
+                    delay: Duration::from_millis(
+                        overrides
+                            .retry_delay_ms
+                            .or(file.retry_delay_ms)
+                            .unwrap_or(DEFAULT_RETRY_DELAY_MS),
+                    ),
+                },
+                rate: overrides.rate.or(file.rate),
+                verbose: overrides.verbose,
+                dry_run: None,
+                show_secrets: false,
+                keep_alive: true,
+                pre_request: file.pre_request,
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Edit(PathBuf, toml_edit::TomlError),
+    Write(PathBuf, io::Error),
+    /// A profile that isn't in the file, and the ones that are.
+    UnknownProfile(String, Vec<String>),
+    /// A profile with its own `profiles`, `default_profile` or `aliases`.
+    NestedProfile(String),
+    /// A key `config get` found nothing at.
+    Unset(String),
+    /// A value `config set` cannot give a key, and why.
+    Invalid(String, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "invalid config {}: {}", path.display(), e),
+            ConfigError::Edit(path, e) => write!(f, "invalid config {}: {}", path.display(), e),
+            ConfigError::Write(path, e) => write!(f, "cannot write {}: {}", path.display(), e),
+            ConfigError::UnknownProfile(name, known) if known.is_empty() => {
+                write!(f, "no profile {:?}: the config file defines none", name)
+            }
+            ConfigError::UnknownProfile(name, known) => write!(
+                f,
+                "no profile {:?}; the config file defines {}",
+                name,
+                known.join(", ")
+            ),
+            ConfigError::NestedProfile(name) => write!(
+                f,
+                "profile {:?} cannot set profiles, default_profile or aliases itself",
+                name
+            ),
+            ConfigError::Unset(key) => write!(f, "{} is not set in the config file", key),
+            ConfigError::Invalid(key, reason) => write!(f, "cannot set {}: {}", key, reason),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// `$XDG_CONFIG_HOME/prediction-cli/config.toml`, or
+/// `~/.config/prediction-cli/config.toml` when that isn't set.
+pub fn default_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("prediction-cli").join("config.toml"))
+}
+
+/// The config file to use: `explicit` if given, else the default location.
+pub fn resolve_path(explicit: Option<&Path>) -> Option<PathBuf> {
+    explicit.map(Path::to_path_buf).or_else(default_path)
+}
+
+/// Reads the config file at `path`. A missing file is only an error if the
+/// user named it; the default location may simply not exist yet.
+pub fn load(path: Option<&Path>, explicit: bool) -> Result<FileConfig, ConfigError> {
+    let Some(path) = path else {
+        return Ok(FileConfig::default());
+    };
+    match fs::read_to_string(path) {
+        Ok(text) => toml::from_str(&text).map_err(|e| ConfigError::Parse(path.to_path_buf(), e)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound && !explicit => Ok(FileConfig::default()),
+        Err(e) => Err(ConfigError::Read(path.to_path_buf(), e)),
+    }
+}
+
+/// Makes `name` the default profile in the config file at `path`, keeping
+/// the rest of the file, comments included, as it was.
+pub fn set_default_profile(path: &Path, name: &str) -> Result<(), ConfigError> {
+    let mut document = read_document(path)?;
+    let profiles = document.get("profiles").and_then(|p| p.as_table_like());
+    if !profiles.is_some_and(|profiles| profiles.contains_key(name)) {
+        let known = profiles
+            .map(|profiles| profiles.iter().map(|(key, _)| key.to_string()).collect())
+            .unwrap_or_default();
+        return Err(ConfigError::UnknownProfile(name.to_string(), known));
+    }
+    document["default_profile"] = toml_edit::value(name);
+    write_document(path, &document)
+}
+
+/// Keys whose values `config list` redacts.
+const SECRET_KEYS: [&str; 3] = ["token", "api_key", "signing_key"];
+
+/// The value at a dotted `key`, such as `timeout_secs` or
+/// `profiles.staging.url`: strings bare, anything else as TOML, and a table
+/// as the `key = value` lines under it.
+pub fn get(path: &Path, key: &str) -> Result<Vec<String>, ConfigError> {
+    let document = read_document(path)?;
+    let mut item = document.as_item();
+    for part in key.split('.') {
+        item = item
+            .get(part)
+            .ok_or_else(|| ConfigError::Unset(key.to_string()))?;
+    }
+    Ok(match item.as_value() {
+        Some(toml_edit::Value::String(value)) => vec![value.value().clone()],
+        Some(value) => vec![bare(value)],
+        None => entries(item, key, true),
+    })
+}
+
+/// Every value the config file sets, one `key = value` line each in file
+/// order, with credentials redacted unless `reveal`.
+pub fn list(path: &Path, reveal: bool) -> Result<Vec<String>, ConfigError> {
+    Ok(entries(read_document(path)?.as_item(), "", reveal))
+}
+
+fn entries(item: &toml_edit::Item, prefix: &str, reveal: bool) -> Vec<String> {
+    let Some(table) = item.as_table_like() else {
+        return Vec::new();
+    };
+    let mut lines = Vec::new();
+    for (name, item) in table.iter() {
+        let key = match prefix {
+            "" => name.to_string(),
+            prefix => format!("{}.{}", prefix, name),
+        };
+        match item.as_value() {
+            Some(_) if !reveal && SECRET_KEYS.contains(&name) => {
+                lines.push(format!("{} = \"[redacted]\"", key))
+            }
+            Some(value) => lines.push(format!("{} = {}", key, bare(value))),
+            None => lines.extend(entries(item, &key, reveal)),
+        }
+    }
+    lines
+}
+
+/// `value` as TOML, without the whitespace and comments around it.
+fn bare(value: &toml_edit::Value) -> String {
+    let mut value = value.clone();
+    value.decor_mut().clear();
+    value.to_string()
+}
+
+/// Sets the dotted `key` to `value` in the config file at `path`, creating
+/// the file if need be and keeping the rest of it, comments included, as it
+/// was. `value` is read as TOML when that gives the key a value of the type
+/// it takes, such as a number or `["a", "b"]`, and as a string otherwise,
+/// so `config set url http://prod:8080` needs no quotes. Nothing is written
+/// unless the whole file is still a valid config afterwards.
+pub fn set(path: &Path, key: &str, value: &str) -> Result<(), ConfigError> {
+    let document = match read_document(path) {
+        Err(ConfigError::Read(_, e)) if e.kind() == io::ErrorKind::NotFound => {
+            toml_edit::DocumentMut::new()
+        }
+        document => document?,
+    };
+    let mut candidates = Vec::new();
+    if let Ok(literal) = value.parse::<toml_edit::Value>() {
+        candidates.push(literal);
+    }
+    if !candidates.first().is_some_and(toml_edit::Value::is_str) {
+        candidates.push(value.into());
+    }
+    let mut first_error = None;
+    for candidate in candidates {
+        let mut edited = document.clone();
+        match insert(&mut edited, key, candidate).and_then(|()| check(&edited, key)) {
+            Ok(()) => return write_document(path, &edited),
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    Err(first_error.expect("there is always a string candidate"))
+}
+
+fn insert(
+    document: &mut toml_edit::DocumentMut,
+    key: &str,
+    value: toml_edit::Value,
+) -> Result<(), ConfigError> {
+    let parts: Vec<&str> = key.split('.').collect();
+    if parts.iter().any(|part| part.is_empty()) {
+        return Err(ConfigError::Invalid(
+            key.to_string(),
+            "empty key".to_string(),
+        ));
+    }
+    let (name, tables) = parts.split_last().expect("split always yields a part");
+    let mut item = document.as_item_mut();
+    for (depth, part) in tables.iter().enumerate() {
+        if item.get(part).is_none() {
+            let mut table = toml_edit::Table::new();
+            // Only the innermost new table needs a `[header]` of its own.
+            table.set_implicit(depth + 1 < tables.len());
+            item[part] = toml_edit::Item::Table(table);
+        }
+        item = &mut item[part];
+        if !item.is_table_like() {
+            let parent = parts[..=depth].join(".");
+            let reason = format!("{} is a value, not a table", parent);
+            return Err(ConfigError::Invalid(key.to_string(), reason));
+        }
+    }
+    item[name] = toml_edit::Item::Value(value);
+    Ok(())
+}
+
+/// That `document` is a config file the CLI would accept.
+fn check(document: &toml_edit::DocumentMut, key: &str) -> Result<(), ConfigError> {
+    let file: FileConfig = toml::from_str(&document.to_string())
+        .map_err(|e| ConfigError::Invalid(key.to_string(), e.message().to_string()))?;
+    if let Some(name) = file
+        .profiles
+        .iter()
+        .find_map(|(name, p)| p.nests().then_some(name))
+    {
+        return Err(ConfigError::NestedProfile(name.clone()));
+    }
+    file.select(None).map(drop)
+}
+
+fn read_document(path: &Path) -> Result<toml_edit::DocumentMut, ConfigError> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => return Err(ConfigError::Read(path.to_path_buf(), e)),
+    };
+    text.parse()
+        .map_err(|e| ConfigError::Edit(path.to_path_buf(), e))
+}
+
+/// Replaces the file at `path` in one step, so an interrupted write leaves
+/// the old one whole.
+fn write_document(path: &Path, document: &toml_edit::DocumentMut) -> Result<(), ConfigError> {
+    // Unique, so two runs of `config set` at once don't write the same file.
+    let tmp = path.with_extension(format!(
+        "toml.{}-{:08x}.tmp",
+        std::process::id(),
+        fastrand::u32(..)
+    ));
+    path.parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|()| write_new(&tmp, path, document.to_string().as_bytes()))
+        .and_then(|()| fs::rename(&tmp, path))
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&tmp);
+        })
+        .map_err(|e| ConfigError::Write(path.to_path_buf(), e))
+}
+
+/// Writes `contents` to the new file `tmp`.
+fn write_new(tmp: &Path, original: &Path, contents: &[u8]) -> io::Result<()> {
+    use std::io::Write;
+
+    let mut file = create(tmp, original)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+/// Creates `tmp` with the permissions of `original`, or owner-only ones
+/// when there is no original yet, so replacing the config never lets anyone
+/// else read the tokens in it.
+#[cfg(unix)]
+fn create(tmp: &Path, original: &Path) -> io::Result<fs::File> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    let mode = match fs::metadata(original) {
+        Ok(metadata) => metadata.permissions().mode() & 0o7777,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => 0o600,
+        Err(e) => return Err(e),
+    };
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(mode)
+        .open(tmp)?;
+    // The umask may have narrowed the mode asked for.
+    file.set_permissions(fs::Permissions::from_mode(mode))?;
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+fn create(tmp: &Path, _original: &Path) -> io::Result<fs::File> {
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(tmp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_line_overrides_file() {
+        let file: FileConfig = toml::from_str(
+            r#"
+            url = "http://staging:8080"
+            output = "json"
+            timeout_secs = 5
+            token = "secret"
+            api_key = "file-key"
+            "#,
+        )
+        .unwrap();
+
+        let overrides = Overrides {
+            url: Some("http://prod:8080".to_string()),
+            timeout_secs: Some(2),
+            api_key: Some("flag-key".to_string()),
+            ..Overrides::default()
+        };
+        let settings = Settings::resolve(overrides, file);
+        assert_eq!(settings.api.base_url, "http://prod:8080");
+        assert_eq!(settings.output, OutputFormat::Json);
+        assert_eq!(settings.api.timeout, Duration::from_secs(2));
+        assert_eq!(settings.api.token.as_deref(), Some("secret"));
+        assert_eq!(settings.api.api_key.as_deref(), Some("flag-key"));
+    }
+
+    #[test]
+    fn test_defaults_without_a_file() {
+        let settings = Settings::resolve(Overrides::default(), FileConfig::default());
+        assert_eq!(settings.api.base_url, DEFAULT_URL);
+        assert_eq!(settings.output, OutputFormat::Plain);
+        assert_eq!(
+            settings.api.timeout,
+            Duration::from_secs(DEFAULT_TIMEOUT_SECS)
+        );
+        assert_eq!(
+            settings.api.connect_timeout,
+            Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS)
+        );
+        assert_eq!(settings.api.retry.retries, DEFAULT_RETRIES);
+    }
+
+    #[test]
+    fn test_missing_default_file_is_not_an_error() {
+        let path = Path::new("/nonexistent/prediction-cli/config.toml");
+        assert!(load(Some(path), false).is_ok());
+        assert!(matches!(load(Some(path), true), Err(ConfigError::Read(..))));
+    }
+
+    const PROFILES: &str = r#"
+        # shared by every profile
+        url = "http://localhost:8080"
+        retries = 1
+
+        [profiles.staging]
+        url = "https://staging:8443"
+        token = "staging-token"
+
+        [profiles.prod]
+        insecure = false
+    "#;
+
+    #[test]
+    fn test_profile_wins_over_top_level() {
+        let file: FileConfig = toml::from_str(PROFILES).unwrap();
+        let selected = file.select(Some("staging")).unwrap();
+        assert_eq!(selected.url.as_deref(), Some("https://staging:8443"));
+        assert_eq!(selected.token.as_deref(), Some("staging-token"));
+        assert_eq!(selected.retries, Some(1));
+
+        let file: FileConfig = toml::from_str(PROFILES).unwrap();
+        let err = file.select(Some("dev")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "no profile \"dev\"; the config file defines prod, staging"
+        );
+    }
+
+    #[test]
+    fn test_default_profile_applies_without_a_name() {
+        let text = format!("default_profile = \"staging\"\n{}", PROFILES);
+        let file: FileConfig = toml::from_str(&text).unwrap();
+        let list = file.profile_list();
+        assert_eq!(list[0].url.as_deref(), Some("http://localhost:8080"));
+        assert!(list[1].default);
+
+        let selected = file.select(None).unwrap();
+        assert_eq!(selected.url.as_deref(), Some("https://staging:8443"));
+    }
+
+    #[test]
+    fn test_set_default_profile_keeps_comments() {
+        let path = env::temp_dir().join(format!("profiles-{}.toml", std::process::id()));
+        fs::write(&path, PROFILES).unwrap();
+
+        set_default_profile(&path, "prod").unwrap();
+        let unknown = set_default_profile(&path, "dev");
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(unknown, Err(ConfigError::UnknownProfile(..))));
+        assert!(text.contains("# shared by every profile"));
+        let file: FileConfig = toml::from_str(&text).unwrap();
+        assert_eq!(file.default_profile.as_deref(), Some("prod"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_set_keeps_the_file_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        let path = env::temp_dir().join(format!("config-mode-{}.toml", std::process::id()));
+        fs::write(&path, "token = \"secret\"\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        set(&path, "timeout_secs", "5").unwrap();
+        let kept = mode(&path);
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        set(&path, "timeout_secs", "6").unwrap();
+        let widened = mode(&path);
+        fs::remove_file(&path).unwrap();
+        set(&path, "token", "secret").unwrap();
+        let created = mode(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(kept, 0o600);
+        assert_eq!(widened, 0o640);
+        assert_eq!(created, 0o600);
+    }
+
+    #[test]
+    fn test_config_get_set_and_list() {
+        let path = env::temp_dir().join(format!("config-set-{}.toml", std::process::id()));
+        fs::write(&path, PROFILES).unwrap();
+
+        set(&path, "timeout_secs", "10").unwrap();
+        set(&path, "profiles.prod.url", "https://prod:8443").unwrap();
+        set(&path, "profiles.dev.token", "12345").unwrap();
+        set(&path, "fallback_urls", r#"["http://b:8080"]"#).unwrap();
+        let bad_type = set(&path, "retries", "many");
+        let unknown = set(&path, "uri", "http://x");
+        let nested = set(&path, "profiles.prod.default_profile", "dev");
+        let got = get(&path, "profiles.prod.url").unwrap();
+        let unset = get(&path, "proxy");
+        let listed = list(&path, false).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            bad_type.unwrap_err().to_string(),
+            "cannot set retries: invalid type: string \"many\", expected u32"
+        );
+        assert!(unknown
+            .unwrap_err()
+            .to_string()
+            .starts_with("cannot set uri: unknown field `uri`"));
+        assert!(matches!(nested, Err(ConfigError::NestedProfile(name)) if name == "prod"));
+        assert_eq!(got, ["https://prod:8443"]);
+        assert_eq!(
+            unset.unwrap_err().to_string(),
+            "proxy is not set in the config file"
+        );
+        assert_eq!(
+            listed,
+            [
+                "url = \"http://localhost:8080\"",
+                "retries = 1",
+                "timeout_secs = 10",
+                "fallback_urls = [\"http://b:8080\"]",
+                "profiles.staging.url = \"https://staging:8443\"",
+                "profiles.staging.token = \"[redacted]\"",
+                "profiles.prod.insecure = false",
+                "profiles.prod.url = \"https://prod:8443\"",
+                "profiles.dev.token = \"[redacted]\"",
+            ]
+        );
+        assert!(text.contains("# shared by every profile"));
+        let file: FileConfig = toml::from_str(&text).unwrap();
+        assert_eq!(file.timeout_secs, Some(10));
+        assert_eq!(file.profiles["dev"].token.as_deref(), Some("12345"));
+    }
+
+    #[test]
+    fn test_rejects_unknown_keys() {
+        assert!(toml::from_str::<FileConfig>("uri = \"http://x\"").is_err());
+    }
+}
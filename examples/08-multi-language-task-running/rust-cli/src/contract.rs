@@ -0,0 +1,366 @@
+use crate::api::{self, Api, StatusError};
+use crate::cache::{self, ResponseCache};
+use colored::*;
+use reqwest::StatusCode;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// How long a fetched spec is used before it is fetched again.
+const SPEC_MAX_AGE: Duration = Duration::from_secs(3600);
+
+/// `$ref`s followed before a schema is taken to be recursive.
+const MAX_DEPTH: usize = 32;
+
+/// The API's OpenAPI spec, for `--validate` to check each response against.
+#[derive(Debug)]
+pub struct Contract {
+    spec: Value,
+    /// Every difference reported so far, so each is reported once a run.
+    drift: Mutex<BTreeSet<String>>,
+}
+
+/// The spec from `GET /openapi.json`. It is kept in the cache directory and
+/// fetched again once it is an hour old; the kept one stands in while the
+/// API can't give a new one.
+pub async fn load(api: &Api) -> Result<Contract, Box<dyn Error>> {
+    let key = format!("{}/openapi.json", api.base_url());
+    let kept = cache::dir().map(|dir| ResponseCache::open(&dir.join("openapi.json"), false));
+    let cached = kept.as_ref().and_then(|kept| kept.answer(&key).ok());
+    if let Some((body, fetched_at)) = &cached {
+        let age = SystemTime::now()
+            .duration_since(*fetched_at)
+            .unwrap_or_default();
+        if age < SPEC_MAX_AGE {
+            return Contract::parse(body);
+        }
+    }
+    let spec = match api::get_openapi(api).await {
+        Ok(spec) => spec,
+        Err(e)
+            if e.downcast_ref::<StatusError>()
+                .is_some_and(|e| e.status == StatusCode::NOT_FOUND) =>
+        {
+            return Err(
+                "the API doesn't publish an OpenAPI spec (GET /openapi.json is not found)".into(),
+            )
+        }
+        Err(e) => match cached {
+            Some((body, fetched_at)) => {
+                eprintln!(
+                    "{} cannot fetch the OpenAPI spec ({}); validating against the one from {}",
+                    "Warning:".yellow().bold(),
+                    e,
+                    cache::describe(fetched_at)
+                );
+                return Contract::parse(&body);
+            }
+            None => return Err(e),
+        },
+    };
+    if let Some(kept) = &kept {
+        if let Err(e) = kept.store(&key, &spec) {
+            tracing::warn!(error = %e, "cannot keep the OpenAPI spec");
+        }
+    }
+    Contract::parse(&spec)
+}
+
+impl Contract {
+    pub fn parse(spec: &[u8]) -> Result<Contract, Box<dyn Error>> {
+        let spec: Value = serde_json::from_slice(spec)
+            .map_err(|e| format!("the OpenAPI spec is not JSON: {}", e))?;
+        if !spec["paths"].is_object() {
+            return Err("the OpenAPI spec has no paths".into());
+        }
+        Ok(Contract {
+            spec,
+            drift: Mutex::new(BTreeSet::new()),
+        })
+    }
+
+    /// Checks one response, printing each difference from the spec to
+    /// stderr the first time it is seen.
+    pub fn report(&self, method: &str, path: &str, status: StatusCode, body: &[u8]) {
+        let problems = self.check(method, path, status, body);
+        let mut drift = self.drift.lock().unwrap();
+        for problem in problems {
+            if drift.insert(problem.clone()) {
+                tracing::warn!(%method, path, drift = %problem, "response differs from the spec");
+                eprintln!("{} {}", "Contract drift:".yellow().bold(), problem);
+            }
+        }
+    }
+
+    /// Every difference reported so far.
+    #[cfg(test)]
+    pub fn drift(&self) -> Vec<String> {
+        self.drift.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// How `body`, answered with `status` to `method path`, differs from
+    /// what the spec declares.
+    pub fn check(&self, method: &str, path: &str, status: StatusCode, body: &[u8]) -> Vec<String> {
+        let path = path.split('?').next().unwrap_or_default();
+        let request = format!("{} {}", method, path);
+        let Some((template, operation)) = self.operation(method, path) else {
+            return vec![format!("{}: not in the spec", request)];
+        };
+        let request = format!("{} {} {}", method, template, status.as_u16());
+        let responses = &operation["responses"];
+        let family = format!("{}XX", status.as_u16() / 100);
+        let Some(response) = [status.as_str(), family.as_str(), "default"]
+            .iter()
+            .find_map(|code| responses.get(code))
+        else {
+            return vec![format!("{}: status not in the spec", request)];
+        };
+        let response = self.resolve(response);
+        let Some(schema) = response["content"].as_object().and_then(|content| {
+            content
+                .iter()
+                .find(|(media, _)| media.contains("json"))
+                .map(|(_, media)| &media["schema"])
+        }) else {
+            return Vec::new();
+        };
+        let value: Value = match serde_json::from_slice(body) {
+            Ok(value) => value,
+            Err(_) => return vec![format!("{}: body is not JSON", request)],
+        };
+        let mut problems = Vec::new();
+        self.validate(schema, &value, "$", 0, &mut problems);
+        problems
+            .into_iter()
+            .map(|problem| format!("{}: {}", request, problem))
+            .collect()
+    }
+
+    /// The operation for `method` on the spec path that matches the end of
+    /// `path`, so a base URL with a path of its own still matches. The
+    /// longest match wins, then the one with the fewest parameters.
+    fn operation(&self, method: &str, path: &str) -> Option<(&str, &Value)> {
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let method = method.to_ascii_lowercase();
+        self.spec["paths"]
+            .as_object()?
+            .iter()
+            .filter_map(|(template, item)| {
+                let parts: Vec<&str> = template.split('/').filter(|s| !s.is_empty()).collect();
+                let tail = segments.len().checked_sub(parts.len())?;
+                let matches = parts.iter().zip(&segments[tail..]).all(|(part, segment)| {
+                    part == segment || (part.starts_with('{') && part.ends_with('}'))
+                });
+                let operation = item.get(&method).filter(|_| matches)?;
+                let params = parts.iter().filter(|part| part.starts_with('{')).count();
+                Some((
+                    (parts.len(), usize::MAX - params),
+                    template.as_str(),
+                    operation,
+                ))
+            })
+            .max_by_key(|(rank, _, _)| *rank)
+            .map(|(_, template, operation)| (template, operation))
+    }
+
+    /// `value` itself, or what its `$ref` points to in the spec.
+    fn resolve<'a>(&'a self, value: &'a Value) -> &'a Value {
+        match value["$ref"].as_str().and_then(|r| r.strip_prefix('#')) {
+            Some(pointer) => self.spec.pointer(pointer).unwrap_or(&Value::Null),
+            None => value,
+        }
+    }
+
+    /// The JSON Schema subset OpenAPI specs use: `type` (and `nullable`),
+    /// `required`, `properties`, `additionalProperties`, `items`, `enum`,
+    /// `allOf`, `anyOf` and `oneOf`. Formats aren't checked.
+    fn validate(
+        &self,
+        schema: &Value,
+        value: &Value,
+        at: &str,
+        depth: usize,
+        problems: &mut Vec<String>,
+    ) {
+        if depth > MAX_DEPTH {
+            return;
+        }
+        let schema = self.resolve(schema);
+        if let Some(all) = schema["allOf"].as_array() {
+            for schema in all {
+                self.validate(schema, value, at, depth + 1, problems);
+            }
+        }
+        for key in ["anyOf", "oneOf"] {
+            if let Some(any) = schema[key].as_array() {
+                let fits = any.iter().any(|schema| {
+                    let mut found = Vec::new();
+                    self.validate(schema, value, at, depth + 1, &mut found);
+                    found.is_empty()
+                });
+                if !fits {
+                    problems.push(format!("{}: matches none of the {} schemas", at, key));
+                }
+            }
+        }
+        if value.is_null() && schema["nullable"] == Value::Bool(true) {
+            return;
+        }
+        let types: Vec<&str> = match &schema["type"] {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|name| is_type(value, name)) {
+            problems.push(format!(
+                "{}: expected {}, got {}",
+                at,
+                types.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+        if let Some(allowed) = schema["enum"].as_array() {
+            if !allowed.contains(value) {
+                problems.push(format!(
+                    "{}: {} is not one of the values allowed",
+                    at, value
+                ));
+            }
+        }
+        if let Value::Object(object) = value {
+            for name in schema["required"].as_array().into_iter().flatten() {
+                if let Some(name) = name.as_str().filter(|name| !object.contains_key(*name)) {
+                    problems.push(format!("{}.{}: required but missing", at, name));
+                }
+            }
+            let properties = &schema["properties"];
+            for (name, field) in object {
+                let field_at = format!("{}.{}", at, name);
+                match (properties.get(name), &schema["additionalProperties"]) {
+                    (Some(declared), _) => {
+                        self.validate(declared, field, &field_at, depth + 1, problems)
+                    }
+                    (None, Value::Bool(false)) => {
+                        problems.push(format!("{}: not in the spec", field_at))
+                    }
+                    (None, extra) if extra.is_object() => {
+                        self.validate(extra, field, &field_at, depth + 1, problems)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if let (Value::Array(items), Some(item)) = (value, schema.get("items")) {
+            for (i, element) in items.iter().enumerate() {
+                let item_at = format!("{}[{}]", at, i);
+                self.validate(item, element, &item_at, depth + 1, problems);
+            }
+        }
+    }
+}
+
+fn is_type(value: &Value, name: &str) -> bool {
+    match name {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "null" => value.is_null(),
+        // A type this doesn't know can't be held against the response.
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r##"{
+        "openapi": "3.0.3",
+        "paths": {
+            "/health": {"get": {"responses": {"200": {"content": {"application/json": {
+                "schema": {"$ref": "#/components/schemas/Health"}}}}}}},
+            "/models/{id}": {"get": {"responses": {
+                "2XX": {"content": {"application/json": {"schema": {
+                    "type": "object",
+                    "properties": {"tags": {"type": "array", "items": {"type": "string"}}}
+                }}}},
+                "404": {"description": "no such model"}
+            }}}
+        },
+        "components": {"schemas": {"Health": {
+            "type": "object",
+            "required": ["status", "version"],
+            "additionalProperties": false,
+            "properties": {
+                "status": {"type": "string", "enum": ["healthy", "degraded"]},
+                "version": {"type": "string"},
+                "timestamp": {"type": "string", "nullable": true}
+            }
+        }}}
+    }"##;
+
+    #[test]
+    fn test_drift_names_the_field_and_the_difference() {
+        let contract = Contract::parse(SPEC.as_bytes()).unwrap();
+        let ok = StatusCode::OK;
+        let check = |path: &str, status: StatusCode, body: &str| {
+            contract.check("GET", path, status, body.as_bytes())
+        };
+
+        assert!(check(
+            "/health",
+            ok,
+            r#"{"status":"healthy","version":"1","timestamp":null}"#
+        )
+        .is_empty());
+        assert_eq!(
+            check(
+                "/health",
+                ok,
+                r#"{"status":"up","version":2,"uptime":"1s"}"#
+            ),
+            [
+                "GET /health 200: $.status: \"up\" is not one of the values allowed",
+                "GET /health 200: $.uptime: not in the spec",
+                "GET /health 200: $.version: expected string, got integer",
+            ]
+        );
+        assert_eq!(
+            check("/health", ok, r#"{"status":"healthy"}"#),
+            ["GET /health 200: $.version: required but missing"]
+        );
+        assert_eq!(
+            check("/api/models/m1?x=1", ok, r#"{"tags":["a",3]}"#),
+            ["GET /models/{id} 200: $.tags[1]: expected string, got integer"]
+        );
+        assert!(check("/models/m1", StatusCode::NOT_FOUND, "no such model").is_empty());
+        assert_eq!(
+            check("/health", StatusCode::SERVICE_UNAVAILABLE, ""),
+            ["GET /health 503: status not in the spec"]
+        );
+        assert_eq!(
+            check("/metrics", ok, "{}"),
+            ["GET /metrics: not in the spec"]
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use keyring::Entry;
+use std::error::Error;
+
+/// The keyring service every token is stored under; the profile is the
+/// account.
+const SERVICE: &str = "prediction-cli";
+
+/// Profile name for the settings outside any profile.
+pub const DEFAULT_PROFILE: &str = "default";
+
+// The keyring's blocking calls run on the blocking pool: on Linux they talk
+// D-Bus through an executor that mustn't be driven from an async task.
+
+/// Saves `token` as `profile`'s in the OS keyring, replacing any earlier one.
+pub async fn store(profile: &str, token: &str) -> Result<(), Box<dyn Error>> {
+    let (profile, token) = (profile.to_string(), token.to_string());
+    tokio::task::spawn_blocking(move || Entry::new(SERVICE, &profile)?.set_password(&token))
+        .await?
+        .map_err(|e| format!("cannot store the token in the OS keyring: {}", e).into())
+}
+
+/// `profile`'s token, if the keyring has one. A keyring that can't be
+/// reached counts as having none, so commands work the same without one.
+pub async fn load(profile: &str) -> Option<String> {
+    let profile = profile.to_string();
+    tokio::task::spawn_blocking(move || Entry::new(SERVICE, &profile)?.get_password())
+        .await
+        .ok()?
+        .ok()
+}
+
+/// Removes `profile`'s token; `false` if there wasn't one.
+pub async fn delete(profile: &str) -> Result<bool, Box<dyn Error>> {
+    let profile = profile.to_string();
+    let deleted =
+        tokio::task::spawn_blocking(move || Entry::new(SERVICE, &profile)?.delete_credential())
+            .await?;
+    match deleted {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(format!("cannot remove the token from the OS keyring: {}", e).into()),
+    }
+}
@@ -0,0 +1,344 @@
+use crate::api::{self, Api, HealthResponse, MetricsResponse, Prediction};
+use crate::watch::MetricsDelta;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::symbols;
+use ratatui::text::Line;
+use ratatui::widgets::{Axis, Block, Chart, Dataset, GraphType, Paragraph, Row, Table};
+use ratatui::Frame;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Samples kept for the rolling graphs.
+const HISTORY: usize = 60;
+
+/// One plotted point: seconds since the dashboard started, and a value.
+type Point = (f64, f64);
+
+/// What the dashboard shows, updated once per refresh.
+pub struct Dashboard {
+    started: Instant,
+    interval: Duration,
+    health: Option<Result<HealthResponse, String>>,
+    metrics: Option<Result<MetricsResponse, String>>,
+    predictions: Option<Result<Vec<Prediction>, String>>,
+    previous: Option<(Instant, MetricsResponse)>,
+    rate: VecDeque<Point>,
+    memory: VecDeque<Point>,
+    refreshes: u64,
+}
+
+impl Dashboard {
+    pub fn new(interval: Duration) -> Self {
+        Dashboard {
+            started: Instant::now(),
+            interval,
+            health: None,
+            metrics: None,
+            predictions: None,
+            previous: None,
+            rate: VecDeque::with_capacity(HISTORY),
+            memory: VecDeque::with_capacity(HISTORY),
+            refreshes: 0,
+        }
+    }
+
+    /// Fetches health, metrics and predictions at once and records them.
+    async fn refresh(&mut self, api: &Api) {
+        let page = api::Page::default();
+        let (health, metrics, predictions) = tokio::join!(
+            api::check_health(api),
+            api::get_metrics(api),
+            api::get_predictions(api, &page)
+        );
+        self.record(
+            health.map_err(|e| e.to_string()),
+            metrics.map_err(|e| e.to_string()),
+            predictions
+                .map(|response| response.predictions)
+                .map_err(|e| e.to_string()),
+            Instant::now(),
+        );
+    }
+
+    fn record(
+        &mut self,
+        health: Result<HealthResponse, String>,
+        metrics: Result<MetricsResponse, String>,
+        predictions: Result<Vec<Prediction>, String>,
+        now: Instant,
+    ) {
+        self.refreshes += 1;
+        if let Ok(current) = &metrics {
+            let t = now.duration_since(self.started).as_secs_f64();
+            if let Some((at, previous)) = &self.previous {
+                let delta = MetricsDelta::between(previous, current, now.duration_since(*at));
+                push(&mut self.rate, (t, delta.requests_per_sec));
+            }
+            push(&mut self.memory, (t, current.memory_usage_mb));
+            self.previous = Some((now, current.clone()));
+        }
+        self.health = Some(health);
+        self.metrics = Some(metrics);
+        self.predictions = Some(predictions);
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [body, footer] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [left, middle, right] = Layout::horizontal([
+            Constraint::Percentage(25),
+            Constraint::Percentage(45),
+            Constraint::Percentage(30),
+        ])
+        .areas(body);
+        let [health, metrics] =
+            Layout::vertical([Constraint::Length(6), Constraint::Min(0)]).areas(left);
+        let [rate, memory] =
+            Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(middle);
+
+        self.draw_health(frame, health);
+        self.draw_metrics(frame, metrics);
+        draw_graph(frame, rate, "Requests/sec", &self.rate, Color::Cyan);
+        draw_graph(frame, memory, "Memory (MB)", &self.memory, Color::Magenta);
+        self.draw_predictions(frame, right);
+
+        let status = format!(
+            " q quit · r refresh · every {}s · {} refreshes",
+            self.interval.as_secs_f64(),
+            self.refreshes
+        );
+        frame.render_widget(Paragraph::new(status).dark_gray(), footer);
+    }
+
+    fn draw_health(&self, frame: &mut Frame, area: Rect) {
+        let lines = match &self.health {
+            None => vec![Line::from("loading…")],
+            Some(Err(e)) => vec![
+                Line::from("unreachable").red().bold(),
+                Line::from(e.as_str()),
+            ],
+            Some(Ok(health)) => {
+                let status = if health.status == "healthy" {
+                    Line::from(health.status.as_str()).green().bold()
+                } else {
+                    Line::from(health.status.as_str()).yellow().bold()
+                };
+                vec![
+                    status,
+                    Line::from(format!("version {}", health.version)),
+                    Line::from(health.timestamp.as_str()).dark_gray(),
+                ]
+            }
+        };
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(" Health ")),
+            area,
+        );
+    }
+
+    fn draw_metrics(&self, frame: &mut Frame, area: Rect) {
+        let lines = match &self.metrics {
+            None => vec![Line::from("loading…")],
+            Some(Err(e)) => vec![Line::from(e.as_str()).red()],
+            Some(Ok(metrics)) => vec![
+                Line::from(format!("uptime      {}", metrics.uptime)),
+                Line::from(format!("requests    {}", metrics.requests_total)),
+                Line::from(format!("memory      {:.2} MB", metrics.memory_usage_mb)),
+                Line::from(format!("goroutines  {}", metrics.goroutines)),
+                Line::from(format!("cpu cores   {}", metrics.cpu_cores)),
+            ],
+        };
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(" Metrics ")),
+            area,
+        );
+    }
+
+    fn draw_predictions(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered().title(" Latest predictions ");
+        let predictions = match &self.predictions {
+            Some(Ok(predictions)) => predictions,
+            Some(Err(e)) => {
+                frame.render_widget(Paragraph::new(e.as_str()).red().block(block), area);
+                return;
+            }
+            None => {
+                frame.render_widget(Paragraph::new("loading…").block(block), area);
+                return;
+            }
+        };
+        let rows = predictions.iter().map(|prediction| {
+            Row::new(vec![
+                prediction.label.clone(),
+                format!("{:.2}%", prediction.confidence * 100.0),
+            ])
+        });
+        let table = Table::new(rows, [Constraint::Fill(1), Constraint::Length(10)])
+            .header(Row::new(vec!["Label", "Confidence"]).add_modifier(Modifier::BOLD))
+            .block(block);
+        frame.render_widget(table, area);
+    }
+}
+
+fn push(history: &mut VecDeque<Point>, point: Point) {
+    if history.len() == HISTORY {
+        history.pop_front();
+    }
+    history.push_back(point);
+}
+
+/// A line chart of `history`, scaled to fit.
+fn draw_graph(frame: &mut Frame, area: Rect, title: &str, history: &VecDeque<Point>, color: Color) {
+    let block = Block::bordered().title(format!(" {} ", title));
+    let points: Vec<Point> = history.iter().copied().collect();
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        frame.render_widget(Paragraph::new("waiting for samples…").block(block), area);
+        return;
+    };
+    let max = points.iter().map(|(_, y)| *y).fold(0.0, f64::max).max(1.0) * 1.1;
+    let chart = Chart::new(vec![Dataset::default()
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(color))
+        .data(&points)])
+    .block(block)
+    .x_axis(Axis::default().bounds([first.0, last.0.max(first.0 + 1.0)]))
+    .y_axis(
+        Axis::default()
+            .bounds([0.0, max])
+            .labels([format!("{:.0}", 0.0), format!("{:.1}", max)]),
+    );
+    frame.render_widget(chart, area);
+}
+
+/// Runs the dashboard full-screen until `q`, Esc or Ctrl-C.
+pub async fn run(api: &Api, interval: Duration) -> Result<(), Box<dyn Error>> {
+    let mut terminal = ratatui::try_init()?;
+    let result = run_loop(&mut terminal, api, interval).await;
+    ratatui::restore();
+    result
+}
+
+/// Terminal input, read on its own thread because reading blocks.
+pub fn terminal_events() -> mpsc::UnboundedReceiver<Event> {
+    let (events, receiver) = mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        while let Ok(event) = event::read() {
+            if events.send(event).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+async fn run_loop(
+    terminal: &mut ratatui::DefaultTerminal,
+    api: &Api,
+    interval: Duration,
+) -> Result<(), Box<dyn Error>> {
+    let mut key_events = terminal_events();
+    let mut dashboard = Dashboard::new(interval);
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        terminal.draw(|frame| dashboard.draw(frame))?;
+        tokio::select! {
+            _ = ticker.tick() => dashboard.refresh(api).await,
+            event = key_events.recv() => match event {
+                Some(Event::Key(key)) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        return Ok(())
+                    }
+                    KeyCode::Char('r') => dashboard.refresh(api).await,
+                    _ => {}
+                },
+                Some(_) => {}
+                None => return Ok(()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn metrics(requests_total: i64) -> MetricsResponse {
+        MetricsResponse {
+            uptime: "5m".to_string(),
+            requests_total,
+            memory_usage_mb: 12.5,
+            goroutines: 7,
+            cpu_cores: 4,
+        }
+    }
+
+    fn render(dashboard: &Dashboard) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|frame| dashboard.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content()
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_shows_each_panel() {
+        let mut dashboard = Dashboard::new(Duration::from_secs(2));
+        let health = HealthResponse {
+            status: "healthy".to_string(),
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            version: "1.0.0".to_string(),
+        };
+        let predictions = vec![Prediction {
+            label: "positive".to_string(),
+            confidence: 0.9,
+            timestamp: None,
+        }];
+        let start = dashboard.started;
+        dashboard.record(Ok(health), Ok(metrics(10)), Ok(vec![]), start);
+        dashboard.record(
+            Err("connection refused".to_string()),
+            Ok(metrics(30)),
+            Ok(predictions),
+            start + Duration::from_secs(2),
+        );
+
+        assert_eq!(dashboard.rate.back(), Some(&(2.0, 10.0)));
+        assert_eq!(dashboard.memory.len(), 2);
+        let screen = render(&dashboard);
+        assert!(screen.contains("unreachable"));
+        assert!(screen.contains("requests    30"));
+        assert!(screen.contains("positive"));
+        assert!(screen.contains("90.00%"));
+        assert!(screen.contains("2 refreshes"));
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut dashboard = Dashboard::new(Duration::from_secs(1));
+        let start = dashboard.started;
+        for i in 0..(HISTORY as u64 + 10) {
+            dashboard.record(
+                Err("down".to_string()),
+                Ok(metrics(i as i64)),
+                Ok(vec![]),
+                start + Duration::from_secs(i),
+            );
+        }
+        assert_eq!(dashboard.memory.len(), HISTORY);
+        assert_eq!(dashboard.rate.len(), HISTORY);
+    }
+}
@@ -0,0 +1,430 @@
+use crate::api::{self, Api, StatusError};
+use crate::unix;
+use reqwest::{StatusCode, Url};
+use serde::Serialize;
+use std::error::Error;
+use std::io;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+/// How one step of `doctor` went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Pass,
+    Fail,
+    /// Not tried: it doesn't apply, or an earlier step failed.
+    Skip,
+}
+
+/// One line of the checklist.
+#[derive(Debug, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub outcome: Outcome,
+    /// What was found, or what went wrong.
+    pub detail: String,
+    /// What to try next, for a failed step.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Diagnosis {
+    pub url: String,
+    pub checks: Vec<Check>,
+}
+
+impl Diagnosis {
+    pub fn failed(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| check.outcome == Outcome::Fail)
+            .count()
+    }
+}
+
+/// The checklist in the order it runs; each step needs the ones before it.
+const STEPS: [&str; 5] = ["DNS", "TCP", "TLS", "HTTP", "Version"];
+
+/// Walks from name resolution up to the API's version, stopping at the
+/// first step that fails and skipping the rest.
+pub async fn diagnose(api: &Api) -> Diagnosis {
+    let mut checks = Vec::new();
+    if let Err(failed) = run_steps(api, &mut checks).await {
+        checks.push(failed);
+    }
+    while checks.len() < STEPS.len() {
+        checks.push(skip(STEPS[checks.len()], "an earlier check failed"));
+    }
+    Diagnosis {
+        url: api.base_url().to_string(),
+        checks,
+    }
+}
+
+/// Pushes each passed or skipped step, returning the one that failed.
+async fn run_steps(api: &Api, checks: &mut Vec<Check>) -> Result<(), Check> {
+    let options = api.options();
+    if let Some(path) = unix::socket_path(&options.base_url) {
+        let detail = format!("requests go to the Unix socket {}", path.display());
+        for step in ["DNS", "TCP", "TLS"] {
+            checks.push(skip(step, &detail));
+        }
+        let started = Instant::now();
+        let sent = api::send_raw(api, "/health", &[]).await;
+        return http_and_version(api, checks, sent, started.elapsed().as_millis(), true).await;
+    }
+    let url = Url::parse(&options.base_url)
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https") && url.host_str().is_some())
+        .ok_or_else(|| {
+            fail(
+                "DNS",
+                format!("{:?} is not an http:// or https:// URL", options.base_url),
+                "set --url, PREDICTION_API_URL or the config file's url to the API's base URL, \
+                 e.g. http://localhost:8080",
+            )
+        })?;
+    let host = url.host_str().unwrap_or_default().to_string();
+    let port = url.port_or_known_default().unwrap_or(80);
+    let https = url.scheme() == "https";
+
+    if let Some(proxy) = &options.proxy {
+        // The proxy resolves and connects; reaching it says nothing about
+        // the API's own host.
+        let detail = format!("requests go through {}", proxy);
+        checks.push(skip("DNS", &detail));
+        checks.push(skip("TCP", &detail));
+    } else {
+        let addrs = resolve(&host, port, options.connect_timeout).await;
+        let addrs = addrs.map_err(|e| {
+            fail(
+                "DNS",
+                format!("cannot resolve {}: {}", host, e),
+                "check the host name in the URL, and that DNS or the VPN it needs is up",
+            )
+        })?;
+        let detail = match url.domain() {
+            Some(_) => format!("{} is {}", host, list_ips(&addrs)),
+            None => format!("{} is an address; nothing to resolve", host),
+        };
+        checks.push(pass("DNS", detail));
+
+        let started = Instant::now();
+        let connected = connect(&addrs, options.connect_timeout).await;
+        let addr = connected.map_err(|e| {
+            let hint = if e.kind() == io::ErrorKind::ConnectionRefused {
+                format!(
+                    "nothing is listening on port {}; start the API \
+                     (gaffer-exec run start-api --graph graph.json) or fix the port in the URL",
+                    port
+                )
+            } else {
+                "a firewall may be dropping the connection, or the host is down; \
+                 --connect-timeout waits longer"
+                    .to_string()
+            };
+            fail(
+                "TCP",
+                format!("cannot connect to port {}: {}", port, e),
+                hint,
+            )
+        })?;
+        checks.push(pass(
+            "TCP",
+            format!(
+                "connected to {} in {} ms",
+                addr,
+                started.elapsed().as_millis()
+            ),
+        ));
+    }
+
+    // The request goes through the same client as every other command, so
+    // --insecure, --cacert and --proxy apply to it as they would there.
+    let started = Instant::now();
+    let sent = api::send_raw(api, "/health", &[]).await;
+    let elapsed_ms = started.elapsed().as_millis();
+    checks.push(tls(&host, https, options.insecure, &sent)?);
+    http_and_version(api, checks, sent, elapsed_ms, false).await
+}
+
+/// The TLS step from how `GET /health` went. With `--insecure` nothing
+/// checked the certificate, so it can't pass.
+fn tls(
+    host: &str,
+    https: bool,
+    insecure: bool,
+    sent: &Result<(), Box<dyn Error>>,
+) -> Result<Check, Check> {
+    match sent {
+        Err(e) if https && is_connect_error(&**e) => Err(fail(
+            "TLS",
+            format!("handshake with {} failed: {}", host, root_cause(&**e)),
+            "for a self-signed or private certificate pass its CA with --cacert, \
+             or -k to skip verification against a local API",
+        )),
+        _ if https && insecure => Ok(skip(
+            "TLS",
+            &format!(
+                "certificate for {} not checked: verification disabled by --insecure",
+                host
+            ),
+        )),
+        _ if https => Ok(pass("TLS", format!("certificate for {} verified", host))),
+        _ => Ok(skip("TLS", "the URL is plain http://")),
+    }
+}
+
+/// The HTTP step from how `GET /health` went, then the version check.
+async fn http_and_version(
+    api: &Api,
+    checks: &mut Vec<Check>,
+    sent: Result<(), Box<dyn Error>>,
+    elapsed_ms: u128,
+    unix_socket: bool,
+) -> Result<(), Check> {
+    match sent {
+        Ok(()) => checks.push(pass(
+            "HTTP",
+            format!("GET /health answered in {} ms", elapsed_ms),
+        )),
+        Err(e) if unix_socket && is_connect_error(&*e) => {
+            return Err(fail(
+                "HTTP",
+                root_cause(&*e),
+                "nothing is listening on the socket; start the backend or fix the path",
+            ));
+        }
+        Err(e) => {
+            let hint = match e.downcast_ref::<StatusError>().map(|e| e.status) {
+                Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) => {
+                    "the API wants credentials: --token, --api-key or `login`"
+                }
+                Some(StatusCode::NOT_FOUND) => {
+                    "this server has no /health; check the URL is the API's base, without a path"
+                }
+                Some(status) if status.is_server_error() => {
+                    "the API is up but failing; check its logs"
+                }
+                _ => "the API accepted the connection but didn't answer; check its logs",
+            };
+            return Err(fail("HTTP", root_cause(&*e), hint));
+        }
+    }
+
+    let health = api::check_health(api).await.map_err(|e| {
+        fail(
+            "Version",
+            format!("/health isn't the prediction API's: {}", e),
+            "check the URL points at the prediction API rather than another service",
+        )
+    })?;
+    let (detail, hint) = compatibility(&health.version, env!("CARGO_PKG_VERSION"));
+    match hint {
+        None => checks.push(pass("Version", detail)),
+        Some(hint) => return Err(fail("Version", detail, hint)),
+    }
+    Ok(())
+}
+
+async fn resolve(host: &str, port: u16, wait: Duration) -> io::Result<Vec<SocketAddr>> {
+    // `Url` keeps IPv6 literals in brackets; the resolver wants them bare.
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let addrs: Vec<SocketAddr> = timeout(wait, tokio::net::lookup_host((host, port)))
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "timed out"))??
+        .collect();
+    if addrs.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "no addresses"));
+    }
+    Ok(addrs)
+}
+
+/// The first of `addrs` that accepts a connection, or the last error.
+async fn connect(addrs: &[SocketAddr], wait: Duration) -> io::Result<SocketAddr> {
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, "no addresses");
+    for &addr in addrs {
+        match timeout(wait, TcpStream::connect(addr)).await {
+            Ok(Ok(_)) => return Ok(addr),
+            Ok(Err(e)) => last_error = e,
+            Err(_) => last_error = io::Error::new(io::ErrorKind::TimedOut, "timed out"),
+        }
+    }
+    Err(last_error)
+}
+
+fn list_ips(addrs: &[SocketAddr]) -> String {
+    let mut ips: Vec<String> = addrs.iter().map(|addr| addr.ip().to_string()).collect();
+    ips.dedup();
+    ips.join(", ")
+}
+
+/// The API's version against the CLI's: the same major version is
+/// compatible. `None` for the hint when they are.
+fn compatibility(api_version: &str, cli_version: &str) -> (String, Option<String>) {
+    let major = |version: &str| {
+        version
+            .trim_start_matches('v')
+            .split('.')
+            .next()
+            .and_then(|major| major.parse::<u64>().ok())
+    };
+    match (major(api_version), major(cli_version)) {
+        (Some(api), Some(cli)) if api == cli => (
+            format!("API {} works with CLI {}", api_version, cli_version),
+            None,
+        ),
+        (Some(api), Some(cli)) => {
+            let hint = if api > cli {
+                format!("upgrade prediction-cli to a {}.x release", api)
+            } else {
+                format!("upgrade the API, or use a {}.x prediction-cli", api)
+            };
+            (
+                format!(
+                    "API {} is not compatible with CLI {}",
+                    api_version, cli_version
+                ),
+                Some(hint),
+            )
+        }
+        _ => (
+            format!("API reports an unrecognized version {:?}", api_version),
+            Some("check the URL points at the prediction API".to_string()),
+        ),
+    }
+}
+
+/// Couldn't connect: for an https:// URL that TCP already reached, the TLS
+/// handshake, and for a `unix://` one the socket.
+fn is_connect_error(error: &(dyn Error + 'static)) -> bool {
+    error
+        .downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_connect())
+}
+
+/// The innermost error, which names what actually went wrong without the
+/// layers of "error sending request" around it.
+fn root_cause(error: &(dyn Error + 'static)) -> String {
+    let mut cause = error;
+    while let Some(source) = cause.source() {
+        cause = source;
+    }
+    cause.to_string()
+}
+
+fn pass(name: &'static str, detail: String) -> Check {
+    Check {
+        name,
+        outcome: Outcome::Pass,
+        detail,
+        hint: None,
+    }
+}
+
+fn fail(name: &'static str, detail: String, hint: impl Into<String>) -> Check {
+    Check {
+        name,
+        outcome: Outcome::Fail,
+        detail,
+        hint: Some(hint.into()),
+    }
+}
+
+fn skip(name: &'static str, detail: &str) -> Check {
+    Check {
+        name,
+        outcome: Outcome::Skip,
+        detail: detail.to_string(),
+        hint: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcomes(diagnosis: &Diagnosis) -> Vec<Outcome> {
+        diagnosis.checks.iter().map(|check| check.outcome).collect()
+    }
+
+    #[tokio::test]
+    async fn test_a_healthy_api_passes_every_check() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/health")
+            .with_body(r#"{"status":"healthy","timestamp":"t","version":"1.0.0"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let diagnosis = diagnose(&Api::for_tests(server.url())).await;
+        use Outcome::*;
+        assert_eq!(outcomes(&diagnosis), vec![Pass, Pass, Skip, Pass, Pass]);
+        assert_eq!(diagnosis.failed(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_a_closed_port_fails_tcp_and_skips_the_rest() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let diagnosis = diagnose(&Api::for_tests(url)).await;
+        use Outcome::*;
+        assert_eq!(outcomes(&diagnosis), vec![Pass, Fail, Skip, Skip, Skip]);
+        assert!(diagnosis.checks[1]
+            .hint
+            .as_deref()
+            .unwrap()
+            .contains("nothing is listening"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_a_dead_socket_fails_http_as_unreachable() {
+        let path = std::env::temp_dir().join(format!("doctor-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let diagnosis = diagnose(&Api::for_tests(format!("unix://{}", path.display()))).await;
+        use Outcome::*;
+        assert_eq!(outcomes(&diagnosis), vec![Skip, Skip, Skip, Fail, Skip]);
+        assert!(diagnosis.checks[3]
+            .hint
+            .as_deref()
+            .unwrap()
+            .contains("nothing is listening on the socket"));
+    }
+
+    #[test]
+    fn test_insecure_tls_is_not_reported_as_verified() {
+        let checked = tls("api", true, false, &Ok(())).unwrap();
+        assert_eq!(checked.outcome, Outcome::Pass);
+        assert_eq!(checked.detail, "certificate for api verified");
+
+        let unchecked = tls("api", true, true, &Ok(())).unwrap();
+        assert_eq!(unchecked.outcome, Outcome::Skip);
+        assert!(unchecked
+            .detail
+            .contains("verification disabled by --insecure"));
+
+        let plain = tls("api", false, true, &Ok(())).unwrap();
+        assert_eq!(plain.detail, "the URL is plain http://");
+    }
+
+    #[test]
+    fn test_only_the_same_major_version_is_compatible() {
+        assert!(compatibility("1.4.2", "1.0.0").1.is_none());
+        assert!(compatibility("v1.0.0", "1.0.0").1.is_none());
+        assert!(compatibility("2.0.0", "1.0.0")
+            .1
+            .unwrap()
+            .contains("upgrade prediction-cli"));
+        assert!(compatibility("0.9.0", "1.0.0")
+            .1
+            .unwrap()
+            .contains("upgrade the API"));
+        assert!(compatibility("dev", "1.0.0").1.is_some());
+    }
+}
@@ -0,0 +1,189 @@
+use crate::api::{DryRun, RequestFailed, StatusError};
+use crate::config::ConfigError;
+use crate::grpc::GrpcError;
+use reqwest::StatusCode;
+use std::error::Error;
+use std::fmt;
+
+/// How a run ended, as the process exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Code {
+    Success = 0,
+    Failure = 1,
+    Connection = 2,
+    Unhealthy = 3,
+    InvalidInput = 4,
+    Auth = 5,
+    BelowThreshold = 6,
+    PartialFailure = 7,
+}
+
+/// Shown at the end of `--help`.
+pub const HELP: &str = "\
+Exit codes:
+  0  success
+  1  any other failure
+  2  could not connect to the API, or it timed out
+  3  the API, or one of several targets, is not healthy
+  4  invalid input: arguments, features, input files or config
+  5  the API rejected the credentials (401 or 403)
+  6  a prediction's confidence was below --threshold
+  7  some rows of a batch failed; the rest were printed";
+
+/// An error that ends the run with a particular [`Code`].
+#[derive(Debug)]
+pub struct Tagged {
+    pub code: Code,
+    pub error: Box<dyn Error>,
+}
+
+impl fmt::Display for Tagged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.fmt(f)
+    }
+}
+
+impl Error for Tagged {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.error.source()
+    }
+}
+
+fn tag(code: Code, error: impl Into<Box<dyn Error>>) -> Box<dyn Error> {
+    Box::new(Tagged {
+        code,
+        error: error.into(),
+    })
+}
+
+pub fn invalid_input(error: impl Into<Box<dyn Error>>) -> Box<dyn Error> {
+    tag(Code::InvalidInput, error)
+}
+
+pub fn unhealthy(error: impl Into<Box<dyn Error>>) -> Box<dyn Error> {
+    tag(Code::Unhealthy, error)
+}
+
+pub fn partial_failure(error: impl Into<Box<dyn Error>>) -> Box<dyn Error> {
+    tag(Code::PartialFailure, error)
+}
+
+pub fn below_threshold(error: impl Into<Box<dyn Error>>) -> Box<dyn Error> {
+    tag(Code::BelowThreshold, error)
+}
+
+/// Prefixes `error`'s message with `context`, keeping its exit code.
+pub fn context(context: impl fmt::Display, error: Box<dyn Error>) -> Box<dyn Error> {
+    tag(code_for(&*error), format!("{}: {}", context, error))
+}
+
+/// The exit code for `error`: its tag if it has one, otherwise whatever its
+/// cause says about the failure.
+pub fn code_for(error: &(dyn Error + 'static)) -> Code {
+    if let Some(tagged) = error.downcast_ref::<Tagged>() {
+        tagged.code
+    } else if error.is::<DryRun>() {
+        Code::Success
+    } else if let Some(failed) = error.downcast_ref::<RequestFailed>() {
+        if failed.unreachable {
+            Code::Connection
+        } else {
+            Code::Failure
+        }
+    } else if let Some(status) = error.downcast_ref::<StatusError>() {
+        code_for_status(status.status)
+    } else if let Some(e) = error.downcast_ref::<reqwest::Error>() {
+        if e.is_connect() || e.is_timeout() {
+            Code::Connection
+        } else {
+            e.status().map_or(Code::Failure, code_for_status)
+        }
+    } else if let Some(failed) = error.downcast_ref::<GrpcError>() {
+        code_for_grpc(failed.status.code())
+    } else if error.is::<ConfigError>() {
+        Code::InvalidInput
+    } else {
+        Code::Failure
+    }
+}
+
+fn code_for_status(status: StatusCode) -> Code {
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Code::Auth,
+        StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => Code::InvalidInput,
+        _ => Code::Failure,
+    }
+}
+
+fn code_for_grpc(code: tonic::Code) -> Code {
+    match code {
+        tonic::Code::Unavailable | tonic::Code::DeadlineExceeded => Code::Connection,
+        tonic::Code::Unauthenticated | tonic::Code::PermissionDenied => Code::Auth,
+        tonic::Code::InvalidArgument => Code::InvalidInput,
+        _ => Code::Failure,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status_error(status: StatusCode) -> Box<dyn Error> {
+        Box::new(StatusError {
+            request: "POST /predict".to_string(),
+            status,
+            body: String::new(),
+        })
+    }
+
+    #[test]
+    fn test_codes_follow_the_cause() {
+        let unreachable: Box<dyn Error> = Box::new(RequestFailed {
+            request: "GET /health".to_string(),
+            attempts: 3,
+            last_error: "connection refused".to_string(),
+            unreachable: true,
+        });
+        assert_eq!(code_for(&*unreachable), Code::Connection);
+        assert_eq!(
+            code_for(&*status_error(StatusCode::UNAUTHORIZED)),
+            Code::Auth
+        );
+        assert_eq!(
+            code_for(&*status_error(StatusCode::BAD_REQUEST)),
+            Code::InvalidInput
+        );
+        assert_eq!(code_for(&*unhealthy("status degraded")), Code::Unhealthy);
+        assert_eq!(code_for(&*Box::<dyn Error>::from("boom")), Code::Failure);
+    }
+
+    #[test]
+    fn test_context_keeps_the_code() {
+        let error = context("row 2", status_error(StatusCode::FORBIDDEN));
+        assert_eq!(code_for(&*error), Code::Auth);
+        assert_eq!(
+            error.to_string(),
+            "row 2: POST /predict returned 403 Forbidden"
+        );
+    }
+
+    #[test]
+    fn test_help_lists_every_code() {
+        for code in [
+            Code::Success,
+            Code::Failure,
+            Code::Connection,
+            Code::Unhealthy,
+            Code::InvalidInput,
+            Code::Auth,
+            Code::BelowThreshold,
+            Code::PartialFailure,
+        ] {
+            assert!(
+                HELP.contains(&format!("\n  {}  ", code as i32)),
+                "{:?}",
+                code
+            );
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use crate::api::{self, Api, Contributions, Prediction, StatusError};
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::error::Error;
+
+/// How much one feature pushed the prediction, and which way.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Contribution {
+    pub feature: String,
+    /// The value sent, when the API names a feature that was sent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+    pub contribution: f64,
+}
+
+/// What `predict --explain` prints.
+#[derive(Debug, Serialize)]
+pub struct Explained {
+    #[serde(flatten)]
+    pub prediction: Prediction,
+    /// Largest effect first.
+    pub contributions: Vec<Contribution>,
+}
+
+/// The contributions of `features`, which were sent as `inputs` gives them:
+/// name and value, in order.
+pub async fn contributions<T: Serialize + ?Sized>(
+    api: &Api,
+    features: &T,
+    inputs: &[(String, f64)],
+) -> Result<Vec<Contribution>, Box<dyn Error>> {
+    let response = api::explain(api, features).await.map_err(|e| {
+        if e.downcast_ref::<StatusError>()
+            .is_some_and(|e| e.status == StatusCode::NOT_FOUND)
+        {
+            "the API doesn't explain predictions (POST /explain is not found)".into()
+        } else {
+            e
+        }
+    })?;
+    Ok(match_up(response.contributions, inputs)?)
+}
+
+/// Names for features sent as a list: the schema's when it has one for
+/// each, otherwise their positions.
+pub fn inputs(features: &[f64], names: &[String]) -> Vec<(String, f64)> {
+    features
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| {
+            let name = match names.get(i) {
+                Some(name) if names.len() == features.len() => name.clone(),
+                _ => format!("feature {}", i + 1),
+            };
+            (name, value)
+        })
+        .collect()
+}
+
+fn match_up(
+    contributions: Contributions,
+    inputs: &[(String, f64)],
+) -> Result<Vec<Contribution>, String> {
+    let mut matched: Vec<Contribution> = match contributions {
+        Contributions::List(list) => {
+            if list.len() != inputs.len() {
+                return Err(format!(
+                    "the API explained {} features, but {} were sent",
+                    list.len(),
+                    inputs.len()
+                ));
+            }
+            inputs
+                .iter()
+                .zip(list)
+                .map(|((feature, value), contribution)| Contribution {
+                    feature: feature.clone(),
+                    value: Some(*value),
+                    contribution,
+                })
+                .collect()
+        }
+        Contributions::Named(named) => named
+            .into_iter()
+            .map(|(feature, contribution)| Contribution {
+                value: inputs
+                    .iter()
+                    .find(|(name, _)| *name == feature)
+                    .map(|(_, value)| *value),
+                feature,
+                contribution,
+            })
+            .collect(),
+    };
+    matched.sort_by(|a, b| b.contribution.abs().total_cmp(&a.contribution.abs()));
+    Ok(matched)
+}
+
+/// A bar `width` cells long for the largest contribution, `max`, and in
+/// proportion for the rest. Eighth blocks keep small differences visible.
+pub fn bar(contribution: f64, max: f64, width: usize) -> String {
+    const EIGHTHS: [char; 8] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉', '█'];
+    if max <= 0.0 || contribution == 0.0 {
+        return String::new();
+    }
+    let eighths = ((contribution.abs() / max) * width as f64 * 8.0).round() as usize;
+    let eighths = eighths.clamp(1, width * 8);
+    let (full, part) = (eighths / 8, eighths % 8);
+    let mut bar = "█".repeat(full);
+    if part > 0 {
+        bar.push(EIGHTHS[part - 1]);
+    }
+    bar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_contributions_are_named_and_ordered_by_effect() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/explain")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({ "features": [5.1, 3.5, 1.4] }),
+            ))
+            .with_body(r#"{"contributions":[0.1,-0.4,0.25]}"#)
+            .create_async()
+            .await;
+        let api = Api::for_tests(server.url());
+        let names = [
+            "sepal".to_string(),
+            "width".to_string(),
+            "petal".to_string(),
+        ];
+        let features = [5.1, 3.5, 1.4];
+
+        let explained = contributions(&api, &features[..], &inputs(&features, &names))
+            .await
+            .unwrap();
+        let order: Vec<&str> = explained.iter().map(|c| c.feature.as_str()).collect();
+        assert_eq!(order, ["width", "petal", "sepal"]);
+        assert_eq!(explained[0].value, Some(3.5));
+
+        let named = Contributions::Named([("b".to_string(), 0.2)].into_iter().collect());
+        let inputs = inputs(&[1.0, 2.0], &[]);
+        assert_eq!(inputs[1].0, "feature 2");
+        assert_eq!(match_up(named, &inputs).unwrap()[0].value, None);
+        assert!(match_up(Contributions::List(vec![0.1]), &inputs).is_err());
+    }
+
+    #[test]
+    fn test_bars_scale_to_the_largest_contribution() {
+        assert_eq!(bar(-0.4, 0.4, 4), "████");
+        assert_eq!(bar(0.1, 0.4, 4), "█");
+        assert_eq!(bar(0.05, 0.4, 4), "▌");
+        assert_eq!(bar(0.0001, 0.4, 4), "▏");
+        assert_eq!(bar(0.0, 0.4, 4), "");
+    }
+}
@@ -0,0 +1,177 @@
+use crate::api::{Prediction, PredictionsResponse};
+use crate::exit;
+use parquet::data_type::{ByteArray, ByteArrayType, DoubleType};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use std::error::Error;
+use std::fs::{self, File};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Columns written for every prediction; `timestamp` is when it was made,
+/// or when the API answered the request for servers that don't say.
+const PARQUET_SCHEMA: &str = "
+    message prediction {
+        REQUIRED BYTE_ARRAY label (UTF8);
+        REQUIRED DOUBLE confidence;
+        REQUIRED BYTE_ARRAY timestamp (UTF8);
+    }
+";
+
+/// Writes the predictions to `path`, as CSV or Parquet by its extension.
+pub fn write_predictions(
+    path: &Path,
+    response: &PredictionsResponse,
+) -> Result<(), Box<dyn Error>> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    let written = match extension.as_deref() {
+        Some("csv") => fs::write(path, to_csv(response)).map_err(Into::into),
+        Some("parquet") => write_parquet(path, response),
+        _ => {
+            return Err(exit::invalid_input(format!(
+                "cannot export to {}: use a .csv or .parquet file",
+                path.display()
+            )))
+        }
+    };
+    written.map_err(|e| format!("cannot write {}: {}", path.display(), e).into())
+}
+
+fn to_csv(response: &PredictionsResponse) -> String {
+    let mut csv = String::from("label,confidence,timestamp\n");
+    for prediction in &response.predictions {
+        csv += &format!(
+            "{},{},{}\n",
+            csv_field(&prediction.label),
+            prediction.confidence,
+            csv_field(time_of(prediction, response))
+        );
+    }
+    csv
+}
+
+fn time_of<'a>(prediction: &'a Prediction, response: &'a PredictionsResponse) -> &'a str {
+    prediction
+        .timestamp
+        .as_deref()
+        .unwrap_or(&response.timestamp)
+}
+
+/// Quotes `value` if it holds a comma, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn write_parquet(path: &Path, response: &PredictionsResponse) -> Result<(), Box<dyn Error>> {
+    let schema = Arc::new(parse_message_type(PARQUET_SCHEMA)?);
+    let properties = Arc::new(WriterProperties::builder().build());
+    let mut writer = SerializedFileWriter::new(File::create(path)?, schema, properties)?;
+
+    let labels: Vec<ByteArray> = response
+        .predictions
+        .iter()
+        .map(|prediction| prediction.label.as_str().into())
+        .collect();
+    let confidences: Vec<f64> = response
+        .predictions
+        .iter()
+        .map(|prediction| prediction.confidence)
+        .collect();
+    let timestamps: Vec<ByteArray> = response
+        .predictions
+        .iter()
+        .map(|prediction| time_of(prediction, response).into())
+        .collect();
+
+    let mut row_group = writer.next_row_group()?;
+    for column in 0.. {
+        let Some(mut writer) = row_group.next_column()? else {
+            break;
+        };
+        match column {
+            0 => writer
+                .typed::<ByteArrayType>()
+                .write_batch(&labels, None, None)?,
+            1 => writer
+                .typed::<DoubleType>()
+                .write_batch(&confidences, None, None)?,
+            _ => writer
+                .typed::<ByteArrayType>()
+                .write_batch(&timestamps, None, None)?,
+        };
+        writer.close()?;
+    }
+    row_group.close()?;
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    fn response() -> PredictionsResponse {
+        PredictionsResponse {
+            predictions: vec![
+                Prediction {
+                    label: "positive".to_string(),
+                    confidence: 0.75,
+                    timestamp: Some("2023-12-31T23:00:00Z".to_string()),
+                },
+                Prediction {
+                    label: "a, \"b\"".to_string(),
+                    confidence: 0.5,
+                    timestamp: None,
+                },
+            ],
+            count: 2,
+            timestamp: "2024-01-01T00:00:00Z".to_string(),
+            next_cursor: None,
+        }
+    }
+
+    #[test]
+    fn test_csv_quotes_awkward_labels() {
+        assert_eq!(
+            to_csv(&response()),
+            "label,confidence,timestamp\n\
+             positive,0.75,2023-12-31T23:00:00Z\n\
+             \"a, \"\"b\"\"\",0.5,2024-01-01T00:00:00Z\n"
+        );
+    }
+
+    #[test]
+    fn test_parquet_round_trips() {
+        let path = std::env::temp_dir().join(format!("predictions-{}.parquet", std::process::id()));
+        write_predictions(&path, &response()).unwrap();
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let rows: Vec<String> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().to_string())
+            .collect();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(
+            rows[0].contains("positive") && rows[0].contains("0.75"),
+            "{}",
+            rows[0]
+        );
+    }
+
+    #[test]
+    fn test_rejects_unknown_extensions() {
+        let err = write_predictions(Path::new("out.xlsx"), &response()).unwrap_err();
+        assert!(err.to_string().contains(".csv or .parquet"), "{}", err);
+    }
+}
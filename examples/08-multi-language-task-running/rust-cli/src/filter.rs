@@ -0,0 +1,249 @@
+use crate::api::PredictionsResponse;
+use clap::ValueEnum;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How `list --sort` orders predictions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    /// Most confident first
+    Confidence,
+    /// Newest first
+    Time,
+}
+
+impl SortKey {
+    fn as_str(self) -> &'static str {
+        match self {
+            SortKey::Confidence => "confidence",
+            SortKey::Time => "time",
+        }
+    }
+}
+
+/// A `--since` cutoff: an RFC 3339 time or an age such as `30m`, `2h` or `7d`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Since {
+    /// Seconds since the Unix epoch.
+    pub epoch: i64,
+    text: String,
+}
+
+impl Since {
+    pub fn parse(text: &str) -> Result<Since, String> {
+        let epoch = match parse_age(text) {
+            Some(age) => now() - age,
+            None => parse_rfc3339(text).ok_or_else(|| {
+                format!(
+                    "expected an RFC 3339 time or an age like 30m, 2h or 7d, got {:?}",
+                    text
+                )
+            })?,
+        };
+        Ok(Since {
+            epoch,
+            text: text.to_string(),
+        })
+    }
+}
+
+/// `list`'s filters, applied to every page whether or not the server
+/// already did.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    pub min_confidence: Option<f64>,
+    /// Keep only these labels; empty keeps all.
+    pub labels: Vec<String>,
+    pub since: Option<Since>,
+    pub sort: Option<SortKey>,
+}
+
+impl Filter {
+    /// The filters as query parameters, for servers that filter themselves.
+    pub fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+        if let Some(min) = self.min_confidence {
+            query.push(("min_confidence", min.to_string()));
+        }
+        for label in &self.labels {
+            query.push(("label", label.clone()));
+        }
+        if let Some(since) = &self.since {
+            query.push(("since", since.epoch.to_string()));
+        }
+        if let Some(sort) = self.sort {
+            query.push(("sort", sort.as_str().to_string()));
+        }
+        query
+    }
+
+    /// A one-line summary for the listing's header; `None` without filters.
+    pub fn describe(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(min) = self.min_confidence {
+            parts.push(format!("confidence >= {}", min));
+        }
+        if !self.labels.is_empty() {
+            parts.push(format!("label {}", self.labels.join(" or ")));
+        }
+        if let Some(since) = &self.since {
+            parts.push(format!("since {}", since.text));
+        }
+        if let Some(sort) = self.sort {
+            parts.push(format!("sorted by {}", sort.as_str()));
+        }
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+
+    /// Drops the predictions that don't match and sorts the rest. A
+    /// prediction without its own timestamp is as old as the response.
+    pub fn apply(&self, response: &mut PredictionsResponse) -> Result<(), String> {
+        let fallback = response.timestamp.clone();
+        let mut timed = Vec::with_capacity(response.predictions.len());
+        for prediction in response.predictions.drain(..) {
+            let stamp = prediction.timestamp.as_deref().unwrap_or(&fallback);
+            let time = match (&self.since, self.sort) {
+                (None, None | Some(SortKey::Confidence)) => 0,
+                _ => parse_rfc3339(stamp)
+                    .ok_or_else(|| format!("cannot read prediction time {:?}", stamp))?,
+            };
+            timed.push((time, prediction));
+        }
+
+        timed.retain(|(time, prediction)| {
+            self.min_confidence
+                .is_none_or(|min| prediction.confidence >= min)
+                && (self.labels.is_empty() || self.labels.contains(&prediction.label))
+                && self.since.as_ref().is_none_or(|since| *time >= since.epoch)
+        });
+        match self.sort {
+            Some(SortKey::Confidence) => {
+                timed.sort_by(|(_, a), (_, b)| b.confidence.total_cmp(&a.confidence))
+            }
+            Some(SortKey::Time) => timed.sort_by(|(a, _), (b, _)| b.cmp(a)),
+            None => {}
+        }
+        response.predictions = timed
+            .into_iter()
+            .map(|(_, prediction)| prediction)
+            .collect();
+        response.count = response.predictions.len();
+        Ok(())
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+/// `90s`, `30m`, `2h` or `7d` in seconds.
+fn parse_age(text: &str) -> Option<i64> {
+    let unit = match text.chars().last()? {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86_400,
+        _ => return None,
+    };
+    let count: i64 = text[..text.len() - 1].parse().ok()?;
+    count.checked_mul(unit)
+}
+
+/// Seconds since the Unix epoch for an RFC 3339 time, as Go's
+/// `time.Time` encodes it: `2024-02-29T12:34:56.789+01:00` or `...Z`.
+fn parse_rfc3339(text: &str) -> Option<i64> {
+    let (date, rest) = text.split_once(['T', 't', ' '])?;
+    let mut date = date.splitn(3, '-');
+    let year: i64 = date.next()?.parse().ok()?;
+    let month: i64 = date.next()?.parse().ok()?;
+    let day: i64 = date.next()?.parse().ok()?;
+
+    let zone_at = rest.find(['Z', 'z', '+', '-'])?;
+    let (time, zone) = rest.split_at(zone_at);
+    let time = time.split('.').next()?;
+    let mut time = time.splitn(3, ':');
+    let hour: i64 = time.next()?.parse().ok()?;
+    let minute: i64 = time.next()?.parse().ok()?;
+    let second: i64 = time.next()?.parse().ok()?;
+    let offset = match zone {
+        "Z" | "z" => 0,
+        _ => {
+            let (hours, minutes) = zone[1..].split_once(':')?;
+            let offset = hours.parse::<i64>().ok()? * 3600 + minutes.parse::<i64>().ok()? * 60;
+            if zone.starts_with('-') {
+                -offset
+            } else {
+                offset
+            }
+        }
+    };
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 {
+        return None;
+    }
+
+    // Days since the epoch from a civil date (Howard Hinnant's algorithm).
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+    Some(days * 86_400 + hour * 3600 + minute * 60 + second - offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Prediction;
+
+    #[test]
+    fn test_parses_go_timestamps_and_ages() {
+        assert_eq!(parse_rfc3339("2024-02-29T12:34:56Z"), Some(1_709_210_096));
+        assert_eq!(
+            parse_rfc3339("2024-02-29T13:34:56.123456789+01:00"),
+            Some(1_709_210_096)
+        );
+        assert_eq!(parse_rfc3339("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(parse_rfc3339("yesterday"), None);
+        assert_eq!(parse_age("2h"), Some(7200));
+        assert_eq!(parse_age("h"), None);
+        assert!(Since::parse("soon").is_err());
+    }
+
+    #[test]
+    fn test_apply_filters_and_sorts() {
+        let prediction = |label: &str, confidence, timestamp: Option<&str>| Prediction {
+            label: label.to_string(),
+            confidence,
+            timestamp: timestamp.map(str::to_string),
+        };
+        let mut response = PredictionsResponse {
+            predictions: vec![
+                prediction("cats", 0.75, Some("2024-01-01T00:00:00Z")),
+                prediction("dogs", 0.9, Some("2024-01-03T00:00:00Z")),
+                prediction("cats", 0.95, None),
+                prediction("cats", 0.5, None),
+            ],
+            count: 4,
+            timestamp: "2024-01-05T00:00:00Z".to_string(),
+            next_cursor: None,
+        };
+        let filter = Filter {
+            min_confidence: Some(0.7),
+            labels: vec!["cats".to_string()],
+            since: Some(Since::parse("2024-01-02T00:00:00Z").unwrap()),
+            sort: Some(SortKey::Time),
+        };
+
+        filter.apply(&mut response).unwrap();
+
+        assert_eq!(response.count, 1);
+        assert_eq!(response.predictions[0].confidence, 0.95);
+        assert_eq!(
+            filter.describe().unwrap(),
+            "confidence >= 0.7, label cats, since 2024-01-02T00:00:00Z, sorted by time"
+        );
+    }
+}
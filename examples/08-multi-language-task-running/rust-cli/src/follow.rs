@@ -0,0 +1,111 @@
+use crate::api::{self, Api, Page, Prediction, PredictionsResponse};
+use crate::filter::Filter;
+use colored::*;
+use std::error::Error;
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// Polls `page` of `/predictions` every `interval` until Ctrl-C, handing
+/// `print` the predictions that weren't there the poll before, oldest first
+/// and through `filter`. With `filter.since`, the predictions already made
+/// since then are printed first. The first poll's error ends the run; later
+/// ones are shown and polling goes on.
+pub async fn follow(
+    api: &Api,
+    page: &Page,
+    filter: &Filter,
+    interval: Duration,
+    mut print: impl FnMut(&[Prediction]) -> Result<(), Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    let first = api::get_predictions(api, page).await?;
+    if filter.since.is_some() {
+        let backlog = first.predictions.iter().rev().cloned().collect();
+        print(&filtered(filter, backlog, &first)?)?;
+        io::stdout().flush()?;
+    }
+    let mut previous = first.predictions;
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // The first tick is immediate, and that poll is the one just made.
+    ticker.tick().await;
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+        match api::get_predictions(api, page).await {
+            Ok(response) => {
+                let arrived = arrivals(&previous, &response.predictions);
+                print(&filtered(filter, arrived, &response)?)?;
+                io::stdout().flush()?;
+                previous = response.predictions;
+            }
+            Err(e) => eprintln!("{} {}", "Error:".red().bold(), e),
+        }
+    }
+}
+
+/// The predictions in `current` that aren't in `previous`, oldest first.
+/// Both lists are newest first, as the API sends them; a prediction that
+/// appears twice is new only if it appears more often than before.
+fn arrivals(previous: &[Prediction], current: &[Prediction]) -> Vec<Prediction> {
+    let mut unmatched: Vec<&Prediction> = previous.iter().collect();
+    let mut arrived = Vec::new();
+    for prediction in current {
+        match unmatched.iter().position(|old| *old == prediction) {
+            Some(i) => {
+                unmatched.remove(i);
+            }
+            None => arrived.push(prediction.clone()),
+        }
+    }
+    arrived.reverse();
+    arrived
+}
+
+/// `predictions` through `filter`, dated by `response` where they have no
+/// timestamp of their own.
+fn filtered(
+    filter: &Filter,
+    predictions: Vec<Prediction>,
+    response: &PredictionsResponse,
+) -> Result<Vec<Prediction>, Box<dyn Error>> {
+    let mut batch = PredictionsResponse {
+        count: predictions.len(),
+        predictions,
+        timestamp: response.timestamp.clone(),
+        next_cursor: None,
+    };
+    filter.apply(&mut batch)?;
+    Ok(batch.predictions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prediction(label: &str, confidence: f64) -> Prediction {
+        Prediction {
+            label: label.to_string(),
+            confidence,
+            timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_arrivals_are_the_new_head_oldest_first() {
+        let cats = prediction("cats", 0.9);
+        let dogs = prediction("dogs", 0.8);
+        let fish = prediction("fish", 0.7);
+        let previous = [cats.clone(), dogs.clone()];
+
+        // Two new ones, one a repeat of an old prediction, and the oldest
+        // pushed out of the page.
+        let current = [fish.clone(), cats.clone(), cats.clone()];
+
+        assert_eq!(arrivals(&previous, &current), [cats.clone(), fish]);
+        assert!(arrivals(&current, &current).is_empty());
+        assert!(arrivals(&previous, &[cats]).is_empty());
+    }
+}
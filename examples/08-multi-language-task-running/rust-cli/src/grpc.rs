@@ -0,0 +1,267 @@
+use crate::api::{self, Api, ApiOptions, HealthResponse, Prediction};
+use crate::bench::{self, BenchReport};
+use crate::exit;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+use tonic::codec::ProstCodec;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::transport::{Channel, Endpoint};
+use tonic::Status;
+
+/// Where the backend serves `prediction.v1.PredictionService`.
+pub const DEFAULT_URL: &str = "http://localhost:50051";
+
+// Messages from proto/prediction.proto, as prost-build writes them.
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HealthRequest {}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HealthReply {
+    #[prost(string, tag = "1")]
+    pub status: String,
+    #[prost(string, tag = "2")]
+    pub timestamp: String,
+    #[prost(string, tag = "3")]
+    pub version: String,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PredictRequest {
+    #[prost(double, repeated, tag = "1")]
+    pub features: Vec<f64>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PredictReply {
+    #[prost(string, tag = "1")]
+    pub label: String,
+    #[prost(double, tag = "2")]
+    pub confidence: f64,
+    #[prost(string, tag = "3")]
+    pub timestamp: String,
+}
+
+impl From<HealthReply> for HealthResponse {
+    fn from(reply: HealthReply) -> Self {
+        HealthResponse {
+            status: reply.status,
+            timestamp: reply.timestamp,
+            version: reply.version,
+        }
+    }
+}
+
+impl From<PredictReply> for Prediction {
+    fn from(reply: PredictReply) -> Self {
+        Prediction {
+            label: reply.label,
+            confidence: reply.confidence,
+            timestamp: Some(reply.timestamp).filter(|t| !t.is_empty()),
+        }
+    }
+}
+
+/// A call that ended in a non-OK gRPC status.
+#[derive(Debug)]
+pub struct GrpcError {
+    pub method: &'static str,
+    pub status: Status,
+}
+
+impl fmt::Display for GrpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "gRPC {} failed: {} ({:?})",
+            self.method,
+            self.status.message(),
+            self.status.code()
+        )
+    }
+}
+
+impl Error for GrpcError {}
+
+/// A client for the backend's gRPC service, sending the same credentials
+/// and honoring the same timeouts as the REST client. Cheap to clone; clones
+/// share one HTTP/2 connection.
+#[derive(Debug, Clone)]
+pub struct Client {
+    inner: tonic::client::Grpc<Channel>,
+    token: Option<String>,
+    api_key: Option<String>,
+}
+
+impl Client {
+    /// Connects on the first call, so an unreachable service surfaces as
+    /// that call's error.
+    pub fn new(url: &str, options: &ApiOptions) -> Result<Self, Box<dyn Error>> {
+        let channel = Endpoint::from_shared(url.to_string())
+            .map_err(|e| format!("invalid gRPC URL {}: {}", url, e))?
+            .timeout(options.timeout)
+            .connect_timeout(options.connect_timeout)
+            .connect_lazy();
+        Ok(Client {
+            inner: tonic::client::Grpc::new(channel),
+            token: options.token.clone(),
+            api_key: options.api_key.clone(),
+        })
+    }
+
+    pub async fn health(&mut self) -> Result<HealthResponse, Box<dyn Error>> {
+        let reply: HealthReply = self.unary("Health", HealthRequest {}).await?;
+        Ok(reply.into())
+    }
+
+    pub async fn predict(&mut self, features: &[f64]) -> Result<Prediction, Box<dyn Error>> {
+        let request = PredictRequest {
+            features: features.to_vec(),
+        };
+        let reply: PredictReply = self.unary("Predict", request).await?;
+        Ok(reply.into())
+    }
+
+    async fn unary<Req, Reply>(
+        &mut self,
+        method: &'static str,
+        message: Req,
+    ) -> Result<Reply, Box<dyn Error>>
+    where
+        Req: prost::Message + 'static,
+        Reply: prost::Message + Default + 'static,
+    {
+        let failed = |status| GrpcError { method, status };
+        self.inner
+            .ready()
+            .await
+            .map_err(|e| failed(Status::unavailable(e.to_string())))?;
+        let mut request = tonic::Request::new(message);
+        let metadata = request.metadata_mut();
+        if let Some(token) = &self.token {
+            let value = format!("Bearer {}", token);
+            metadata.insert("authorization", metadata_value(&value)?);
+        }
+        if let Some(key) = &self.api_key {
+            metadata.insert("x-api-key", metadata_value(key)?);
+        }
+        let path = format!("/prediction.v1.PredictionService/{}", method);
+        let path = PathAndQuery::try_from(path).expect("method names are valid paths");
+        let reply = self
+            .inner
+            .unary(request, path, ProstCodec::default())
+            .await
+            .map_err(failed)?;
+        Ok(reply.into_inner())
+    }
+}
+
+fn metadata_value(value: &str) -> Result<MetadataValue<Ascii>, Box<dyn Error>> {
+    value
+        .parse()
+        .map_err(|_| exit::invalid_input("credentials must be printable ASCII"))
+}
+
+/// One transport's answer and timings in a [`TransportComparison`].
+#[derive(Debug, Serialize)]
+pub struct Transport {
+    pub prediction: Option<Prediction>,
+    pub error: Option<String>,
+    pub bench: BenchReport,
+}
+
+/// The same prediction made over REST and over gRPC.
+#[derive(Debug, Serialize)]
+pub struct TransportComparison {
+    pub rest: Transport,
+    pub grpc: Transport,
+    /// Both answered, with the same label.
+    pub same_label: bool,
+}
+
+/// Predicts `features` once over each transport to compare the answers,
+/// then `requests` more times, `concurrency` at a time, to compare latency.
+pub async fn compare(
+    api: &Api,
+    client: &Client,
+    features: &[f64],
+    requests: usize,
+    concurrency: usize,
+) -> TransportComparison {
+    let rest_answer = api::make_prediction(api, features).await;
+    let grpc_answer = client.clone().predict(features).await;
+    let rest_bench = bench::measure(api, "REST POST /predict", requests, concurrency, || async {
+        api::send_raw(api, "/predict", features)
+            .await
+            .map_err(|e| e.to_string())
+    })
+    .await;
+    let grpc_bench = bench::measure(api, "gRPC Predict", requests, concurrency, || async {
+        client
+            .clone()
+            .predict(features)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    })
+    .await;
+
+    let same_label = matches!(
+        (&rest_answer, &grpc_answer),
+        (Ok(rest), Ok(grpc)) if rest.label == grpc.label
+    );
+    TransportComparison {
+        rest: Transport {
+            error: rest_answer.as_ref().err().map(|e| e.to_string()),
+            prediction: rest_answer.ok(),
+            bench: rest_bench,
+        },
+        grpc: Transport {
+            error: grpc_answer.as_ref().err().map(|e| e.to_string()),
+            prediction: grpc_answer.ok(),
+            bench: grpc_bench,
+        },
+        same_label,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exit::Code;
+    use prost::Message;
+    use std::time::Duration;
+
+    #[test]
+    fn test_messages_match_the_proto() {
+        // Field 1, packed: tag 0x0a, 16 bytes, then two little-endian doubles.
+        let bytes = PredictRequest {
+            features: vec![1.0, 2.0],
+        }
+        .encode_to_vec();
+        assert_eq!(&bytes[..2], &[0x0a, 16]);
+        assert_eq!(&bytes[2..10], &1.0f64.to_le_bytes());
+
+        let reply = PredictReply {
+            label: "cats".to_string(),
+            confidence: 0.5,
+            timestamp: String::new(),
+        };
+        let prediction = Prediction::from(PredictReply::decode(&*reply.encode_to_vec()).unwrap());
+        assert_eq!(prediction.label, "cats");
+        assert_eq!(prediction.timestamp, None);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_service_is_a_connection_error() {
+        let mut options = Api::for_tests(String::new()).options().clone();
+        options.connect_timeout = Duration::from_millis(200);
+        let mut client = Client::new("http://127.0.0.1:1", &options).unwrap();
+
+        let error = client.health().await.unwrap_err();
+
+        assert_eq!(exit::code_for(&*error), Code::Connection);
+    }
+}
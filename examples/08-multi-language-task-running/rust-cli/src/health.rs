@@ -0,0 +1,131 @@
+use crate::api::{self, Api};
+use serde::Serialize;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+use tokio::task::JoinSet;
+
+/// How one target answered `/health`.
+#[derive(Debug, Serialize)]
+pub struct TargetHealth {
+    pub url: String,
+    /// `status` from the response, `None` if there wasn't one.
+    pub status: Option<String>,
+    pub version: Option<String>,
+    pub latency_ms: u128,
+    pub error: Option<String>,
+}
+
+impl TargetHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.status.as_deref() == Some("healthy")
+    }
+}
+
+/// A line of a services file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Service {
+    /// The name before the URL, or the URL itself if there isn't one.
+    pub name: String,
+    pub url: String,
+}
+
+/// Reads a services file: one base URL per line, optionally after a name
+/// (`go-api http://localhost:8080`), with blank lines and `#` comments
+/// ignored.
+pub fn read_services(path: &Path) -> Result<Vec<Service>, Box<dyn Error>> {
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    let services = parse_services(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+    if services.is_empty() {
+        return Err(format!("{}: no service URLs", path.display()).into());
+    }
+    Ok(services)
+}
+
+fn parse_services(text: &str) -> Result<Vec<Service>, String> {
+    let mut services = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let words: Vec<&str> = line
+            .split('#')
+            .next()
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect();
+        let (name, url) = match words[..] {
+            [] => continue,
+            [url] => (url, url),
+            [name, url] => (name, url),
+            _ => {
+                return Err(format!(
+                    "line {}: expected a URL, or a name and a URL",
+                    i + 1
+                ))
+            }
+        };
+        services.push(Service {
+            name: name.to_string(),
+            url: url.to_string(),
+        });
+    }
+    Ok(services)
+}
+
+/// Checks every URL at once with `api`'s settings, returning results in the
+/// order the URLs were given.
+pub async fn check_all(api: &Api, urls: &[String]) -> Vec<TargetHealth> {
+    let mut checks = JoinSet::new();
+    for (i, url) in urls.iter().enumerate() {
+        let api = api.with_base_url(url);
+        checks.spawn(async move {
+            let started = Instant::now();
+            let result = api::check_health(&api).await;
+            let latency_ms = started.elapsed().as_millis();
+            let target = match result {
+                Ok(health) => TargetHealth {
+                    url: api.base_url().to_string(),
+                    status: Some(health.status),
+                    version: Some(health.version),
+                    latency_ms,
+                    error: None,
+                },
+                Err(e) => TargetHealth {
+                    url: api.base_url().to_string(),
+                    status: None,
+                    version: None,
+                    latency_ms,
+                    error: Some(e.to_string()),
+                },
+            };
+            (i, target)
+        });
+    }
+    let mut results: Vec<(usize, TargetHealth)> = checks.join_all().await;
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, target)| target).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_services_file_skips_comments_and_blanks() {
+        let services =
+            parse_services("# staging\nhttp://a:8080\n\n  prod http://b:8080  # main\n#\n")
+                .unwrap();
+        let service = |name: &str, url: &str| Service {
+            name: name.to_string(),
+            url: url.to_string(),
+        };
+        assert_eq!(
+            services,
+            vec![
+                service("http://a:8080", "http://a:8080"),
+                service("prod", "http://b:8080")
+            ]
+        );
+        assert!(parse_services("a b c\n").is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::error::Error;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Runs the config file's `pre_request` command through the shell before a
+/// request, with `PREDICTION_CLI_METHOD` and `PREDICTION_CLI_URL` set to the
+/// request's, and returns the headers it printed, one `Name: value` a line.
+/// They are marked sensitive, so printed requests redact them.
+pub async fn pre_request(
+    command: &str,
+    method: &str,
+    url: &str,
+    timeout: Duration,
+) -> Result<HeaderMap, Box<dyn Error>> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell
+        .arg(command)
+        .env("PREDICTION_CLI_METHOD", method)
+        .env("PREDICTION_CLI_URL", url)
+        .stdin(Stdio::null())
+        .kill_on_drop(true);
+    let output = tokio::time::timeout(timeout, shell.output())
+        .await
+        .map_err(|_| format!("pre_request hook timed out after {:?}", timeout))?
+        .map_err(|e| format!("cannot run the pre_request hook: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(match stderr.trim() {
+            "" => format!("pre_request hook failed ({})", output.status),
+            stderr => format!("pre_request hook failed ({}): {}", output.status, stderr),
+        }
+        .into());
+    }
+    headers(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn headers(printed: &str) -> Result<HeaderMap, Box<dyn Error>> {
+    let mut headers = HeaderMap::new();
+    for line in printed.lines().filter(|line| !line.trim().is_empty()) {
+        let invalid = || {
+            format!(
+                "pre_request hook printed {:?}, not a `Name: value` header",
+                line
+            )
+        };
+        let (name, value) = line.split_once(':').ok_or_else(invalid)?;
+        let name = HeaderName::from_bytes(name.trim().as_bytes()).map_err(|_| invalid())?;
+        let mut value = HeaderValue::from_str(value.trim()).map_err(|_| invalid())?;
+        value.set_sensitive(true);
+        headers.insert(name, value);
+    }
+    Ok(headers)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_hook_output_becomes_headers() {
+        let timeout = Duration::from_secs(5);
+        let headers = pre_request(
+            r#"echo "Authorization: Bearer $PREDICTION_CLI_METHOD"; echo; echo "X-Url: $PREDICTION_CLI_URL""#,
+            "GET",
+            "http://api/health",
+            timeout,
+        )
+        .await
+        .unwrap();
+        assert_eq!(headers["authorization"], "Bearer GET");
+        assert_eq!(headers["x-url"], "http://api/health");
+        assert!(headers["authorization"].is_sensitive());
+
+        let err = pre_request("echo 'no vault' >&2; exit 3", "GET", "/", timeout)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().ends_with("): no vault"), "{}", err);
+        let err = pre_request("echo token", "GET", "/", timeout)
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("\"token\", not a `Name: value` header"));
+    }
+}
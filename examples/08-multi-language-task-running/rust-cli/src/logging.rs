@@ -0,0 +1,201 @@
+use clap::ValueEnum;
+use colored::Colorize;
+use opentelemetry::propagation::Injector;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::TracerProvider;
+use opentelemetry_sdk::{runtime, Resource};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::error::Error;
+use std::fs::OpenOptions;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+use tracing::level_filters::LevelFilter;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::prelude::*;
+
+/// The least severe events `--log-level` keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    /// Every API call
+    Info,
+    /// Retries, and each span as it closes
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+/// What [`init`] set up that needs seeing off before the process exits.
+#[derive(Default)]
+pub struct Telemetry {
+    provider: Option<TracerProvider>,
+}
+
+impl Telemetry {
+    /// Sends the spans still buffered for the OTLP collector, if there is
+    /// one; a run that ends before the next batch would otherwise lose them.
+    pub fn shutdown(self) {
+        if let Some(provider) = self.provider {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("{} cannot export traces: {}", "Warning:".yellow().bold(), e);
+            }
+        }
+    }
+}
+
+/// Starts logging the CLI's own events as JSON lines, appended to `path` or
+/// written to stderr without one, each with the spans it happened in: the
+/// command, the API request and, for a retried one, the attempt. At `debug`
+/// and `trace` every span is also logged as it closes, with its timings.
+/// Nothing is logged unless a level or a file is given; a file alone logs at
+/// `info`.
+///
+/// With an `otlp` collector endpoint, the spans are exported to it as well,
+/// and requests carry a W3C `traceparent` header so the backend's spans join
+/// the same trace.
+pub fn init(
+    level: Option<LogLevel>,
+    path: Option<&Path>,
+    otlp: Option<&str>,
+) -> Result<Telemetry, Box<dyn Error>> {
+    let logs = if level.is_some() || path.is_some() {
+        let writer = match path {
+            Some(path) => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| format!("cannot open log file {}: {}", path.display(), e))?;
+                BoxMakeWriter::new(Mutex::new(file))
+            }
+            None => BoxMakeWriter::new(io::stderr),
+        };
+        let level = level.unwrap_or(LogLevel::Info);
+        let layer = tracing_subscriber::fmt::layer()
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(true)
+            .with_span_events(if level >= LogLevel::Debug {
+                FmtSpan::CLOSE
+            } else {
+                FmtSpan::NONE
+            })
+            .with_writer(writer)
+            .with_filter(crate_only(level.into()));
+        Some(layer)
+    } else {
+        None
+    };
+
+    let mut telemetry = Telemetry::default();
+    let traces = match otlp {
+        Some(endpoint) => {
+            let exporter = opentelemetry_otlp::SpanExporter::builder()
+                .with_tonic()
+                .with_endpoint(endpoint)
+                .build()
+                .map_err(|e| format!("cannot export traces to {}: {}", endpoint, e))?;
+            let provider = TracerProvider::builder()
+                .with_batch_exporter(exporter, runtime::Tokio)
+                .with_resource(Resource::new([KeyValue::new(
+                    "service.name",
+                    env!("CARGO_PKG_NAME"),
+                )]))
+                .build();
+            let tracer = provider.tracer(env!("CARGO_PKG_NAME"));
+            opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+            telemetry.provider = Some(provider);
+            let level = level.unwrap_or(LogLevel::Info);
+            Some(
+                tracing_opentelemetry::layer()
+                    .with_tracer(tracer)
+                    .with_filter(crate_only(level.into())),
+            )
+        }
+        None => None,
+    };
+
+    if logs.is_some() || traces.is_some() {
+        tracing_subscriber::registry()
+            .with(logs)
+            .with(traces)
+            .try_init()?;
+    }
+    Ok(telemetry)
+}
+
+/// Only this crate: the HTTP and gRPC libraries log a lot at debug.
+fn crate_only(level: LevelFilter) -> Targets {
+    Targets::new().with_target(env!("CARGO_CRATE_NAME"), level)
+}
+
+/// Adds the current span's trace context to `headers` as `traceparent`,
+/// when [`init`] is exporting traces.
+pub fn propagate(headers: &mut HeaderMap) {
+    struct Headers<'a>(&'a mut HeaderMap);
+
+    impl Injector for Headers<'_> {
+        fn set(&mut self, key: &str, value: String) {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(key.as_bytes()),
+                HeaderValue::from_str(&value),
+            ) {
+                self.0.insert(name, value);
+            }
+        }
+    }
+
+    let context = tracing::Span::current().context();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut Headers(headers))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::TraceContextExt;
+
+    #[test]
+    fn test_requests_carry_the_span_trace_context() {
+        let provider = TracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request");
+            let _entered = span.enter();
+            let mut headers = HeaderMap::new();
+            propagate(&mut headers);
+
+            let trace_id = span.context().span().span_context().trace_id();
+            let traceparent = headers["traceparent"].to_str().unwrap();
+            assert!(
+                traceparent.starts_with(&format!("00-{}-", trace_id)),
+                "{}",
+                traceparent
+            );
+        });
+    }
+}
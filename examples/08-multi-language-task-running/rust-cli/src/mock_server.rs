@@ -0,0 +1,337 @@
+use crate::api::{HealthResponse, MetricsResponse, Prediction, PredictionsResponse};
+use axum::body::Bytes;
+use axum::extract::{Query, Request, State};
+use axum::http::{header, HeaderValue, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::error::Error;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::net::TcpListener;
+
+/// Labels the Go API picks from.
+const LABELS: [&str; 4] = ["cats", "dogs", "birds", "fish"];
+
+/// What the endpoints here answer, for `--validate`.
+const OPENAPI: &str = include_str!("../openapi.json");
+
+/// Predictions `/predictions` returns, newest first.
+const RECENT: usize = 10;
+
+struct MockState {
+    started: Instant,
+    requests: AtomicI64,
+    recent: Mutex<VecDeque<Prediction>>,
+}
+
+/// `/predictions` paging: `cursor` is just the offset to resume from.
+#[derive(Deserialize)]
+struct PageQuery {
+    limit: Option<usize>,
+    offset: Option<usize>,
+    cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PredictRequest {
+    features: Features,
+}
+
+/// `import`'s records, written as they are.
+#[derive(Deserialize)]
+struct ImportRequest {
+    predictions: Vec<Prediction>,
+    #[serde(default)]
+    replace: bool,
+}
+
+/// A list, as the Go API takes, or `predict --feature`'s named values.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Features {
+    List(Vec<f64>),
+    Named(BTreeMap<String, f64>),
+}
+
+/// Serves canned versions of the Go API's endpoints on `addr` until Ctrl-C.
+pub async fn serve(addr: SocketAddr) -> Result<(), Box<dyn Error>> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("cannot listen on {}: {}", addr, e))?;
+    println!(
+        "Mock prediction API on http://{} (Ctrl-C to stop)",
+        listener.local_addr()?
+    );
+    axum::serve(listener, router())
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
+
+fn router() -> Router {
+    // Starts with the Go API's canned list.
+    let started = rfc3339(SystemTime::now());
+    let recent = [("cats", 0.95), ("dogs", 0.87), ("birds", 0.72)]
+        .into_iter()
+        .map(|(label, confidence)| Prediction {
+            label: label.to_string(),
+            confidence,
+            timestamp: Some(started.clone()),
+        })
+        .collect();
+    let state = Arc::new(MockState {
+        started: Instant::now(),
+        requests: AtomicI64::new(0),
+        recent: Mutex::new(recent),
+    });
+    Router::new()
+        .route("/health", get(health))
+        .route("/metrics", get(metrics))
+        .route("/predictions", get(predictions).post(import))
+        .route("/predict", post(predict))
+        .route("/openapi.json", get(openapi))
+        .layer(middleware::from_fn(cors))
+        .with_state(state)
+}
+
+/// The Go API's permissive CORS, so the frontend can call the mock from a
+/// browser too.
+async fn cors(request: Request, next: Next) -> Response {
+    let mut response = if request.method() == Method::OPTIONS {
+        StatusCode::OK.into_response()
+    } else {
+        next.run(request).await
+    };
+    let headers = response.headers_mut();
+    headers.insert(
+        header::ACCESS_CONTROL_ALLOW_ORIGIN,
+        HeaderValue::from_static("*"),
+    );
+    headers.insert(
+        header::ACCESS_CONTROL_ALLOW_METHODS,
+        HeaderValue::from_static("GET, POST, OPTIONS"),
+    );
+    headers.insert(
+        header::ACCESS_CONTROL_ALLOW_HEADERS,
+        HeaderValue::from_static("Content-Type"),
+    );
+    response
+}
+
+async fn health(State(state): State<Arc<MockState>>) -> Json<HealthResponse> {
+    state.requests.fetch_add(1, Ordering::Relaxed);
+    Json(HealthResponse {
+        status: "healthy".to_string(),
+        timestamp: rfc3339(SystemTime::now()),
+        version: "1.0.0-mock".to_string(),
+    })
+}
+
+async fn metrics(State(state): State<Arc<MockState>>) -> Json<MetricsResponse> {
+    Json(MetricsResponse {
+        uptime: go_duration(state.started.elapsed()),
+        // Like the Go API, the count doesn't include this request.
+        requests_total: state.requests.fetch_add(1, Ordering::Relaxed),
+        memory_usage_mb: 12.5,
+        goroutines: 4,
+        cpu_cores: std::thread::available_parallelism().map_or(1, |n| n.get() as i32),
+    })
+}
+
+async fn openapi() -> Response {
+    ([(header::CONTENT_TYPE, "application/json")], OPENAPI).into_response()
+}
+
+async fn predictions(
+    State(state): State<Arc<MockState>>,
+    Query(page): Query<PageQuery>,
+) -> Response {
+    state.requests.fetch_add(1, Ordering::Relaxed);
+    let start = match page.cursor.as_deref().map(str::parse) {
+        Some(Ok(offset)) => offset,
+        Some(Err(_)) => return (StatusCode::BAD_REQUEST, "Invalid cursor\n").into_response(),
+        None => page.offset.unwrap_or(0),
+    };
+    let recent = state.recent.lock().unwrap();
+    let end = page.limit.map_or(recent.len(), |limit| {
+        start.saturating_add(limit).min(recent.len())
+    });
+    let predictions: Vec<Prediction> = recent
+        .iter()
+        .skip(start)
+        .take(end.saturating_sub(start))
+        .cloned()
+        .collect();
+    Json(PredictionsResponse {
+        count: predictions.len(),
+        predictions,
+        timestamp: rfc3339(SystemTime::now()),
+        next_cursor: (end < recent.len()).then(|| end.to_string()),
+    })
+    .into_response()
+}
+
+async fn predict(State(state): State<Arc<MockState>>, body: Bytes) -> Response {
+    state.requests.fetch_add(1, Ordering::Relaxed);
+    let features = match serde_json::from_slice::<PredictRequest>(&body) {
+        Ok(PredictRequest {
+            features: Features::List(features),
+        }) => features,
+        Ok(PredictRequest {
+            features: Features::Named(features),
+        }) => features.into_values().collect(),
+        Err(_) => Vec::new(),
+    };
+    if features.is_empty() {
+        return (StatusCode::BAD_REQUEST, "Invalid request body\n").into_response();
+    }
+    let prediction = predict_for(&features);
+    let mut recent = state.recent.lock().unwrap();
+    recent.push_front(prediction.clone());
+    recent.truncate(RECENT);
+    Json(prediction).into_response()
+}
+
+async fn import(State(state): State<Arc<MockState>>, body: Bytes) -> Response {
+    state.requests.fetch_add(1, Ordering::Relaxed);
+    let Ok(request) = serde_json::from_slice::<ImportRequest>(&body) else {
+        return (StatusCode::BAD_REQUEST, "Invalid request body\n").into_response();
+    };
+    let mut recent = state.recent.lock().unwrap();
+    if request.replace {
+        recent.retain(|old| {
+            !request.predictions.iter().any(|new| {
+                new.timestamp.is_some() && new.timestamp == old.timestamp && new.label == old.label
+            })
+        });
+    }
+    let imported = request.predictions.len();
+    // Dumps are newest first, like `/predictions`.
+    for prediction in request.predictions.into_iter().rev() {
+        recent.push_front(prediction);
+    }
+    recent.truncate(RECENT);
+    Json(serde_json::json!({ "imported": imported })).into_response()
+}
+
+/// A made-up prediction that is always the same for the same features, so
+/// scripts developed against the mock give repeatable output.
+fn predict_for(features: &[f64]) -> Prediction {
+    let score = features.iter().sum::<f64>().abs();
+    Prediction {
+        label: LABELS[(score * 1000.0) as usize % LABELS.len()].to_string(),
+        confidence: 0.7 + 0.3 * (score * 7.0).fract(),
+        timestamp: Some(rfc3339(SystemTime::now())),
+    }
+}
+
+/// `time` as UTC RFC 3339 with seconds, like `2024-01-01T12:00:00Z`.
+pub fn rfc3339(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since the epoch (Howard Hinnant's algorithm).
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// `d` the way Go prints a `time.Duration`, to whole seconds: `1h2m3s`.
+fn go_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    let (h, m, s) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    match (h, m) {
+        (0, 0) => format!("{}s", s),
+        (0, _) => format!("{}m{}s", m, s),
+        _ => format!("{}h{}m{}s", h, m, s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{self, Api};
+    use crate::contract;
+
+    #[test]
+    fn test_formats_match_the_go_api() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        assert_eq!(rfc3339(time), "2024-02-29T12:34:56Z");
+        assert_eq!(rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(go_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(go_duration(Duration::from_secs(3723)), "1h2m3s");
+    }
+
+    #[tokio::test]
+    async fn test_serves_what_the_cli_expects() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, router()).await });
+        let contract = Arc::new(contract::load(&Api::for_tests(url.clone())).await.unwrap());
+        let api = Api::for_tests(url).with_contract(contract.clone());
+
+        assert_eq!(api::check_health(&api).await.unwrap().status, "healthy");
+        let first = api::make_prediction(&api, &[0.1, 0.2]).await.unwrap();
+        let again = api::make_prediction(&api, &[0.1, 0.2]).await.unwrap();
+        assert_eq!(first.label, again.label);
+        assert!((0.7..=1.0).contains(&first.confidence));
+        assert!(api::make_prediction(&api, &[]).await.is_err());
+        let named = api::NamedFeatures(vec![("b".to_string(), 0.2), ("a".to_string(), 0.1)]);
+        let by_name = api::make_named_prediction(&api, &named).await.unwrap();
+        assert_eq!(by_name.label, first.label);
+
+        let recent = api::get_predictions(&api, &api::Page::default())
+            .await
+            .unwrap();
+        assert_eq!(recent.count, 6);
+        assert_eq!(recent.predictions[0].label, by_name.label);
+        let page = api::Page {
+            limit: Some(2),
+            offset: Some(1),
+            ..api::Page::default()
+        };
+        let tail = api::get_all_predictions(&api, &page).await.unwrap();
+        assert_eq!(tail.count, 5);
+        assert_eq!(tail.predictions[0].label, again.label);
+        let replacement = api::Prediction {
+            confidence: 0.5,
+            ..tail.predictions[4].clone()
+        };
+        let imported = api::import_predictions(&api, &[replacement], true)
+            .await
+            .unwrap();
+        assert_eq!(imported, 1);
+        let recent = api::get_predictions(&api, &api::Page::default())
+            .await
+            .unwrap();
+        assert_eq!(recent.count, 6);
+        assert_eq!(recent.predictions[0].confidence, 0.5);
+        assert_eq!(api::get_metrics(&api).await.unwrap().requests_total, 11);
+        assert_eq!(contract.drift(), Vec::<String>::new());
+    }
+}
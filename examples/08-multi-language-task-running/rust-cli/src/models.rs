@@ -0,0 +1,84 @@
+use crate::api::{self, Api, Model, StatusError};
+use crate::exit;
+use reqwest::StatusCode;
+use std::error::Error;
+
+/// Every model the backend has, the active one included.
+pub async fn list(api: &Api) -> Result<Vec<Model>, Box<dyn Error>> {
+    api::get_models(api).await.map_err(|e| {
+        if is_not_found(&*e) {
+            "the API has no model registry (GET /models is not found)".into()
+        } else {
+            e
+        }
+    })
+}
+
+pub async fn show(api: &Api, id: &str) -> Result<Model, Box<dyn Error>> {
+    api::get_model(api, id)
+        .await
+        .map_err(|e| unknown_model(id, e))
+}
+
+/// Switches `/predict` to the model `id`.
+pub async fn activate(api: &Api, id: &str) -> Result<Model, Box<dyn Error>> {
+    api::activate_model(api, id)
+        .await
+        .map_err(|e| unknown_model(id, e))
+}
+
+fn unknown_model(id: &str, error: Box<dyn Error>) -> Box<dyn Error> {
+    if is_not_found(&*error) {
+        exit::invalid_input(format!(
+            "no model {:?}; `models list` shows the ones there are",
+            id
+        ))
+    } else {
+        error
+    }
+}
+
+fn is_not_found(error: &(dyn Error + 'static)) -> bool {
+    error
+        .downcast_ref::<StatusError>()
+        .is_some_and(|e| e.status == StatusCode::NOT_FOUND)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_models_are_unwrapped_from_the_envelope() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/models")
+            .with_body(
+                r#"{"success":true,"data":[
+                    {"id":"m1","version":"1.0","active":true,"accuracy":0.91},
+                    {"id":"m2","version":"1.1"}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/models/m%202/activate")
+            .with_body(r#"{"success":true,"data":{"id":"m 2","active":true}}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/models/gone")
+            .with_status(404)
+            .create_async()
+            .await;
+        let api = Api::for_tests(server.url());
+
+        let models = list(&api).await.unwrap();
+        assert_eq!(models.len(), 2);
+        assert!(models[0].active && !models[1].active);
+        assert_eq!(models[0].details["accuracy"], 0.91);
+        assert!(activate(&api, "m 2").await.unwrap().active);
+        let err = show(&api, "gone").await.unwrap_err();
+        assert_eq!(exit::code_for(&*err), exit::Code::InvalidInput);
+    }
+}
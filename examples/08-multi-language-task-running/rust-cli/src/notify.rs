@@ -0,0 +1,61 @@
+use colored::*;
+use notify_rust::Notification;
+use std::error::Error;
+use std::time::Duration;
+
+/// Tells the desktop that `task` ended, `elapsed` after it started, with
+/// its error if it failed. Where there's no notification service, as over
+/// SSH, there is only a warning on stderr.
+pub fn finished(task: &str, elapsed: Duration, error: Option<&dyn Error>) {
+    let (summary, body) = message(task, elapsed, error);
+    if let Err(e) = Notification::new()
+        .appname("prediction-cli")
+        .summary(&summary)
+        .body(&body)
+        .show()
+    {
+        eprintln!(
+            "{} cannot show a desktop notification: {}",
+            "Warning:".yellow().bold(),
+            e
+        );
+    }
+}
+
+fn message(task: &str, elapsed: Duration, error: Option<&dyn Error>) -> (String, String) {
+    let took = took(elapsed);
+    match error {
+        None => (format!("{} finished", task), format!("Done in {}", took)),
+        Some(e) => (format!("{} failed", task), format!("After {}: {}", took, e)),
+    }
+}
+
+fn took(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    if secs < 60 {
+        format!("{:.1}s", elapsed.as_secs_f64())
+    } else {
+        format!("{}m{}s", secs / 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_says_how_it_ended() {
+        assert_eq!(
+            message("Benchmark", Duration::from_millis(2500), None),
+            ("Benchmark finished".to_string(), "Done in 2.5s".to_string())
+        );
+        let error: Box<dyn Error> = "row 3: connection refused".into();
+        assert_eq!(
+            message("Batch prediction", Duration::from_secs(125), Some(&*error)),
+            (
+                "Batch prediction failed".to_string(),
+                "After 2m5s: row 3: connection refused".to_string()
+            )
+        );
+    }
+}
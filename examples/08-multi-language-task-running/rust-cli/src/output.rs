@@ -1,55 +1,2106 @@
-use crate::api::{HealthResponse, MetricsResponse, Prediction, PredictionsResponse};
+use crate::aggregate::AggregateReport;
+use crate::alias::AliasSummary;
+use crate::api::{Event, HealthResponse, MetricsResponse, Model, Prediction, PredictionsResponse};
+use crate::batch::{BatchReport, RowFailure};
+use crate::bench::BenchReport;
+use crate::compare::Comparison;
+use crate::config::ProfileSummary;
+use crate::doctor::{Diagnosis, Outcome};
+use crate::explain::{self, Explained};
+use crate::grpc::{Transport, TransportComparison};
+use crate::health::TargetHealth;
+use crate::query::Query;
+use crate::repeat::RepeatReport;
+use crate::schema::Schema;
+use crate::snapshot::MetricsDiff;
+use crate::stream::Scored;
+use crate::tasks::{Task, TaskStatus};
+use crate::transfer::ImportReport;
+use crate::watch::MetricsSample;
+use clap::ValueEnum;
 use colored::*;
+use comfy_table::{presets, CellAlignment, ContentArrangement, Row, TableComponent};
+use gaffer_discovery::DiscoveredService;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::io::{self, IsTerminal, Write};
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
 
-pub fn print_health(health: &HealthResponse) {
-    println!("{}", "=== API Health ===".green().bold());
-    println!("{}: {}", "Status".bold(), health.status.green());
-    println!("{}: {}", "Version".bold(), health.version);
-    println!("{}: {}", "Timestamp".bold(), health.timestamp);
+/// How command results are written to stdout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Colored text for humans
+    #[default]
+    Plain,
+    /// Pretty-printed JSON
+    Json,
+    /// YAML
+    Yaml,
+    /// Aligned columns
+    Table,
+    /// Exactly one bare value, the one --field picks, for scripts
+    #[serde(skip)]
+    Value,
+    /// Bare values, one result per line and tab-separated, for `--quiet`
+    #[value(skip)]
+    #[serde(skip)]
+    Quiet,
 }
 
-pub fn print_predictions(response: &PredictionsResponse) {
-    println!("{}", "=== Recent Predictions ===".cyan().bold());
-    println!("{}: {}", "Count".bold(), response.count);
-    println!("{}: {}", "Timestamp".bold(), response.timestamp);
-    println!();
+impl OutputFormat {
+    /// What `--quiet` turns this format into: JSON and YAML are already
+    /// bare, the formats for people lose their decoration.
+    pub fn quieted(self) -> OutputFormat {
+        match self {
+            OutputFormat::Plain | OutputFormat::Table => OutputFormat::Quiet,
+            other => other,
+        }
+    }
+}
+
+/// Turns colored output on or off for the whole run: off with `--no-color`,
+/// a non-empty `NO_COLOR`, or when stdout isn't a terminal (captured by a
+/// gaffer task log, piped to a file); `CLICOLOR_FORCE` turns it back on for
+/// the last case.
+pub fn init_color(no_color: bool) {
+    let enabled = color_enabled(
+        no_color,
+        env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()),
+        env::var_os("CLICOLOR_FORCE").is_some_and(|value| !value.is_empty() && value != "0"),
+        io::stdout().is_terminal(),
+    );
+    colored::control::set_override(enabled);
+}
+
+fn color_enabled(flag: bool, no_color_env: bool, force_env: bool, terminal: bool) -> bool {
+    !flag && !no_color_env && (terminal || force_env)
+}
+
+/// A discovered service as emitted by `-o json` and `-o yaml`.
+#[derive(Debug, Serialize)]
+struct ServiceView<'a> {
+    instance: &'a str,
+    url: Option<String>,
+    host: &'a str,
+    port: u16,
+    addresses: &'a [IpAddr],
+    properties: BTreeMap<&'a String, &'a String>,
+}
+
+impl<'a> From<&'a DiscoveredService> for ServiceView<'a> {
+    fn from(service: &'a DiscoveredService) -> Self {
+        ServiceView {
+            instance: &service.instance,
+            url: service.url(),
+            host: &service.host,
+            port: service.port,
+            addresses: &service.addresses,
+            properties: service.properties.iter().collect(),
+        }
+    }
+}
+
+/// Draw tables with ASCII instead of Unicode box-drawing characters.
+static ASCII_TABLES: AtomicBool = AtomicBool::new(false);
+
+/// Picks the border characters for every table in this run.
+pub fn init_tables(ascii: bool) {
+    ASCII_TABLES.store(ascii, Ordering::Relaxed);
+}
+
+/// `--query`, applied to every structured result in this run.
+static QUERY: OnceLock<Query> = OnceLock::new();
+
+pub fn init_query(query: Option<Query>) {
+    if let Some(query) = query {
+        let _ = QUERY.set(query);
+    }
+}
+
+/// Bordered columns, fitted to the terminal's width when there is one by
+/// cutting long cells short rather than wrapping them.
+struct Table {
+    inner: comfy_table::Table,
+}
+
+impl Table {
+    fn new(headers: &[&str]) -> Self {
+        Table::styled(headers, ASCII_TABLES.load(Ordering::Relaxed))
+    }
+
+    fn styled(headers: &[&str], ascii: bool) -> Self {
+        let mut inner = comfy_table::Table::new();
+        inner
+            .load_preset(if ascii {
+                presets::ASCII_FULL_CONDENSED
+            } else {
+                presets::UTF8_FULL_CONDENSED
+            })
+            .set_content_arrangement(ContentArrangement::Dynamic)
+            .set_truncation_indicator(if ascii { "..." } else { "…" })
+            .set_header(headers.to_vec());
+        if ascii {
+            // The preset leaves the header rule unbroken between columns.
+            inner.set_style(TableComponent::MiddleHeaderIntersections, '+');
+        }
+        // Snapshots mustn't depend on the width of the terminal running them.
+        #[cfg(test)]
+        inner.force_no_tty();
+        Table { inner }
+    }
+
+    /// A two-column `Field`/`Value` table.
+    fn fields(fields: &[(&str, String)]) -> Self {
+        let mut table = Table::new(&["Field", "Value"]);
+        for (name, value) in fields {
+            table.row(vec![name.to_string(), value.clone()]);
+        }
+        table
+    }
+
+    fn row(&mut self, cells: Vec<String>) {
+        let mut row = Row::from(cells);
+        row.max_height(1);
+        self.inner.add_row(row);
+    }
+
+    /// Right-aligns the numbers in `columns`.
+    fn numeric(mut self, columns: &[usize]) -> Self {
+        for &index in columns {
+            if let Some(column) = self.inner.column_mut(index) {
+                column.set_cell_alignment(CellAlignment::Right);
+            }
+        }
+        self
+    }
+
+    fn render(&self) -> String {
+        self.inner.to_string()
+    }
+}
+
+/// Writes `value` as JSON or YAML; `false` for the formats each command
+/// renders itself. With `--query`, writes what the query picks out of the
+/// JSON document instead, whatever the format.
+fn print_structured<T: Serialize + ?Sized>(
+    out: &mut dyn Write,
+    value: &T,
+    format: OutputFormat,
+) -> Result<bool, Box<dyn Error>> {
+    if let Some(query) = QUERY.get() {
+        let picked = query.apply(&serde_json::to_value(value)?);
+        match format {
+            OutputFormat::Json => writeln!(out, "{}", serde_json::to_string_pretty(&picked)?)?,
+            OutputFormat::Yaml => write!(out, "{}", serde_yaml::to_string(&picked)?)?,
+            OutputFormat::Plain | OutputFormat::Table | OutputFormat::Quiet => {
+                writeln!(out, "{}", bare(&picked)?)?
+            }
+            OutputFormat::Value => writeln!(out, "{}", single(&picked)?)?,
+        }
+        return Ok(true);
+    }
+    match format {
+        OutputFormat::Json => writeln!(out, "{}", serde_json::to_string_pretty(value)?)?,
+        OutputFormat::Yaml => write!(out, "{}", serde_yaml::to_string(value)?)?,
+        OutputFormat::Plain | OutputFormat::Table | OutputFormat::Quiet | OutputFormat::Value => {
+            return Ok(false)
+        }
+    }
+    Ok(true)
+}
+
+/// `--query`'s answer for one item of a stream, on one line; `false`
+/// without `--query`.
+fn print_queried_line<T: Serialize>(
+    out: &mut dyn Write,
+    value: &T,
+    format: OutputFormat,
+) -> Result<bool, Box<dyn Error>> {
+    let Some(query) = QUERY.get() else {
+        return Ok(false);
+    };
+    let picked = query.apply(&serde_json::to_value(value)?);
+    match format {
+        OutputFormat::Json => writeln!(out, "{}", serde_json::to_string(&picked)?)?,
+        OutputFormat::Yaml => write!(out, "---\n{}", serde_yaml::to_string(&picked)?)?,
+        OutputFormat::Plain | OutputFormat::Table | OutputFormat::Quiet => {
+            writeln!(out, "{}", bare(&picked)?)?
+        }
+        OutputFormat::Value => writeln!(out, "{}", single(&picked)?)?,
+    }
+    Ok(true)
+}
+
+/// `-o value`'s answer: a string, number or boolean, bare. Anything else is
+/// an error rather than output a script would have to pick apart.
+fn single(value: &serde_json::Value) -> Result<String, String> {
+    match value {
+        serde_json::Value::String(text) => Ok(text.clone()),
+        serde_json::Value::Number(_) | serde_json::Value::Bool(_) => Ok(value.to_string()),
+        serde_json::Value::Null => Err("--field found nothing to print".to_string()),
+        serde_json::Value::Array(_) => Err(
+            "--field picked a list, but -o value prints a single value; add an index or drop the wildcard"
+                .to_string(),
+        ),
+        serde_json::Value::Object(_) => Err(
+            "--field picked an object, but -o value prints a single value; name one of its fields"
+                .to_string(),
+        ),
+    }
+}
+
+/// A string without its quotes, anything else as JSON, like `jq -r`.
+fn bare(value: &serde_json::Value) -> Result<String, serde_json::Error> {
+    match value {
+        serde_json::Value::String(text) => Ok(text.clone()),
+        value if value.is_array() || value.is_object() => serde_json::to_string_pretty(value),
+        value => Ok(value.to_string()),
+    }
+}
+
+/// `--quiet`'s line for a set of metrics: the values in field order.
+fn metrics_line(metrics: &MetricsResponse) -> String {
+    format!(
+        "{}\t{}\t{:.2}\t{}\t{}",
+        metrics.uptime,
+        metrics.requests_total,
+        metrics.memory_usage_mb,
+        metrics.goroutines,
+        metrics.cpu_cores
+    )
+}
+
+pub fn print_health(
+    out: &mut dyn Write,
+    health: &HealthResponse,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, health, format)? {
+        return Ok(());
+    }
+    if format == OutputFormat::Quiet {
+        writeln!(out, "{}", health.status)?;
+        return Ok(());
+    }
+    if format == OutputFormat::Table {
+        let table = Table::fields(&[
+            ("Status", health.status.clone()),
+            ("Version", health.version.clone()),
+            ("Timestamp", health.timestamp.clone()),
+        ]);
+        writeln!(out, "{}", table.render())?;
+        return Ok(());
+    }
+
+    writeln!(out, "{}", "=== API Health ===".green().bold())?;
+    writeln!(out, "{}: {}", "Status".bold(), health.status.green())?;
+    writeln!(out, "{}: {}", "Version".bold(), health.version)?;
+    writeln!(out, "{}: {}", "Timestamp".bold(), health.timestamp)?;
+    Ok(())
+}
+
+/// One row per target from `health` with several URLs.
+pub fn print_targets(
+    out: &mut dyn Write,
+    targets: &[TargetHealth],
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, targets, format)? {
+        return Ok(());
+    }
+    if format == OutputFormat::Quiet {
+        for target in targets {
+            let status = target.status.as_deref().unwrap_or("unreachable");
+            writeln!(out, "{}\t{}", target.url, status)?;
+        }
+        return Ok(());
+    }
+    let mut table = Table::new(&["URL", "Status", "Version", "Latency", "Error"]).numeric(&[3]);
+    for target in targets {
+        table.row(vec![
+            target.url.clone(),
+            target
+                .status
+                .clone()
+                .unwrap_or_else(|| "unreachable".to_string()),
+            target.version.clone().unwrap_or_default(),
+            format!("{} ms", target.latency_ms),
+            target.error.clone().unwrap_or_default(),
+        ]);
+    }
+    if format == OutputFormat::Plain {
+        let healthy = targets.iter().filter(|t| t.is_healthy()).count();
+        let title = format!("=== API Health ({}/{} healthy) ===", healthy, targets.len());
+        if healthy == targets.len() {
+            writeln!(out, "{}", title.green().bold())?;
+        } else {
+            writeln!(out, "{}", title.red().bold())?;
+        }
+    }
+    writeln!(out, "{}", table.render())?;
+    Ok(())
+}
+
+/// `filter` describes the filters applied, for the plain header.
+pub fn print_predictions(
+    out: &mut dyn Write,
+    response: &PredictionsResponse,
+    filter: Option<&str>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, response, format)? {
+        return Ok(());
+    }
+    if format == OutputFormat::Quiet {
+        for pred in &response.predictions {
+            writeln!(out, "{}", pred.label)?;
+        }
+        return Ok(());
+    }
+    if format == OutputFormat::Table {
+        let mut table = Table::new(&["#", "Label", "Confidence"]).numeric(&[0, 2]);
+        for (i, pred) in response.predictions.iter().enumerate() {
+            table.row(vec![
+                (i + 1).to_string(),
+                pred.label.clone(),
+                format!("{:.2}%", pred.confidence * 100.0),
+            ]);
+        }
+        writeln!(out, "{}", table.render())?;
+        return Ok(());
+    }
+
+    writeln!(out, "{}", "=== Recent Predictions ===".cyan().bold())?;
+    if let Some(filter) = filter {
+        writeln!(out, "{}: {}", "Filter".bold(), filter)?;
+    }
+    writeln!(out, "{}: {}", "Count".bold(), response.count)?;
+    writeln!(out, "{}: {}", "Timestamp".bold(), response.timestamp)?;
+    writeln!(out)?;
 
     for (i, pred) in response.predictions.iter().enumerate() {
-        println!(
+        writeln!(
+            out,
             "{}. {} (confidence: {:.2}%)",
             i + 1,
             pred.label.yellow().bold(),
             pred.confidence * 100.0
-        );
+        )?;
     }
+    if let Some(cursor) = &response.next_cursor {
+        writeln!(out)?;
+        writeln!(out, "More predictions: list --cursor {} (or --all)", cursor)?;
+    }
+    Ok(())
 }
 
-pub fn print_prediction(prediction: &Prediction) {
-    println!("{}", "=== Prediction Result ===".magenta().bold());
-    println!("{}: {}", "Label".bold(), prediction.label.yellow());
-    println!(
+pub fn print_prediction(
+    out: &mut dyn Write,
+    prediction: &Prediction,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, prediction, format)? {
+        return Ok(());
+    }
+    if format == OutputFormat::Quiet {
+        writeln!(out, "{}", prediction.label)?;
+        return Ok(());
+    }
+    if format == OutputFormat::Table {
+        let table = Table::fields(&[
+            ("Label", prediction.label.clone()),
+            (
+                "Confidence",
+                format!("{:.2}%", prediction.confidence * 100.0),
+            ),
+        ]);
+        writeln!(out, "{}", table.render())?;
+        return Ok(());
+    }
+
+    writeln!(out, "{}", "=== Prediction Result ===".magenta().bold())?;
+    writeln!(out, "{}: {}", "Label".bold(), prediction.label.yellow())?;
+    writeln!(
+        out,
         "{}: {:.2}%",
         "Confidence".bold(),
         prediction.confidence * 100.0
+    )?;
+    Ok(())
+}
+
+/// Cells in the longest `predict --explain` bar.
+const BAR_WIDTH: usize = 30;
+
+/// The prediction, then a bar per feature for its contribution: green when
+/// it pushed towards the label, red when against.
+pub fn print_explained(
+    out: &mut dyn Write,
+    explained: &Explained,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, explained, format)? {
+        return Ok(());
+    }
+    print_prediction(out, &explained.prediction, format)?;
+    if format == OutputFormat::Quiet {
+        return Ok(());
+    }
+    let contributions = &explained.contributions;
+    let value = |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| v.to_string());
+    writeln!(out)?;
+    if format == OutputFormat::Table {
+        let mut table = Table::new(&["Feature", "Value", "Contribution"]).numeric(&[1, 2]);
+        for c in contributions {
+            table.row(vec![
+                c.feature.clone(),
+                value(c.value),
+                format!("{:+.4}", c.contribution),
+            ]);
+        }
+        writeln!(out, "{}", table.render())?;
+        return Ok(());
+    }
+
+    writeln!(out, "{}", "=== Feature Contributions ===".magenta().bold())?;
+    let max = contributions
+        .iter()
+        .map(|c| c.contribution.abs())
+        .fold(0.0, f64::max);
+    let name_width = contributions
+        .iter()
+        .map(|c| c.feature.chars().count())
+        .max()
+        .unwrap_or(0);
+    let value_width = contributions
+        .iter()
+        .map(|c| value(c.value).len())
+        .max()
+        .unwrap_or(0);
+    for c in contributions {
+        let bar = format!(
+            "{:<width$}",
+            explain::bar(c.contribution, max, BAR_WIDTH),
+            width = BAR_WIDTH
+        );
+        let bar = if c.contribution < 0.0 {
+            bar.red()
+        } else {
+            bar.green()
+        };
+        writeln!(
+            out,
+            "{:<name_width$}  {:>value_width$}  {} {:+.4}",
+            c.feature,
+            value(c.value),
+            bar,
+            c.contribution,
+        )?;
+    }
+    Ok(())
+}
+
+pub fn print_batch(
+    out: &mut dyn Write,
+    report: &BatchReport,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, report, format)? {
+        return Ok(());
+    }
+    if format == OutputFormat::Quiet {
+        for result in &report.results {
+            writeln!(out, "{}", result.label)?;
+        }
+        print_row_failures(&report.failures);
+        return Ok(());
+    }
+
+    let mut table = Table::new(&["Row", "Features", "Label", "Confidence"]).numeric(&[0, 3]);
+    for result in &report.results {
+        let features: Vec<String> = result.features.iter().map(f64::to_string).collect();
+        table.row(vec![
+            result.row.to_string(),
+            features.join(","),
+            result.label.clone(),
+            format!("{:.2}%", result.confidence * 100.0),
+        ]);
+    }
+    let summary = &report.summary;
+    let labels: Vec<String> = summary
+        .labels
+        .iter()
+        .map(|(label, count)| format!("{} {}", label, count))
+        .collect();
+    let confidence = format!(
+        "mean {:.2}%, min {:.2}%, max {:.2}%",
+        summary.mean_confidence * 100.0,
+        summary.min_confidence * 100.0,
+        summary.max_confidence * 100.0
     );
+
+    let mut failures = Table::new(&["Row", "Features", "Error"]).numeric(&[0]);
+    for failure in &report.failures {
+        let features: Vec<String> = failure.features.iter().map(f64::to_string).collect();
+        failures.row(vec![
+            failure.row.to_string(),
+            features.join(","),
+            failure.error.clone(),
+        ]);
+    }
+    let rows = match summary.failed {
+        0 => summary.rows.to_string(),
+        failed => format!(
+            "{} ({} succeeded, {} failed)",
+            summary.rows, summary.succeeded, failed
+        ),
+    };
+
+    if format == OutputFormat::Table {
+        writeln!(out, "{}", table.render())?;
+        writeln!(out)?;
+        if !report.failures.is_empty() {
+            writeln!(out, "{}", failures.render())?;
+            writeln!(out)?;
+        }
+        let summary = Table::fields(&[
+            ("Rows", rows),
+            ("Confidence", confidence),
+            ("Labels", labels.join(", ")),
+        ]);
+        writeln!(out, "{}", summary.render())?;
+        return Ok(());
+    }
+
+    writeln!(out, "{}", "=== Batch Predictions ===".magenta().bold())?;
+    writeln!(out, "{}", table.render())?;
+    writeln!(out)?;
+    if !report.failures.is_empty() {
+        writeln!(out, "{}", "=== Failed Rows ===".red().bold())?;
+        writeln!(out, "{}", failures.render())?;
+        writeln!(out)?;
+    }
+    writeln!(out, "{}: {}", "Rows".bold(), rows)?;
+    writeln!(out, "{}: {}", "Confidence".bold(), confidence)?;
+    writeln!(out, "{}: {}", "Labels".bold(), labels.join(", "))?;
+    Ok(())
+}
+
+/// Each failed row's number and error on stderr, for outputs that only
+/// show the predictions.
+pub fn print_row_failures(failures: &[RowFailure]) {
+    for failure in failures {
+        eprintln!("row {}: {}", failure.row, failure.error);
+    }
 }
 
-pub fn print_metrics(metrics: &MetricsResponse) {
-    println!("{}", "=== API Metrics ===".blue().bold());
-    println!("{}: {}", "Uptime".bold(), metrics.uptime);
-    println!("{}: {}", "Total Requests".bold(), metrics.requests_total);
-    println!(
+pub fn print_metrics(
+    out: &mut dyn Write,
+    metrics: &MetricsResponse,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, metrics, format)? {
+        return Ok(());
+    }
+    if format == OutputFormat::Quiet {
+        writeln!(out, "{}", metrics_line(metrics))?;
+        return Ok(());
+    }
+    if format == OutputFormat::Table {
+        let table = Table::fields(&[
+            ("Uptime", metrics.uptime.clone()),
+            ("Total Requests", metrics.requests_total.to_string()),
+            ("Memory Usage", format!("{:.2} MB", metrics.memory_usage_mb)),
+            ("Goroutines", metrics.goroutines.to_string()),
+            ("CPU Cores", metrics.cpu_cores.to_string()),
+        ]);
+        writeln!(out, "{}", table.render())?;
+        return Ok(());
+    }
+
+    writeln!(out, "{}", "=== API Metrics ===".blue().bold())?;
+    writeln!(out, "{}: {}", "Uptime".bold(), metrics.uptime)?;
+    writeln!(
+        out,
+        "{}: {}",
+        "Total Requests".bold(),
+        metrics.requests_total
+    )?;
+    writeln!(
+        out,
         "{}: {:.2} MB",
         "Memory Usage".bold(),
         metrics.memory_usage_mb
-    );
-    println!("{}: {}", "Goroutines".bold(), metrics.goroutines);
-    println!("{}: {}", "CPU Cores".bold(), metrics.cpu_cores);
+    )?;
+    writeln!(out, "{}: {}", "Goroutines".bold(), metrics.goroutines)?;
+    writeln!(out, "{}: {}", "CPU Cores".bold(), metrics.cpu_cores)?;
+    Ok(())
+}
+
+/// `metrics --all`: one row per service and a total row. Figures a service
+/// doesn't report are shown as `-`.
+pub fn print_aggregate(
+    out: &mut dyn Write,
+    report: &AggregateReport,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, report, format)? {
+        return Ok(());
+    }
+    let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    if format == OutputFormat::Quiet {
+        for service in &report.services {
+            let metrics = service.metrics.clone().unwrap_or_default();
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}",
+                service.name,
+                or_dash(metrics.uptime_secs.map(|secs| format!("{:.0}", secs))),
+                or_dash(metrics.requests.map(|n| n.to_string())),
+                or_dash(metrics.memory_mb.map(|mb| format!("{:.2}", mb)))
+            )?;
+        }
+        return Ok(());
+    }
+
+    let mut table = Table::new(&[
+        "Service",
+        "URL",
+        "Uptime",
+        "Requests",
+        "Memory",
+        "Workers",
+        "CPU Cores",
+        "Latency",
+        "Error",
+    ])
+    .numeric(&[2, 3, 4, 5, 6, 7]);
+    for service in &report.services {
+        let metrics = service.metrics.clone().unwrap_or_default();
+        table.row(vec![
+            service.name.clone(),
+            service.url.clone(),
+            or_dash(metrics.uptime_secs.map(uptime_text)),
+            or_dash(metrics.requests.map(|n| n.to_string())),
+            or_dash(metrics.memory_mb.map(|mb| format!("{:.2} MB", mb))),
+            or_dash(metrics.workers.map(|n| n.to_string())),
+            or_dash(metrics.cpu_cores.map(|n| n.to_string())),
+            format!("{} ms", service.latency_ms),
+            service.error.clone().unwrap_or_default(),
+        ]);
+    }
+    let totals = &report.totals;
+    table.row(vec![
+        "Total".to_string(),
+        String::new(),
+        String::new(),
+        totals.requests.to_string(),
+        format!("{:.2} MB", totals.memory_mb),
+        totals.workers.to_string(),
+        String::new(),
+        String::new(),
+        String::new(),
+    ]);
+    if format == OutputFormat::Plain {
+        let answered = report.services.len() - report.failed();
+        let title = format!(
+            "=== Service Metrics ({}/{} reporting) ===",
+            answered,
+            report.services.len()
+        );
+        writeln!(out, "{}", title.blue().bold())?;
+    }
+    writeln!(out, "{}", table.render())?;
+    Ok(())
+}
+
+/// Whole seconds as `1h2m3s`, like Go prints durations.
+fn uptime_text(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    let (hours, minutes, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+    match (hours, minutes) {
+        (0, 0) => format!("{}s", secs),
+        (0, _) => format!("{}m{}s", minutes, secs),
+        _ => format!("{}h{}m{}s", hours, minutes, secs),
+    }
+}
+
+pub fn print_schema(
+    out: &mut dyn Write,
+    schema: &Schema,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, schema, format)? {
+        return Ok(());
+    }
+    if format == OutputFormat::Quiet {
+        writeln!(out, "{}", schema.feature_count)?;
+        return Ok(());
+    }
+    let mut table = Table::new(&["#", "Feature"]).numeric(&[0]);
+    for (i, name) in schema.feature_names.iter().enumerate() {
+        table.row(vec![(i + 1).to_string(), name.clone()]);
+    }
+    if format == OutputFormat::Plain {
+        writeln!(out, "{}", "=== Feature Schema ===".cyan().bold())?;
+        writeln!(out, "{}: {}", "Features".bold(), schema.feature_count)?;
+        if schema.feature_names.is_empty() {
+            return Ok(());
+        }
+        writeln!(out)?;
+    }
+    writeln!(out, "{}", table.render())?;
+    Ok(())
+}
+
+pub fn print_models(
+    out: &mut dyn Write,
+    models: &[Model],
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, models, format)? {
+        return Ok(());
+    }
+    if format == OutputFormat::Quiet {
+        for model in models {
+            writeln!(out, "{}", model.id)?;
+        }
+        return Ok(());
+    }
+    let or_dash = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+    if format == OutputFormat::Table {
+        let mut table = Table::new(&["Model", "Name", "Version", "Created", "Active"]);
+        for model in models {
+            let active = if model.active { "yes" } else { "" };
+            table.row(vec![
+                model.id.clone(),
+                or_dash(&model.name),
+                or_dash(&model.version),
+                or_dash(&model.created_at),
+                active.to_string(),
+            ]);
+        }
+        writeln!(out, "{}", table.render())?;
+        return Ok(());
+    }
+
+    if models.is_empty() {
+        writeln!(out, "No models")?;
+        return Ok(());
+    }
+    for model in models {
+        let mark = if model.active { "*" } else { " " };
+        writeln!(
+            out,
+            "{} {} {} {}",
+            mark.green().bold(),
+            model.id.yellow().bold(),
+            or_dash(&model.version),
+            or_dash(&model.created_at).dimmed()
+        )?;
+    }
+    Ok(())
+}
+
+/// One model, with whatever else the backend reports about it.
+pub fn print_model(
+    out: &mut dyn Write,
+    model: &Model,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, model, format)? {
+        return Ok(());
+    }
+    if format == OutputFormat::Quiet {
+        writeln!(out, "{}\t{}", model.id, model.active)?;
+        return Ok(());
+    }
+    let mut table = Table::fields(&[
+        ("ID", model.id.clone()),
+        ("Active", model.active.to_string()),
+    ]);
+    for (name, value) in [
+        ("Name", &model.name),
+        ("Version", &model.version),
+        ("Created", &model.created_at),
+    ] {
+        if let Some(value) = value {
+            table.row(vec![name.to_string(), value.clone()]);
+        }
+    }
+    for (name, value) in &model.details {
+        let value = match value {
+            serde_json::Value::String(text) => text.clone(),
+            other => other.to_string(),
+        };
+        table.row(vec![name.clone(), value]);
+    }
+    if format == OutputFormat::Plain {
+        writeln!(out, "{}", "=== Model ===".cyan().bold())?;
+    }
+    writeln!(out, "{}", table.render())?;
+    Ok(())
+}
+
+/// A snapshot's metrics, the current ones and the change between them.
+pub fn print_metrics_diff(
+    out: &mut dyn Write,
+    diff: &MetricsDiff,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, diff, format)? {
+        return Ok(());
+    }
+    let (before, after, delta) = (&diff.before, &diff.after, &diff.delta);
+    if format == OutputFormat::Quiet {
+        writeln!(out, "{}\t{:.2}", delta.requests, delta.memory_growth_mb)?;
+        return Ok(());
+    }
+    let mut table = Table::new(&["Field", "Snapshot", "Now", "Change"]).numeric(&[1, 2, 3]);
+    table.row(vec![
+        "Uptime".to_string(),
+        before.uptime.clone(),
+        after.uptime.clone(),
+        String::new(),
+    ]);
+    table.row(vec![
+        "Total Requests".to_string(),
+        before.requests_total.to_string(),
+        after.requests_total.to_string(),
+        format!("{:+}", delta.requests),
+    ]);
+    table.row(vec![
+        "Memory Usage".to_string(),
+        format!("{:.2} MB", before.memory_usage_mb),
+        format!("{:.2} MB", after.memory_usage_mb),
+        format!("{:+.2} MB", delta.memory_growth_mb),
+    ]);
+    table.row(vec![
+        "Goroutines".to_string(),
+        before.goroutines.to_string(),
+        after.goroutines.to_string(),
+        format!("{:+}", after.goroutines - before.goroutines),
+    ]);
+    if format == OutputFormat::Plain {
+        writeln!(
+            out,
+            "{}",
+            "=== API Metrics Since Snapshot ===".blue().bold()
+        )?;
+    }
+    writeln!(out, "{}", table.render())?;
+    if delta.interval_secs > 0.0 {
+        writeln!(out)?;
+        writeln!(
+            out,
+            "{}: {:.2} over {:.0}s",
+            "Requests/sec".bold(),
+            delta.requests_per_sec,
+            delta.interval_secs
+        )?;
+    }
+    Ok(())
+}
+
+/// Both deployments' values side by side, differing ones highlighted.
+pub fn print_comparison(
+    out: &mut dyn Write,
+    comparison: &Comparison,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, comparison, format)? {
+        return Ok(());
+    }
+    let value = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+    if format == OutputFormat::Quiet {
+        // Only what differs, so an empty result means the two agree.
+        for field in comparison.fields.iter().filter(|field| field.differs) {
+            writeln!(
+                out,
+                "{}\t{}\t{}",
+                field.name,
+                value(&field.a),
+                value(&field.b)
+            )?;
+        }
+    } else if format == OutputFormat::Table {
+        let mut table = Table::new(&["Field", "A", "B", "Diff"]);
+        for field in &comparison.fields {
+            let marker = if field.differs { "≠" } else { "" };
+            table.row(vec![
+                field.name.to_string(),
+                value(&field.a),
+                value(&field.b),
+                marker.to_string(),
+            ]);
+        }
+        writeln!(out, "{}", table.render())?;
+    } else {
+        writeln!(out, "{}", "=== Compare ===".magenta().bold())?;
+        writeln!(out, "{}: {}", "A".bold(), comparison.a.url)?;
+        writeln!(out, "{}: {}", "B".bold(), comparison.b.url)?;
+        writeln!(out)?;
+        let width = comparison
+            .fields
+            .iter()
+            .map(|field| field.name.len())
+            .max()
+            .unwrap_or(0);
+        for field in &comparison.fields {
+            let line = format!(
+                "{:<width$}  {} → {}",
+                field.name,
+                value(&field.a),
+                value(&field.b),
+                width = width
+            );
+            if field.differs {
+                writeln!(out, "{}", line.yellow())?;
+            } else {
+                writeln!(out, "{}", line.dimmed())?;
+            }
+        }
+    }
+    for (side, label) in [(&comparison.a, "A"), (&comparison.b, "B")] {
+        for error in &side.errors {
+            eprintln!(
+                "{} {}",
+                format!("{} {}:", label, side.url).red().bold(),
+                error
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn print_bench(
+    out: &mut dyn Write,
+    report: &BenchReport,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, report, format)? {
+        return Ok(());
+    }
+    let latency = &report.latency_ms;
+    if format == OutputFormat::Quiet {
+        writeln!(
+            out,
+            "{:.1}\t{:.2}\t{:.2}\t{:.2}",
+            report.requests_per_sec, latency.p50, latency.p95, latency.p99
+        )?;
+    } else {
+        let table = Table::fields(&[
+            ("Endpoint", report.endpoint.clone()),
+            (
+                "Requests",
+                format!("{} ({} at a time)", report.requests, report.concurrency),
+            ),
+            ("Succeeded", report.succeeded.to_string()),
+            ("Failed", report.failed.to_string()),
+            ("Elapsed", format!("{:.2}s", report.elapsed_secs)),
+            (
+                "Throughput",
+                format!("{:.1} req/s", report.requests_per_sec),
+            ),
+            (
+                "Connections",
+                if report.keep_alive {
+                    "reused"
+                } else {
+                    "new per request"
+                }
+                .to_string(),
+            ),
+            ("Latency min", format!("{:.2} ms", latency.min)),
+            ("Latency mean", format!("{:.2} ms", latency.mean)),
+            ("Latency p50", format!("{:.2} ms", latency.p50)),
+            ("Latency p95", format!("{:.2} ms", latency.p95)),
+            ("Latency p99", format!("{:.2} ms", latency.p99)),
+            ("Latency max", format!("{:.2} ms", latency.max)),
+        ]);
+        if format == OutputFormat::Plain {
+            writeln!(out, "{}", "=== Benchmark ===".blue().bold())?;
+        }
+        writeln!(out, "{}", table.render())?;
+    }
+    for error in &report.errors {
+        eprintln!("{} {}", "Error:".red().bold(), error);
+    }
+    Ok(())
+}
+
+/// `predict --repeat`: response times, the labels that came back and how
+/// far the confidence moved, with a warning when the answers varied.
+pub fn print_repeat(
+    out: &mut dyn Write,
+    report: &RepeatReport,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, report, format)? {
+        return Ok(());
+    }
+    let latency = &report.latency_ms;
+    let confidence = &report.confidence;
+    if format == OutputFormat::Quiet {
+        writeln!(
+            out,
+            "{}\t{:.2}\t{:.2}\t{}",
+            report.succeeded, latency.p50, latency.p95, report.varied
+        )?;
+    } else {
+        let labels: Vec<String> = report
+            .labels
+            .iter()
+            .map(|(label, count)| format!("{} ×{}", label, count))
+            .collect();
+        let table = Table::fields(&[
+            (
+                "Requests",
+                format!("{} ({} failed)", report.requests, report.failed),
+            ),
+            ("Labels", labels.join(", ")),
+            (
+                "Confidence",
+                format!(
+                    "{:.4} to {:.4} (mean {:.4}, stddev {:.4})",
+                    confidence.min, confidence.max, confidence.mean, confidence.stddev
+                ),
+            ),
+            ("Latency min", format!("{:.2} ms", latency.min)),
+            ("Latency mean", format!("{:.2} ms", latency.mean)),
+            ("Latency p50", format!("{:.2} ms", latency.p50)),
+            ("Latency p95", format!("{:.2} ms", latency.p95)),
+            ("Latency max", format!("{:.2} ms", latency.max)),
+        ]);
+        if format == OutputFormat::Plain {
+            writeln!(out, "{}", "=== Repeated Prediction ===".blue().bold())?;
+        }
+        writeln!(out, "{}", table.render())?;
+        if report.varied {
+            eprintln!(
+                "{} the answers varied for the same features",
+                "Warning:".yellow().bold()
+            );
+        }
+    }
+    for error in &report.errors {
+        eprintln!("{} {}", "Error:".red().bold(), error);
+    }
+    Ok(())
+}
+
+pub fn print_transport_comparison(
+    out: &mut dyn Write,
+    comparison: &TransportComparison,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, comparison, format)? {
+        return Ok(());
+    }
+    let sides = [("REST", &comparison.rest), ("gRPC", &comparison.grpc)];
+    if format == OutputFormat::Quiet {
+        writeln!(
+            out,
+            "{:.2}\t{:.2}",
+            comparison.rest.bench.latency_ms.p50, comparison.grpc.bench.latency_ms.p50
+        )?;
+    } else {
+        let row = |name: &str, value: &dyn Fn(&Transport) -> String| {
+            let mut row = vec![name.to_string()];
+            row.extend(sides.iter().map(|(_, side)| value(side)));
+            row
+        };
+        let mut table = Table::new(&["", "REST", "gRPC"]);
+        let label = |side: &Transport| side.prediction.as_ref().map(|p| p.label.clone());
+        table.row(row("Label", &|side| {
+            label(side).unwrap_or_else(|| "-".to_string())
+        }));
+        table.row(row("Confidence", &|side| {
+            side.prediction
+                .as_ref()
+                .map_or("-".to_string(), |p| format!("{:.2}%", p.confidence * 100.0))
+        }));
+        table.row(row("Succeeded", &|side| {
+            format!("{}/{}", side.bench.succeeded, side.bench.requests)
+        }));
+        table.row(row("Throughput", &|side| {
+            format!("{:.1} req/s", side.bench.requests_per_sec)
+        }));
+        table.row(row("Latency p50", &|side| {
+            format!("{:.2} ms", side.bench.latency_ms.p50)
+        }));
+        table.row(row("Latency p95", &|side| {
+            format!("{:.2} ms", side.bench.latency_ms.p95)
+        }));
+        table.row(row("Latency p99", &|side| {
+            format!("{:.2} ms", side.bench.latency_ms.p99)
+        }));
+        if format == OutputFormat::Plain {
+            writeln!(out, "{}", "=== REST vs gRPC ===".magenta().bold())?;
+        }
+        writeln!(out, "{}", table.render())?;
+        if format == OutputFormat::Plain
+            && comparison.rest.prediction.is_some()
+            && comparison.grpc.prediction.is_some()
+            && !comparison.same_label
+        {
+            writeln!(out, "{}", "The transports disagree on the label".yellow())?;
+        }
+    }
+    for (name, side) in sides {
+        let repeated = |error: &&String| side.error.as_ref() == Some(*error);
+        for error in side
+            .error
+            .iter()
+            .chain(side.bench.errors.iter().filter(|e| !repeated(e)))
+        {
+            eprintln!("{} {}", format!("{}:", name).red().bold(), error);
+        }
+    }
+    Ok(())
+}
+
+/// One event from `subscribe`: a JSON line, a YAML document, or a
+/// `#seq type: fields` line.
+/// Predictions as `list --follow` finds them. JSON is written one line per
+/// prediction and YAML as one document per prediction, so either can be
+/// streamed.
+pub fn print_followed(
+    out: &mut dyn Write,
+    predictions: &[Prediction],
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    for prediction in predictions {
+        if print_queried_line(out, prediction, format)? {
+            continue;
+        }
+        match format {
+            OutputFormat::Json => writeln!(out, "{}", serde_json::to_string(prediction)?)?,
+            OutputFormat::Yaml => write!(out, "---\n{}", serde_yaml::to_string(prediction)?)?,
+            OutputFormat::Quiet | OutputFormat::Value => writeln!(out, "{}", prediction.label)?,
+            OutputFormat::Plain | OutputFormat::Table => writeln!(
+                out,
+                "{} {} (confidence: {:.2}%)",
+                prediction.timestamp.as_deref().unwrap_or("-").dimmed(),
+                prediction.label.yellow().bold(),
+                prediction.confidence * 100.0
+            )?,
+        }
+    }
+    Ok(())
+}
+
+/// One `predict --stream` result as a JSON line, whatever `-o` says, so the
+/// CLI can sit in a pipeline between two JSON-lines programs.
+pub fn print_scored(out: &mut dyn Write, scored: &Scored) -> Result<(), Box<dyn Error>> {
+    if !print_queried_line(out, scored, OutputFormat::Json)? {
+        writeln!(out, "{}", serde_json::to_string(scored)?)?;
+    }
+    Ok(())
+}
+
+pub fn print_event(
+    out: &mut dyn Write,
+    event: &Event,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_queried_line(out, event, format)? {
+        return Ok(());
+    }
+    match format {
+        OutputFormat::Json => writeln!(out, "{}", serde_json::to_string(event)?)?,
+        OutputFormat::Yaml => write!(out, "---\n{}", serde_yaml::to_string(event)?)?,
+        OutputFormat::Plain | OutputFormat::Table | OutputFormat::Quiet | OutputFormat::Value => {
+            writeln!(
+                out,
+                "{} {}: {}",
+                format!("#{}", event.seq).dimmed(),
+                event.kind.cyan().bold(),
+                serde_json::to_string(&event.fields)?
+            )?
+        }
+    }
+    Ok(())
+}
+
+/// A `metrics --watch` sample. JSON is written one line per sample and YAML
+/// as one document per sample, so either can be streamed.
+pub fn print_metrics_sample(
+    out: &mut dyn Write,
+    sample: &MetricsSample,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_queried_line(out, sample, format)? {
+        return Ok(());
+    }
+    match format {
+        OutputFormat::Json => {
+            writeln!(out, "{}", serde_json::to_string(sample)?)?;
+            return Ok(());
+        }
+        OutputFormat::Yaml => {
+            write!(out, "---\n{}", serde_yaml::to_string(sample)?)?;
+            return Ok(());
+        }
+        OutputFormat::Quiet | OutputFormat::Value => {
+            let rate = sample.delta.as_ref().map_or("-".to_string(), |delta| {
+                format!("{:.2}", delta.requests_per_sec)
+            });
+            writeln!(out, "{}\t{}", metrics_line(&sample.metrics), rate)?;
+            return Ok(());
+        }
+        OutputFormat::Plain | OutputFormat::Table => {}
+    }
+    print_metrics(out, &sample.metrics, format)?;
+
+    let (rate, growth) = match &sample.delta {
+        Some(delta) => (
+            format!(
+                "{:.2} ({:+} requests)",
+                delta.requests_per_sec, delta.requests
+            ),
+            format!("{:+.2} MB", delta.memory_growth_mb),
+        ),
+        None => ("-".to_string(), "-".to_string()),
+    };
+    if format == OutputFormat::Table {
+        writeln!(out)?;
+        let table = Table::fields(&[("Requests/sec", rate), ("Memory Growth", growth)]);
+        writeln!(out, "{}", table.render())?;
+        return Ok(());
+    }
+
+    writeln!(out, "{}: {}", "Requests/sec".bold(), rate)?;
+    writeln!(out, "{}: {}", "Memory Growth".bold(), growth)?;
+    Ok(())
+}
+
+pub fn print_services(
+    out: &mut dyn Write,
+    services: &[DiscoveredService],
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    let views: Vec<ServiceView> = services.iter().map(ServiceView::from).collect();
+    if print_structured(out, &views, format)? {
+        return Ok(());
+    }
+    if format == OutputFormat::Quiet {
+        for view in &views {
+            writeln!(out, "{}", view.url.as_deref().unwrap_or(view.host))?;
+        }
+        return Ok(());
+    }
+    if format == OutputFormat::Table {
+        let mut table = Table::new(&["Instance", "URL", "Properties"]);
+        for view in &views {
+            let properties: Vec<String> = view
+                .properties
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect();
+            table.row(vec![
+                view.instance.to_string(),
+                view.url.clone().unwrap_or_else(|| view.host.to_string()),
+                properties.join(" "),
+            ]);
+        }
+        writeln!(out, "{}", table.render())?;
+        return Ok(());
+    }
+
+    writeln!(out, "{}", "=== Discovered APIs ===".green().bold())?;
+    if services.is_empty() {
+        writeln!(
+            out,
+            "No services answered on {}",
+            gaffer_discovery::SERVICE_TYPE
+        )?;
+        return Ok(());
+    }
+
+    for service in services {
+        writeln!(
+            out,
+            "{} {}",
+            service.instance.yellow().bold(),
+            service.url().unwrap_or_else(|| service.host.clone())
+        )?;
+        let mut properties: Vec<_> = service.properties.iter().collect();
+        properties.sort();
+        for (key, value) in properties {
+            writeln!(out, "   {}: {}", key.bold(), value)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn print_aliases(
+    out: &mut dyn Write,
+    aliases: &[AliasSummary],
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, aliases, format)? {
+        return Ok(());
+    }
+    if format == OutputFormat::Quiet {
+        for alias in aliases {
+            writeln!(out, "{}", alias.name)?;
+        }
+        return Ok(());
+    }
+    if format == OutputFormat::Table {
+        let mut table = Table::new(&["Alias", "Command", "Shadowed"]);
+        for alias in aliases {
+            let shadowed = if alias.shadowed { "yes" } else { "" };
+            table.row(vec![
+                alias.name.clone(),
+                alias.command.join(" "),
+                shadowed.to_string(),
+            ]);
+        }
+        writeln!(out, "{}", table.render())?;
+        return Ok(());
+    }
+
+    if aliases.is_empty() {
+        writeln!(out, "No aliases; add an [aliases] table to the config file")?;
+        return Ok(());
+    }
+    let width = aliases
+        .iter()
+        .map(|alias| alias.name.len())
+        .max()
+        .unwrap_or(0);
+    for alias in aliases {
+        let line = format!(
+            "{:<width$}  {}",
+            alias.name,
+            alias.command.join(" "),
+            width = width
+        );
+        if alias.shadowed {
+            writeln!(
+                out,
+                "{}  {}",
+                line.dimmed(),
+                "(hidden by the built-in command)".yellow()
+            )?;
+        } else {
+            writeln!(out, "{}", line)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn print_profiles(
+    out: &mut dyn Write,
+    profiles: &[ProfileSummary],
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, profiles, format)? {
+        return Ok(());
+    }
+    if format == OutputFormat::Quiet {
+        for profile in profiles {
+            writeln!(out, "{}", profile.name)?;
+        }
+        return Ok(());
+    }
+    let url = |profile: &ProfileSummary| profile.url.clone().unwrap_or_else(|| "-".to_string());
+    if format == OutputFormat::Table {
+        let mut table = Table::new(&["Profile", "URL", "Default"]);
+        for profile in profiles {
+            let default = if profile.default { "yes" } else { "" };
+            table.row(vec![
+                profile.name.clone(),
+                url(profile),
+                default.to_string(),
+            ]);
+        }
+        writeln!(out, "{}", table.render())?;
+        return Ok(());
+    }
+
+    if profiles.is_empty() {
+        writeln!(
+            out,
+            "No profiles; add a [profiles.<name>] table to the config file"
+        )?;
+        return Ok(());
+    }
+    for profile in profiles {
+        let mark = if profile.default { "*" } else { " " };
+        writeln!(
+            out,
+            "{} {} {}",
+            mark.green().bold(),
+            profile.name.yellow().bold(),
+            url(profile)
+        )?;
+    }
+    Ok(())
+}
+
+/// The tasks in dependency order, each with what it waited for.
+pub fn print_tasks(
+    out: &mut dyn Write,
+    status: &TaskStatus,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, status, format)? {
+        return Ok(());
+    }
+    if format == OutputFormat::Quiet {
+        for task in &status.tasks {
+            writeln!(out, "{}\t{}", task.name, task.status)?;
+        }
+        return Ok(());
+    }
+    let duration = |task: &Task| {
+        task.duration_ms.map_or_else(
+            || "-".to_string(),
+            |ms| format!("{:.1}s", ms as f64 / 1000.0),
+        )
+    };
+    if format == OutputFormat::Table {
+        let mut table = Table::new(&["Task", "Status", "Duration", "Depends on"]).numeric(&[2]);
+        for task in &status.tasks {
+            table.row(vec![
+                task.name.clone(),
+                task.status.clone(),
+                duration(task),
+                task.deps.join(", "),
+            ]);
+        }
+        writeln!(out, "{}", table.render())?;
+        return Ok(());
+    }
+
+    writeln!(out, "{}", "=== Gaffer Tasks ===".cyan().bold())?;
+    let width = status
+        .tasks
+        .iter()
+        .map(|task| task.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for task in &status.tasks {
+        *counts.entry(task.status.as_str()).or_default() += 1;
+        let mark = match task.status.as_str() {
+            "succeeded" => "✓".green().bold(),
+            "cached" => "✓".dimmed(),
+            "failed" => "✗".red().bold(),
+            "running" => "▶".yellow().bold(),
+            _ => "-".dimmed(),
+        };
+        let mut line = format!(
+            "{} {:<width$}  {:<9} {:>7}",
+            mark,
+            task.name,
+            task.status,
+            duration(task)
+        );
+        if !task.deps.is_empty() {
+            line += &format!("  {}", format!("← {}", task.deps.join(", ")).dimmed());
+        }
+        writeln!(out, "{}", line)?;
+    }
+    let counts: Vec<String> = counts
+        .iter()
+        .map(|(status, n)| format!("{} {}", n, status))
+        .collect();
+    writeln!(out)?;
+    writeln!(out, "{} tasks: {}", status.tasks.len(), counts.join(", "))?;
+    Ok(())
+}
+
+/// `doctor`'s checklist, with a hint under each failed step.
+pub fn print_diagnosis(
+    out: &mut dyn Write,
+    diagnosis: &Diagnosis,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, diagnosis, format)? {
+        return Ok(());
+    }
+    let outcome = |outcome: Outcome| match outcome {
+        Outcome::Pass => "pass",
+        Outcome::Fail => "fail",
+        Outcome::Skip => "skip",
+    };
+    if format == OutputFormat::Quiet {
+        for check in &diagnosis.checks {
+            writeln!(out, "{}\t{}", check.name, outcome(check.outcome))?;
+        }
+        return Ok(());
+    }
+    if format == OutputFormat::Table {
+        let mut table = Table::new(&["Check", "Result", "Detail", "Hint"]);
+        for check in &diagnosis.checks {
+            table.row(vec![
+                check.name.to_string(),
+                outcome(check.outcome).to_string(),
+                check.detail.clone(),
+                check.hint.clone().unwrap_or_default(),
+            ]);
+        }
+        writeln!(out, "{}", table.render())?;
+        return Ok(());
+    }
+
+    writeln!(out, "{}", "=== Doctor ===".cyan().bold())?;
+    writeln!(out, "{}: {}", "URL".bold(), diagnosis.url)?;
+    writeln!(out)?;
+    for check in &diagnosis.checks {
+        let mark = match check.outcome {
+            Outcome::Pass => "✓".green().bold(),
+            Outcome::Fail => "✗".red().bold(),
+            Outcome::Skip => "-".dimmed(),
+        };
+        let line = format!("{:<7} {}", check.name, check.detail);
+        if check.outcome == Outcome::Skip {
+            writeln!(out, "{} {}", mark, line.dimmed())?;
+        } else {
+            writeln!(out, "{} {}", mark, line)?;
+        }
+        if let Some(hint) = &check.hint {
+            writeln!(out, "          {} {}", "hint:".yellow(), hint)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn print_import(
+    out: &mut dyn Write,
+    report: &ImportReport,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    if print_structured(out, report, format)? {
+        return Ok(());
+    }
+    if format == OutputFormat::Quiet {
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}",
+            report.created, report.overwritten, report.unchanged, report.skipped
+        )?;
+        return Ok(());
+    }
+    let (title, new, replaced) = if report.dry_run {
+        (
+            "=== Import (dry run) ===",
+            "Would import",
+            "Would overwrite",
+        )
+    } else {
+        ("=== Import ===", "Imported", "Overwritten")
+    };
+    let table = Table::fields(&[
+        ("From", report.source.clone()),
+        ("To", report.target.clone()),
+        ("Records", report.records.to_string()),
+        (new, report.created.to_string()),
+        ("Already there", report.unchanged.to_string()),
+        (replaced, report.overwritten.to_string()),
+        ("Conflicts kept", report.skipped.to_string()),
+    ]);
+    if format == OutputFormat::Plain {
+        writeln!(out, "{}", title.blue().bold())?;
+    }
+    writeln!(out, "{}", table.render())?;
+    if !report.conflicts.is_empty() {
+        let mut conflicts = Table::new(&["Timestamp", "API", "Dump"]);
+        for conflict in &report.conflicts {
+            let describe = |p: &Prediction| format!("{} ({:.4})", p.label, p.confidence);
+            conflicts.row(vec![
+                conflict.incoming.timestamp.clone().unwrap_or_default(),
+                describe(&conflict.existing),
+                describe(&conflict.incoming),
+            ]);
+        }
+        writeln!(out)?;
+        writeln!(out, "{}", conflicts.render())?;
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::aggregate::{NormalizedMetrics, ServiceMetrics, Totals};
+    use crate::batch::{RowResult, Summary};
+    use crate::bench::Latency;
+    use crate::compare::{Field, Side};
+    use crate::doctor::Check;
+    use crate::explain::Contribution;
+    use crate::repeat::Spread;
+    use crate::transfer::Conflict;
+    use crate::watch::MetricsDelta;
+    use std::sync::Mutex;
+
+    /// Color is switched for the whole process, so renders take turns.
+    static COLOR: Mutex<()> = Mutex::new(());
+
+    /// What `print` writes in every format, plain both with color (escape
+    /// codes shown as `␛`) and without, for one snapshot per renderer.
+    fn render_all(
+        print: impl Fn(&mut dyn Write, OutputFormat) -> Result<(), Box<dyn Error>>,
+    ) -> String {
+        let _turn = COLOR.lock().unwrap_or_else(|e| e.into_inner());
+        let mut rendered = String::new();
+        for (name, format, color) in [
+            ("plain", OutputFormat::Plain, true),
+            ("plain, no color", OutputFormat::Plain, false),
+            ("table", OutputFormat::Table, false),
+            ("quiet", OutputFormat::Quiet, false),
+            ("json", OutputFormat::Json, false),
+            ("yaml", OutputFormat::Yaml, false),
+        ] {
+            colored::control::set_override(color);
+            let mut out = Vec::new();
+            print(&mut out, format).unwrap();
+            let text = String::from_utf8(out).unwrap().replace('\u{1b}', "␛");
+            rendered += &format!("--- {} ---\n{}", name, text);
+        }
+        colored::control::unset_override();
+        rendered
+    }
+
+    fn health() -> HealthResponse {
+        serde_json::from_str(
+            r#"{"status":"healthy","timestamp":"2024-01-01T12:00:00Z","version":"1.0.0"}"#,
+        )
+        .unwrap()
+    }
+
+    fn metrics(requests_total: i64, memory_usage_mb: f64) -> MetricsResponse {
+        MetricsResponse {
+            uptime: "1h2m3s".to_string(),
+            requests_total,
+            memory_usage_mb,
+            goroutines: 8,
+            cpu_cores: 4,
+        }
+    }
+
+    fn prediction(label: &str, confidence: f64) -> Prediction {
+        Prediction {
+            label: label.to_string(),
+            confidence,
+            timestamp: Some("2024-01-01T12:00:00Z".to_string()),
+        }
+    }
+
+    fn latency() -> Latency {
+        Latency {
+            min: 0.41,
+            mean: 0.87,
+            p50: 0.62,
+            p95: 1.9,
+            p99: 3.25,
+            max: 4.02,
+        }
+    }
+
+    fn bench(endpoint: &str) -> BenchReport {
+        BenchReport {
+            endpoint: endpoint.to_string(),
+            requests: 100,
+            concurrency: 4,
+            succeeded: 99,
+            failed: 1,
+            elapsed_secs: 0.5,
+            requests_per_sec: 200.0,
+            keep_alive: true,
+            latency_ms: latency(),
+            errors: vec!["connection reset".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_health_renderers_match_snapshots() {
+        insta::assert_snapshot!(
+            "health",
+            render_all(|out, format| print_health(out, &health(), format))
+        );
+        let targets = [
+            TargetHealth {
+                url: "http://a:8080".to_string(),
+                status: Some("healthy".to_string()),
+                version: Some("1.0.0".to_string()),
+                latency_ms: 3,
+                error: None,
+            },
+            TargetHealth {
+                url: "http://b:8080".to_string(),
+                status: None,
+                version: None,
+                latency_ms: 5001,
+                error: Some("timed out".to_string()),
+            },
+        ];
+        insta::assert_snapshot!(
+            "targets",
+            render_all(|out, format| print_targets(out, &targets, format))
+        );
+        let diagnosis = Diagnosis {
+            url: "https://api:8443".to_string(),
+            checks: vec![
+                Check {
+                    name: "DNS",
+                    outcome: Outcome::Pass,
+                    detail: "api → 10.0.0.7".to_string(),
+                    hint: None,
+                },
+                Check {
+                    name: "TCP",
+                    outcome: Outcome::Fail,
+                    detail: "connection refused".to_string(),
+                    hint: Some("is the API running?".to_string()),
+                },
+                Check {
+                    name: "TLS",
+                    outcome: Outcome::Skip,
+                    detail: "an earlier check failed".to_string(),
+                    hint: None,
+                },
+            ],
+        };
+        insta::assert_snapshot!(
+            "diagnosis",
+            render_all(|out, format| print_diagnosis(out, &diagnosis, format))
+        );
+        let services = [DiscoveredService {
+            instance: "go-api".to_string(),
+            host: "box.local.".to_string(),
+            addresses: vec!["192.168.1.20".parse().unwrap()],
+            port: 8080,
+            properties: [("version".to_string(), "1.0.0".to_string())].into(),
+        }];
+        insta::assert_snapshot!(
+            "services",
+            render_all(|out, format| print_services(out, &services, format))
+        );
+    }
+
+    #[test]
+    fn test_prediction_renderers_match_snapshots() {
+        let response = PredictionsResponse {
+            predictions: vec![prediction("cats", 0.95), prediction("dogs", 0.8712)],
+            count: 2,
+            timestamp: "2024-01-01T12:00:00Z".to_string(),
+            next_cursor: Some("2".to_string()),
+        };
+        insta::assert_snapshot!(
+            "predictions",
+            render_all(|out, format| print_predictions(out, &response, Some("label=cats"), format))
+        );
+        insta::assert_snapshot!(
+            "followed",
+            render_all(|out, format| print_followed(out, &response.predictions, format))
+        );
+        insta::assert_snapshot!(
+            "prediction",
+            render_all(|out, format| print_prediction(out, &prediction("cats", 0.95), format))
+        );
+        let explained = Explained {
+            prediction: prediction("cats", 0.95),
+            contributions: vec![
+                Contribution {
+                    feature: "petal".to_string(),
+                    value: Some(1.4),
+                    contribution: 0.4,
+                },
+                Contribution {
+                    feature: "sepal".to_string(),
+                    value: None,
+                    contribution: -0.1,
+                },
+            ],
+        };
+        insta::assert_snapshot!(
+            "explained",
+            render_all(|out, format| print_explained(out, &explained, format))
+        );
+        let report = BatchReport {
+            results: vec![RowResult {
+                row: 1,
+                features: vec![0.1, 0.2],
+                label: "cats".to_string(),
+                confidence: 0.95,
+            }],
+            failures: vec![RowFailure {
+                row: 2,
+                features: vec![9.0],
+                error: "400 Bad Request".to_string(),
+            }],
+            summary: Summary {
+                rows: 2,
+                succeeded: 1,
+                failed: 1,
+                mean_confidence: 0.95,
+                min_confidence: 0.95,
+                max_confidence: 0.95,
+                labels: [("cats".to_string(), 1)].into(),
+            },
+        };
+        insta::assert_snapshot!(
+            "batch",
+            render_all(|out, format| print_batch(out, &report, format))
+        );
+        let scored = [
+            Scored::Result(RowResult {
+                row: 1,
+                features: vec![0.1],
+                label: "cats".to_string(),
+                confidence: 0.95,
+            }),
+            Scored::Invalid {
+                row: 2,
+                error: "not a number".to_string(),
+            },
+        ];
+        insta::assert_snapshot!(
+            "scored",
+            render_all(|out, _| scored.iter().try_for_each(|s| print_scored(out, s)))
+        );
+        let repeat = RepeatReport {
+            features: vec![0.1, 0.2],
+            requests: 3,
+            succeeded: 3,
+            failed: 0,
+            latency_ms: latency(),
+            labels: [("cats".to_string(), 2), ("dogs".to_string(), 1)].into(),
+            confidence: Spread {
+                min: 0.8,
+                max: 0.95,
+                mean: 0.9,
+                stddev: 0.07,
+            },
+            varied: true,
+            errors: Vec::new(),
+            confidences: vec![0.95, 0.95, 0.8],
+        };
+        insta::assert_snapshot!(
+            "repeat",
+            render_all(|out, format| print_repeat(out, &repeat, format))
+        );
+        let comparison = TransportComparison {
+            rest: Transport {
+                prediction: Some(prediction("cats", 0.95)),
+                error: None,
+                bench: bench("REST POST /predict"),
+            },
+            grpc: Transport {
+                prediction: None,
+                error: Some("unavailable".to_string()),
+                bench: bench("gRPC Predict"),
+            },
+            same_label: false,
+        };
+        insta::assert_snapshot!(
+            "transport_comparison",
+            render_all(|out, format| print_transport_comparison(out, &comparison, format))
+        );
+    }
+
+    #[test]
+    fn test_metrics_renderers_match_snapshots() {
+        insta::assert_snapshot!(
+            "metrics",
+            render_all(|out, format| print_metrics(out, &metrics(42, 12.5), format))
+        );
+        let delta = MetricsDelta {
+            interval_secs: 5.0,
+            requests: 10,
+            requests_per_sec: 2.0,
+            memory_growth_mb: -0.5,
+        };
+        let sample = MetricsSample {
+            metrics: metrics(52, 12.0),
+            delta: Some(delta.clone()),
+        };
+        insta::assert_snapshot!(
+            "metrics_sample",
+            render_all(|out, format| print_metrics_sample(out, &sample, format))
+        );
+        let diff = MetricsDiff {
+            before: metrics(42, 12.5),
+            after: metrics(52, 12.0),
+            delta,
+        };
+        insta::assert_snapshot!(
+            "metrics_diff",
+            render_all(|out, format| print_metrics_diff(out, &diff, format))
+        );
+        let report = AggregateReport {
+            services: vec![
+                ServiceMetrics {
+                    name: "go-api".to_string(),
+                    url: "http://localhost:8080".to_string(),
+                    metrics: Some(NormalizedMetrics {
+                        uptime_secs: Some(3723.0),
+                        requests: Some(42),
+                        memory_mb: Some(12.5),
+                        workers: Some(8),
+                        cpu_cores: Some(4),
+                    }),
+                    latency_ms: 2,
+                    error: None,
+                },
+                ServiceMetrics {
+                    name: "python-ml".to_string(),
+                    url: "http://localhost:5000".to_string(),
+                    metrics: None,
+                    latency_ms: 1,
+                    error: Some("connection refused".to_string()),
+                },
+            ],
+            totals: Totals {
+                requests: 42,
+                memory_mb: 12.5,
+                workers: 8,
+            },
+        };
+        insta::assert_snapshot!(
+            "aggregate",
+            render_all(|out, format| print_aggregate(out, &report, format))
+        );
+        let comparison = Comparison {
+            a: Side {
+                url: "http://a:8080".to_string(),
+                health: Some(health()),
+                metrics: Some(metrics(42, 12.5)),
+                ..Side::default()
+            },
+            b: Side {
+                url: "http://b:8080".to_string(),
+                errors: vec!["metrics: 503 Service Unavailable".to_string()],
+                ..Side::default()
+            },
+            fields: vec![
+                Field {
+                    name: "version",
+                    a: Some("1.0.0".to_string()),
+                    b: Some("1.1.0".to_string()),
+                    differs: true,
+                },
+                Field {
+                    name: "requests",
+                    a: Some("42".to_string()),
+                    b: None,
+                    differs: true,
+                },
+            ],
+        };
+        insta::assert_snapshot!(
+            "comparison",
+            render_all(|out, format| print_comparison(out, &comparison, format))
+        );
+        insta::assert_snapshot!(
+            "bench",
+            render_all(|out, format| print_bench(out, &bench("GET /health"), format))
+        );
+        let event: Event = serde_json::from_str(
+            r#"{"seq":7,"timestamp":1704110400,"type":"prediction","label":"cats"}"#,
+        )
+        .unwrap();
+        insta::assert_snapshot!(
+            "event",
+            render_all(|out, format| print_event(out, &event, format))
+        );
+    }
+
+    #[test]
+    fn test_admin_renderers_match_snapshots() {
+        let schema = Schema {
+            feature_count: 2,
+            feature_names: vec!["height".to_string(), "weight".to_string()],
+        };
+        insta::assert_snapshot!(
+            "schema",
+            render_all(|out, format| print_schema(out, &schema, format))
+        );
+        let models: Vec<Model> = serde_json::from_str(
+            r#"[
+                {"id":"m1","name":"iris","version":"1.0","created_at":"2024-01-01","active":true,"accuracy":0.91},
+                {"id":"m2","version":"1.1"}
+            ]"#,
+        )
+        .unwrap();
+        insta::assert_snapshot!(
+            "models",
+            render_all(|out, format| print_models(out, &models, format))
+        );
+        insta::assert_snapshot!(
+            "model",
+            render_all(|out, format| print_model(out, &models[0], format))
+        );
+        let status: TaskStatus = serde_json::from_str(
+            r#"{"tasks":[
+                {"name":"install-go","status":"cached","duration_ms":3},
+                {"name":"build-go","status":"failed","duration_ms":61200,"deps":["install-go"]},
+                {"name":"build-all","status":"pending","deps":["build-go"]}
+            ]}"#,
+        )
+        .unwrap();
+        insta::assert_snapshot!(
+            "tasks",
+            render_all(|out, format| print_tasks(out, &status, format))
+        );
+        let report = ImportReport {
+            source: "dump.json".to_string(),
+            target: "http://localhost:8080".to_string(),
+            dry_run: true,
+            records: 3,
+            created: 1,
+            unchanged: 1,
+            overwritten: 0,
+            skipped: 1,
+            conflicts: vec![Conflict {
+                incoming: prediction("cats", 0.5),
+                existing: prediction("cats", 0.95),
+            }],
+        };
+        insta::assert_snapshot!(
+            "import",
+            render_all(|out, format| print_import(out, &report, format))
+        );
+        let aliases = [AliasSummary {
+            name: "hm".to_string(),
+            command: vec![
+                "health".to_string(),
+                "-u".to_string(),
+                "http://prod:8080".to_string(),
+            ],
+            shadowed: false,
+        }];
+        insta::assert_snapshot!(
+            "aliases",
+            render_all(|out, format| print_aliases(out, &aliases, format))
+        );
+        let profiles = [
+            ProfileSummary {
+                name: "prod".to_string(),
+                url: Some("https://prod:8443".to_string()),
+                default: true,
+            },
+            ProfileSummary {
+                name: "staging".to_string(),
+                url: None,
+                default: false,
+            },
+        ];
+        insta::assert_snapshot!(
+            "profiles",
+            render_all(|out, format| print_profiles(out, &profiles, format))
+        );
+    }
 
     #[test]
     fn test_output_functions_dont_panic() {
@@ -58,12 +2109,62 @@ mod tests {
             timestamp: "2024-01-01".to_string(),
             version: "1.0.0".to_string(),
         };
-        print_health(&health);
-
         let prediction = Prediction {
             label: "test".to_string(),
             confidence: 0.95,
+            timestamp: None,
         };
-        print_prediction(&prediction);
+        for format in OutputFormat::value_variants()
+            .iter()
+            .chain([&OutputFormat::Quiet])
+        {
+            print_health(&mut io::sink(), &health, *format).unwrap();
+            print_prediction(&mut io::sink(), &prediction, *format).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_value_output_is_one_scalar() {
+        let prediction = serde_json::json!({"label": "cat", "confidence": 0.95, "tags": []});
+        let field = |path: &str| single(&Query::parse(path).unwrap().apply(&prediction));
+        assert_eq!(field("label").unwrap(), "cat");
+        assert_eq!(field("$.confidence").unwrap(), "0.95");
+        assert!(field("tags").unwrap_err().contains("picked a list"));
+        assert!(field("missing").unwrap_err().contains("found nothing"));
+    }
+
+    #[test]
+    fn test_color_only_on_a_terminal_unless_forced() {
+        assert!(color_enabled(false, false, false, true));
+        assert!(!color_enabled(false, false, false, false));
+        assert!(color_enabled(false, false, true, false));
+        assert!(!color_enabled(true, false, true, true));
+        assert!(!color_enabled(false, true, true, true));
+    }
+
+    #[test]
+    fn test_table_aligns_and_truncates_columns() {
+        let mut table = Table::styled(&["#", "Label"], true).numeric(&[0]);
+        table.row(vec!["1".to_string(), "cat".to_string()]);
+        table.row(vec!["10".to_string(), "dog".to_string()]);
+        assert_eq!(
+            table.render(),
+            "+----+-------+\n\
+             |  # | Label |\n\
+             +====+=======+\n\
+             |  1 | cat   |\n\
+             | 10 | dog   |\n\
+             +----+-------+"
+        );
+
+        table.row(vec!["3".to_string(), "a very long label".to_string()]);
+        table.inner.set_width(16);
+        let rendered = table.render();
+        assert!(rendered.contains("..."), "{}", rendered);
+        assert!(
+            rendered.lines().all(|line| line.len() <= 16),
+            "{}",
+            rendered
+        );
     }
 }
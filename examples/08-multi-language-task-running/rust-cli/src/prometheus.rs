@@ -0,0 +1,102 @@
+use crate::aggregate;
+use crate::api::MetricsResponse;
+use std::fmt::Write;
+
+/// Prefix of every metric name, so the API's figures don't collide with the
+/// host's own in a node_exporter textfile collector.
+const PREFIX: &str = "prediction_api";
+
+/// `metrics` in the Prometheus text exposition format, with `HELP` and
+/// `TYPE` lines. Memory is converted to bytes and uptime to seconds, the
+/// base units Prometheus expects; an uptime that can't be read is left out.
+pub fn render(metrics: &MetricsResponse) -> String {
+    let mut text = String::new();
+    if let Some(secs) = aggregate::parse_go_duration(&metrics.uptime) {
+        sample(
+            &mut text,
+            "uptime_seconds",
+            "gauge",
+            "Seconds since the API started.",
+            secs,
+        );
+    }
+    sample(
+        &mut text,
+        "requests_total",
+        "counter",
+        "Requests the API has served.",
+        metrics.requests_total as f64,
+    );
+    sample(
+        &mut text,
+        "memory_usage_bytes",
+        "gauge",
+        "Memory the API is using.",
+        (metrics.memory_usage_mb * 1024.0 * 1024.0).round(),
+    );
+    sample(
+        &mut text,
+        "goroutines",
+        "gauge",
+        "Goroutines running in the API.",
+        metrics.goroutines as f64,
+    );
+    sample(
+        &mut text,
+        "cpu_cores",
+        "gauge",
+        "CPU cores available to the API.",
+        metrics.cpu_cores as f64,
+    );
+    text
+}
+
+fn sample(text: &mut String, name: &str, kind: &str, help: &str, value: f64) {
+    let _ = writeln!(text, "# HELP {}_{} {}", PREFIX, name, help);
+    let _ = writeln!(text, "# TYPE {}_{} {}", PREFIX, name, kind);
+    let _ = writeln!(text, "{}_{} {}", PREFIX, name, value);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_render_in_base_units() {
+        let text = render(&MetricsResponse {
+            uptime: "1m30s".to_string(),
+            requests_total: 42,
+            memory_usage_mb: 1.5,
+            goroutines: 8,
+            cpu_cores: 4,
+        });
+        let samples: Vec<&str> = text.lines().filter(|l| !l.starts_with('#')).collect();
+        assert_eq!(
+            samples,
+            [
+                "prediction_api_uptime_seconds 90",
+                "prediction_api_requests_total 42",
+                "prediction_api_memory_usage_bytes 1572864",
+                "prediction_api_goroutines 8",
+                "prediction_api_cpu_cores 4",
+            ]
+        );
+        assert!(text.starts_with(
+            "# HELP prediction_api_uptime_seconds Seconds since the API started.\n\
+             # TYPE prediction_api_uptime_seconds gauge\n"
+        ));
+        assert!(text.contains("# TYPE prediction_api_requests_total counter\n"));
+    }
+
+    #[test]
+    fn test_an_unreadable_uptime_is_left_out() {
+        let text = render(&MetricsResponse {
+            uptime: "a while".to_string(),
+            requests_total: 0,
+            memory_usage_mb: 0.0,
+            goroutines: 0,
+            cpu_cores: 0,
+        });
+        assert!(!text.contains("uptime"));
+    }
+}
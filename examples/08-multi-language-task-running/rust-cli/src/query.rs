@@ -0,0 +1,157 @@
+use serde_json::Value;
+
+/// A `--query` path: a JSONPath subset such as `$.predictions[0].label`,
+/// or jq's `.predictions[0].label`. Supports `.key`, `['key']`, `[index]`
+/// (negative counts from the end) and the `[*]`/`.*` wildcard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Key(String),
+    Index(i64),
+    Wildcard,
+}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Query, String> {
+        let fail = |why: &str| format!("invalid query {:?}: {}", text, why);
+        let trimmed = text.trim();
+        let path = match trimmed.strip_prefix('$') {
+            Some(path) => path.to_string(),
+            // A bare `predictions[0]` starts with a key.
+            None if !trimmed.starts_with(['.', '[']) => format!(".{}", trimmed),
+            None => trimmed.to_string(),
+        };
+        let mut rest = path.as_str();
+        let mut steps = Vec::new();
+        while !rest.is_empty() {
+            if let Some(tail) = rest.strip_prefix('.') {
+                let end = tail.find(['.', '[']).unwrap_or(tail.len());
+                let key = &tail[..end];
+                match key {
+                    "" if end == tail.len() && steps.is_empty() => {}
+                    "" => return Err(fail("expected a key after '.'")),
+                    "*" => steps.push(Step::Wildcard),
+                    key => steps.push(Step::Key(key.to_string())),
+                }
+                rest = &tail[end..];
+            } else if let Some(tail) = rest.strip_prefix('[') {
+                let end = bracket_end(tail).ok_or_else(|| fail("unclosed '['"))?;
+                let inner = tail[..end].trim();
+                steps.push(match inner {
+                    "*" => Step::Wildcard,
+                    _ if inner.len() >= 2
+                        && (inner.starts_with('\'') && inner.ends_with('\'')
+                            || inner.starts_with('"') && inner.ends_with('"')) =>
+                    {
+                        Step::Key(inner[1..inner.len() - 1].to_string())
+                    }
+                    _ => Step::Index(
+                        inner
+                            .parse()
+                            .map_err(|_| fail(&format!("{:?} is not an index", inner)))?,
+                    ),
+                });
+                rest = &tail[end + 1..];
+            } else {
+                return Err(fail("expected '.' or '['"));
+            }
+        }
+        Ok(Query { steps })
+    }
+
+    /// What the query picks out of `value`: `null` when a step finds
+    /// nothing, and an array of the matches once a wildcard is involved.
+    pub fn apply(&self, value: &Value) -> Value {
+        let mut matches = vec![value.clone()];
+        let mut many = false;
+        for step in &self.steps {
+            matches = matches
+                .iter()
+                .flat_map(|value| match step {
+                    Step::Key(key) => vec![value.get(key).cloned().unwrap_or(Value::Null)],
+                    Step::Index(index) => vec![index_into(value, *index)],
+                    Step::Wildcard => match value {
+                        Value::Array(items) => items.clone(),
+                        Value::Object(fields) => fields.values().cloned().collect(),
+                        _ => Vec::new(),
+                    },
+                })
+                .collect();
+            many |= *step == Step::Wildcard;
+        }
+        if many {
+            Value::Array(matches)
+        } else {
+            matches.pop().unwrap_or(Value::Null)
+        }
+    }
+}
+
+/// Where the `]` closing a bracket opened just before `text` is, skipping
+/// over a quoted key.
+fn bracket_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, ']') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn index_into(value: &Value, index: i64) -> Value {
+    let Value::Array(items) = value else {
+        return Value::Null;
+    };
+    let index = if index < 0 {
+        items.len() as i64 + index
+    } else {
+        index
+    };
+    usize::try_from(index)
+        .ok()
+        .and_then(|i| items.get(i))
+        .cloned()
+        .unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_paths_pick_nested_values() {
+        let document = json!({
+            "build_info": { "languages": ["rust", "go"] },
+            "predictions": [
+                { "label": "cats", "confidence": 0.9 },
+                { "label": "dogs", "confidence": 0.8 }
+            ]
+        });
+        let query = |text: &str| Query::parse(text).unwrap().apply(&document);
+
+        assert_eq!(query("$.build_info.languages[0]"), json!("rust"));
+        assert_eq!(query(".predictions[-1].label"), json!("dogs"));
+        assert_eq!(query("$['build_info'][\"languages\"][1]"), json!("go"));
+        assert_eq!(query("$.predictions[*].label"), json!(["cats", "dogs"]));
+        assert_eq!(query("predictions.*.confidence"), json!([0.9, 0.8]));
+        assert_eq!(query("$.missing.deeper"), Value::Null);
+        assert_eq!(query("."), document);
+        assert_eq!(query("$"), document);
+    }
+
+    #[test]
+    fn test_rejects_malformed_paths() {
+        assert!(Query::parse("$.a[0").is_err());
+        assert!(Query::parse("$.a[x]").is_err());
+        assert!(Query::parse("$..a").is_err());
+    }
+}
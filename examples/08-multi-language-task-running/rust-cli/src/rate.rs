@@ -0,0 +1,104 @@
+use serde::Deserialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// A `--rate` limit such as `10/s`, `300/m` or `5000/h`; a bare number is
+/// per second.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Rate {
+    /// Time between two requests.
+    pub interval: Duration,
+}
+
+impl Rate {
+    pub fn parse(text: &str) -> Result<Rate, String> {
+        let invalid = || {
+            format!(
+                "expected requests per unit like 10/s, 300/m or 5000/h, got {:?}",
+                text
+            )
+        };
+        let (count, unit) = text.trim().split_once('/').unwrap_or((text.trim(), "s"));
+        let unit = match unit.trim() {
+            "s" | "sec" => Duration::from_secs(1),
+            "m" | "min" => Duration::from_secs(60),
+            "h" | "hr" => Duration::from_secs(3600),
+            _ => return Err(invalid()),
+        };
+        let count: f64 = count.trim().parse().map_err(|_| invalid())?;
+        if !count.is_finite() || count <= 0.0 {
+            return Err(invalid());
+        }
+        Ok(Rate {
+            interval: unit.div_f64(count),
+        })
+    }
+}
+
+impl TryFrom<String> for Rate {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Rate, String> {
+        Rate::parse(&text)
+    }
+}
+
+/// Spaces requests at least one [`Rate::interval`] apart, across every task
+/// that shares it. Nothing builds up while idle, so a pause isn't followed
+/// by a burst.
+#[derive(Debug)]
+pub struct Limiter {
+    interval: Duration,
+    /// When the next request may go.
+    next: Mutex<Option<Instant>>,
+}
+
+impl Limiter {
+    pub fn new(rate: Rate) -> Self {
+        Limiter {
+            interval: rate.interval,
+            next: Mutex::new(None),
+        }
+    }
+
+    /// Waits for this request's turn.
+    pub async fn wait(&self) {
+        let slot = {
+            let mut next = self.next.lock().unwrap();
+            let now = Instant::now();
+            let slot = next.map_or(now, |next| next.max(now));
+            *next = Some(slot + self.interval);
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rates_take_a_unit() {
+        let interval = |text: &str| Rate::parse(text).unwrap().interval;
+        assert_eq!(interval("10/s"), Duration::from_millis(100));
+        assert_eq!(interval("4"), Duration::from_millis(250));
+        assert_eq!(interval("120/m"), Duration::from_millis(500));
+        assert_eq!(interval("0.5/s"), Duration::from_secs(2));
+        assert_eq!(interval("3600/h"), Duration::from_secs(1));
+        assert!(Rate::parse("0/s").is_err());
+        assert!(Rate::parse("10/d").is_err());
+        assert!(Rate::parse("fast").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_limiter_spaces_concurrent_requests() {
+        let limiter = Limiter::new(Rate::parse("50/s").unwrap());
+        let started = Instant::now();
+        futures::future::join_all((0..5).map(|_| limiter.wait())).await;
+        // The first goes at once, the other four 20 ms apart.
+        assert!(started.elapsed() >= Duration::from_millis(80));
+    }
+}
@@ -0,0 +1,179 @@
+use crate::api::{self, Api, DryRun};
+use crate::bench::{self, Latency};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+/// The spread of the confidences that came back.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Spread {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub stddev: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepeatReport {
+    pub features: Vec<f64>,
+    pub requests: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Over successful requests only.
+    pub latency_ms: Latency,
+    /// How many answers had each label.
+    pub labels: BTreeMap<String, usize>,
+    pub confidence: Spread,
+    /// The answers didn't all agree, in label or confidence.
+    pub varied: bool,
+    /// The first few distinct failures.
+    pub errors: Vec<String>,
+    /// Every confidence, in the order the answers came, for `--threshold`.
+    #[serde(skip)]
+    pub confidences: Vec<f64>,
+}
+
+/// Distinct errors kept in a report.
+const MAX_ERRORS: usize = 5;
+
+/// Confidences closer than this count as the same answer.
+const EPSILON: f64 = 1e-9;
+
+/// Predicts `features` `requests` times, one after another and `interval`
+/// apart, timing each answer and comparing them. A failed request is
+/// counted and the run goes on.
+pub async fn run(
+    api: &Api,
+    features: &[f64],
+    requests: usize,
+    interval: Duration,
+) -> Result<RepeatReport, Box<dyn Error>> {
+    let mut latencies = Vec::with_capacity(requests);
+    let mut labels = BTreeMap::new();
+    let mut confidences = Vec::with_capacity(requests);
+    let mut errors: Vec<String> = Vec::new();
+    for i in 0..requests {
+        if i > 0 && !interval.is_zero() {
+            tokio::time::sleep(interval).await;
+        }
+        api.throttle().await;
+        let sent = Instant::now();
+        match api::make_prediction(api, features).await {
+            Ok(prediction) => {
+                latencies.push(sent.elapsed());
+                *labels.entry(prediction.label).or_default() += 1;
+                confidences.push(prediction.confidence);
+            }
+            Err(e) if e.is::<DryRun>() => return Err(e),
+            Err(e) => {
+                let e = e.to_string();
+                if errors.len() < MAX_ERRORS && !errors.contains(&e) {
+                    errors.push(e);
+                }
+            }
+        }
+    }
+    let confidence = spread(&confidences);
+    Ok(RepeatReport {
+        features: features.to_vec(),
+        requests,
+        succeeded: latencies.len(),
+        failed: requests - latencies.len(),
+        latency_ms: bench::summarize(&mut latencies),
+        varied: labels.len() > 1 || confidence.max - confidence.min > EPSILON,
+        labels,
+        confidence,
+        errors,
+        confidences,
+    })
+}
+
+fn spread(confidences: &[f64]) -> Spread {
+    if confidences.is_empty() {
+        return Spread::default();
+    }
+    let n = confidences.len() as f64;
+    let mean = confidences.iter().sum::<f64>() / n;
+    let variance = confidences.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / n;
+    Spread {
+        min: confidences.iter().copied().fold(f64::INFINITY, f64::min),
+        max: confidences
+            .iter()
+            .copied()
+            .fold(f64::NEG_INFINITY, f64::max),
+        mean,
+        stddev: variance.sqrt(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_differing_answers_are_flagged() {
+        let mut server = mockito::Server::new_async().await;
+        let steady = server
+            .mock("POST", "/predict")
+            .with_body(r#"{"label":"cats","confidence":0.5}"#)
+            .expect(3)
+            .create_async()
+            .await;
+        let api = Api::for_tests(server.url());
+
+        let report = run(&api, &[0.1], 3, Duration::ZERO).await.unwrap();
+
+        steady.assert_async().await;
+        assert_eq!(report.succeeded, 3);
+        assert!(!report.varied);
+        assert_eq!(report.confidence.stddev, 0.0);
+
+        steady.remove_async().await;
+        let calls = AtomicUsize::new(0);
+        server
+            .mock("POST", "/predict")
+            .with_body_from_request(move |_| match calls.fetch_add(1, Ordering::Relaxed) % 2 {
+                0 => br#"{"label":"cats","confidence":0.5}"#.to_vec(),
+                _ => br#"{"label":"dogs","confidence":0.9}"#.to_vec(),
+            })
+            .create_async()
+            .await;
+
+        let report = run(&api, &[0.1], 4, Duration::ZERO).await.unwrap();
+
+        assert!(report.varied);
+        assert_eq!(report.labels["cats"], 2);
+        assert_eq!(report.labels["dogs"], 2);
+        assert_eq!(report.confidences, [0.5, 0.9, 0.5, 0.9]);
+    }
+
+    #[test]
+    fn test_confidence_spread() {
+        let spread = spread(&[0.2, 0.4, 0.6]);
+        assert!((spread.mean - 0.4).abs() < EPSILON);
+        assert_eq!((spread.min, spread.max), (0.2, 0.6));
+        assert!((spread.stddev - (0.08f64 / 3.0).sqrt()).abs() < EPSILON);
+        assert_eq!(super::spread(&[]), Spread::default());
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_counted_and_the_run_goes_on() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/predict")
+            .with_status(503)
+            .expect(4)
+            .create_async()
+            .await;
+
+        let report = run(&Api::for_tests(server.url()), &[0.1], 4, Duration::ZERO)
+            .await
+            .unwrap();
+
+        assert_eq!((report.succeeded, report.failed), (0, 4));
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.latency_ms, Latency::default());
+    }
+}
@@ -0,0 +1,135 @@
+use crate::api::{self, Api, StatusError};
+use crate::exit;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+/// The features the model expects, from `GET /schema`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Schema {
+    pub feature_count: usize,
+    /// Names in input order; empty if the API doesn't name them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub feature_names: Vec<String>,
+}
+
+impl Schema {
+    /// Checks one row of features against the schema.
+    pub fn check(&self, features: &[f64]) -> Result<(), String> {
+        if features.len() == self.feature_count {
+            return Ok(());
+        }
+        let mut message = format!(
+            "the model expects {} features, got {}",
+            self.feature_count,
+            features.len()
+        );
+        if !self.feature_names.is_empty() {
+            message += &format!(" (expected: {})", self.feature_names.join(", "));
+        }
+        Err(message)
+    }
+
+    /// Checks features given by name: every expected name once, and no
+    /// others. Only the count can be checked when the schema has no names.
+    pub fn check_names(&self, names: &[&str]) -> Result<(), String> {
+        if self.feature_names.is_empty() {
+            return self.check(&vec![0.0; names.len()]);
+        }
+        if let Some(unknown) = names
+            .iter()
+            .find(|name| !self.feature_names.iter().any(|known| known == *name))
+        {
+            return Err(format!(
+                "the model has no feature {:?} (expected: {})",
+                unknown,
+                self.feature_names.join(", ")
+            ));
+        }
+        let missing: Vec<&str> = self
+            .feature_names
+            .iter()
+            .map(String::as_str)
+            .filter(|known| !names.contains(known))
+            .collect();
+        if !missing.is_empty() {
+            return Err(format!("missing features: {}", missing.join(", ")));
+        }
+        Ok(())
+    }
+
+    /// Checks every row, naming the first that doesn't fit by its 1-based
+    /// position.
+    pub fn check_rows(&self, rows: &[Vec<f64>]) -> Result<(), String> {
+        for (i, row) in rows.iter().enumerate() {
+            self.check(row)
+                .map_err(|e| format!("row {}: {}", i + 1, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// The API's schema, or `None` from servers that don't publish one.
+pub async fn fetch(api: &Api) -> Result<Option<Schema>, Box<dyn Error>> {
+    match api::get_schema(api).await {
+        Ok(schema) => Ok(Some(schema)),
+        Err(e)
+            if e.downcast_ref::<StatusError>()
+                .is_some_and(|e| e.status == StatusCode::NOT_FOUND) =>
+        {
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Checks `rows` against the API's schema before they are sent, so a wrong
+/// feature count is caught here rather than as a bare 400. Passes when the
+/// API publishes no schema.
+pub async fn validate(api: &Api, rows: &[Vec<f64>]) -> Result<(), Box<dyn Error>> {
+    let Some(schema) = fetch(api).await? else {
+        return Ok(());
+    };
+    let checked = match rows {
+        [row] => schema.check(row),
+        rows => schema.check_rows(rows),
+    };
+    checked.map_err(exit::invalid_input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_names_the_expected_features() {
+        let schema = Schema {
+            feature_count: 2,
+            feature_names: vec!["height".to_string(), "weight".to_string()],
+        };
+        assert!(schema.check(&[1.0, 2.0]).is_ok());
+        assert!(schema.check_names(&["weight", "height"]).is_ok());
+        assert_eq!(
+            schema.check_names(&["height"]).unwrap_err(),
+            "missing features: weight"
+        );
+        assert!(schema.check_names(&["height", "age"]).is_err());
+        assert_eq!(
+            schema.check_rows(&[vec![1.0, 2.0], vec![1.0]]).unwrap_err(),
+            "row 2: the model expects 2 features, got 1 (expected: height, weight)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_servers_without_a_schema_skip_validation() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/schema")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let api = Api::for_tests(server.url());
+        assert_eq!(fetch(&api).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,81 @@
+use crate::api;
+use hmac::{Hmac, Mac};
+use reqwest::header::HeaderValue;
+use reqwest::Request;
+use sha2::Sha256;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds since the Unix epoch when the request was signed.
+pub const TIMESTAMP_HEADER: &str = "X-Signature-Timestamp";
+/// Hex HMAC-SHA256 of the request, see [`signature`].
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+
+/// The hex HMAC-SHA256, under `key`, of the method, the path with its query,
+/// the timestamp and the body, each followed by a newline but the body:
+///
+/// ```text
+/// POST\n/predict\n1700000000\n{"features":[0.1,0.2]}
+/// ```
+pub fn signature(key: &[u8], method: &str, path: &str, timestamp: u64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(format!("{}\n{}\n{}\n", method, path, timestamp).as_bytes());
+    mac.update(body);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Adds the timestamp and signature headers to `request`. Called for every
+/// attempt, so a retry carries a fresh timestamp.
+pub fn sign(request: &mut Request, key: &str) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let path = api::request_path(request.url());
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .unwrap_or_default();
+    let signature = signature(
+        key.as_bytes(),
+        request.method().as_str(),
+        &path,
+        timestamp,
+        body,
+    );
+    let headers = request.headers_mut();
+    headers.insert(TIMESTAMP_HEADER, HeaderValue::from(timestamp));
+    headers.insert(
+        SIGNATURE_HEADER,
+        HeaderValue::from_str(&signature).expect("hex is a valid header value"),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_covers_each_part() {
+        // printf 'GET\n/metrics\n1700000000\n' | openssl dgst -sha256 -hmac key
+        assert_eq!(
+            signature(b"key", "GET", "/metrics", 1_700_000_000, b""),
+            "90b642487b46572e6d750c58fbb95b88097e3a95a333736fa16b988cdaa880b6"
+        );
+        let signed = signature(b"key", "POST", "/predict", 1_700_000_000, b"{}");
+        assert_ne!(
+            signed,
+            signature(b"key", "POST", "/predict", 1_700_000_001, b"{}")
+        );
+        assert_ne!(
+            signed,
+            signature(b"key", "POST", "/predict", 1_700_000_000, b"[]")
+        );
+        assert_ne!(
+            signed,
+            signature(b"other", "POST", "/predict", 1_700_000_000, b"{}")
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use crate::api::MetricsResponse;
+use crate::watch::MetricsDelta;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Metrics saved by `metrics snapshot` for a later `metrics --diff`. Plain
+/// `metrics -o json` output reads as a snapshot too, just without a time.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Seconds since the Unix epoch when it was taken.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub taken_at: Option<u64>,
+    #[serde(flatten)]
+    pub metrics: MetricsResponse,
+}
+
+/// A snapshot's metrics next to the current ones.
+#[derive(Debug, Serialize)]
+pub struct MetricsDiff {
+    pub before: MetricsResponse,
+    pub after: MetricsResponse,
+    pub delta: MetricsDelta,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+pub fn save(path: &Path, metrics: MetricsResponse) -> Result<(), Box<dyn Error>> {
+    let snapshot = Snapshot {
+        taken_at: Some(now()),
+        metrics,
+    };
+    fs::write(path, serde_json::to_string_pretty(&snapshot)? + "\n")
+        .map_err(|e| format!("cannot write {}: {}", path.display(), e).into())
+}
+
+pub fn load(path: &Path) -> Result<Snapshot, Box<dyn Error>> {
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    serde_json::from_str(&text)
+        .map_err(|e| format!("{} is not a metrics snapshot: {}", path.display(), e).into())
+}
+
+/// From `snapshot` to `current`; the rate is zero for snapshots without a
+/// time.
+pub fn diff(snapshot: Snapshot, current: MetricsResponse) -> MetricsDiff {
+    let elapsed = snapshot.taken_at.map_or(Duration::ZERO, |taken| {
+        Duration::from_secs(now().saturating_sub(taken))
+    });
+    MetricsDiff {
+        delta: MetricsDelta::between(&snapshot.metrics, &current, elapsed),
+        before: snapshot.metrics,
+        after: current,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_snapshot_round_trips_and_diffs() {
+        let metrics = |requests_total, memory_usage_mb| MetricsResponse {
+            uptime: "1m".to_string(),
+            requests_total,
+            memory_usage_mb,
+            goroutines: 4,
+            cpu_cores: 2,
+        };
+        let path = env::temp_dir().join(format!("snapshot-{}.json", std::process::id()));
+        save(&path, metrics(100, 10.0)).unwrap();
+        let snapshot = load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(snapshot.taken_at.is_some());
+        let diff = diff(snapshot, metrics(150, 12.5));
+        assert_eq!(diff.delta.requests, 50);
+        assert_eq!(diff.delta.memory_growth_mb, 2.5);
+
+        let plain: Snapshot = serde_json::from_value(serde_json::json!({
+            "uptime": "1m", "requests_total": 3, "memory_usage_mb": 1.0,
+            "goroutines": 1, "cpu_cores": 1
+        }))
+        .unwrap();
+        assert_eq!(plain.taken_at, None);
+    }
+}
@@ -0,0 +1,151 @@
+use crate::api::{self, Api, DryRun};
+use crate::batch::{self, RowFailure, RowResult};
+use crate::schema::Schema;
+use futures::future;
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::error::Error;
+use std::pin::pin;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+/// What `predict --stream` writes for one input line.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum Scored {
+    Result(RowResult),
+    /// The API refused the prediction or couldn't be reached.
+    Failure(RowFailure),
+    /// The line isn't features the model takes, so nothing was sent.
+    Invalid {
+        row: usize,
+        error: String,
+    },
+}
+
+impl Scored {
+    pub fn succeeded(&self) -> bool {
+        matches!(self, Scored::Result(_))
+    }
+}
+
+/// Lines scored by the end of the input, and how many of them failed.
+#[derive(Debug, Default, PartialEq)]
+pub struct Tally {
+    pub rows: usize,
+    pub failed: usize,
+}
+
+/// Predicts each JSON line of `input` as it is read, up to `concurrency` at
+/// a time, handing `emit` one result per line in input order until the
+/// input ends. Rows are numbered like a batch's, blank lines aside. A line
+/// that can't be read, doesn't fit `schema` or fails to predict is emitted
+/// as an error and the stream goes on.
+pub async fn score_lines<R: AsyncBufRead + Unpin>(
+    api: &Api,
+    input: R,
+    concurrency: usize,
+    schema: Option<&Schema>,
+    mut emit: impl FnMut(&Scored) -> Result<(), Box<dyn Error>>,
+) -> Result<Tally, Box<dyn Error>> {
+    let lines = stream::unfold(input.lines(), |mut lines| async move {
+        lines
+            .next_line()
+            .await
+            .transpose()
+            .map(|line| (line, lines))
+    });
+    let mut scored = pin!(lines
+        .filter(|line| future::ready(line.as_ref().map_or(true, |line| !line.trim().is_empty())))
+        .enumerate()
+        .map(|(i, line)| async move {
+            let row = i + 1;
+            let line = line.map_err(|e| format!("cannot read stdin: {}", e))?;
+            let features = batch::parse_json_line(line.trim()).and_then(|features| match schema {
+                Some(schema) => schema.check(&features).map(|()| features),
+                None => Ok(features),
+            });
+            let features = match features {
+                Ok(features) => features,
+                Err(error) => return Ok(Scored::Invalid { row, error }),
+            };
+            api.throttle().await;
+            let scored = match api::make_prediction(api, &features).await {
+                Ok(prediction) => Scored::Result(RowResult {
+                    row,
+                    features,
+                    label: prediction.label,
+                    confidence: prediction.confidence,
+                }),
+                Err(e) if e.is::<DryRun>() => return Err(e),
+                Err(e) => Scored::Failure(RowFailure {
+                    row,
+                    features,
+                    error: e.to_string(),
+                }),
+            };
+            Ok::<_, Box<dyn Error>>(scored)
+        })
+        .buffered(concurrency.max(1)));
+
+    let mut tally = Tally::default();
+    while let Some(scored) = scored.next().await {
+        let scored = scored?;
+        tally.rows += 1;
+        if !scored.succeeded() {
+            tally.failed += 1;
+        }
+        emit(&scored)?;
+    }
+    Ok(tally)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_every_line_gets_one_result_in_order() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/predict")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({ "features": [0.0, 0.0] }),
+            ))
+            .with_status(500)
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/predict")
+            .with_body(r#"{"label":"ok","confidence":0.5}"#)
+            .create_async()
+            .await;
+        let schema = Schema {
+            feature_count: 2,
+            feature_names: vec![],
+        };
+        let input = "[1, 2]\n\nnot json\n{\"features\": [0, 0]}\n[3]\n[4, 5]\n";
+        let mut lines = Vec::new();
+
+        let tally = score_lines(
+            &Api::for_tests(server.url()),
+            input.as_bytes(),
+            3,
+            Some(&schema),
+            |scored| {
+                lines.push(serde_json::to_value(scored)?);
+                Ok(())
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(tally, Tally { rows: 5, failed: 3 });
+        let rows: Vec<u64> = lines.iter().map(|l| l["row"].as_u64().unwrap()).collect();
+        assert_eq!(rows, [1, 2, 3, 4, 5]);
+        assert_eq!(lines[0]["label"], "ok");
+        assert!(lines[1]["error"].as_str().unwrap().contains("JSON array"));
+        assert!(lines[2]["error"].as_str().unwrap().contains("500"));
+        assert!(lines[3]["error"].as_str().unwrap().contains("expects 2"));
+        assert_eq!(lines[4]["features"], serde_json::json!([4.0, 5.0]));
+    }
+}
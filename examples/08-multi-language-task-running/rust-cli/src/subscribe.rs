@@ -0,0 +1,113 @@
+use crate::api::{self, Api};
+use crate::output::{self, OutputFormat};
+use colored::*;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
+
+/// Seconds the backend holds each poll open waiting for an event; its own
+/// default `poll_timeout_secs`.
+pub const POLL_TIMEOUT_SECS: u64 = 30;
+
+/// Pause after the first failed poll, doubled after each one in a row up to
+/// [`MAX_RECONNECT_DELAY`].
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Follows the backend's `/events/poll` log after `cursor` until Ctrl-C,
+/// printing each event as it arrives. Failed polls are reported and retried
+/// with backoff from the same cursor, so nothing is skipped across a
+/// reconnect. With `cursor_file`, the cursor is read from it on start and
+/// saved to it after every batch.
+pub async fn subscribe(
+    api: &Api,
+    cursor: Option<u64>,
+    cursor_file: Option<&Path>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    let mut cursor = match (cursor, cursor_file) {
+        (Some(cursor), _) => cursor,
+        (None, Some(path)) => read_cursor(path)?,
+        (None, None) => 0,
+    };
+    let mut delay = RECONNECT_DELAY;
+    loop {
+        let batch = tokio::select! {
+            batch = api::poll_events(api, cursor, POLL_TIMEOUT_SECS) => batch,
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        };
+        match batch {
+            Ok(batch) => {
+                delay = RECONNECT_DELAY;
+                if batch.missed {
+                    eprintln!(
+                        "{} some events were missed (backend restarted or this client fell behind)",
+                        "Warning:".yellow().bold()
+                    );
+                }
+                for event in &batch.events {
+                    output::print_event(&mut io::stdout(), event, format)?;
+                }
+                io::stdout().flush()?;
+                cursor = batch.next_cursor;
+                if let Some(path) = cursor_file {
+                    write_cursor(path, cursor)?;
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "{} {} (reconnecting in {}s)",
+                    "Error:".red().bold(),
+                    e,
+                    delay.as_secs()
+                );
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = tokio::signal::ctrl_c() => return Ok(()),
+                }
+                delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+            }
+        }
+    }
+}
+
+/// The cursor saved in `path`, or 0 if there's no file yet.
+fn read_cursor(path: &Path) -> Result<u64, Box<dyn Error>> {
+    match fs::read_to_string(path) {
+        Ok(text) => text
+            .trim()
+            .parse()
+            .map_err(|_| format!("{}: not a cursor: {:?}", path.display(), text.trim()).into()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(format!("cannot read {}: {}", path.display(), e).into()),
+    }
+}
+
+/// Saves `cursor` through a temporary file, so a crash never leaves a torn
+/// one behind.
+fn write_cursor(path: &Path, cursor: u64) -> Result<(), Box<dyn Error>> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, format!("{}\n", cursor))
+        .and_then(|()| fs::rename(&tmp, path))
+        .map_err(|e| format!("cannot save cursor to {}: {}", path.display(), e).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_file_round_trips() {
+        let path = std::env::temp_dir().join(format!("cursor-{}", std::process::id()));
+        assert_eq!(read_cursor(&path).unwrap(), 0);
+
+        write_cursor(&path, 42).unwrap();
+        assert_eq!(read_cursor(&path).unwrap(), 42);
+
+        fs::write(&path, "soon").unwrap();
+        assert!(read_cursor(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,115 @@
+use crate::api::{self, Api};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// One task of the gaffer build, as `/gaffer/tasks` or a status file
+/// reports it.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Task {
+    pub name: String,
+    /// `succeeded`, `failed`, `running`, `pending`, `cached` or `skipped`;
+    /// other values are shown as they are.
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Tasks that run first, as in graph.json.
+    #[serde(default)]
+    pub deps: Vec<String>,
+}
+
+impl Task {
+    pub fn failed(&self) -> bool {
+        self.status == "failed"
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TaskStatus {
+    pub tasks: Vec<Task>,
+}
+
+impl TaskStatus {
+    pub fn failed(&self) -> usize {
+        self.tasks.iter().filter(|task| task.failed()).count()
+    }
+}
+
+pub async fn fetch(api: &Api) -> Result<TaskStatus, Box<dyn Error>> {
+    let status = api::get_tasks(api).await?;
+    Ok(ordered(status))
+}
+
+/// A status file gaffer wrote, in the same format as `/gaffer/tasks`.
+pub fn load(path: &Path) -> Result<TaskStatus, Box<dyn Error>> {
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    let status = serde_json::from_str(&text)
+        .map_err(|e| format!("{} is not a gaffer status file: {}", path.display(), e))?;
+    Ok(ordered(status))
+}
+
+/// Puts every task after its dependencies, otherwise keeping the order
+/// given. Tasks in a cycle go last, as given.
+fn ordered(status: TaskStatus) -> TaskStatus {
+    let names: HashSet<&str> = status.tasks.iter().map(|t| t.name.as_str()).collect();
+    let mut done: HashSet<String> = HashSet::new();
+    let mut pending = status.tasks.clone();
+    let mut tasks = Vec::with_capacity(pending.len());
+    loop {
+        let (ready, waiting): (Vec<Task>, Vec<Task>) = pending.into_iter().partition(|task| {
+            task.deps
+                .iter()
+                .all(|dep| done.contains(dep) || !names.contains(dep.as_str()))
+        });
+        if ready.is_empty() {
+            tasks.extend(waiting);
+            break;
+        }
+        done.extend(ready.iter().map(|task| task.name.clone()));
+        tasks.extend(ready);
+        pending = waiting;
+    }
+    TaskStatus { tasks }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tasks_come_after_their_dependencies() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/gaffer/tasks")
+            .with_body(
+                r#"{"success":true,"data":{"tasks":[
+                    {"name":"build-all","status":"failed","deps":["build-go","build-rust"]},
+                    {"name":"build-go","status":"failed","duration_ms":1200,"deps":["install-go"]},
+                    {"name":"install-go","status":"cached","duration_ms":3},
+                    {"name":"build-rust","status":"succeeded","deps":["install-rust"]},
+                    {"name":"loop-a","status":"pending","deps":["loop-b"]},
+                    {"name":"loop-b","status":"pending","deps":["loop-a"]}
+                ]}}"#,
+            )
+            .create_async()
+            .await;
+
+        let status = fetch(&Api::for_tests(server.url())).await.unwrap();
+        let names: Vec<&str> = status.tasks.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "install-go",
+                "build-rust",
+                "build-go",
+                "build-all",
+                "loop-a",
+                "loop-b"
+            ]
+        );
+        assert_eq!(status.failed(), 2);
+    }
+}
@@ -0,0 +1,120 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::error::Error;
+
+/// A `--format` template such as `{{label}}: {{confidence}}`, printed once
+/// per result. Placeholders name a field of the result's JSON form; dots
+/// reach into nested objects and arrays (`{{features.0}}`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Field(Vec<String>),
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Template, String> {
+        let mut parts = Vec::new();
+        let mut rest = source;
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                parts.push(Part::Text(rest[..start].to_string()));
+            }
+            let after = &rest[start + 2..];
+            let end = after.find("}}").ok_or_else(|| {
+                format!(
+                    "unclosed {{{{ at offset {}",
+                    source.len() - rest.len() + start
+                )
+            })?;
+            let name = after[..end].trim();
+            if name.is_empty() {
+                return Err("empty {{}} placeholder".to_string());
+            }
+            parts.push(Part::Field(name.split('.').map(str::to_string).collect()));
+            rest = &after[end + 2..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_string()));
+        }
+        Ok(Template { parts })
+    }
+
+    /// Fills the placeholders from `value`'s JSON form. Strings are written
+    /// without quotes, null as nothing, and objects and arrays as JSON.
+    pub fn render<T: Serialize + ?Sized>(&self, value: &T) -> Result<String, Box<dyn Error>> {
+        let value = serde_json::to_value(value)?;
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => out.push_str(text),
+                Part::Field(path) => match lookup(&value, path)? {
+                    Value::String(s) => out.push_str(s),
+                    Value::Null => {}
+                    other => out.push_str(&other.to_string()),
+                },
+            }
+        }
+        Ok(out)
+    }
+}
+
+fn lookup<'a>(value: &'a Value, path: &[String]) -> Result<&'a Value, String> {
+    let mut current = value;
+    for (depth, key) in path.iter().enumerate() {
+        let next = match current {
+            Value::Object(map) => map.get(key),
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        };
+        current = next.ok_or_else(|| {
+            let name = path[..=depth].join(".");
+            match current {
+                Value::Object(map) => {
+                    let known: Vec<&str> = map.keys().map(String::as_str).collect();
+                    format!("no field {:?} (have: {})", name, known.join(", "))
+                }
+                _ => format!("no field {:?}", name),
+            }
+        })?;
+    }
+    Ok(current)
+}
+
+/// Prints one line per item of `items`.
+pub fn print_each<T: Serialize>(template: &Template, items: &[T]) -> Result<(), Box<dyn Error>> {
+    for item in items {
+        println!("{}", template.render(item)?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_fills_fields() {
+        let template = Template::parse("{{label}}: {{ confidence }} [{{features.1}}]").unwrap();
+        let value = json!({"label": "cat", "confidence": 0.87, "features": [0.1, 0.2]});
+        assert_eq!(template.render(&value).unwrap(), "cat: 0.87 [0.2]");
+    }
+
+    #[test]
+    fn test_bad_templates_and_fields_are_reported() {
+        assert_eq!(
+            Template::parse("{{label").unwrap_err(),
+            "unclosed {{ at offset 0"
+        );
+        assert!(Template::parse("a {{ }} b").is_err());
+
+        let template = Template::parse("{{lable}}").unwrap();
+        let err = template.render(&json!({"label": "cat"})).unwrap_err();
+        assert_eq!(err.to_string(), "no field \"lable\" (have: label)");
+    }
+}
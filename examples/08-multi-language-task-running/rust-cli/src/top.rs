@@ -0,0 +1,346 @@
+use crate::aggregate::{self, AggregateReport};
+use crate::api::Api;
+use crate::dashboard;
+use crate::health::Service;
+use clap::ValueEnum;
+use ratatui::crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Stylize};
+use ratatui::widgets::{Block, Paragraph, Row, Table};
+use ratatui::Frame;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::error::Error;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+/// A column `top` can sort by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Column {
+    Service,
+    /// Requests per second since the last refresh
+    Rate,
+    /// How long `/metrics` took to answer
+    Latency,
+    Memory,
+    Requests,
+    Workers,
+    Uptime,
+}
+
+impl Column {
+    const ALL: [Column; 7] = [
+        Column::Service,
+        Column::Rate,
+        Column::Latency,
+        Column::Memory,
+        Column::Requests,
+        Column::Workers,
+        Column::Uptime,
+    ];
+
+    fn title(self) -> &'static str {
+        match self {
+            Column::Service => "Service",
+            Column::Rate => "Req/s",
+            Column::Latency => "Latency",
+            Column::Memory => "Memory",
+            Column::Requests => "Requests",
+            Column::Workers => "Workers",
+            Column::Uptime => "Uptime",
+        }
+    }
+
+    /// The next column to the right (`step` 1) or left (`step` -1).
+    fn step(self, step: isize) -> Column {
+        let i = Column::ALL.iter().position(|&c| c == self).unwrap_or(0) as isize;
+        let n = Column::ALL.len() as isize;
+        Column::ALL[(i + step).rem_euclid(n) as usize]
+    }
+}
+
+/// One service's line in the view.
+#[derive(Debug, Clone, PartialEq)]
+struct TopRow {
+    name: String,
+    rate: Option<f64>,
+    latency_ms: u128,
+    memory_mb: Option<f64>,
+    requests: Option<i64>,
+    workers: Option<i64>,
+    uptime_secs: Option<f64>,
+    error: Option<String>,
+}
+
+impl TopRow {
+    /// The value sorted on; text sorts by name.
+    fn key(&self, column: Column) -> Option<f64> {
+        match column {
+            Column::Service => None,
+            Column::Rate => self.rate,
+            Column::Latency => Some(self.latency_ms as f64),
+            Column::Memory => self.memory_mb,
+            Column::Requests => self.requests.map(|n| n as f64),
+            Column::Workers => self.workers.map(|n| n as f64),
+            Column::Uptime => self.uptime_secs,
+        }
+    }
+}
+
+/// What `top` shows, updated once per refresh.
+pub struct Top {
+    services: Vec<Service>,
+    interval: Duration,
+    rows: Vec<TopRow>,
+    /// Each service's request count at the last refresh.
+    previous: HashMap<String, (Instant, i64)>,
+    sort: Column,
+    descending: bool,
+    refreshes: u64,
+}
+
+impl Top {
+    pub fn new(services: Vec<Service>, interval: Duration, sort: Column) -> Self {
+        Top {
+            services,
+            interval,
+            rows: Vec::new(),
+            previous: HashMap::new(),
+            sort,
+            descending: sort != Column::Service,
+            refreshes: 0,
+        }
+    }
+
+    /// Starts fetching every service's metrics in `refreshing`, unless a
+    /// fetch is already running there.
+    fn refresh(&self, api: &Api, refreshing: &mut JoinSet<AggregateReport>) {
+        if !refreshing.is_empty() {
+            return;
+        }
+        let (api, services) = (api.clone(), self.services.clone());
+        refreshing.spawn(async move { aggregate::fetch_all(&api, &services).await });
+    }
+
+    fn record(&mut self, report: AggregateReport, now: Instant) {
+        self.refreshes += 1;
+        self.rows = report
+            .services
+            .into_iter()
+            .map(|service| {
+                let metrics = service.metrics.unwrap_or_default();
+                let rate = metrics.requests.and_then(|requests| {
+                    let (at, before) = self
+                        .previous
+                        .insert(service.name.clone(), (now, requests))?;
+                    let secs = now.duration_since(at).as_secs_f64();
+                    // A count that went down means the service restarted.
+                    let served = if requests >= before {
+                        requests - before
+                    } else {
+                        requests
+                    };
+                    (secs > 0.0).then(|| served as f64 / secs)
+                });
+                TopRow {
+                    name: service.name,
+                    rate,
+                    latency_ms: service.latency_ms,
+                    memory_mb: metrics.memory_mb,
+                    requests: metrics.requests,
+                    workers: metrics.workers,
+                    uptime_secs: metrics.uptime_secs,
+                    error: service.error,
+                }
+            })
+            .collect();
+    }
+
+    /// The rows in the chosen order. Services missing the sorted value come
+    /// last either way.
+    fn sorted(&self) -> Vec<&TopRow> {
+        let mut rows: Vec<&TopRow> = self.rows.iter().collect();
+        rows.sort_by(|a, b| {
+            let order = match (a.key(self.sort), b.key(self.sort)) {
+                _ if self.sort == Column::Service => a.name.cmp(&b.name),
+                (Some(x), Some(y)) => x.total_cmp(&y),
+                (Some(_), None) => return Ordering::Less,
+                (None, Some(_)) => return Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            };
+            if self.descending {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+        rows
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [body, footer] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let dash = || "-".to_string();
+        let header = Column::ALL.iter().map(|&column| {
+            let arrow = match (column == self.sort, self.descending) {
+                (false, _) => "",
+                (true, true) => " ▼",
+                (true, false) => " ▲",
+            };
+            format!("{}{}", column.title(), arrow)
+        });
+        let rows = self.sorted().into_iter().map(|row| {
+            let cells = vec![
+                row.name.clone(),
+                row.rate.map_or_else(dash, |rate| format!("{:.1}", rate)),
+                format!("{} ms", row.latency_ms),
+                row.memory_mb
+                    .map_or_else(dash, |mb| format!("{:.1} MB", mb)),
+                row.requests.map_or_else(dash, |n| n.to_string()),
+                row.workers.map_or_else(dash, |n| n.to_string()),
+                row.uptime_secs
+                    .map_or_else(dash, |secs| format!("{:.0}s", secs)),
+                row.error.clone().unwrap_or_default(),
+            ];
+            if row.error.is_some() {
+                Row::new(cells).red()
+            } else {
+                Row::new(cells)
+            }
+        });
+        let mut widths = vec![Constraint::Length(16)];
+        widths.extend([Constraint::Length(10); 6]);
+        widths.push(Constraint::Fill(1));
+        let table = Table::new(rows, widths)
+            .header(Row::new(header.chain(["Error".to_string()])).add_modifier(Modifier::BOLD))
+            .block(Block::bordered().title(" top "));
+        frame.render_widget(table, body);
+
+        let status = format!(
+            " q quit · ←/→ sort column · i invert · r refresh · every {}s · {} refreshes",
+            self.interval.as_secs_f64(),
+            self.refreshes
+        );
+        frame.render_widget(Paragraph::new(status).dark_gray(), footer);
+    }
+}
+
+/// Runs the view full-screen until `q`, Esc or Ctrl-C.
+pub async fn run(
+    api: &Api,
+    services: Vec<Service>,
+    interval: Duration,
+    sort: Column,
+) -> Result<(), Box<dyn Error>> {
+    let mut terminal = ratatui::try_init()?;
+    let result = run_loop(&mut terminal, api, Top::new(services, interval, sort)).await;
+    ratatui::restore();
+    result
+}
+
+async fn run_loop(
+    terminal: &mut ratatui::DefaultTerminal,
+    api: &Api,
+    mut top: Top,
+) -> Result<(), Box<dyn Error>> {
+    let mut key_events = dashboard::terminal_events();
+    let mut ticker = tokio::time::interval(top.interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // Fetched alongside the key events, so a slow service can't hold up
+    // `q`; returning drops the set, which aborts a fetch still running.
+    let mut refreshing = JoinSet::new();
+    loop {
+        terminal.draw(|frame| top.draw(frame))?;
+        tokio::select! {
+            _ = ticker.tick() => top.refresh(api, &mut refreshing),
+            Some(fetched) = refreshing.join_next() => {
+                if let Ok(report) = fetched {
+                    top.record(report, Instant::now());
+                }
+            }
+            event = key_events.recv() => match event {
+                Some(Event::Key(key)) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        return Ok(())
+                    }
+                    KeyCode::Right | KeyCode::Char('>') => top.sort = top.sort.step(1),
+                    KeyCode::Left | KeyCode::Char('<') => top.sort = top.sort.step(-1),
+                    KeyCode::Char('i') => top.descending = !top.descending,
+                    KeyCode::Char('r') => top.refresh(api, &mut refreshing),
+                    _ => {}
+                },
+                Some(_) => {}
+                None => return Ok(()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aggregate::{NormalizedMetrics, ServiceMetrics, Totals};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn report(requests: &[(&str, Option<i64>)]) -> AggregateReport {
+        AggregateReport {
+            services: requests
+                .iter()
+                .map(|&(name, requests)| ServiceMetrics {
+                    name: name.to_string(),
+                    url: format!("http://{}", name),
+                    metrics: requests.map(|requests| NormalizedMetrics {
+                        requests: Some(requests),
+                        memory_mb: Some(10.0),
+                        ..NormalizedMetrics::default()
+                    }),
+                    latency_ms: 3,
+                    error: requests.is_none().then(|| "connection refused".to_string()),
+                })
+                .collect(),
+            totals: Totals::default(),
+        }
+    }
+
+    #[test]
+    fn test_rates_and_sorting() {
+        let mut top = Top::new(Vec::new(), Duration::from_secs(2), Column::Rate);
+        let start = Instant::now();
+        top.record(report(&[("go", Some(10)), ("rust", Some(100))]), start);
+        top.record(
+            report(&[("go", Some(50)), ("rust", Some(110)), ("python", None)]),
+            start + Duration::from_secs(2),
+        );
+
+        let names = |top: &Top| -> Vec<String> {
+            top.sorted().iter().map(|row| row.name.clone()).collect()
+        };
+        assert_eq!(top.rows[0].rate, Some(20.0));
+        assert_eq!(names(&top), ["go", "rust", "python"]);
+        top.descending = false;
+        assert_eq!(names(&top), ["rust", "go", "python"]);
+        top.sort = Column::Service;
+        assert_eq!(names(&top), ["go", "python", "rust"]);
+        assert_eq!(Column::Service.step(-1), Column::Uptime);
+    }
+
+    #[test]
+    fn test_shows_a_row_per_service() {
+        let mut top = Top::new(Vec::new(), Duration::from_secs(2), Column::Memory);
+        top.record(
+            report(&[("go", Some(10)), ("python", None)]),
+            Instant::now(),
+        );
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 10)).unwrap();
+        terminal.draw(|frame| top.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        let screen: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+        assert!(screen.contains("Memory ▼"));
+        assert!(screen.contains("10.0 MB"));
+        assert!(screen.contains("connection refused"));
+        assert!(screen.contains("1 refreshes"));
+    }
+}
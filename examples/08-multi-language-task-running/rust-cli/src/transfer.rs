@@ -0,0 +1,243 @@
+use crate::api::{self, Api, Page, Prediction};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// What `export` writes and `import` reads.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Dump {
+    /// Base URL of the API it was exported from.
+    pub source: String,
+    /// The API's time when the last page was fetched.
+    pub exported_at: String,
+    pub count: usize,
+    pub predictions: Vec<Prediction>,
+}
+
+/// What `import` does with a record the target has with the same label and
+/// time but a different confidence.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OnConflict {
+    /// Keep the target's record.
+    #[default]
+    Skip,
+    /// Replace the target's record with the dump's.
+    Overwrite,
+    /// Import nothing if any record conflicts.
+    Fail,
+}
+
+/// A dump record next to the target's record for the same label and time.
+#[derive(Debug, Serialize)]
+pub struct Conflict {
+    pub incoming: Prediction,
+    pub existing: Prediction,
+}
+
+/// What an import wrote, or with `--dry-run` would write.
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub source: String,
+    pub target: String,
+    pub dry_run: bool,
+    /// Records in the dump.
+    pub records: usize,
+    /// Records the target didn't have.
+    pub created: usize,
+    /// Records the target already has exactly; never sent.
+    pub unchanged: usize,
+    /// Conflicts replaced with the dump's record.
+    pub overwritten: usize,
+    /// Conflicts where the target's record was kept.
+    pub skipped: usize,
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Every prediction `api` has, following the pages to the end.
+pub async fn export(api: &Api) -> Result<Dump, Box<dyn Error>> {
+    let all = api::get_all_predictions(api, &Page::default()).await?;
+    Ok(Dump {
+        source: api.base_url().to_string(),
+        exported_at: all.timestamp,
+        count: all.predictions.len(),
+        predictions: all.predictions,
+    })
+}
+
+pub fn load(path: &Path) -> Result<Dump, Box<dyn Error>> {
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    serde_json::from_str(&text)
+        .map_err(|e| format!("{} is not an export dump: {}", path.display(), e).into())
+}
+
+/// Compares `dump` with what `api` already has and writes the difference.
+/// With `dry_run`, or when `on_conflict` is [`OnConflict::Fail`] and a
+/// record conflicts, nothing is written and the report says what would
+/// have been.
+pub async fn import(
+    api: &Api,
+    dump: Dump,
+    on_conflict: OnConflict,
+    dry_run: bool,
+) -> Result<ImportReport, Box<dyn Error>> {
+    let existing = api::get_all_predictions(api, &Page::default()).await?;
+    let records = dump.predictions.len();
+    let (created, conflicts, unchanged) = plan(dump.predictions, &existing.predictions);
+
+    let mut report = ImportReport {
+        source: dump.source,
+        target: api.base_url().to_string(),
+        dry_run,
+        records,
+        created: created.len(),
+        unchanged,
+        overwritten: 0,
+        skipped: 0,
+        conflicts: Vec::new(),
+    };
+    let mut sending = created;
+    match on_conflict {
+        OnConflict::Overwrite => {
+            report.overwritten = conflicts.len();
+            sending.extend(conflicts.iter().map(|c| c.incoming.clone()));
+        }
+        OnConflict::Skip | OnConflict::Fail => report.skipped = conflicts.len(),
+    }
+    let refused = on_conflict == OnConflict::Fail && !conflicts.is_empty();
+    report.conflicts = conflicts;
+    if dry_run || refused || sending.is_empty() {
+        return Ok(report);
+    }
+    api::import_predictions(api, &sending, on_conflict == OnConflict::Overwrite).await?;
+    Ok(report)
+}
+
+/// Splits `incoming` into records the target lacks and ones that conflict
+/// with its records, counting those it already has. Predictions made in the
+/// same second get the same timestamp, so a record is only the same as one
+/// with its label too; one without a timestamp only matches an identical
+/// record.
+fn plan(
+    incoming: Vec<Prediction>,
+    existing: &[Prediction],
+) -> (Vec<Prediction>, Vec<Conflict>, usize) {
+    let mut created = Vec::new();
+    let mut conflicts = Vec::new();
+    let mut unchanged = 0;
+    for record in incoming {
+        if existing.contains(&record) {
+            unchanged += 1;
+            continue;
+        }
+        let same = record.timestamp.as_ref().and_then(|time| {
+            existing
+                .iter()
+                .find(|other| other.timestamp.as_ref() == Some(time) && other.label == record.label)
+        });
+        match same {
+            Some(other) => conflicts.push(Conflict {
+                incoming: record,
+                existing: other.clone(),
+            }),
+            None => created.push(record),
+        }
+    }
+    (created, conflicts, unchanged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn prediction(label: &str, confidence: f64, timestamp: &str) -> Prediction {
+        Prediction {
+            label: label.to_string(),
+            confidence,
+            timestamp: Some(timestamp.to_string()),
+        }
+    }
+
+    fn dump() -> Dump {
+        Dump {
+            source: "http://a".to_string(),
+            exported_at: "t".to_string(),
+            count: 3,
+            predictions: vec![
+                prediction("cats", 0.9, "2024-01-01T00:00:03Z"),
+                prediction("dogs", 0.8, "2024-01-01T00:00:02Z"),
+                prediction("fish", 0.7, "2024-01-01T00:00:01Z"),
+            ],
+        }
+    }
+
+    async fn target(server: &mut mockito::Server) {
+        server
+            .mock("GET", "/predictions")
+            .with_body(
+                json!({
+                    "predictions": [
+                        {"label": "dogs", "confidence": 0.5, "timestamp": "2024-01-01T00:00:02Z"},
+                        {"label": "birds", "confidence": 0.6, "timestamp": "2024-01-01T00:00:03Z"},
+                        {"label": "fish", "confidence": 0.7, "timestamp": "2024-01-01T00:00:01Z"},
+                    ],
+                    "count": 3,
+                    "timestamp": "t",
+                })
+                .to_string(),
+            )
+            .create_async()
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_import_sends_only_what_the_target_lacks() {
+        let mut server = mockito::Server::new_async().await;
+        target(&mut server).await;
+        let write = server
+            .mock("POST", "/predictions")
+            .match_body(mockito::Matcher::Json(json!({
+                "predictions": [
+                    {"label": "cats", "confidence": 0.9, "timestamp": "2024-01-01T00:00:03Z"},
+                    {"label": "dogs", "confidence": 0.8, "timestamp": "2024-01-01T00:00:02Z"},
+                ],
+                "replace": true,
+            })))
+            .with_body(r#"{"imported":2}"#)
+            .create_async()
+            .await;
+
+        let api = Api::for_tests(server.url());
+        let report = import(&api, dump(), OnConflict::Overwrite, false)
+            .await
+            .unwrap();
+
+        write.assert_async().await;
+        assert_eq!(
+            (report.created, report.unchanged, report.overwritten),
+            (1, 1, 1)
+        );
+        assert_eq!(report.conflicts[0].existing.confidence, 0.5);
+    }
+
+    #[tokio::test]
+    async fn test_dry_runs_and_refused_conflicts_write_nothing() {
+        let mut server = mockito::Server::new_async().await;
+        target(&mut server).await;
+        let write = server
+            .mock("POST", "/predictions")
+            .expect(0)
+            .create_async()
+            .await;
+        let api = Api::for_tests(server.url());
+
+        let preview = import(&api, dump(), OnConflict::Skip, true).await.unwrap();
+        assert_eq!((preview.created, preview.skipped), (1, 1));
+        let refused = import(&api, dump(), OnConflict::Fail, false).await.unwrap();
+        assert_eq!(refused.conflicts.len(), 1);
+        write.assert_async().await;
+    }
+}
@@ -0,0 +1,116 @@
+use std::error::Error;
+use std::path::Path;
+
+/// The socket a `unix:///path/to.sock` URL names.
+pub fn socket_path(url: &str) -> Option<&Path> {
+    url.strip_prefix("unix://")
+        .filter(|path| !path.is_empty())
+        .map(Path::new)
+}
+
+/// The base requests to `url` are built on: `url` itself, or for a
+/// `unix://` URL a placeholder `http://localhost`, since its client connects
+/// straight to the socket and only the path and `Host` come from the URL.
+pub fn request_base(url: &str) -> &str {
+    match socket_path(url) {
+        Some(_) => "http://localhost",
+        None => url,
+    }
+}
+
+/// `builder` connecting every request to the socket `path`. A socket that
+/// nothing listens on fails the request as a connection error, like a
+/// closed port, so it fails over and exits as unreachable.
+#[cfg(unix)]
+pub fn connect_to(
+    builder: reqwest::ClientBuilder,
+    path: &Path,
+) -> Result<reqwest::ClientBuilder, Box<dyn Error>> {
+    Ok(builder.unix_socket(path))
+}
+
+#[cfg(not(unix))]
+pub fn connect_to(
+    _builder: reqwest::ClientBuilder,
+    path: &Path,
+) -> Result<reqwest::ClientBuilder, Box<dyn Error>> {
+    Err(format!(
+        "cannot reach the Unix socket {}: unix:// URLs need a Unix-like OS",
+        path.display()
+    )
+    .into())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::api::{self, Api};
+
+    fn socket(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "prediction-cli-{}-{}.sock",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[tokio::test]
+    async fn test_requests_reach_the_socket() {
+        let path = socket("health");
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                axum::Router::new().route(
+                    "/health",
+                    axum::routing::get(|| async {
+                        r#"{"status":"healthy","timestamp":"now","version":"1"}"#
+                    }),
+                ),
+            )
+            .await
+        });
+        let url = format!("unix://{}", path.display());
+
+        let api = Api::for_tests(url.clone());
+        assert_eq!(api.base_url(), url);
+        assert_eq!(api::check_health(&api).await.unwrap().status, "healthy");
+
+        std::fs::remove_file(&path).unwrap();
+        let missing = Api::for_tests(url);
+        let err = api::check_health(&missing).await.unwrap_err();
+        assert_eq!(crate::exit::code_for(&*err), crate::exit::Code::Connection);
+    }
+
+    #[tokio::test]
+    async fn test_a_dead_socket_fails_over() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/health")
+            .with_body(r#"{"status":"healthy","timestamp":"now","version":"1"}"#)
+            .create_async()
+            .await;
+        let mut options = Api::for_tests(format!("unix://{}", socket("dead").display()))
+            .options()
+            .clone();
+        options.fallback_urls = vec![server.url()];
+        let api = Api::new(options).unwrap();
+
+        assert_eq!(api::check_health(&api).await.unwrap().status, "healthy");
+        mock.assert_async().await;
+    }
+
+    #[test]
+    fn test_only_unix_urls_name_a_socket() {
+        assert_eq!(
+            socket_path("unix:///run/api.sock"),
+            Some(Path::new("/run/api.sock"))
+        );
+        assert_eq!(socket_path("http://localhost:8080"), None);
+        assert_eq!(socket_path("unix://"), None);
+        assert_eq!(request_base("unix:///run/api.sock"), "http://localhost");
+        assert_eq!(request_base("http://api:8080"), "http://api:8080");
+    }
+}
@@ -0,0 +1,140 @@
+use crate::api::{self, Api};
+use crate::exit;
+use colored::*;
+use std::error::Error;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+/// Seconds `wait` keeps polling without `--timeout`.
+pub const DEFAULT_WAIT_SECS: u64 = 60;
+
+/// Pause after the first failed poll, doubled after each one up to
+/// [`MAX_POLL_DELAY`].
+const FIRST_POLL_DELAY: Duration = Duration::from_millis(250);
+const MAX_POLL_DELAY: Duration = Duration::from_secs(5);
+
+/// Waits for every API at once, failing with each one that never became
+/// healthy.
+pub async fn wait_all(
+    apis: Vec<Api>,
+    timeout: Duration,
+    quiet: bool,
+) -> Result<(), Box<dyn Error>> {
+    let mut waits = JoinSet::new();
+    for api in apis {
+        waits.spawn(async move {
+            let waited = wait_healthy(&api, timeout, quiet).await;
+            (api.base_url().to_string(), waited)
+        });
+    }
+    let mut failures = Vec::new();
+    for (url, waited) in waits.join_all().await {
+        match waited {
+            Ok(elapsed) if !quiet => println!(
+                "{} {} after {:.1}s",
+                "Healthy:".green().bold(),
+                url,
+                elapsed.as_secs_f64()
+            ),
+            Ok(_) => {}
+            Err(e) => failures.push(e),
+        }
+    }
+    if !failures.is_empty() {
+        return Err(exit::unhealthy(failures.join("\n")));
+    }
+    Ok(())
+}
+
+/// Polls `/health` until it reports `healthy`, returning how long that took,
+/// or the last problem seen once `timeout` has passed. Progress goes to
+/// stderr unless `quiet`.
+pub async fn wait_healthy(api: &Api, timeout: Duration, quiet: bool) -> Result<Duration, String> {
+    let started = Instant::now();
+    let mut delay = FIRST_POLL_DELAY;
+    let mut problem = String::new();
+    for attempt in 1.. {
+        let remaining = timeout.saturating_sub(started.elapsed());
+        match tokio::time::timeout(remaining, api::check_health(api)).await {
+            Ok(Ok(health)) if health.status == "healthy" => return Ok(started.elapsed()),
+            Ok(Ok(health)) => problem = format!("status {}", health.status),
+            Ok(Err(e)) => problem = e.to_string(),
+            // Cut short by the deadline; an earlier answer says more.
+            Err(_) if problem.is_empty() => problem = "timed out".to_string(),
+            Err(_) => {}
+        }
+        let remaining = timeout.saturating_sub(started.elapsed());
+        if remaining.is_zero() {
+            return Err(format!(
+                "{} not healthy after {:.1}s: {}",
+                api.base_url(),
+                timeout.as_secs_f64(),
+                problem
+            ));
+        }
+        if !quiet {
+            eprintln!(
+                "{} {} (attempt {}): {}",
+                "waiting for".dimmed(),
+                api.base_url(),
+                attempt,
+                problem
+            );
+        }
+        tokio::time::sleep(delay.min(remaining)).await;
+        delay = (delay * 2).min(MAX_POLL_DELAY);
+    }
+    unreachable!("polls until healthy or out of time")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api(url: String) -> Api {
+        Api::for_tests(url)
+    }
+
+    fn health(status: &str) -> String {
+        format!(
+            r#"{{"status":"{}","timestamp":"now","version":"1"}}"#,
+            status
+        )
+    }
+
+    #[tokio::test]
+    async fn test_returns_once_healthy() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/health")
+            .with_body(health("healthy"))
+            .create_async()
+            .await;
+
+        let waited = wait_healthy(&api(server.url()), Duration::from_secs(5), true).await;
+
+        assert!(waited.unwrap() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_with_the_last_problem() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/health")
+            .with_body(health("degraded"))
+            .expect_at_least(2)
+            .create_async()
+            .await;
+
+        let err = wait_healthy(&api(server.url()), Duration::from_secs(1), true)
+            .await
+            .unwrap_err();
+
+        mock.assert_async().await;
+        assert!(
+            err.ends_with("not healthy after 1.0s: status degraded"),
+            "{}",
+            err
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use crate::api::{self, Api, MetricsResponse};
+use crate::output::{self, OutputFormat};
+use colored::*;
+use serde::Serialize;
+use std::error::Error;
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+/// Change between two consecutive metrics samples.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricsDelta {
+    pub interval_secs: f64,
+    pub requests: i64,
+    pub requests_per_sec: f64,
+    pub memory_growth_mb: f64,
+}
+
+impl MetricsDelta {
+    /// From `previous` to `current`, `elapsed` apart. A request count that
+    /// went down means the server restarted, so everything counted since
+    /// then is new.
+    pub fn between(
+        previous: &MetricsResponse,
+        current: &MetricsResponse,
+        elapsed: Duration,
+    ) -> Self {
+        let requests = if current.requests_total >= previous.requests_total {
+            current.requests_total - previous.requests_total
+        } else {
+            current.requests_total
+        };
+        let secs = elapsed.as_secs_f64();
+        MetricsDelta {
+            interval_secs: secs,
+            requests,
+            requests_per_sec: if secs > 0.0 {
+                requests as f64 / secs
+            } else {
+                0.0
+            },
+            memory_growth_mb: current.memory_usage_mb - previous.memory_usage_mb,
+        }
+    }
+}
+
+/// One refresh of `metrics --watch`.
+#[derive(Debug, Serialize)]
+pub struct MetricsSample {
+    pub metrics: MetricsResponse,
+    /// Missing for the first sample.
+    pub delta: Option<MetricsDelta>,
+}
+
+/// Polls `/metrics` every `interval` until Ctrl-C, redrawing the display in
+/// place on a terminal. JSON and YAML get one document per sample instead,
+/// so the stream can be piped. Failed polls are shown and polling goes on.
+pub async fn watch_metrics(
+    api: &Api,
+    interval: Duration,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    let redraw =
+        matches!(format, OutputFormat::Plain | OutputFormat::Table) && io::stdout().is_terminal();
+    let mut previous: Option<(Instant, MetricsResponse)> = None;
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+
+        let result = api::get_metrics(api).await;
+        let now = Instant::now();
+        if redraw {
+            // Clear the screen and move the cursor home.
+            print!("\x1b[2J\x1b[H");
+        }
+        match result {
+            Ok(metrics) => {
+                let delta = previous
+                    .as_ref()
+                    .map(|(at, last)| MetricsDelta::between(last, &metrics, now - *at));
+                let sample = MetricsSample { metrics, delta };
+                output::print_metrics_sample(&mut io::stdout(), &sample, format)?;
+                previous = Some((now, sample.metrics));
+            }
+            Err(e) => eprintln!("{} {}", "Error:".red().bold(), e),
+        }
+        if redraw {
+            println!();
+            println!(
+                "Refreshing every {}s, Ctrl-C to stop",
+                interval.as_secs_f64()
+            );
+        }
+        io::stdout().flush()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(requests_total: i64, memory_usage_mb: f64) -> MetricsResponse {
+        MetricsResponse {
+            uptime: "1m".to_string(),
+            requests_total,
+            memory_usage_mb,
+            goroutines: 4,
+            cpu_cores: 2,
+        }
+    }
+
+    #[test]
+    fn test_delta_rates() {
+        let delta = MetricsDelta::between(
+            &metrics(100, 10.0),
+            &metrics(120, 12.5),
+            Duration::from_secs(4),
+        );
+        assert_eq!(delta.requests, 20);
+        assert_eq!(delta.requests_per_sec, 5.0);
+        assert_eq!(delta.memory_growth_mb, 2.5);
+    }
+
+    #[test]
+    fn test_delta_after_restart_counts_from_zero() {
+        let delta = MetricsDelta::between(
+            &metrics(100, 10.0),
+            &metrics(6, 8.0),
+            Duration::from_secs(2),
+        );
+        assert_eq!(delta.requests, 6);
+        assert_eq!(delta.requests_per_sec, 3.0);
+        assert_eq!(delta.memory_growth_mb, -2.0);
+    }
+}
@@ -0,0 +1,239 @@
+//! Platform detection shared by the cross-platform example and any crate that
+//! wants the same facts, such as a server's health endpoint.
+//!
+//! [`detect`] returns a [`PlatformReport`]: what the binary was compiled for,
+//! the SIMD extensions the CPU has and the host's resources, ready to
+//! serialize with serde. A binary whose build script embeds its provenance
+//! adds it with [`provenance!`].
+
+#[cfg(target_os = "macos")]
+mod macos;
+mod provenance;
+mod system;
+#[cfg(target_arch = "wasm32")]
+mod wasm;
+#[cfg(windows)]
+mod windows;
+
+#[cfg(target_os = "macos")]
+pub use macos::MacosInfo;
+pub use provenance::Provenance;
+pub use system::{LoadAverage, SystemInfo};
+#[cfg(target_arch = "wasm32")]
+pub use wasm::WasmInfo;
+#[cfg(windows)]
+pub use windows::WindowsInfo;
+
+use serde::Serialize;
+use std::env;
+
+/// Everything detected about the platform the binary was built for and the
+/// host it runs on.
+#[derive(Serialize)]
+pub struct PlatformReport {
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub family: &'static str,
+    pub pointer_width: u32,
+    pub endianness: &'static str,
+    pub build: BuildInfo,
+    /// How the binary was built, when it sets it from [`provenance!`];
+    /// [`detect`] leaves it out, as a library can't see its dependent's
+    /// build.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    pub features: Vec<&'static str>,
+    /// SIMD extensions the host CPU actually has, detected at runtime.
+    pub simd: Vec<&'static str>,
+    /// Left out where the OS can't be read, such as under WebAssembly.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<SystemInfo>,
+    /// Only in WebAssembly builds.
+    #[cfg(target_arch = "wasm32")]
+    pub wasm: WasmInfo,
+    /// Only in Windows builds.
+    #[cfg(windows)]
+    pub windows: WindowsInfo,
+    /// Only in macOS builds.
+    #[cfg(target_os = "macos")]
+    pub macos: MacosInfo,
+}
+
+/// The cargo profile's kind and this crate's version.
+#[derive(Serialize)]
+pub struct BuildInfo {
+    pub mode: &'static str,
+    pub version: &'static str,
+}
+
+/// Detects the platform. The compile-time facts cost nothing; the SIMD and
+/// system probes ask the CPU and the OS each time.
+pub fn detect() -> PlatformReport {
+    PlatformReport {
+        os: env::consts::OS,
+        arch: env::consts::ARCH,
+        family: env::consts::FAMILY,
+        pointer_width: usize::BITS,
+        endianness: if cfg!(target_endian = "little") {
+            "little"
+        } else {
+            "big"
+        },
+        build: BuildInfo {
+            mode: if cfg!(debug_assertions) {
+                "debug"
+            } else {
+                "release"
+            },
+            version: env!("CARGO_PKG_VERSION"),
+        },
+        provenance: None,
+        features: platform_features(),
+        simd: simd_extensions(),
+        system: system::probe(),
+        #[cfg(target_arch = "wasm32")]
+        wasm: wasm::probe(),
+        #[cfg(windows)]
+        windows: windows::probe(),
+        #[cfg(target_os = "macos")]
+        macos: macos::probe(),
+    }
+}
+
+/// Which SIMD extensions this CPU supports, asked of the CPU itself rather
+/// than assumed from the target the binary was compiled for.
+fn simd_extensions() -> Vec<&'static str> {
+    let mut simd = Vec::new();
+
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        macro_rules! detect {
+            ($($feature:tt),*) => {
+                $(if is_x86_feature_detected!($feature) {
+                    simd.push($feature);
+                })*
+            };
+        }
+        detect!(
+            "sse", "sse2", "sse3", "ssse3", "sse4.1", "sse4.2", "avx", "avx2", "fma", "avx512f"
+        );
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        macro_rules! detect {
+            ($($feature:tt),*) => {
+                $(if std::arch::is_aarch64_feature_detected!($feature) {
+                    simd.push($feature);
+                })*
+            };
+        }
+        detect!("neon", "fp16", "dotprod", "sve", "sve2");
+    }
+
+    // WebAssembly can't ask at runtime: a module built with SIMD only loads
+    // in an engine that has it.
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    simd.push("simd128");
+
+    simd
+}
+
+fn platform_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+
+    #[cfg(target_os = "linux")]
+    features.extend([
+        "Linux-specific code paths enabled",
+        "POSIX API available",
+        "Native threading support",
+    ]);
+
+    #[cfg(target_os = "macos")]
+    features.extend([
+        "macOS-specific code paths enabled",
+        "Apple frameworks available",
+        "Grand Central Dispatch support",
+    ]);
+
+    #[cfg(target_os = "windows")]
+    features.extend([
+        "Windows-specific code paths enabled",
+        "Windows API available",
+        "MSVC runtime linked",
+    ]);
+
+    #[cfg(target_arch = "x86_64")]
+    features.push("x86-64 instruction set");
+
+    #[cfg(target_arch = "aarch64")]
+    features.push("ARM64 instruction set");
+
+    #[cfg(target_arch = "wasm32")]
+    features.push("WebAssembly (wasm32) instruction set");
+
+    #[cfg(target_os = "wasi")]
+    features.push("WASI system interface");
+
+    #[cfg(target_env = "gnu")]
+    features.push("GNU environment (glibc)");
+
+    #[cfg(target_env = "msvc")]
+    features.push("MSVC environment");
+
+    features
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_matches_the_compile_target() {
+        let report = detect();
+        assert_eq!(report.os, env::consts::OS);
+        assert_eq!(report.arch, env::consts::ARCH);
+        assert_eq!(report.family, env::consts::FAMILY);
+        assert_eq!(report.pointer_width, usize::BITS);
+        assert_eq!(
+            report.endianness,
+            if cfg!(target_endian = "little") {
+                "little"
+            } else {
+                "big"
+            }
+        );
+        assert_eq!(report.build.version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn test_provenance_is_left_to_the_binary() {
+        let report = detect();
+        assert!(report.provenance.is_none());
+        let json = serde_json::to_value(&report).unwrap();
+        assert!(json.get("provenance").is_none());
+    }
+
+    #[test]
+    fn test_report_serializes_the_system() {
+        let json = serde_json::to_value(detect()).unwrap();
+        if sysinfo::IS_SUPPORTED_SYSTEM {
+            let system = &json["system"];
+            assert!(system["logical_cores"].as_u64().unwrap() > 0);
+            assert!(system["total_memory_bytes"].as_u64().unwrap() > 0);
+        } else {
+            assert!(json.get("system").is_none());
+        }
+    }
+
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    #[test]
+    fn test_glibc_version_on_linux_gnu() {
+        let version = system::glibc_version().unwrap();
+        assert!(version.starts_with("2."), "{}", version);
+        assert_eq!(
+            detect().system.unwrap().glibc_version.as_deref(),
+            Some(version.as_str())
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use serde::Serialize;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::{mem, ptr};
+
+extern "C" {
+    fn sysctlbyname(
+        name: *const c_char,
+        oldp: *mut c_void,
+        oldlenp: *mut usize,
+        newp: *mut c_void,
+        newlen: usize,
+    ) -> c_int;
+}
+
+/// The Mac the binary is running on, read from `sysctl`, so a binary built
+/// for one architecture can tell what it actually ran on.
+#[derive(Serialize)]
+pub struct MacosInfo {
+    /// Such as "Apple M2 Pro", or the Intel CPU's brand string.
+    pub chip: Option<String>,
+    /// The hardware model, such as "Mac14,9".
+    pub model: Option<String>,
+    /// Whether the machine has an Apple silicon CPU, even if this binary is
+    /// an x86-64 one running under Rosetta.
+    pub apple_silicon: bool,
+    /// Whether this process is x86-64 code translated by Rosetta 2.
+    pub rosetta: bool,
+    /// Only on CPUs with more than one kind of core.
+    pub performance_cores: Option<u32>,
+    pub efficiency_cores: Option<u32>,
+}
+
+pub fn probe() -> MacosInfo {
+    // Apple silicon lists its kinds of cores fastest first, as perflevel0,
+    // perflevel1 and so on; Intel Macs have a single level.
+    let levels = int("hw.nperflevels").unwrap_or(1);
+    let cores = |level: u32| int(&format!("hw.perflevel{}.physicalcpu", level));
+    let (performance_cores, efficiency_cores) = if levels > 1 {
+        (cores(0), cores(1))
+    } else {
+        (None, None)
+    };
+    MacosInfo {
+        chip: string("machdep.cpu.brand_string"),
+        model: string("hw.model"),
+        apple_silicon: int("hw.optional.arm64") == Some(1),
+        // Missing altogether where Rosetta isn't installed.
+        rosetta: int("sysctl.proc_translated") == Some(1),
+        performance_cores,
+        efficiency_cores,
+    }
+}
+
+fn int(name: &str) -> Option<u32> {
+    let name = CString::new(name).ok()?;
+    let mut value: c_int = 0;
+    let mut size = mem::size_of_val(&value);
+    // SAFETY: `name` is NUL-terminated and `size` is `value`'s size, which
+    // sysctl won't write past.
+    let status = unsafe {
+        sysctlbyname(
+            name.as_ptr(),
+            (&mut value as *mut c_int).cast(),
+            &mut size,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    (status == 0).then_some(value as u32)
+}
+
+fn string(name: &str) -> Option<String> {
+    let name = CString::new(name).ok()?;
+    let mut size = 0;
+    // SAFETY: with no buffer, sysctl only reports the size the value needs.
+    let status = unsafe {
+        sysctlbyname(
+            name.as_ptr(),
+            ptr::null_mut(),
+            &mut size,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    if status != 0 || size == 0 {
+        return None;
+    }
+    let mut buffer = vec![0u8; size];
+    // SAFETY: `buffer` holds the `size` bytes sysctl asked for.
+    let status = unsafe {
+        sysctlbyname(
+            name.as_ptr(),
+            buffer.as_mut_ptr().cast(),
+            &mut size,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    if status != 0 {
+        return None;
+    }
+    let value = CStr::from_bytes_until_nul(&buffer).ok()?;
+    Some(value.to_string_lossy().trim().to_string()).filter(|value| !value.is_empty())
+}
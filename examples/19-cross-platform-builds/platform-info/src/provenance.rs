@@ -0,0 +1,40 @@
+use serde::Serialize;
+
+/// How a binary was built, as embedded by its build script; see
+/// [`provenance!`](crate::provenance!).
+#[derive(Serialize)]
+pub struct Provenance {
+    pub target: &'static str,
+    pub host: &'static str,
+    /// The linker configured for the target, or `default` for rustc's own.
+    pub linker: &'static str,
+    pub rustc: &'static str,
+    pub profile: &'static str,
+    pub opt_level: &'static str,
+    pub features: Vec<&'static str>,
+    /// The commit built, suffixed `-dirty` for uncommitted changes.
+    pub git_sha: Option<&'static str>,
+}
+
+/// The [`Provenance`] of the crate calling it, read from the `PROVENANCE_*`
+/// variables its own build script sets with `cargo:rustc-env`, so that the
+/// profile and features are the binary's and not this library's. See
+/// `rust-bin/build.rs` for a build script that sets them all.
+#[macro_export]
+macro_rules! provenance {
+    () => {
+        $crate::Provenance {
+            target: env!("PROVENANCE_TARGET"),
+            host: env!("PROVENANCE_HOST"),
+            linker: env!("PROVENANCE_LINKER"),
+            rustc: env!("PROVENANCE_RUSTC"),
+            profile: env!("PROVENANCE_PROFILE"),
+            opt_level: env!("PROVENANCE_OPT_LEVEL"),
+            features: env!("PROVENANCE_FEATURES")
+                .split(',')
+                .filter(|feature| !feature.is_empty())
+                .collect(),
+            git_sha: Some(env!("PROVENANCE_GIT_SHA")).filter(|sha| !sha.is_empty()),
+        }
+    };
+}
@@ -0,0 +1,79 @@
+use serde::Serialize;
+use sysinfo::{CpuRefreshKind, MemoryRefreshKind, RefreshKind, System};
+
+/// The host's resources at the moment the binary ran, read from the OS
+/// rather than decided at compile time.
+#[derive(Serialize)]
+pub struct SystemInfo {
+    pub hostname: Option<String>,
+    /// The OS's own name and version, such as "Linux (Debian GNU/Linux 12)".
+    pub os_version: Option<String>,
+    pub cpu_model: Option<String>,
+    /// The glibc the binary is running against; `None` off glibc.
+    pub glibc_version: Option<String>,
+    pub logical_cores: usize,
+    pub physical_cores: Option<usize>,
+    pub total_memory_bytes: u64,
+    pub available_memory_bytes: u64,
+    pub uptime_secs: u64,
+    /// Not kept by Windows.
+    pub load_average: Option<LoadAverage>,
+}
+
+/// Runnable processes averaged over the last one, five and fifteen minutes.
+#[derive(Serialize)]
+pub struct LoadAverage {
+    pub one: f64,
+    pub five: f64,
+    pub fifteen: f64,
+}
+
+/// `None` where sysinfo can't read the OS, such as under WebAssembly.
+pub fn probe() -> Option<SystemInfo> {
+    if !sysinfo::IS_SUPPORTED_SYSTEM {
+        return None;
+    }
+    let system = System::new_with_specifics(
+        RefreshKind::nothing()
+            .with_memory(MemoryRefreshKind::nothing().with_ram())
+            .with_cpu(CpuRefreshKind::nothing()),
+    );
+    let load = System::load_average();
+    Some(SystemInfo {
+        hostname: System::host_name(),
+        os_version: System::long_os_version(),
+        cpu_model: system
+            .cpus()
+            .first()
+            .map(|cpu| cpu.brand().trim().to_string())
+            .filter(|brand| !brand.is_empty()),
+        glibc_version: glibc_version(),
+        logical_cores: system.cpus().len(),
+        physical_cores: system.physical_core_count(),
+        total_memory_bytes: system.total_memory(),
+        available_memory_bytes: system.available_memory(),
+        uptime_secs: System::uptime(),
+        load_average: (!cfg!(windows)).then_some(LoadAverage {
+            one: load.one,
+            five: load.five,
+            fifteen: load.fifteen,
+        }),
+    })
+}
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+pub(crate) fn glibc_version() -> Option<String> {
+    use std::ffi::{c_char, CStr};
+
+    extern "C" {
+        fn gnu_get_libc_version() -> *const c_char;
+    }
+    // SAFETY: glibc returns a pointer to a static, NUL-terminated string.
+    let version = unsafe { CStr::from_ptr(gnu_get_libc_version()) };
+    Some(version.to_string_lossy().into_owned())
+}
+
+#[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+pub(crate) fn glibc_version() -> Option<String> {
+    None
+}
@@ -0,0 +1,37 @@
+use serde::Serialize;
+use std::env;
+use std::path::Path;
+
+/// What the WebAssembly runtime, such as wasmtime, gave the module. WASI
+/// grants capabilities explicitly, so these say as much about how the module
+/// was started (`wasmtime run --dir . --env KEY=value`) as about the runtime.
+#[derive(Serialize)]
+pub struct WasmInfo {
+    /// `wasip1`, `wasip2`, or `none` when the embedder provides no system
+    /// interface, as for `wasm32-unknown-unknown`.
+    pub interface: &'static str,
+    /// The linear memory's current size; it starts small and grows on demand.
+    pub memory_bytes: usize,
+    /// Whether the module was built with shared memory and atomics.
+    pub threads: bool,
+    /// Environment variables the runtime passed in.
+    pub env_vars: usize,
+    /// Whether the runtime preopened the current directory.
+    pub current_dir: bool,
+}
+
+pub fn probe() -> WasmInfo {
+    WasmInfo {
+        interface: if cfg!(all(target_os = "wasi", target_env = "p2")) {
+            "wasip2"
+        } else if cfg!(target_os = "wasi") {
+            "wasip1"
+        } else {
+            "none"
+        },
+        memory_bytes: std::arch::wasm32::memory_size::<0>() * 65536,
+        threads: cfg!(target_feature = "atomics"),
+        env_vars: env::vars_os().count(),
+        current_dir: Path::new(".").metadata().is_ok(),
+    }
+}
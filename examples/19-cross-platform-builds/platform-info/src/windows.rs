@@ -0,0 +1,127 @@
+use serde::Serialize;
+use std::{mem, ptr};
+use windows_sys::Wdk::System::SystemServices::RtlGetVersion;
+use windows_sys::Win32::Foundation::ERROR_SUCCESS;
+use windows_sys::Win32::System::Registry::{
+    RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_DWORD, RRF_RT_REG_SZ,
+};
+use windows_sys::Win32::System::SystemInformation::{
+    IMAGE_FILE_MACHINE, IMAGE_FILE_MACHINE_AMD64, IMAGE_FILE_MACHINE_ARM64,
+    IMAGE_FILE_MACHINE_I386, IMAGE_FILE_MACHINE_UNKNOWN, OSVERSIONINFOW,
+};
+use windows_sys::Win32::System::Threading::{GetCurrentProcess, IsWow64Process2};
+
+const CURRENT_VERSION: &str = r"SOFTWARE\Microsoft\Windows NT\CurrentVersion";
+const FILE_SYSTEM: &str = r"SYSTEM\CurrentControlSet\Control\FileSystem";
+
+/// The Windows the binary is running on, asked of Windows itself rather than
+/// read off `cfg(target_os)`.
+#[derive(Serialize)]
+pub struct WindowsInfo {
+    /// Such as "10.0.22631": Windows 11 still reports 10.0, so the build
+    /// number is what tells them apart.
+    pub version: String,
+    pub build: u32,
+    /// The update build revision, the ".3593" of "22631.3593".
+    pub revision: Option<u32>,
+    /// Such as "Professional" or "ServerDatacenter".
+    pub edition: Option<String>,
+    /// The feature update, such as "23H2".
+    pub display_version: Option<String>,
+    /// Whether paths beyond 260 characters are enabled system-wide
+    /// (`LongPathsEnabled`); `None` when the setting is absent.
+    pub long_paths: Option<bool>,
+    /// Whether this is a 32-bit process under WOW64 on a 64-bit Windows.
+    pub wow64: bool,
+    /// The machine's own architecture, which differs from the report's
+    /// `arch` when the binary is emulated, as x86-64 code on ARM64 is.
+    pub native_arch: Option<&'static str>,
+}
+
+pub fn probe() -> WindowsInfo {
+    // RtlGetVersion, unlike GetVersionEx, answers the same whatever Windows
+    // versions the executable's manifest claims to support.
+    // SAFETY: `info` is zeroed and sized as RtlGetVersion requires.
+    let (major, minor, build) = unsafe {
+        let mut info: OSVERSIONINFOW = mem::zeroed();
+        info.dwOSVersionInfoSize = mem::size_of::<OSVERSIONINFOW>() as u32;
+        RtlGetVersion(&mut info);
+        (info.dwMajorVersion, info.dwMinorVersion, info.dwBuildNumber)
+    };
+
+    let mut process = IMAGE_FILE_MACHINE_UNKNOWN;
+    let mut native = IMAGE_FILE_MACHINE_UNKNOWN;
+    // SAFETY: the current-process pseudo handle is always valid, and both
+    // out-pointers are to live locals.
+    let machines = unsafe { IsWow64Process2(GetCurrentProcess(), &mut process, &mut native) };
+
+    WindowsInfo {
+        version: format!("{}.{}.{}", major, minor, build),
+        build,
+        revision: registry_dword(CURRENT_VERSION, "UBR"),
+        edition: registry_string(CURRENT_VERSION, "EditionID"),
+        display_version: registry_string(CURRENT_VERSION, "DisplayVersion"),
+        long_paths: registry_dword(FILE_SYSTEM, "LongPathsEnabled").map(|enabled| enabled != 0),
+        wow64: machines != 0 && process != IMAGE_FILE_MACHINE_UNKNOWN,
+        native_arch: if machines != 0 { arch(native) } else { None },
+    }
+}
+
+/// `machine` named as `std::env::consts::ARCH` names it.
+fn arch(machine: IMAGE_FILE_MACHINE) -> Option<&'static str> {
+    match machine {
+        IMAGE_FILE_MACHINE_AMD64 => Some("x86_64"),
+        IMAGE_FILE_MACHINE_ARM64 => Some("aarch64"),
+        IMAGE_FILE_MACHINE_I386 => Some("x86"),
+        _ => None,
+    }
+}
+
+fn registry_string(key: &str, value: &str) -> Option<String> {
+    let (key, value) = (wide(key), wide(value));
+    let mut data = [0u16; 256];
+    let mut size = mem::size_of_val(&data) as u32;
+    // SAFETY: both names are NUL-terminated, and `size` is `data`'s size in
+    // bytes, which RegGetValueW won't write past.
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            key.as_ptr(),
+            value.as_ptr(),
+            RRF_RT_REG_SZ,
+            ptr::null_mut(),
+            data.as_mut_ptr().cast(),
+            &mut size,
+        )
+    };
+    if status != ERROR_SUCCESS {
+        return None;
+    }
+    // `size` now counts the bytes written, terminating NUL included.
+    let len = (size as usize / 2).saturating_sub(1);
+    Some(String::from_utf16_lossy(&data[..len]))
+}
+
+fn registry_dword(key: &str, value: &str) -> Option<u32> {
+    let (key, value) = (wide(key), wide(value));
+    let mut data = 0u32;
+    let mut size = mem::size_of_val(&data) as u32;
+    // SAFETY: as for `registry_string`, with room for exactly one DWORD.
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            key.as_ptr(),
+            value.as_ptr(),
+            RRF_RT_REG_DWORD,
+            ptr::null_mut(),
+            (&mut data as *mut u32).cast(),
+            &mut size,
+        )
+    };
+    (status == ERROR_SUCCESS).then_some(data)
+}
+
+/// `text` as the NUL-terminated UTF-16 the W functions take.
+fn wide(text: &str) -> Vec<u16> {
+    text.encode_utf16().chain(Some(0)).collect()
+}
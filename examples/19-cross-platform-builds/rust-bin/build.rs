@@ -0,0 +1,66 @@
+//! Embeds how the binary was built, for the report's "Build Provenance"
+//! section. Nothing here depends on the time of the build, so building the
+//! same commit the same way embeds the same values.
+
+use std::env;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    // Edited sources change whether the tree is dirty.
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-env-changed=RUSTC_LINKER");
+
+    let var = |name: &str| env::var(name).unwrap_or_default();
+    let rustc = output(&var("RUSTC"), &["--version"]).unwrap_or_else(|| "unknown".to_string());
+    let mut features: Vec<String> = env::vars()
+        .filter_map(|(name, _)| name.strip_prefix("CARGO_FEATURE_").map(str::to_string))
+        .map(|feature| feature.to_lowercase().replace('_', "-"))
+        .collect();
+    features.sort();
+
+    let embed = |name: &str, value: &str| println!("cargo:rustc-env={}={}", name, value);
+    embed("PROVENANCE_TARGET", &var("TARGET"));
+    embed("PROVENANCE_HOST", &var("HOST"));
+    embed(
+        "PROVENANCE_LINKER",
+        &env::var("RUSTC_LINKER").unwrap_or_else(|_| "default".to_string()),
+    );
+    embed("PROVENANCE_RUSTC", &rustc);
+    embed("PROVENANCE_PROFILE", &var("PROFILE"));
+    embed("PROVENANCE_OPT_LEVEL", &var("OPT_LEVEL"));
+    embed("PROVENANCE_FEATURES", &features.join(","));
+    embed("PROVENANCE_GIT_SHA", &git_sha().unwrap_or_default());
+}
+
+/// The commit being built, with `-dirty` when the working tree has changes,
+/// or `None` outside a git checkout.
+fn git_sha() -> Option<String> {
+    // Rebuild when HEAD moves to another commit or branch.
+    for path in ["HEAD", "index"] {
+        if let Some(path) = output("git", &["rev-parse", "--git-path", path]) {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+    if let Some(head) = output("git", &["symbolic-ref", "-q", "HEAD"]) {
+        if let Some(path) = output("git", &["rev-parse", "--git-path", &head]) {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+
+    let sha = output("git", &["rev-parse", "--short=12", "HEAD"])?;
+    let dirty = Command::new("git")
+        .args(["diff-index", "--quiet", "HEAD", "--"])
+        .status()
+        .is_ok_and(|status| !status.success());
+    Some(if dirty { format!("{}-dirty", sha) } else { sha })
+}
+
+/// What `program` printed, trimmed, if it ran and succeeded.
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
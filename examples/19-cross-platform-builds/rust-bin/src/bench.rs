@@ -0,0 +1,147 @@
+use crate::simd;
+use serde::Serialize;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// What `bench` measured, and on which architecture, so runs across a build
+/// matrix can be compared.
+#[derive(Serialize)]
+pub struct BenchReport {
+    pub arch: &'static str,
+    pub target: &'static str,
+    pub benchmarks: Vec<Measurement>,
+}
+
+/// How one micro-benchmark did.
+#[derive(Serialize)]
+pub struct Measurement {
+    pub name: String,
+    pub iterations: u32,
+    /// Elements handled per iteration.
+    pub size: usize,
+    pub mean_ms: f64,
+    pub min_ms: f64,
+    /// Million elements per second, at the fastest iteration.
+    pub throughput_meps: f64,
+    /// For a SIMD dot product, how many times faster than the scalar one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speedup: Option<f64>,
+}
+
+/// Runs each micro-benchmark `iterations` times over `size` elements: a
+/// floating-point sum, a memory copy, an integer sort, an FNV-1a hash, and a
+/// dot product in scalar and every SIMD form the CPU supports.
+pub fn run(arch: &'static str, target: &'static str, iterations: u32, size: usize) -> BenchReport {
+    let floats: Vec<f64> = (0..size).map(|i| i as f64 * 0.5).collect();
+    let bytes: Vec<u8> = (0..size).map(|i| i as u8).collect();
+    let integers: Vec<u64> = pseudo_random(size);
+    let (a, b): (Vec<f32>, Vec<f32>) = (0..size)
+        .map(|i| ((i % 7) as f32 * 0.25, (i % 5) as f32 * 0.5))
+        .unzip();
+
+    let mut benchmarks = vec![
+        measure("sum", iterations, size, || {
+            black_box(black_box(&floats).iter().sum::<f64>());
+        }),
+        measure("memcpy", iterations, size, || {
+            black_box(black_box(&bytes).clone());
+        }),
+        measure("sort", iterations, size, || {
+            let mut integers = black_box(&integers).clone();
+            integers.sort_unstable();
+            black_box(integers);
+        }),
+        measure("hash", iterations, size, || {
+            black_box(fnv1a(black_box(&bytes)));
+        }),
+    ];
+
+    let mut scalar_ms = None;
+    for (variant, kernel) in simd::kernels() {
+        let mut measurement = measure(&format!("dot/{}", variant), iterations, size, || {
+            black_box(kernel(black_box(&a), black_box(&b)));
+        });
+        match scalar_ms {
+            None => scalar_ms = Some(measurement.min_ms),
+            Some(scalar_ms) => {
+                measurement.speedup = Some(scalar_ms / measurement.min_ms.max(f64::EPSILON))
+            }
+        }
+        benchmarks.push(measurement);
+    }
+
+    BenchReport {
+        arch,
+        target,
+        benchmarks,
+    }
+}
+
+fn measure(name: &str, iterations: u32, size: usize, mut work: impl FnMut()) -> Measurement {
+    let mut total = Duration::ZERO;
+    let mut min = Duration::MAX;
+    for _ in 0..iterations {
+        let started = Instant::now();
+        work();
+        let elapsed = started.elapsed();
+        total += elapsed;
+        min = min.min(elapsed);
+    }
+    let min_secs = min.as_secs_f64().max(f64::EPSILON);
+    Measurement {
+        name: name.to_string(),
+        iterations,
+        size,
+        mean_ms: total.as_secs_f64() * 1000.0 / f64::from(iterations),
+        min_ms: min.as_secs_f64() * 1000.0,
+        throughput_meps: size as f64 / min_secs / 1_000_000.0,
+        speedup: None,
+    }
+}
+
+/// A repeatable xorshift sequence, so every run sorts the same input.
+fn pseudo_random(size: usize) -> Vec<u64> {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    (0..size)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        })
+        .collect()
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+pub fn print_text(report: &BenchReport) {
+    println!("Benchmarks on {} ({}):\n", report.arch, report.target);
+    println!(
+        "{:<11} {:>12} {:>12} {:>16} {:>8}",
+        "Bench", "Mean", "Min", "Throughput", "Speedup"
+    );
+    for measurement in &report.benchmarks {
+        let speedup = measurement
+            .speedup
+            .map_or(String::new(), |speedup| format!("{:.2}x", speedup));
+        let line = format!(
+            "{:<11} {:>9.3} ms {:>9.3} ms {:>8.1} Melem/s {:>8}",
+            measurement.name,
+            measurement.mean_ms,
+            measurement.min_ms,
+            measurement.throughput_meps,
+            speedup
+        );
+        println!("{}", line.trim_end());
+    }
+    if let Some(first) = report.benchmarks.first() {
+        println!(
+            "\n{} iterations over {} elements each; speedups are against dot/scalar",
+            first.iterations, first.size
+        );
+    }
+}
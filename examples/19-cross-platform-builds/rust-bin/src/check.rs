@@ -0,0 +1,532 @@
+use clap::Args;
+use platform_info::PlatformReport;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The exit code for a policy file that can't be read or understood.
+const INVALID_POLICY: i32 = 3;
+
+/// Shown at the end of `check --help`.
+pub const EXIT_CODES: &str = "\
+Exit codes:
+  0   every requirement is met
+  2   invalid arguments
+  3   the policy file can't be read or parsed
+  10  wrong OS, OS family or architecture
+  11  wrong pointer width or byte order
+  12  glibc missing or older than required
+  13  a required CPU feature is missing
+  14  not a release build
+When several requirements fail, the code is the first one's in this list.";
+
+/// What `check` requires of the platform, from the flags and a `--policy`
+/// file together. Each one left out is not checked; a repeated `--os` or
+/// `--arch` accepts any of the values given, while every `--simd` extension
+/// is required.
+#[derive(Args)]
+pub struct Requirements {
+    /// TOML file of requirements, added to the flags
+    #[arg(long, value_name = "PATH")]
+    policy: Option<PathBuf>,
+
+    /// Operating system, such as linux, macos or windows
+    #[arg(long)]
+    os: Vec<String>,
+
+    /// CPU architecture, such as x86_64 or aarch64
+    #[arg(long)]
+    arch: Vec<String>,
+
+    /// OS family: unix or windows
+    #[arg(long)]
+    family: Option<String>,
+
+    /// Pointer width in bits
+    #[arg(long, value_name = "BITS")]
+    pointer_width: Option<u32>,
+
+    /// Byte order: little or big
+    #[arg(long)]
+    endianness: Option<String>,
+
+    /// SIMD extension the CPU must have, such as avx2 or neon
+    #[arg(long, value_name = "EXTENSION")]
+    simd: Vec<String>,
+
+    /// Require an optimized (release) build
+    #[arg(long)]
+    release: bool,
+}
+
+/// The layout of a `--policy` file. Every key is optional:
+///
+/// ```toml
+/// os = ["linux", "macos"]
+/// family = "unix"
+/// min_pointer_width = 64
+/// min_glibc = "2.31"
+/// cpu_features = ["sse4.2", "avx2"]
+/// release = true
+/// ```
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Policy {
+    /// Any of these operating systems.
+    os: Vec<String>,
+    /// Any of these architectures.
+    arch: Vec<String>,
+    family: Option<String>,
+    pointer_width: Option<u32>,
+    min_pointer_width: Option<u32>,
+    endianness: Option<String>,
+    /// Oldest glibc accepted, such as `"2.31"`.
+    min_glibc: Option<String>,
+    /// Every one of these SIMD extensions, as the report names them.
+    cpu_features: Vec<String>,
+    release: bool,
+}
+
+impl Requirements {
+    /// The policy file's requirements and the flags', where a flag wins over
+    /// the file's value for the same requirement and adds to its lists.
+    pub fn policy(&self) -> Result<Policy, InvalidPolicy> {
+        let file = match &self.policy {
+            Some(path) => load(path)?,
+            None => Policy::default(),
+        };
+        let either = |flag: &Vec<String>, file: Vec<String>| {
+            file.into_iter().chain(flag.iter().cloned()).collect()
+        };
+        Ok(Policy {
+            os: either(&self.os, file.os),
+            arch: either(&self.arch, file.arch),
+            family: self.family.clone().or(file.family),
+            pointer_width: self.pointer_width.or(file.pointer_width),
+            min_pointer_width: file.min_pointer_width,
+            endianness: self.endianness.clone().or(file.endianness),
+            min_glibc: file.min_glibc,
+            cpu_features: either(&self.simd, file.cpu_features),
+            release: self.release || file.release,
+        })
+    }
+}
+
+/// Why a `--policy` file can't be used.
+#[derive(Debug)]
+pub struct InvalidPolicy(String);
+
+impl InvalidPolicy {
+    /// What `check` exits with; see [`EXIT_CODES`].
+    pub fn exit_code(&self) -> i32 {
+        INVALID_POLICY
+    }
+}
+
+impl fmt::Display for InvalidPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for InvalidPolicy {}
+
+fn load(path: &Path) -> Result<Policy, InvalidPolicy> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| InvalidPolicy(format!("cannot read policy {}: {}", path.display(), e)))?;
+    let policy: Policy = toml::from_str(&text)
+        .map_err(|e| InvalidPolicy(format!("invalid policy {}: {}", path.display(), e)))?;
+    if let Some(min) = &policy.min_glibc {
+        version(min).ok_or_else(|| {
+            InvalidPolicy(format!(
+                "invalid policy {}: min_glibc {:?} is not a version like \"2.31\"",
+                path.display(),
+                min
+            ))
+        })?;
+    }
+    Ok(policy)
+}
+
+/// Whether the platform met the requirements, as printed by `check`.
+#[derive(Serialize)]
+pub struct Outcome {
+    pub passed: bool,
+    /// What the process exits with; see [`EXIT_CODES`].
+    pub exit_code: i32,
+    pub checks: Vec<Check>,
+}
+
+/// One requirement and what the platform turned out to have.
+#[derive(Serialize)]
+pub struct Check {
+    pub requirement: &'static str,
+    pub expected: String,
+    pub actual: String,
+    pub passed: bool,
+    #[serde(skip)]
+    kind: Kind,
+}
+
+/// What a requirement is about, which picks the exit code when it fails.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Kind {
+    Platform = 10,
+    Layout = 11,
+    Glibc = 12,
+    CpuFeatures = 13,
+    Build = 14,
+}
+
+pub fn run(policy: &Policy, report: &PlatformReport) -> Outcome {
+    let mut checks = Vec::new();
+    let mut check = |kind, requirement, expected: String, actual: String, passed| {
+        checks.push(Check {
+            requirement,
+            expected,
+            actual,
+            passed,
+            kind,
+        })
+    };
+
+    for (requirement, expected, actual) in [
+        ("os", &policy.os, report.os),
+        ("arch", &policy.arch, report.arch),
+    ] {
+        if !expected.is_empty() {
+            let passed = expected.iter().any(|value| value == actual);
+            check(
+                Kind::Platform,
+                requirement,
+                expected.join(" or "),
+                actual.to_string(),
+                passed,
+            );
+        }
+    }
+    if let Some(family) = &policy.family {
+        let passed = family == report.family;
+        check(
+            Kind::Platform,
+            "family",
+            family.clone(),
+            report.family.to_string(),
+            passed,
+        );
+    }
+    if let Some(bits) = policy.pointer_width {
+        let actual = report.pointer_width.to_string();
+        check(
+            Kind::Layout,
+            "pointer_width",
+            bits.to_string(),
+            actual,
+            bits == report.pointer_width,
+        );
+    }
+    if let Some(bits) = policy.min_pointer_width {
+        let actual = report.pointer_width.to_string();
+        check(
+            Kind::Layout,
+            "min_pointer_width",
+            format!(">= {}", bits),
+            actual,
+            report.pointer_width >= bits,
+        );
+    }
+    if let Some(endianness) = &policy.endianness {
+        let passed = endianness == report.endianness;
+        check(
+            Kind::Layout,
+            "endianness",
+            endianness.clone(),
+            report.endianness.to_string(),
+            passed,
+        );
+    }
+    if let Some(min) = &policy.min_glibc {
+        let glibc = report
+            .system
+            .as_ref()
+            .and_then(|system| system.glibc_version.as_deref());
+        let passed = glibc.and_then(version) >= version(min) && glibc.is_some();
+        let actual = glibc.unwrap_or("none").to_string();
+        check(
+            Kind::Glibc,
+            "min_glibc",
+            format!(">= {}", min),
+            actual,
+            passed,
+        );
+    }
+    for feature in &policy.cpu_features {
+        let found = report.simd.contains(&feature.as_str());
+        let actual = if found {
+            feature.clone()
+        } else {
+            "missing".to_string()
+        };
+        check(
+            Kind::CpuFeatures,
+            "cpu_feature",
+            feature.clone(),
+            actual,
+            found,
+        );
+    }
+    if policy.release {
+        let passed = report.build.mode == "release";
+        check(
+            Kind::Build,
+            "build",
+            "release".to_string(),
+            report.build.mode.to_string(),
+            passed,
+        );
+    }
+
+    let failed = checks
+        .iter()
+        .filter(|check| !check.passed)
+        .map(|check| check.kind)
+        .min();
+    Outcome {
+        passed: failed.is_none(),
+        exit_code: failed.map_or(0, |kind| kind as i32),
+        checks,
+    }
+}
+
+/// `"2.31"` as `[2, 31]`, so versions compare part by part. Trailing zero
+/// parts are dropped, so `"2.31.0"` is the same version as `"2.31"`.
+fn version(text: &str) -> Option<Vec<u32>> {
+    let mut parts: Vec<u32> = text
+        .trim()
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    while parts.last() == Some(&0) {
+        parts.pop();
+    }
+    Some(parts)
+}
+
+pub fn print_text(outcome: &Outcome) {
+    if outcome.checks.is_empty() {
+        println!("No requirements given; nothing to check.");
+        return;
+    }
+    for check in &outcome.checks {
+        if check.passed {
+            println!("  ✓ {:<17} {}", check.requirement, check.actual);
+        } else {
+            println!(
+                "  ✗ {:<17} {} (expected {})",
+                check.requirement, check.actual, check.expected
+            );
+        }
+    }
+    let failed = outcome.checks.iter().filter(|check| !check.passed).count();
+    if failed == 0 {
+        println!(
+            "\n✓ Platform meets all {} requirements",
+            outcome.checks.len()
+        );
+    } else {
+        println!(
+            "\n✗ Platform fails {} of {} requirements",
+            failed,
+            outcome.checks.len()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use platform_info::SystemInfo;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(flatten)]
+        requirements: Requirements,
+    }
+
+    fn requirements(args: &[&str]) -> Requirements {
+        Cli::parse_from(["check"].iter().chain(args)).requirements
+    }
+
+    fn policy_file(name: &str, toml: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "cross-platform-rust-{}-{}.toml",
+            name,
+            std::process::id()
+        ));
+        fs::write(&path, toml).unwrap();
+        path
+    }
+
+    /// A 64-bit little-endian Linux x86-64 debug build with SSE2 and glibc.
+    fn report(glibc: Option<&str>) -> PlatformReport {
+        let mut report = platform_info::detect();
+        report.os = "linux";
+        report.arch = "x86_64";
+        report.family = "unix";
+        report.pointer_width = 64;
+        report.endianness = "little";
+        report.build.mode = "debug";
+        report.simd = vec!["sse", "sse2"];
+        report.system = Some(SystemInfo {
+            hostname: None,
+            os_version: None,
+            cpu_model: None,
+            glibc_version: glibc.map(str::to_string),
+            logical_cores: 1,
+            physical_cores: None,
+            total_memory_bytes: 0,
+            available_memory_bytes: 0,
+            uptime_secs: 0,
+            load_average: None,
+        });
+        report
+    }
+
+    fn outcome(toml: &str, glibc: Option<&str>) -> Outcome {
+        run(&toml::from_str(toml).unwrap(), &report(glibc))
+    }
+
+    #[test]
+    fn test_exit_code_is_the_lowest_failing_kind() {
+        let passing = outcome(
+            "os = ['macos', 'linux']\nmin_pointer_width = 32\ncpu_features = ['sse2']",
+            None,
+        );
+        assert!(passing.passed);
+        assert_eq!(passing.exit_code, 0);
+        assert_eq!(passing.checks.len(), 3);
+
+        let no_requirements = outcome("", None);
+        assert!(no_requirements.passed && no_requirements.checks.is_empty());
+
+        let failing = outcome(
+            "release = true\ncpu_features = ['avx2']\npointer_width = 32\nfamily = 'unix'",
+            None,
+        );
+        assert!(!failing.passed);
+        assert_eq!(failing.exit_code, 11);
+        let failed: Vec<_> = failing
+            .checks
+            .iter()
+            .filter(|check| !check.passed)
+            .map(|check| (check.requirement, check.actual.as_str()))
+            .collect();
+        assert_eq!(
+            failed,
+            [
+                ("pointer_width", "64"),
+                ("cpu_feature", "missing"),
+                ("build", "debug"),
+            ]
+        );
+
+        assert_eq!(
+            outcome("arch = ['aarch64']\nrelease = true", None).exit_code,
+            10
+        );
+        assert_eq!(
+            outcome("cpu_features = ['avx2']\nrelease = true", None).exit_code,
+            13
+        );
+        assert_eq!(outcome("release = true", None).exit_code, 14);
+    }
+
+    #[test]
+    fn test_min_glibc() {
+        assert_eq!(version("2.31"), Some(vec![2, 31]));
+        assert_eq!(version(" 2.4.1 "), Some(vec![2, 4, 1]));
+        assert_eq!(version("2.31.0"), Some(vec![2, 31]));
+        assert_eq!(version("2.x"), None);
+        assert_eq!(version(""), None);
+
+        assert_eq!(outcome("min_glibc = '2.31'", Some("2.36")).exit_code, 0);
+        assert_eq!(outcome("min_glibc = '2.31'", Some("2.31")).exit_code, 0);
+        // Trailing zeros don't make a version newer.
+        assert_eq!(outcome("min_glibc = '2.31.0'", Some("2.31")).exit_code, 0);
+        assert_eq!(outcome("min_glibc = '2.31'", Some("2.31.0")).exit_code, 0);
+        assert_eq!(outcome("min_glibc = '2.31.1'", Some("2.31")).exit_code, 12);
+        // Compared as numbers, not strings.
+        assert_eq!(outcome("min_glibc = '2.4'", Some("2.36")).exit_code, 0);
+        assert_eq!(outcome("min_glibc = '2.36'", Some("2.4")).exit_code, 12);
+
+        let musl = outcome("min_glibc = '2.17'", None);
+        assert_eq!(musl.exit_code, 12);
+        assert_eq!(musl.checks[0].actual, "none");
+    }
+
+    #[test]
+    fn test_flags_merge_over_the_policy_file() {
+        let path = policy_file(
+            "merge",
+            "os = ['linux']\nfamily = 'windows'\npointer_width = 32\nmin_glibc = '2.17'\ncpu_features = ['sse2']",
+        );
+        let path = path.to_str().unwrap();
+        let policy = requirements(&[
+            "--policy",
+            path,
+            "--os",
+            "macos",
+            "--family",
+            "unix",
+            "--simd",
+            "avx2",
+            "--release",
+        ])
+        .policy()
+        .unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(policy.os, ["linux", "macos"]);
+        assert_eq!(policy.family.as_deref(), Some("unix"));
+        assert_eq!(policy.pointer_width, Some(32));
+        assert_eq!(policy.min_glibc.as_deref(), Some("2.17"));
+        assert_eq!(policy.cpu_features, ["sse2", "avx2"]);
+        assert!(policy.release);
+
+        let flags_only = requirements(&["--pointer-width", "64"]).policy().unwrap();
+        assert_eq!(flags_only.pointer_width, Some(64));
+        assert!(flags_only.os.is_empty() && !flags_only.release);
+    }
+
+    #[test]
+    fn test_invalid_policies_are_rejected() {
+        for (name, toml, expected) in [
+            (
+                "unknown",
+                "min_kernel = '6.1'",
+                "unknown field `min_kernel`",
+            ),
+            ("glibc", "min_glibc = 'new'", "is not a version"),
+            ("type", "pointer_width = 'wide'", "invalid type"),
+        ] {
+            let path = policy_file(name, toml);
+            let err = requirements(&["--policy", path.to_str().unwrap()])
+                .policy()
+                .err()
+                .unwrap();
+            fs::remove_file(&path).unwrap();
+            assert!(err.to_string().contains(expected), "{}", err);
+            assert_eq!(err.exit_code(), 3);
+        }
+
+        let err = requirements(&["--policy", "/nonexistent/policy.toml"])
+            .policy()
+            .err()
+            .unwrap();
+        assert!(err.to_string().starts_with("cannot read policy"), "{}", err);
+        assert_eq!(err.exit_code(), 3);
+    }
+}
@@ -0,0 +1,122 @@
+use serde::Serialize;
+use serde_json::Value;
+
+/// `value` as `KEY=value` lines, one per field, ready for a shell to `eval`
+/// or source. Nested fields join their names with `_` under `prefix`
+/// (`GAFFER_TARGET_BUILD_MODE`), lists of plain values become one
+/// comma-separated value, lists of records are numbered from 0, and a
+/// missing value is empty. Values are single-quoted where a shell would
+/// otherwise split or expand them.
+pub fn to_env<T: Serialize + ?Sized>(prefix: &str, value: &T) -> String {
+    let value = serde_json::to_value(value).expect("reports are plain data");
+    let mut lines = String::new();
+    flatten(prefix, &value, &mut lines);
+    lines
+}
+
+fn flatten(key: &str, value: &Value, lines: &mut String) {
+    match value {
+        Value::Object(fields) => {
+            for (name, value) in fields {
+                flatten(&format!("{}_{}", key, name.to_uppercase()), value, lines);
+            }
+        }
+        Value::Array(items) if items.iter().any(|item| item.is_object()) => {
+            for (index, item) in items.iter().enumerate() {
+                flatten(&format!("{}_{}", key, index), item, lines);
+            }
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(plain).collect();
+            push(key, &items.join(","), lines);
+        }
+        value => push(key, &plain(value), lines),
+    }
+}
+
+fn plain(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn push(key: &str, value: &str, lines: &mut String) {
+    let safe = value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_-.,:/+@%".contains(c));
+    if safe {
+        lines.push_str(&format!("{}={}\n", key, value));
+    } else {
+        lines.push_str(&format!("{}='{}'\n", key, value.replace('\'', r"'\''")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_flattening() {
+        let value = json!({
+            "os": "linux",
+            "pointer_width": 64,
+            "release": false,
+            "build": {"mode": "debug", "git_sha": null},
+            "simd": ["sse", "sse4.2"],
+            "checks": [{"name": "os", "passed": true}, {"name": "arch", "passed": false}],
+            "empty": [],
+        });
+        assert_eq!(
+            to_env("GAFFER", &value),
+            "\
+GAFFER_OS=linux
+GAFFER_POINTER_WIDTH=64
+GAFFER_RELEASE=false
+GAFFER_BUILD_MODE=debug
+GAFFER_BUILD_GIT_SHA=
+GAFFER_SIMD=sse,sse4.2
+GAFFER_CHECKS_0_NAME=os
+GAFFER_CHECKS_0_PASSED=true
+GAFFER_CHECKS_1_NAME=arch
+GAFFER_CHECKS_1_PASSED=false
+GAFFER_EMPTY=
+"
+        );
+    }
+
+    #[test]
+    fn test_quoting() {
+        let value = json!({
+            "spaced": "Apple M2 Pro",
+            "quoted": "it's $HOME",
+            "blank": "",
+            "version": "rustc 1.80.0 (051478957 2024-07-21)",
+        });
+        assert_eq!(
+            to_env("X", &value),
+            "\
+X_SPACED='Apple M2 Pro'
+X_QUOTED='it'\\''s $HOME'
+X_BLANK=
+X_VERSION='rustc 1.80.0 (051478957 2024-07-21)'
+"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shell_reads_back_every_value() {
+        let values = ["Apple M2 Pro", "it's $HOME `id` \"x\"", "", "a\\b;c|d&e"];
+        let lines = to_env("X", &json!({ "value": values.join("\n") }));
+        let output = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(format!("{}printf %s \"$X_VALUE\"", lines))
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), values.join("\n"));
+    }
+}
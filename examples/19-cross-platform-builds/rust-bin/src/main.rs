@@ -1,88 +1,120 @@
-use std::env;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use std::process;
 
-fn main() {
-    println!("╔════════════════════════════════════════╗");
-    println!("║  Cross-Platform Rust Application       ║");
-    println!("╚════════════════════════════════════════╝");
-    println!();
-    
-    print_platform_info();
-    print_build_info();
-    print_platform_features();
-    
-    println!("\n✓ Rust application executed successfully!");
+mod bench;
+mod check;
+mod env;
+mod simd;
+mod text;
+
+/// Reports on, checks and measures the platform this binary was built for.
+#[derive(Parser)]
+#[command(name = "cross-platform-rust", version)]
+struct Cli {
+    /// How to print the result
+    #[arg(long, global = true, value_enum, default_value = "text")]
+    format: Format,
+
+    /// Same as --format json
+    #[arg(long, global = true, conflicts_with_all = ["yaml", "format"])]
+    json: bool,
+
+    /// Same as --format yaml
+    #[arg(long, global = true, conflicts_with = "format")]
+    yaml: bool,
+
+    /// What to do [default: info]
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-fn print_platform_info() {
-    println!("Platform Information:");
-    println!("  OS:           {}", env::consts::OS);
-    println!("  Architecture: {}", env::consts::ARCH);
-    println!("  Family:       {}", env::consts::FAMILY);
-    
-    #[cfg(target_pointer_width = "64")]
-    println!("  Pointer Size: 64-bit");
-    
-    #[cfg(target_pointer_width = "32")]
-    println!("  Pointer Size: 32-bit");
-    
-    #[cfg(target_endian = "little")]
-    println!("  Endianness:   Little-endian");
-    
-    #[cfg(target_endian = "big")]
-    println!("  Endianness:   Big-endian");
+#[derive(Subcommand)]
+enum Command {
+    /// Report the platform: OS, architecture, build and CPU features
+    Info,
+    /// Fail unless the platform meets every requirement given
+    #[command(after_help = check::EXIT_CODES)]
+    Check(check::Requirements),
+    /// Time a few micro-benchmarks on this host
+    Bench(BenchArgs),
 }
 
-fn print_build_info() {
-    println!("\nBuild Configuration:");
-    
-    #[cfg(debug_assertions)]
-    println!("  Mode:         Debug");
-    
-    #[cfg(not(debug_assertions))]
-    println!("  Mode:         Release");
-    
-    println!("  Rust Version: {}", env!("CARGO_PKG_VERSION"));
+#[derive(Args)]
+struct BenchArgs {
+    /// Times each benchmark runs
+    #[arg(short = 'n', long, default_value_t = 20, value_parser = clap::value_parser!(u32).range(1..))]
+    iterations: u32,
+
+    /// Elements each benchmark works through
+    #[arg(long, default_value_t = 1_000_000, value_parser = clap::value_parser!(u64).range(1..))]
+    size: u64,
 }
 
-fn print_platform_features() {
-    println!("\nPlatform-Specific Features:");
-    
-    #[cfg(target_os = "linux")]
-    {
-        println!("  - Linux-specific code paths enabled");
-        println!("  - POSIX API available");
-        println!("  - Native threading support");
-    }
-    
-    #[cfg(target_os = "macos")]
-    {
-        println!("  - macOS-specific code paths enabled");
-        println!("  - Apple frameworks available");
-        println!("  - Grand Central Dispatch support");
-    }
-    
-    #[cfg(target_os = "windows")]
-    {
-        println!("  - Windows-specific code paths enabled");
-        println!("  - Windows API available");
-        println!("  - MSVC runtime linked");
-    }
-    
-    #[cfg(target_arch = "x86_64")]
-    {
-        println!("  - x86-64 instruction set");
-        println!("  - SSE/AVX optimizations possible");
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Decorated text for people
+    Text,
+    Json,
+    Yaml,
+    Toml,
+    /// `KEY=value` lines for a shell to eval or source, such as GAFFER_TARGET_OS=linux
+    Env,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let format = if cli.json {
+        Format::Json
+    } else if cli.yaml {
+        Format::Yaml
+    } else {
+        cli.format
+    };
+    let mut report = platform_info::detect();
+    // Set by build.rs from this binary's own profile and features.
+    report.provenance = Some(platform_info::provenance!());
+
+    match cli.command.unwrap_or(Command::Info) {
+        Command::Info => emit(&report, format, "GAFFER_TARGET", text::print_text),
+        Command::Check(requirements) => {
+            let policy = requirements.policy().unwrap_or_else(|e| {
+                eprintln!("error: {}", e);
+                process::exit(e.exit_code());
+            });
+            let outcome = check::run(&policy, &report);
+            emit(&outcome, format, "GAFFER_CHECK", check::print_text);
+            process::exit(outcome.exit_code);
+        }
+        Command::Bench(args) => {
+            let results = bench::run(
+                report.arch,
+                env!("PROVENANCE_TARGET"),
+                args.iterations,
+                args.size as usize,
+            );
+            emit(&results, format, "GAFFER_BENCH", bench::print_text);
+        }
     }
-    
-    #[cfg(target_arch = "aarch64")]
-    {
-        println!("  - ARM64 instruction set");
-        println!("  - NEON optimizations possible");
+}
+
+/// Prints `value` as `format` asks, using `text` for the plain text form and
+/// `prefix` to name the variables of the env form.
+fn emit<T: Serialize + ?Sized>(value: &T, format: Format, prefix: &str, text: impl Fn(&T)) {
+    match format {
+        Format::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(value).expect("reports are plain data")
+        ),
+        Format::Yaml => print!(
+            "{}",
+            serde_yaml::to_string(value).expect("reports are plain data")
+        ),
+        Format::Toml => print!(
+            "{}",
+            toml::to_string(value).expect("reports are plain data")
+        ),
+        Format::Env => print!("{}", env::to_env(prefix, value)),
+        Format::Text => text(value),
     }
-    
-    #[cfg(target_env = "gnu")]
-    println!("  - GNU environment (glibc)");
-    
-    #[cfg(target_env = "msvc")]
-    println!("  - MSVC environment");
 }
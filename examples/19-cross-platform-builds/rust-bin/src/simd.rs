@@ -0,0 +1,168 @@
+//! A dot product in a scalar form and in each SIMD form this CPU supports,
+//! for `bench` to compare.
+
+pub type Kernel = fn(&[f32], &[f32]) -> f32;
+
+/// The scalar kernel first, then each SIMD variant the CPU turns out to have.
+pub fn kernels() -> Vec<(&'static str, Kernel)> {
+    #[allow(unused_mut)]
+    let mut kernels: Vec<(&'static str, Kernel)> = vec![("scalar", dot_scalar)];
+
+    #[cfg(target_arch = "x86_64")]
+    {
+        // SSE is part of the x86-64 baseline.
+        kernels.push(("sse", x86::dot_sse));
+        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+            kernels.push(("avx2", x86::dot_avx2));
+        }
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    kernels.push(("neon", arm::dot_neon));
+
+    // Compiled in with `-C target-feature=+simd128`; there's nothing to detect.
+    #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+    kernels.push(("simd128", wasm::dot_simd128));
+
+    kernels
+}
+
+/// One multiply-add at a time, in order: floating-point addition isn't
+/// associative, so the compiler may not vectorize this for us.
+fn dot_scalar(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).fold(0.0, |sum, (x, y)| sum + x * y)
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    pub fn dot_sse(a: &[f32], b: &[f32]) -> f32 {
+        let n = a.len().min(b.len());
+        let lanes = n / 4 * 4;
+        // SAFETY: SSE is always present on x86-64, and every load reads four
+        // floats below `lanes`, within both slices.
+        let sum = unsafe {
+            let mut sum = _mm_setzero_ps();
+            for i in (0..lanes).step_by(4) {
+                let x = _mm_loadu_ps(a.as_ptr().add(i));
+                let y = _mm_loadu_ps(b.as_ptr().add(i));
+                sum = _mm_add_ps(sum, _mm_mul_ps(x, y));
+            }
+            let mut parts = [0.0f32; 4];
+            _mm_storeu_ps(parts.as_mut_ptr(), sum);
+            parts.iter().sum::<f32>()
+        };
+        sum + super::dot_scalar(&a[lanes..n], &b[lanes..n])
+    }
+
+    pub fn dot_avx2(a: &[f32], b: &[f32]) -> f32 {
+        // SAFETY: `kernels` only offers this once AVX2 and FMA were detected.
+        unsafe { dot_fma(a, b) }
+    }
+
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn dot_fma(a: &[f32], b: &[f32]) -> f32 {
+        let n = a.len().min(b.len());
+        let lanes = n / 8 * 8;
+        // SAFETY: every load reads eight floats below `lanes`, within both
+        // slices.
+        let sum = unsafe {
+            let mut sum = _mm256_setzero_ps();
+            for i in (0..lanes).step_by(8) {
+                let x = _mm256_loadu_ps(a.as_ptr().add(i));
+                let y = _mm256_loadu_ps(b.as_ptr().add(i));
+                sum = _mm256_fmadd_ps(x, y, sum);
+            }
+            let mut parts = [0.0f32; 8];
+            _mm256_storeu_ps(parts.as_mut_ptr(), sum);
+            parts.iter().sum::<f32>()
+        };
+        sum + super::dot_scalar(&a[lanes..n], &b[lanes..n])
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod arm {
+    use std::arch::aarch64::*;
+
+    pub fn dot_neon(a: &[f32], b: &[f32]) -> f32 {
+        let n = a.len().min(b.len());
+        let lanes = n / 4 * 4;
+        // SAFETY: NEON is part of the AArch64 baseline, and every load reads
+        // four floats below `lanes`, within both slices.
+        let sum = unsafe {
+            let mut sum = vdupq_n_f32(0.0);
+            for i in (0..lanes).step_by(4) {
+                let x = vld1q_f32(a.as_ptr().add(i));
+                let y = vld1q_f32(b.as_ptr().add(i));
+                sum = vfmaq_f32(sum, x, y);
+            }
+            vaddvq_f32(sum)
+        };
+        sum + super::dot_scalar(&a[lanes..n], &b[lanes..n])
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod wasm {
+    use std::arch::wasm32::*;
+
+    pub fn dot_simd128(a: &[f32], b: &[f32]) -> f32 {
+        let n = a.len().min(b.len());
+        let lanes = n / 4 * 4;
+        let mut sum = f32x4_splat(0.0);
+        for i in (0..lanes).step_by(4) {
+            // SAFETY: each load reads four floats below `lanes`, within both
+            // slices, and `v128_load` needs no alignment.
+            let (x, y) = unsafe {
+                (
+                    v128_load(a.as_ptr().add(i).cast()),
+                    v128_load(b.as_ptr().add(i).cast()),
+                )
+            };
+            sum = f32x4_add(sum, f32x4_mul(x, y));
+        }
+        let sum = f32x4_extract_lane::<0>(sum)
+            + f32x4_extract_lane::<1>(sum)
+            + f32x4_extract_lane::<2>(sum)
+            + f32x4_extract_lane::<3>(sum);
+        sum + super::dot_scalar(&a[lanes..n], &b[lanes..n])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_kernel_matches_scalar() {
+        for len in [0, 1, 7, 8, 9, 1001] {
+            let a: Vec<f32> = (0..len).map(|i| (i % 13) as f32 * 0.25 - 1.0).collect();
+            let b: Vec<f32> = (0..len).map(|i| (i % 7) as f32 * 0.5 + 0.125).collect();
+            let expected = dot_scalar(&a, &b);
+            for (name, kernel) in kernels() {
+                let actual = kernel(&a, &b);
+                // Lanes sum in a different order, so allow for rounding.
+                let tolerance = 1e-4 * expected.abs().max(1.0);
+                assert!(
+                    (actual - expected).abs() <= tolerance,
+                    "{} over {}: {} != {}",
+                    name,
+                    len,
+                    actual,
+                    expected
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_kernels_stop_at_the_shorter_slice() {
+        let a = [1.0; 9];
+        let b = [2.0; 5];
+        for (name, kernel) in kernels() {
+            assert_eq!(kernel(&a, &b), 10.0, "{}", name);
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! The decorated text form of the platform report, for people rather than
+//! programs.
+
+#[cfg(target_os = "macos")]
+use platform_info::MacosInfo;
+#[cfg(target_arch = "wasm32")]
+use platform_info::WasmInfo;
+#[cfg(windows)]
+use platform_info::WindowsInfo;
+use platform_info::{BuildInfo, PlatformReport, Provenance, SystemInfo};
+
+/// Prints `report` section by section.
+pub fn print_text(report: &PlatformReport) {
+    println!("╔════════════════════════════════════════╗");
+    println!("║  Cross-Platform Rust Application       ║");
+    println!("╚════════════════════════════════════════╝");
+    println!();
+
+    print_platform_info(report);
+    if let Some(system) = &report.system {
+        print_system(system);
+    }
+    #[cfg(target_arch = "wasm32")]
+    print_wasm(&report.wasm);
+    #[cfg(windows)]
+    print_windows(&report.windows, report.arch);
+    #[cfg(target_os = "macos")]
+    print_macos(&report.macos, report.arch);
+    print_build_info(&report.build);
+    if let Some(provenance) = &report.provenance {
+        print_provenance(provenance);
+    }
+    print_platform_features(&report.features);
+    print_simd_extensions(&report.simd);
+
+    println!("\n✓ Rust application executed successfully!");
+}
+
+fn print_platform_info(report: &PlatformReport) {
+    println!("Platform Information:");
+    println!("  OS:           {}", report.os);
+    println!("  Architecture: {}", report.arch);
+    println!("  Family:       {}", report.family);
+    println!("  Pointer Size: {}-bit", report.pointer_width);
+
+    if report.endianness == "little" {
+        println!("  Endianness:   Little-endian");
+    } else {
+        println!("  Endianness:   Big-endian");
+    }
+}
+
+fn print_build_info(build: &BuildInfo) {
+    println!("\nBuild Configuration:");
+
+    if build.mode == "debug" {
+        println!("  Mode:         Debug");
+    } else {
+        println!("  Mode:         Release");
+    }
+
+    println!("  Rust Version: {}", build.version);
+}
+
+fn print_platform_features(features: &[&str]) {
+    println!("\nPlatform-Specific Features:");
+    for feature in features {
+        println!("  - {}", feature);
+    }
+}
+
+fn print_simd_extensions(simd: &[&str]) {
+    println!("\nSIMD Extensions (detected at runtime):");
+    if simd.is_empty() {
+        println!("  - none");
+    } else {
+        println!("  - {}", simd.join(", "));
+    }
+}
+
+fn print_system(system: &SystemInfo) {
+    let unknown = || "unknown".to_string();
+    println!("\nSystem Resources:");
+    println!(
+        "  Hostname:     {}",
+        system.hostname.clone().unwrap_or_else(unknown)
+    );
+    println!(
+        "  OS Version:   {}",
+        system.os_version.clone().unwrap_or_else(unknown)
+    );
+    println!(
+        "  CPU:          {}",
+        system.cpu_model.clone().unwrap_or_else(unknown)
+    );
+    if let Some(glibc) = &system.glibc_version {
+        println!("  glibc:        {}", glibc);
+    }
+    match system.physical_cores {
+        Some(physical) => println!(
+            "  Cores:        {} physical, {} logical",
+            physical, system.logical_cores
+        ),
+        None => println!("  Cores:        {} logical", system.logical_cores),
+    }
+    println!(
+        "  Memory:       {} available of {}",
+        gib(system.available_memory_bytes),
+        gib(system.total_memory_bytes)
+    );
+    println!("  Uptime:       {}", duration(system.uptime_secs));
+    if let Some(load) = &system.load_average {
+        println!(
+            "  Load Average: {:.2}, {:.2}, {:.2}",
+            load.one, load.five, load.fifteen
+        );
+    }
+}
+
+fn print_provenance(provenance: &Provenance) {
+    println!("\nBuild Provenance:");
+    println!("  Target:       {}", provenance.target);
+    if provenance.target == provenance.host {
+        println!("  Host:         {}", provenance.host);
+    } else {
+        println!("  Host:         {} (cross-compiled)", provenance.host);
+    }
+    println!("  Linker:       {}", provenance.linker);
+    println!("  Compiler:     {}", provenance.rustc);
+    println!(
+        "  Profile:      {} (opt-level {})",
+        provenance.profile, provenance.opt_level
+    );
+    if provenance.features.is_empty() {
+        println!("  Features:     none");
+    } else {
+        println!("  Features:     {}", provenance.features.join(", "));
+    }
+    println!(
+        "  Git Commit:   {}",
+        provenance.git_sha.unwrap_or("unknown")
+    );
+}
+
+#[cfg(target_arch = "wasm32")]
+fn print_wasm(wasm: &WasmInfo) {
+    let yes_no = |granted| if granted { "yes" } else { "no" };
+    println!("\nWebAssembly Runtime:");
+    println!("  Interface:    {}", wasm.interface);
+    println!(
+        "  Memory:       {:.1} MiB linear",
+        wasm.memory_bytes as f64 / (1024.0 * 1024.0)
+    );
+    println!("  Threads:      {}", yes_no(wasm.threads));
+    println!("  Env Vars:     {} passed in", wasm.env_vars);
+    println!("  Current Dir:  {}", yes_no(wasm.current_dir));
+}
+
+#[cfg(windows)]
+fn print_windows(windows: &WindowsInfo, arch: &str) {
+    println!("\nWindows:");
+    match windows.revision {
+        Some(revision) => println!("  Version:      {}.{}", windows.version, revision),
+        None => println!("  Version:      {}", windows.version),
+    }
+    match (&windows.edition, &windows.display_version) {
+        (Some(edition), Some(update)) => println!("  Edition:      {} ({})", edition, update),
+        (Some(edition), None) => println!("  Edition:      {}", edition),
+        _ => {}
+    }
+    println!(
+        "  Long Paths:   {}",
+        match windows.long_paths {
+            Some(true) => "enabled",
+            Some(false) => "disabled",
+            None => "not configured",
+        }
+    );
+    if windows.wow64 {
+        println!("  WOW64:        yes, a 32-bit process on 64-bit Windows");
+    }
+    match windows.native_arch {
+        Some(native) if native != arch => {
+            println!("  Native Arch:  {} (this binary is emulated)", native)
+        }
+        Some(native) => println!("  Native Arch:  {}", native),
+        None => {}
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn print_macos(macos: &MacosInfo, arch: &str) {
+    println!("\nmacOS:");
+    if let Some(chip) = &macos.chip {
+        println!("  Chip:         {}", chip);
+    }
+    if let Some(model) = &macos.model {
+        println!("  Model:        {}", model);
+    }
+    if let (Some(performance), Some(efficiency)) = (macos.performance_cores, macos.efficiency_cores)
+    {
+        println!(
+            "  Cores:        {} performance, {} efficiency",
+            performance, efficiency
+        );
+    }
+    if macos.rosetta {
+        println!("  Runs As:      {} under Rosetta 2, on Apple silicon", arch);
+    } else if macos.apple_silicon {
+        println!("  Runs As:      {}, natively on Apple silicon", arch);
+    } else {
+        println!("  Runs As:      {}, natively on Intel", arch);
+    }
+}
+
+fn gib(bytes: u64) -> String {
+    format!("{:.1} GiB", bytes as f64 / (1024.0 * 1024.0 * 1024.0))
+}
+
+/// `secs` as days, hours and minutes, leaving out leading zero units.
+fn duration(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86_400, secs / 3_600 % 24, secs / 60 % 60);
+    match (days, hours) {
+        (0, 0) => format!("{}m", minutes),
+        (0, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h {}m", days, hours, minutes),
+    }
+}